@@ -7,6 +7,8 @@ const RECONNECT_MAX_MS = 30000;
 const MAX_RETRIES = 8;
 
 const HANDSHAKE_TIMEOUT_MS = 2000;
+// Wire protocol revision sent as `proto` in hello; must match the bridge's hello_ack
+const BRIDGE_PROTO = 1;
 const L3_CONFIRM_TIMEOUT_MS = 30000;
 
 // --- CDP Method Allowlist ---
@@ -175,6 +177,8 @@ async function connect() {
       role: "extension",
       token: token,
       version: "0.2.0",
+      proto: BRIDGE_PROTO,
+      extension_version: chrome.runtime.getManifest().version,
     });
 
     // Start handshake timeout - if no hello_ack within this window, treat as auth failure
@@ -203,11 +207,23 @@ async function connect() {
 
     // Handle hello_ack from server (explicit auth confirmation)
     if (!handshakeCompleted && msg.type === "hello_ack") {
-      handshakeCompleted = true;
       if (handshakeTimer) {
         clearTimeout(handshakeTimer);
         handshakeTimer = null;
       }
+      // Bridges that predate `proto` speak revision 1
+      const ackProto = msg.proto === undefined ? 1 : msg.proto;
+      if (ackProto !== BRIDGE_PROTO) {
+        if (ws) { ws.close(); ws = null; }
+        connectionState = "failed";
+        logStateTransition(
+          "failed",
+          `bridge speaks protocol ${ackProto}, extension speaks ${BRIDGE_PROTO}`
+        );
+        broadcastState();
+        return;
+      }
+      handshakeCompleted = true;
       connectionState = "connected";
       retryCount = 0;
       reconnectDelay = RECONNECT_BASE_MS;
@@ -261,7 +277,12 @@ async function connect() {
     // Normal command message - must be authenticated first
     if (!handshakeCompleted) return;
 
+    // The CLI gave up on this request; the bridge no longer waits for a reply
+    if (msg.type === "cancel") return;
+
     const response = await handleCommand(msg);
+    // Echo the bridge's generation so stale responses can be told apart
+    if (response && msg.gen !== undefined) response.gen = msg.gen;
     wsSend(response);
   };
 
@@ -380,6 +401,9 @@ async function handleExtensionCommand(id, method, params) {
     case "Extension.ping":
       return { id, result: { status: "pong", timestamp: Date.now() } };
 
+    case "Extension.version":
+      return { id, result: { version: chrome.runtime.getManifest().version } };
+
     case "Extension.listTabs": {
       const tabs = await chrome.tabs.query({});
       const tabList = tabs.map((t) => ({
@@ -936,7 +960,7 @@ async function tryNativeMessagingConnect() {
   try {
     chrome.runtime.sendNativeMessage(
       NATIVE_HOST_NAME,
-      { type: "get_token" },
+      { type: "get_token", extension_version: chrome.runtime.getManifest().version },
       (response) => {
         if (chrome.runtime.lastError) {
           const errMsg = chrome.runtime.lastError.message || "";
@@ -955,6 +979,10 @@ async function tryNativeMessagingConnect() {
           return;
         }
 
+        if (response && response.compat_warning) {
+          debugLog(`[actionbook] Native host v${response.host_version}: ${response.compat_warning}`);
+        }
+
         if (response && response.type === "token" && response.token && response.bridge_running) {
           debugLog("[actionbook] Token received via native messaging");
           if (!isValidTokenFormat(response.token)) {
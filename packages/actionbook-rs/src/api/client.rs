@@ -13,9 +13,28 @@ pub struct ApiClient {
     api_key: Option<String>,
 }
 
+impl std::fmt::Debug for ApiClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ApiClient")
+            .field("base_url", &self.base_url)
+            .field("api_key", &self.api_key.as_ref().map(|_| "<redacted>"))
+            .finish()
+    }
+}
+
 impl ApiClient {
     /// Create a new API client from config
     pub fn from_config(config: &Config) -> Result<Self> {
+        Self::build(config, config.api.resolve_api_key()?)
+    }
+
+    /// Create an API client for `config`'s endpoint that authenticates with
+    /// `api_key` instead of the configured key (which is not resolved at all)
+    pub fn with_key(config: &Config, api_key: &str) -> Result<Self> {
+        Self::build(config, Some(api_key.to_string()))
+    }
+
+    fn build(config: &Config, api_key: Option<String>) -> Result<Self> {
         let client = Client::builder()
             .timeout(Duration::from_secs(30))
             .build()
@@ -26,7 +45,7 @@ impl ApiClient {
         Ok(Self {
             client,
             base_url: config.api.base_url.clone(),
-            api_key: config.api.api_key.clone(),
+            api_key,
         })
     }
 
@@ -92,8 +111,42 @@ impl ApiClient {
         self.handle_text_response(response).await
     }
 
-    /// Get action by area ID (returns plain text)
+    /// Get action by area ID (returns plain text). A malformed ID is
+    /// rejected before any request is made.
     pub async fn get_action_by_area_id(&self, area_id: &str) -> Result<String> {
+        let response = self.send_get_action(area_id).await?;
+        self.handle_text_response(response).await
+    }
+
+    /// Like [`ApiClient::get_action_by_area_id`], copying the body to `out`
+    /// chunk by chunk as it arrives, so a large action is neither held in
+    /// memory nor held back until the download ends. Returns the number of
+    /// bytes written.
+    pub async fn stream_action_by_area_id(
+        &self,
+        area_id: &str,
+        out: &mut impl std::io::Write,
+    ) -> Result<u64> {
+        let mut response = self.send_get_action(area_id).await?;
+        if !response.status().is_success() {
+            return Err(Self::error_from_response(response).await);
+        }
+
+        let mut written = 0;
+        while let Some(chunk) = response
+            .chunk()
+            .await
+            .map_err(|e| ActionbookError::ApiError(format!("Failed to read response: {}", e)))?
+        {
+            out.write_all(&chunk)?;
+            out.flush()?;
+            written += chunk.len() as u64;
+        }
+        Ok(written)
+    }
+
+    async fn send_get_action(&self, area_id: &str) -> Result<reqwest::Response> {
+        validate_area_id(area_id)?;
         let response = self
             .request_text(reqwest::Method::GET, "/api/get_action_by_area_id")
             .query(&[("area_id", area_id)])
@@ -101,7 +154,12 @@ impl ApiClient {
             .await
             .map_err(|e| ActionbookError::ApiError(format!("Request failed: {}", e)))?;
 
-        self.handle_text_response(response).await
+        if response.status() == StatusCode::NOT_FOUND {
+            return Err(ActionbookError::AreaNotFound {
+                area_id: area_id.to_string(),
+            });
+        }
+        Ok(response)
     }
 
     // ============================================
@@ -239,21 +297,221 @@ impl ApiClient {
                 .await
                 .map_err(|e| ActionbookError::ApiError(format!("Failed to read response: {}", e)))
         } else {
-            let error_msg = match status {
-                StatusCode::NOT_FOUND => "Resource not found".to_string(),
-                StatusCode::TOO_MANY_REQUESTS => {
-                    "Rate limited. Please try again later.".to_string()
-                }
-                StatusCode::UNAUTHORIZED => "Invalid or missing API key".to_string(),
-                _ => {
-                    // Try to read error text
-                    match response.text().await {
-                        Ok(text) if !text.is_empty() => text,
-                        _ => format!("API error: {}", status),
-                    }
+            Err(Self::error_from_response(response).await)
+        }
+    }
+
+    /// The error for a non-success text response.
+    async fn error_from_response(response: reqwest::Response) -> ActionbookError {
+        let status = response.status();
+        let error_msg = match status {
+            StatusCode::NOT_FOUND => "Resource not found".to_string(),
+            StatusCode::TOO_MANY_REQUESTS => "Rate limited. Please try again later.".to_string(),
+            StatusCode::UNAUTHORIZED => "Invalid or missing API key".to_string(),
+            _ => {
+                // Try to read error text
+                match response.text().await {
+                    Ok(text) if !text.is_empty() => text,
+                    _ => format!("API error: {}", status),
                 }
-            };
-            Err(ActionbookError::ApiError(error_msg))
+            }
+        };
+        ActionbookError::ApiError(error_msg)
+    }
+}
+
+/// Check that `area_id` has the documented `site:path:area` shape, e.g.
+/// `airbnb.com:/:default` or `etsy.com:/search:search_results`. The site is
+/// everything before the first `:`, the area everything after the last one.
+pub fn validate_area_id(area_id: &str) -> Result<()> {
+    let invalid = |reason: &str| ActionbookError::InvalidAreaId {
+        area_id: area_id.to_string(),
+        reason: reason.to_string(),
+    };
+    if area_id.chars().any(char::is_whitespace) {
+        return Err(invalid("it contains whitespace"));
+    }
+    let (Some((site, rest)), Some((_, area))) = (area_id.split_once(':'), area_id.rsplit_once(':'))
+    else {
+        return Err(invalid("it has no ':' separators"));
+    };
+    let Some((path, _)) = rest.rsplit_once(':') else {
+        return Err(invalid("it has only one ':' separator"));
+    };
+    if site.is_empty() {
+        return Err(invalid("the site is empty"));
+    }
+    if !path.starts_with('/') {
+        return Err(invalid("the path must start with '/'"));
+    }
+    if area.is_empty() {
+        return Err(invalid("the area is empty"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::sync::oneshot;
+
+    /// Answer one request with `status` and a plain-text body, and hand back
+    /// the raw request.
+    async fn capture_request(status: &'static str) -> (String, oneshot::Receiver<String>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let (tx, rx) = oneshot::channel();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 4096];
+            let n = stream.read(&mut buf).await.unwrap();
+            let _ = tx.send(String::from_utf8_lossy(&buf[..n]).into_owned());
+            let reply = format!(
+                "HTTP/1.1 {}\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok",
+                status
+            );
+            let _ = stream.write_all(reply.as_bytes()).await;
+        });
+        (base_url, rx)
+    }
+
+    #[tokio::test]
+    async fn per_call_key_overrides_configured_key() {
+        let (base_url, request) = capture_request("200 OK").await;
+        let mut config = Config::default();
+        config.api.base_url = base_url;
+        config.api.api_key = Some("config-key".to_string());
+
+        let client = ApiClient::with_key(&config, "call-key").unwrap();
+        assert_eq!(client.get_action_by_area_id("a.com:/:default").await.unwrap(), "ok");
+
+        let request = request.await.unwrap().to_ascii_lowercase();
+        assert!(request.contains("x-api-key: call-key"), "request: {}", request);
+        assert!(!request.contains("config-key"), "request: {}", request);
+    }
+
+    #[test]
+    fn malformed_area_ids_are_rejected() {
+        for ok in ["airbnb.com:/:default", "etsy.com:/search:search_results", "a.com:/x:y:z"] {
+            assert!(validate_area_id(ok).is_ok(), "{}", ok);
+        }
+        for bad in [
+            "airbnb.com",
+            "airbnb.com:default",
+            ":/:default",
+            "airbnb.com:search:default",
+            "airbnb.com:/:",
+            "airbnb.com:/ :default",
+        ] {
+            assert!(
+                matches!(
+                    validate_area_id(bad),
+                    Err(ActionbookError::InvalidAreaId { .. })
+                ),
+                "{} should be rejected",
+                bad
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn missing_area_maps_to_area_not_found() {
+        let (base_url, _request) = capture_request("404 Not Found").await;
+        let mut config = Config::default();
+        config.api.base_url = base_url;
+        let client = ApiClient::from_config(&config).unwrap();
+
+        let err = client
+            .get_action_by_area_id("airbnb.com:/:missing")
+            .await
+            .unwrap_err();
+        match err {
+            ActionbookError::AreaNotFound { area_id } => {
+                assert_eq!(area_id, "airbnb.com:/:missing")
+            }
+            other => panic!("expected AreaNotFound, got {:?}", other),
         }
     }
+
+    /// Collects each write as a separate message.
+    struct ChunkSink(tokio::sync::mpsc::UnboundedSender<Vec<u8>>);
+
+    impl std::io::Write for ChunkSink {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            let _ = self.0.send(buf.to_vec());
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn action_body_is_streamed_as_it_arrives() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let mut config = Config::default();
+        config.api.base_url = format!("http://{}", listener.local_addr().unwrap());
+        let (release_tx, release_rx) = oneshot::channel::<()>();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 4096];
+            let _ = stream.read(&mut buf).await.unwrap();
+            let head = "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\
+                        Connection: close\r\n\r\n";
+            stream.write_all(head.as_bytes()).await.unwrap();
+            stream.write_all(b"6\r\nfirst \r\n").await.unwrap();
+            stream.flush().await.unwrap();
+            // The rest of the body is only sent once the first part came out
+            let _ = release_rx.await;
+            stream.write_all(b"6\r\nsecond\r\n0\r\n\r\n").await.unwrap();
+        });
+
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let download = tokio::spawn(async move {
+            let client = ApiClient::from_config(&config).unwrap();
+            client
+                .stream_action_by_area_id("a.com:/:default", &mut ChunkSink(tx))
+                .await
+        });
+
+        let first = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+            .await
+            .expect("first chunk should be written before the body is complete")
+            .unwrap();
+        assert_eq!(first, b"first ");
+        release_tx.send(()).unwrap();
+
+        assert_eq!(download.await.unwrap().unwrap(), 12);
+        let mut rest = Vec::new();
+        while let Some(chunk) = rx.recv().await {
+            rest.extend(chunk);
+        }
+        assert_eq!(rest, b"second");
+    }
+
+    #[tokio::test]
+    async fn streamed_missing_area_maps_to_area_not_found() {
+        let (base_url, _request) = capture_request("404 Not Found").await;
+        let mut config = Config::default();
+        config.api.base_url = base_url;
+        let client = ApiClient::from_config(&config).unwrap();
+
+        let mut out = Vec::new();
+        let err = client
+            .stream_action_by_area_id("airbnb.com:/:missing", &mut out)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ActionbookError::AreaNotFound { .. }), "{:?}", err);
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn debug_output_redacts_api_key() {
+        let client = ApiClient::with_key(&Config::default(), "secret-key").unwrap();
+        let debug = format!("{:?}", client);
+        assert!(!debug.contains("secret-key"), "{}", debug);
+        assert!(debug.contains("<redacted>"), "{}", debug);
+    }
 }
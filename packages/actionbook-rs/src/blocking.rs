@@ -0,0 +1,58 @@
+//! Synchronous wrappers for callers without a tokio runtime.
+//!
+//! Enable with `--features blocking`. Each call builds a current-thread
+//! runtime, drives the async operation to completion, and tears the runtime
+//! down again. Do not call these from inside an async context; tokio panics
+//! when a runtime is started from within another.
+
+use crate::browser::{extension_bridge, extension_installer};
+use crate::error::{ActionbookError, Result};
+
+fn runtime() -> Result<tokio::runtime::Runtime> {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| ActionbookError::Other(format!("Failed to start tokio runtime: {}", e)))
+}
+
+/// Blocking version of [`extension_bridge::send_command`].
+pub fn send_command_blocking(
+    port: u16,
+    method: &str,
+    params: serde_json::Value,
+) -> Result<serde_json::Value> {
+    runtime()?.block_on(extension_bridge::send_command(port, method, params))
+}
+
+/// Blocking version of [`extension_bridge::send_command_with_token`].
+pub fn send_command_with_token_blocking(
+    port: u16,
+    method: &str,
+    params: serde_json::Value,
+    token: &str,
+) -> Result<serde_json::Value> {
+    runtime()?.block_on(extension_bridge::send_command_with_token(
+        port, method, params, token,
+    ))
+}
+
+/// Blocking version of [`extension_installer::download_and_install`], into
+/// the default install directory (or `ACTIONBOOK_EXTENSION_DIR`), from
+/// `source` or else the public GitHub releases.
+/// Returns the installed extension version.
+pub fn install_blocking(
+    source: Option<&extension_installer::ReleaseSource>,
+    force: bool,
+) -> Result<String> {
+    let dir = extension_installer::extension_dir(None)?;
+    let source = source.cloned().unwrap_or_default();
+    runtime()?.block_on(extension_installer::download_and_install(&dir, &source, force))
+}
+
+/// Blocking version of [`extension_bridge::is_bridge_running`].
+/// Returns `false` if a runtime cannot be started.
+pub fn is_bridge_running_blocking(port: u16) -> bool {
+    runtime()
+        .map(|rt| rt.block_on(extension_bridge::is_bridge_running(port)))
+        .unwrap_or(false)
+}
@@ -1,3 +1,6 @@
+use std::path::Path;
+
+use crate::browser::extension_installer;
 use crate::error::{ActionbookError, Result};
 use serde::Deserialize;
 
@@ -16,28 +19,133 @@ struct CdpTarget {
     web_socket_debugger_url: String,
 }
 
+/// URL of a CDP HTTP endpoint such as `/json/version` on `host:port`.
+/// IPv6 literals are bracketed.
+pub fn cdp_http_url(host: &str, port: u16, path: &str) -> String {
+    if host.contains(':') && !host.starts_with('[') {
+        format!("http://[{}]:{}{}", host, port, path)
+    } else {
+        format!("http://{}:{}{}", host, port, path)
+    }
+}
+
+/// Chrome's `/json/version` payload: which browser is behind a CDP port.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct CdpVersion {
+    /// e.g. "Chrome/131.0.6778.85" or "HeadlessChrome/131.0.6778.85"
+    #[serde(rename = "Browser")]
+    pub browser: String,
+    /// DevTools protocol version, e.g. "1.3"
+    #[serde(rename = "Protocol-Version")]
+    pub protocol_version: String,
+    #[serde(rename = "User-Agent", default)]
+    pub user_agent: String,
+    #[serde(rename = "V8-Version", default)]
+    pub v8_version: String,
+    #[serde(rename = "WebKit-Version", default)]
+    pub webkit_version: String,
+    /// Browser-level WebSocket endpoint
+    #[serde(rename = "webSocketDebuggerUrl", default)]
+    pub web_socket_debugger_url: String,
+}
+
+/// HTTP client shared by the CDP endpoint helpers: no proxy (CDP is
+/// usually on localhost) and a 5 second timeout per request. Built on first
+/// use so repeated polling reuses one connection pool.
+pub fn shared_client() -> &'static reqwest::Client {
+    static CLIENT: std::sync::OnceLock<reqwest::Client> = std::sync::OnceLock::new();
+    CLIENT.get_or_init(|| {
+        reqwest::Client::builder()
+            .no_proxy()
+            .timeout(std::time::Duration::from_secs(5))
+            .build()
+            .unwrap_or_else(|_| reqwest::Client::new())
+    })
+}
+
+/// Fetch and parse Chrome's `/json/version` on the given CDP host and port.
+pub async fn get_version(cdp_host: &str, cdp_port: u16) -> Result<CdpVersion> {
+    get_version_with(shared_client(), cdp_host, cdp_port).await
+}
+
+/// [`get_version`] using the caller's HTTP client.
+pub async fn get_version_with(
+    client: &reqwest::Client,
+    cdp_host: &str,
+    cdp_port: u16,
+) -> Result<CdpVersion> {
+    let url = cdp_http_url(cdp_host, cdp_port, "/json/version");
+    let resp = client.get(&url).send().await.map_err(|e| {
+        ActionbookError::CdpConnectionFailed(format!(
+            "Cannot reach CDP at {}. Is the browser running with --remote-debugging-port={}? \
+             Error: {}",
+            url, cdp_port, e
+        ))
+    })?;
+    if !resp.status().is_success() {
+        return Err(ActionbookError::CdpConnectionFailed(format!(
+            "CDP /json/version returned {}",
+            resp.status()
+        )));
+    }
+    Ok(resp.json().await?)
+}
+
+/// Attempts for a single `/json/list` fetch before giving up on a transient
+/// connection error or server error.
+const TARGET_LIST_ATTEMPTS: u32 = 3;
+
+/// Fetch and parse Chrome's `/json/list` on the given CDP host and port, retrying
+/// request failures and 5xx responses with a short backoff. This is separate
+/// from callers polling for a target that hasn't appeared yet.
+async fn fetch_targets(
+    client: &reqwest::Client,
+    cdp_host: &str,
+    cdp_port: u16,
+) -> Result<Vec<CdpTarget>> {
+    let url = cdp_http_url(cdp_host, cdp_port, "/json/list");
+
+    let mut delay_ms = 100u64;
+    let mut attempt = 1;
+    loop {
+        let failure = match client.get(&url).send().await {
+            Ok(resp) if resp.status().is_server_error() => {
+                format!("CDP /json/list returned {}", resp.status())
+            }
+            Ok(resp) => {
+                return Ok(resp.json().await?);
+            }
+            Err(e) => format!("Failed to query CDP /json/list: {}", e),
+        };
+
+        if attempt >= TARGET_LIST_ATTEMPTS {
+            return Err(ActionbookError::Other(failure));
+        }
+        tracing::debug!(
+            "{} (attempt {}/{}), retrying",
+            failure,
+            attempt,
+            TARGET_LIST_ATTEMPTS
+        );
+        tokio::time::sleep(std::time::Duration::from_millis(delay_ms)).await;
+        delay_ms *= 2;
+        attempt += 1;
+    }
+}
+
 /// Query Chrome's `/json/list` and find the service worker target for the given extension ID.
 ///
 /// Returns the `webSocketDebuggerUrl` for the matching target.
-async fn find_service_worker_target(cdp_port: u16, ext_id: &str) -> Result<String> {
-    let url = format!("http://127.0.0.1:{}/json/list", cdp_port);
-    let client = reqwest::Client::builder()
-        .no_proxy()
-        .timeout(std::time::Duration::from_secs(5))
-        .build()
-        .unwrap_or_else(|_| reqwest::Client::new());
-
-    let targets: Vec<CdpTarget> = client
-        .get(&url)
-        .send()
-        .await
-        .map_err(|e| ActionbookError::Other(format!("Failed to query CDP /json/list: {}", e)))?
-        .json()
-        .await
-        .map_err(|e| ActionbookError::Other(format!("Failed to parse CDP /json/list: {}", e)))?;
+async fn find_service_worker_target(
+    client: &reqwest::Client,
+    cdp_host: &str,
+    cdp_port: u16,
+    ext_id: &str,
+) -> Result<String> {
+    let targets = fetch_targets(client, cdp_host, cdp_port).await?;
 
     let pattern = format!("chrome-extension://{}/", ext_id);
-    targets
+    let ws_url = targets
         .into_iter()
         .find(|t| t.r#type == "service_worker" && t.url.starts_with(&pattern))
         .map(|t| t.web_socket_debugger_url)
@@ -47,146 +155,369 @@ async fn find_service_worker_target(cdp_port: u16, ext_id: &str) -> Result<Strin
                 "No service_worker target found for extension {}",
                 ext_id
             ))
-        })
+        })?;
+    check_ws_url_endpoint(&ws_url, cdp_host, cdp_port)?;
+    Ok(ws_url)
+}
+
+/// Reject a target's `webSocketDebuggerUrl` unless it is a `ws://` or `wss://`
+/// URL on the CDP endpoint it was listed by. A remote or compromised endpoint
+/// could otherwise send us (and the bridge token) to any host.
+fn check_ws_url_endpoint(ws_url: &str, cdp_host: &str, cdp_port: u16) -> Result<()> {
+    let unexpected = || ActionbookError::CdpUnexpectedWebSocketUrl {
+        url: ws_url.to_string(),
+        expected: cdp_http_url(cdp_host, cdp_port, ""),
+    };
+    let url = reqwest::Url::parse(ws_url).map_err(|_| unexpected())?;
+    let strip = |host: &str| host.trim_start_matches('[').trim_end_matches(']').to_lowercase();
+    let same_host = url.host_str().map(strip) == Some(strip(cdp_host));
+    let same_port = url.port_or_known_default() == Some(cdp_port);
+    if matches!(url.scheme(), "ws" | "wss") && same_host && same_port {
+        Ok(())
+    } else {
+        Err(unexpected())
+    }
 }
 
-/// The service worker filename used by the Actionbook extension.
+/// The service worker filename used by the Actionbook extension when the
+/// installed manifest doesn't say otherwise.
 /// Used to distinguish our extension from other extensions when the ext_id is unknown.
-const ACTIONBOOK_SW_FILENAME: &str = "background.js";
+const DEFAULT_SW_FILENAME: &str = "background.js";
+
+/// Service worker script of the extension installed in `dir`, per its
+/// manifest's `background.service_worker`, falling back to `background.js`.
+fn sw_filename_for(dir: &Path) -> String {
+    extension_installer::service_worker_path(dir).unwrap_or_else(|| DEFAULT_SW_FILENAME.to_string())
+}
+
+/// Whether a target URL is an extension service worker running `sw_filename`.
+fn is_extension_sw_url(url: &str, sw_filename: &str) -> bool {
+    url.starts_with("chrome-extension://") && url.ends_with(&format!("/{}", sw_filename))
+}
 
 /// Find the Actionbook extension's service worker target (when ext_id is unknown,
 /// e.g. already-running case).
 ///
-/// Matches `service_worker` targets whose URL matches `chrome-extension://<id>/background.js`
-/// to avoid injecting the token into a different extension's storage.
-async fn find_any_extension_service_worker(cdp_port: u16) -> Result<(String, String)> {
-    let url = format!("http://127.0.0.1:{}/json/list", cdp_port);
-    let client = reqwest::Client::builder()
-        .no_proxy()
-        .timeout(std::time::Duration::from_secs(5))
-        .build()
-        .unwrap_or_else(|_| reqwest::Client::new());
-
-    let targets: Vec<CdpTarget> = client
-        .get(&url)
-        .send()
-        .await
-        .map_err(|e| ActionbookError::Other(format!("Failed to query CDP /json/list: {}", e)))?
-        .json()
-        .await
-        .map_err(|e| ActionbookError::Other(format!("Failed to parse CDP /json/list: {}", e)))?;
+/// Matches `service_worker` targets whose URL matches `chrome-extension://<id>/<sw>`,
+/// where `<sw>` comes from the manifest installed in `ext_dir` (default
+/// `background.js`), to avoid injecting the token into a different extension's storage.
+async fn find_any_extension_service_worker(
+    client: &reqwest::Client,
+    cdp_host: &str,
+    cdp_port: u16,
+    ext_dir: &Path,
+) -> Result<(String, String)> {
+    let targets = fetch_targets(client, cdp_host, cdp_port).await?;
 
     // Match only service workers whose URL ends with our known SW filename.
     // This prevents injecting the bridge token into a random third-party
     // extension's chrome.storage.local.
-    let sw_suffix = format!("/{}", ACTIONBOOK_SW_FILENAME);
-    targets
+    let sw_filename = sw_filename_for(ext_dir);
+    let (ws_url, sw_url) = targets
         .into_iter()
-        .find(|t| {
-            t.r#type == "service_worker"
-                && t.url.starts_with("chrome-extension://")
-                && t.url.ends_with(&sw_suffix)
-        })
+        .find(|t| t.r#type == "service_worker" && is_extension_sw_url(&t.url, &sw_filename))
         .map(|t| (t.web_socket_debugger_url.clone(), t.url.clone()))
         .filter(|(ws, _)| !ws.is_empty())
         .ok_or_else(|| {
-            ActionbookError::ExtensionError(
+            ActionbookError::ExtensionError(format!(
                 "No Actionbook extension service_worker target found via CDP. \
-                 Looking for a service_worker with background.js"
-                    .to_string(),
-            )
-        })
+                 Looking for a service_worker with {}",
+                sw_filename
+            ))
+        })?;
+    check_ws_url_endpoint(&ws_url, cdp_host, cdp_port)?;
+    Ok((ws_url, sw_url))
 }
 
-/// Connect to a target's WebSocket and evaluate a JS expression via `Runtime.evaluate`.
-///
-/// Returns the stringified result or an error.
-async fn evaluate_in_target(ws_url: &str, expression: &str) -> Result<serde_json::Value> {
-    use futures::SinkExt;
-    use tokio_tungstenite::tungstenite::Message;
-
-    let (mut ws_stream, _) = tokio_tungstenite::connect_async(ws_url)
-        .await
-        .map_err(|e| {
-            ActionbookError::Other(format!("Failed to connect to CDP WebSocket {}: {}", ws_url, e))
-        })?;
+type CdpSocket =
+    tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>;
 
-    let request = serde_json::json!({
-        "id": 1,
-        "method": "Runtime.evaluate",
-        "params": {
-            "expression": expression,
-            "awaitPromise": true,
-            "returnByValue": true
-        }
-    });
-
-    ws_stream
-        .send(Message::Text(request.to_string().into()))
-        .await
-        .map_err(|e| ActionbookError::Other(format!("Failed to send CDP evaluate: {}", e)))?;
-
-    // Read messages until we get our response (id: 1)
-    use futures::StreamExt;
-    let timeout = tokio::time::Duration::from_secs(10);
-    let result = tokio::time::timeout(timeout, async {
-        while let Some(msg) = ws_stream.next().await {
-            let msg = msg.map_err(|e| {
-                ActionbookError::Other(format!("CDP WebSocket read error: {}", e))
+/// How long a single CDP command may take before it is abandoned.
+const CDP_COMMAND_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// An open DevTools WebSocket to one target. Commands get monotonically
+/// increasing ids and responses are matched by id, so one client can issue
+/// any number of commands; events received in between are skipped.
+pub(crate) struct CdpClient {
+    ws: CdpSocket,
+    next_id: u64,
+    /// Events that arrived while waiting for a response, oldest first
+    events: std::collections::VecDeque<serde_json::Value>,
+}
+
+/// Events kept for [`CdpClient::next_event`] while a command is in flight.
+const MAX_BUFFERED_EVENTS: usize = 1000;
+
+impl CdpClient {
+    /// Open a WebSocket to a target's `webSocketDebuggerUrl`.
+    pub(crate) async fn connect(ws_url: &str) -> Result<Self> {
+        let (ws, _) = tokio_tungstenite::connect_async(ws_url)
+            .await
+            .map_err(|e| {
+                ActionbookError::CdpConnectionFailed(format!(
+                    "Failed to connect to CDP WebSocket {}: {}",
+                    ws_url, e
+                ))
             })?;
+        Ok(Self {
+            ws,
+            next_id: 1,
+            events: std::collections::VecDeque::new(),
+        })
+    }
+
+    /// Send `method` and return its `result`. A CDP-level error response
+    /// becomes an error naming the method.
+    pub(crate) async fn send(
+        &mut self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        let response = self.call(method, params).await?;
+        if let Some(error) = response.get("error") {
+            return Err(ActionbookError::CdpCommandFailed {
+                method: method.to_string(),
+                message: error
+                    .get("message")
+                    .and_then(|m| m.as_str())
+                    .unwrap_or("unknown error")
+                    .to_string(),
+            });
+        }
+        Ok(response
+            .get("result")
+            .cloned()
+            .unwrap_or(serde_json::Value::Null))
+    }
+
+    /// Evaluate a JS expression via `Runtime.evaluate`.
+    ///
+    /// Returns the expression's value (by value, so it must be JSON-serializable),
+    /// or `Null` for `undefined`. When `await_promise` is set a returned promise is
+    /// awaited first. A thrown JS error becomes [`ActionbookError::JavaScriptError`]
+    /// carrying its description.
+    pub(crate) async fn evaluate(
+        &mut self,
+        expression: &str,
+        await_promise: bool,
+    ) -> Result<serde_json::Value> {
+        let result = self
+            .send(
+                "Runtime.evaluate",
+                serde_json::json!({
+                    "expression": expression,
+                    "awaitPromise": await_promise,
+                    "returnByValue": true
+                }),
+            )
+            .await?;
+
+        if let Some(exception) = result.get("exceptionDetails") {
+            let desc = exception
+                .pointer("/exception/description")
+                .or_else(|| exception.get("text"))
+                .and_then(|d| d.as_str())
+                .unwrap_or("unknown exception");
+            return Err(ActionbookError::JavaScriptError(desc.to_string()));
+        }
+        Ok(result
+            .pointer("/result/value")
+            .cloned()
+            .unwrap_or(serde_json::Value::Null))
+    }
+
+    /// Wait for the next CDP event (a frame with a `method` and no `id`),
+    /// starting with any that arrived during earlier commands. Returns `None`
+    /// once the connection closes.
+    pub(crate) async fn next_event(&mut self) -> Result<Option<serde_json::Value>> {
+        use futures::StreamExt;
+        use tokio_tungstenite::tungstenite::Message;
 
+        if let Some(event) = self.events.pop_front() {
+            return Ok(Some(event));
+        }
+        while let Some(msg) = self.ws.next().await {
+            let msg = msg
+                .map_err(|e| ActionbookError::Other(format!("CDP WebSocket read error: {}", e)))?;
             if let Message::Text(text) = msg {
-                let parsed: serde_json::Value = serde_json::from_str(&text).map_err(|e| {
-                    ActionbookError::Other(format!("Failed to parse CDP response: {}", e))
-                })?;
+                let parsed: serde_json::Value = serde_json::from_str(&text)?;
+                if parsed.get("method").is_some() {
+                    return Ok(Some(parsed));
+                }
+            }
+        }
+        Ok(None)
+    }
 
-                if parsed.get("id").and_then(|v| v.as_i64()) == Some(1) {
-                    // Check for CDP-level error
-                    if let Some(error) = parsed.get("error") {
-                        let message = error
-                            .get("message")
-                            .and_then(|m| m.as_str())
-                            .unwrap_or("unknown");
-                        return Err(ActionbookError::Other(format!(
-                            "CDP Runtime.evaluate error: {}",
-                            message
-                        )));
-                    }
+    /// Close the WebSocket gracefully.
+    pub(crate) async fn close(mut self) {
+        let _ = self.ws.close(None).await;
+    }
 
-                    // Check for JS exception
-                    if let Some(result) = parsed.get("result") {
-                        if let Some(exception) = result.get("exceptionDetails") {
-                            let desc = exception
-                                .pointer("/exception/description")
-                                .and_then(|d| d.as_str())
-                                .unwrap_or("unknown exception");
-                            return Err(ActionbookError::Other(format!(
-                                "JS exception during token injection: {}",
-                                desc
-                            )));
-                        }
-                        return Ok(result.clone());
-                    }
+    /// Send a command and wait (bounded by [`CDP_COMMAND_TIMEOUT`]) for the
+    /// raw response frame carrying the same id.
+    async fn call(&mut self, method: &str, params: serde_json::Value) -> Result<serde_json::Value> {
+        use futures::{SinkExt, StreamExt};
+        use tokio_tungstenite::tungstenite::Message;
 
-                    return Ok(serde_json::Value::Null);
+        let id = self.next_id;
+        self.next_id += 1;
+        let request = serde_json::json!({ "id": id, "method": method, "params": params });
+        self.ws
+            .send(Message::Text(request.to_string().into()))
+            .await
+            .map_err(|e| ActionbookError::Other(format!("Failed to send CDP {}: {}", method, e)))?;
+
+        let ws = &mut self.ws;
+        let events = &mut self.events;
+        let response = tokio::time::timeout(CDP_COMMAND_TIMEOUT, async {
+            while let Some(msg) = ws.next().await {
+                let msg = msg.map_err(|e| {
+                    ActionbookError::Other(format!("CDP WebSocket read error: {}", e))
+                })?;
+                if let Message::Text(text) = msg {
+                    let parsed: serde_json::Value = serde_json::from_str(&text)?;
+                    if parsed.get("id").and_then(|v| v.as_u64()) == Some(id) {
+                        return Ok(parsed);
+                    }
+                    if parsed.get("method").is_some() && events.len() < MAX_BUFFERED_EVENTS {
+                        events.push_back(parsed);
+                    }
                 }
             }
+            Err(ActionbookError::Other(format!(
+                "CDP WebSocket closed before receiving {} response",
+                method
+            )))
+        })
+        .await;
+
+        response.unwrap_or_else(|_| {
+            Err(ActionbookError::Other(format!(
+                "Timed out waiting for CDP {} response ({}s)",
+                method,
+                CDP_COMMAND_TIMEOUT.as_secs()
+            )))
+        })
+    }
+}
+
+/// One console line from an extension's service worker.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ConsoleEntry {
+    /// `log`, `info`, `warning`, `error`, `debug`, ...
+    pub level: String,
+    pub text: String,
+}
+
+/// Extract a console entry from a `Runtime.consoleAPICalled`,
+/// `Log.entryAdded`, or `Runtime.exceptionThrown` event.
+pub(crate) fn parse_console_entry(event: &serde_json::Value) -> Option<ConsoleEntry> {
+    let params = event.get("params")?;
+    match event.get("method")?.as_str()? {
+        "Runtime.consoleAPICalled" => {
+            let text = params
+                .get("args")?
+                .as_array()?
+                .iter()
+                .map(|arg| match arg.get("value") {
+                    Some(serde_json::Value::String(s)) => s.clone(),
+                    Some(v) => v.to_string(),
+                    None => arg
+                        .get("description")
+                        .and_then(|d| d.as_str())
+                        .unwrap_or_else(|| arg.get("type").and_then(|t| t.as_str()).unwrap_or(""))
+                        .to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join(" ");
+            Some(ConsoleEntry {
+                level: params.get("type")?.as_str()?.to_string(),
+                text,
+            })
         }
-        Err(ActionbookError::Other(
-            "CDP WebSocket closed before receiving response".to_string(),
-        ))
-    })
-    .await;
+        "Log.entryAdded" => {
+            let entry = params.get("entry")?;
+            Some(ConsoleEntry {
+                level: entry.get("level")?.as_str()?.to_string(),
+                text: entry.get("text")?.as_str()?.to_string(),
+            })
+        }
+        "Runtime.exceptionThrown" => {
+            let details = params.get("exceptionDetails")?;
+            let text = details
+                .pointer("/exception/description")
+                .or_else(|| details.get("text"))?
+                .as_str()?
+                .to_string();
+            Some(ConsoleEntry {
+                level: "error".to_string(),
+                text,
+            })
+        }
+        _ => None,
+    }
+}
 
-    // Close the WebSocket gracefully
-    let _ = ws_stream.close(None).await;
+/// Attach to the service worker of the Actionbook extension installed in
+/// `ext_dir`, running on `cdp_host:cdp_port`, and pass each console entry to
+/// `on_entry`, starting with the ones Chrome has buffered. Runs until the
+/// target goes away.
+pub(crate) async fn stream_extension_logs(
+    cdp_host: &str,
+    cdp_port: u16,
+    ext_dir: &Path,
+    mut on_entry: impl FnMut(ConsoleEntry),
+) -> Result<()> {
+    let (ws_url, _sw_url) =
+        find_any_extension_service_worker(shared_client(), cdp_host, cdp_port, ext_dir).await?;
+    let mut client = CdpClient::connect(&ws_url).await?;
+    client.send("Runtime.enable", serde_json::json!({})).await?;
+    client.send("Log.enable", serde_json::json!({})).await?;
 
-    match result {
-        Ok(inner) => inner,
-        Err(_) => Err(ActionbookError::Other(
-            "Timed out waiting for CDP Runtime.evaluate response (10s)".to_string(),
-        )),
+    while let Some(event) = client.next_event().await? {
+        if let Some(entry) = parse_console_entry(&event) {
+            on_entry(entry);
+        }
     }
+    Ok(())
+}
+
+/// Connect to a target, evaluate one expression, and disconnect.
+/// See [`CdpClient::evaluate`].
+pub(crate) async fn evaluate_in_target(
+    ws_url: &str,
+    expression: &str,
+    await_promise: bool,
+) -> Result<serde_json::Value> {
+    let mut client = CdpClient::connect(ws_url).await?;
+    let result = client.evaluate(expression, await_promise).await;
+    client.close().await;
+    result
+}
+
+/// The `chrome.storage.local.set(...)` call that pairs the extension with a
+/// bridge. The token is JSON-escaped to prevent injection.
+fn token_injection_expression(token: &str, bridge_port: u16) -> Result<String> {
+    let token_json = serde_json::to_string(token)?;
+    Ok(format!(
+        "chrome.storage.local.set({{ bridgeToken: {}, bridgePort: {} }})",
+        token_json, bridge_port
+    ))
+}
+
+/// First wait between service worker polls in [`inject_token_via_cdp`]
+const SW_POLL_INITIAL_DELAY_MS: u64 = 200;
+
+/// Longest wait between service worker polls
+const SW_POLL_MAX_DELAY_MS: u64 = 2000;
+
+/// A random wait in `[delay_ms / 2, delay_ms]` ("equal jitter"), so isolated
+/// instances started together stop polling CDP in lockstep. Never longer
+/// than `delay_ms`, so the backoff cap still holds.
+fn jittered_delay_ms(delay_ms: u64, rng: &mut impl rand::Rng) -> u64 {
+    let half = delay_ms / 2;
+    half + rng.gen_range(0..=delay_ms - half)
 }
 
 /// Inject bridge token and port into the extension's `chrome.storage.local` via CDP.
@@ -194,20 +525,22 @@ async fn evaluate_in_target(ws_url: &str, expression: &str) -> Result<serde_json
 /// This polls for the extension's service worker target (it may not appear immediately
 /// after `Extensions.loadUnpacked`), then evaluates `chrome.storage.local.set(...)`.
 pub async fn inject_token_via_cdp(
+    cdp_host: &str,
     cdp_port: u16,
     ext_id: &str,
     token: &str,
     bridge_port: u16,
 ) -> Result<()> {
-    // Poll for the service worker target with exponential backoff
+    // Poll for the service worker target with jittered exponential backoff
     let mut ws_url = None;
-    let mut delay_ms = 200u64;
+    let mut delay_ms = SW_POLL_INITIAL_DELAY_MS;
     for attempt in 1..=15 {
-        match find_service_worker_target(cdp_port, ext_id).await {
+        match find_service_worker_target(shared_client(), cdp_host, cdp_port, ext_id).await {
             Ok(url) => {
                 ws_url = Some(url);
                 break;
             }
+            Err(e @ ActionbookError::CdpUnexpectedWebSocketUrl { .. }) => return Err(e),
             Err(e) => {
                 tracing::debug!(
                     "CDP SW target not found (attempt {}/15): {}",
@@ -215,8 +548,9 @@ pub async fn inject_token_via_cdp(
                     e
                 );
                 if attempt < 15 {
-                    tokio::time::sleep(std::time::Duration::from_millis(delay_ms)).await;
-                    delay_ms = (delay_ms * 2).min(2000);
+                    let sleep_ms = jittered_delay_ms(delay_ms, &mut rand::thread_rng());
+                    tokio::time::sleep(std::time::Duration::from_millis(sleep_ms)).await;
+                    delay_ms = (delay_ms * 2).min(SW_POLL_MAX_DELAY_MS);
                 }
             }
         }
@@ -228,17 +562,8 @@ pub async fn inject_token_via_cdp(
         )
     })?;
 
-    // JSON-escape the token to prevent injection
-    let token_json = serde_json::to_string(token).map_err(|e| {
-        ActionbookError::Other(format!("Failed to JSON-encode token: {}", e))
-    })?;
-
-    let expression = format!(
-        "chrome.storage.local.set({{ bridgeToken: {}, bridgePort: {} }})",
-        token_json, bridge_port
-    );
-
-    evaluate_in_target(&ws_url, &expression).await?;
+    let expression = token_injection_expression(token, bridge_port)?;
+    evaluate_in_target(&ws_url, &expression, true).await?;
 
     Ok(())
 }
@@ -248,30 +573,117 @@ pub async fn inject_token_via_cdp(
 /// Used when Chrome is already running and we need to find the extension's
 /// service worker without knowing the extension ID upfront.
 pub async fn inject_token_existing(
+    cdp_host: &str,
     cdp_port: u16,
+    ext_dir: &Path,
     token: &str,
     bridge_port: u16,
 ) -> Result<()> {
-    let (ws_url, _sw_url) = find_any_extension_service_worker(cdp_port).await?;
+    let (ws_url, _sw_url) =
+        find_any_extension_service_worker(shared_client(), cdp_host, cdp_port, ext_dir).await?;
+
+    let expression = token_injection_expression(token, bridge_port)?;
+    evaluate_in_target(&ws_url, &expression, true).await?;
+
+    Ok(())
+}
 
-    let token_json = serde_json::to_string(token).map_err(|e| {
-        ActionbookError::Other(format!("Failed to JSON-encode token: {}", e))
+/// Evaluated in the extension's service worker to reload the extension from
+/// its unpacked directory.
+const RELOAD_EXTENSION_EXPRESSION: &str = "chrome.runtime.reload()";
+
+/// How long [`reload_extension`] waits for the old service worker to go away.
+const RELOAD_SETTLE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Reload the Actionbook extension installed in `ext_dir` in the Chrome on
+/// `cdp_host:cdp_port` and, given a `(token, bridge_port)` pairing, inject it
+/// into the restarted service worker. Returns the extension ID.
+pub async fn reload_extension(
+    cdp_host: &str,
+    cdp_port: u16,
+    ext_dir: &Path,
+    pairing: Option<(&str, u16)>,
+) -> Result<String> {
+    let (ws_url, sw_url) =
+        find_any_extension_service_worker(shared_client(), cdp_host, cdp_port, ext_dir).await?;
+    let ext_id = extension_id_from_url(&sw_url).ok_or_else(|| {
+        ActionbookError::ExtensionError(format!(
+            "Cannot read the extension ID from service worker URL {}",
+            sw_url
+        ))
     })?;
 
-    let expression = format!(
-        "chrome.storage.local.set({{ bridgeToken: {}, bridgePort: {} }})",
-        token_json, bridge_port
-    );
+    trigger_reload(&ws_url).await?;
+    wait_for_target_gone(cdp_host, cdp_port, &ws_url).await;
 
-    evaluate_in_target(&ws_url, &expression).await?;
+    if let Some((token, bridge_port)) = pairing {
+        inject_token_via_cdp(cdp_host, cdp_port, ext_id, token, bridge_port).await?;
+    }
+    Ok(ext_id.to_string())
+}
 
-    Ok(())
+/// `abc` from `chrome-extension://abc/background.js`.
+fn extension_id_from_url(url: &str) -> Option<&str> {
+    url.strip_prefix("chrome-extension://")?
+        .split('/')
+        .next()
+        .filter(|id| !id.is_empty())
+}
+
+/// Ask the service worker at `ws_url` to reload its extension. The worker is
+/// torn down mid-call, so losing the connection counts as success; only an
+/// exception thrown by the expression is an error.
+async fn trigger_reload(ws_url: &str) -> Result<()> {
+    let mut client = CdpClient::connect(ws_url).await?;
+    match client.evaluate(RELOAD_EXTENSION_EXPRESSION, false).await {
+        Ok(_) => {
+            client.close().await;
+            Ok(())
+        }
+        Err(e @ ActionbookError::JavaScriptError(_)) => Err(e),
+        Err(e) => {
+            tracing::debug!("Service worker went away while reloading: {}", e);
+            Ok(())
+        }
+    }
+}
+
+/// Poll `/json/list` until the target at `ws_url` is gone, so the next
+/// lookup finds the restarted worker. Gives up quietly after
+/// [`RELOAD_SETTLE_TIMEOUT`].
+async fn wait_for_target_gone(cdp_host: &str, cdp_port: u16, ws_url: &str) {
+    let started = std::time::Instant::now();
+    while started.elapsed() < RELOAD_SETTLE_TIMEOUT {
+        match fetch_targets(shared_client(), cdp_host, cdp_port).await {
+            Ok(targets) if targets.iter().all(|t| t.web_socket_debugger_url != ws_url) => return,
+            Ok(_) => {}
+            Err(e) => tracing::debug!("Target list unavailable during reload: {}", e),
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+    tracing::debug!("Old service worker still listed after {:?}", RELOAD_SETTLE_TIMEOUT);
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn poll_delay_jitter_stays_within_bounds() {
+        let mut rng = rand::thread_rng();
+        let mut delay_ms = SW_POLL_INITIAL_DELAY_MS;
+        for _ in 0..10 {
+            for _ in 0..200 {
+                let jittered = jittered_delay_ms(delay_ms, &mut rng);
+                assert!(jittered >= delay_ms / 2, "{} < {}/2", jittered, delay_ms);
+                assert!(jittered <= delay_ms, "{} > {}", jittered, delay_ms);
+                assert!(jittered <= SW_POLL_MAX_DELAY_MS);
+            }
+            delay_ms = (delay_ms * 2).min(SW_POLL_MAX_DELAY_MS);
+        }
+        assert_eq!(jittered_delay_ms(0, &mut rng), 0);
+    }
+
     #[test]
     fn cdp_target_deserialize() {
         let json = r#"[
@@ -308,12 +720,7 @@ mod tests {
     #[test]
     fn expression_format() {
         let token = "abk_0123456789abcdef0123456789abcdef";
-        let port = 19222u16;
-        let token_json = serde_json::to_string(token).unwrap();
-        let expr = format!(
-            "chrome.storage.local.set({{ bridgeToken: {}, bridgePort: {} }})",
-            token_json, port
-        );
+        let expr = token_injection_expression(token, 19222).unwrap();
         assert!(expr.contains("bridgeToken: \"abk_0123456789abcdef0123456789abcdef\""));
         assert!(expr.contains("bridgePort: 19222"));
     }
@@ -322,24 +729,429 @@ mod tests {
     fn sw_filename_filter_matches_actionbook_only() {
         // Actionbook extension: background.js → should match
         let actionbook_url = "chrome-extension://abcdef123456/background.js";
-        let sw_suffix = format!("/{}", ACTIONBOOK_SW_FILENAME);
         assert!(
-            actionbook_url.starts_with("chrome-extension://") && actionbook_url.ends_with(&sw_suffix),
+            is_extension_sw_url(actionbook_url, DEFAULT_SW_FILENAME),
             "Actionbook SW URL should match the filter"
         );
 
         // Third-party extension with different SW filename → should NOT match
         let other_url = "chrome-extension://xyz789/service-worker.js";
         assert!(
-            !(other_url.starts_with("chrome-extension://") && other_url.ends_with(&sw_suffix)),
+            !is_extension_sw_url(other_url, DEFAULT_SW_FILENAME),
             "Non-Actionbook SW URL should not match the filter"
         );
 
         // Another third-party with no path suffix → should NOT match
         let bare_url = "chrome-extension://xyz789/sw.js";
         assert!(
-            !(bare_url.starts_with("chrome-extension://") && bare_url.ends_with(&sw_suffix)),
+            !is_extension_sw_url(bare_url, DEFAULT_SW_FILENAME),
             "Non-Actionbook bare SW URL should not match the filter"
         );
     }
+
+    /// Serve one canned HTTP response per connection, in order.
+    async fn serve_responses(responses: Vec<(&'static str, &'static str)>) -> u16 {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            for (status, body) in responses {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut buf = [0u8; 1024];
+                let _ = stream.read(&mut buf).await;
+                let reply = format!(
+                    "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                let _ = stream.write_all(reply.as_bytes()).await;
+            }
+        });
+        port
+    }
+
+    #[test]
+    fn cdp_http_url_brackets_ipv6_hosts() {
+        assert_eq!(
+            cdp_http_url("chrome-sidecar", 9222, "/json/version"),
+            "http://chrome-sidecar:9222/json/version"
+        );
+        assert_eq!(
+            cdp_http_url("::1", 9222, "/json/list"),
+            "http://[::1]:9222/json/list"
+        );
+    }
+
+    #[tokio::test]
+    async fn fetch_targets_retries_server_error() {
+        let targets = r#"[{"type": "service_worker", "url": "chrome-extension://abc/background.js", "webSocketDebuggerUrl": "ws://127.0.0.1:9333/devtools/page/ABC"}]"#;
+        let port = serve_responses(vec![
+            ("500 Internal Server Error", "oops"),
+            ("200 OK", targets),
+        ])
+        .await;
+
+        let list = fetch_targets(shared_client(), "127.0.0.1", port)
+            .await
+            .expect("second attempt should succeed");
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0].r#type, "service_worker");
+    }
+
+    #[tokio::test]
+    async fn fetch_targets_gives_up_after_retries() {
+        let port = serve_responses(vec![
+            ("500 Internal Server Error", "oops"),
+            ("503 Service Unavailable", "oops"),
+            ("502 Bad Gateway", "oops"),
+        ])
+        .await;
+
+        let err = fetch_targets(shared_client(), "127.0.0.1", port)
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("502"), "unexpected error: {}", err);
+    }
+
+    /// Serve one canned `/json/list` response, built from the server's own
+    /// port, and hand back the raw request.
+    async fn serve_capturing_request(
+        body: impl FnOnce(u16) -> String,
+    ) -> (u16, tokio::sync::oneshot::Receiver<String>) {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let body = body(port);
+        let (request_tx, request_rx) = tokio::sync::oneshot::channel();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 2048];
+            let n = stream.read(&mut buf).await.unwrap_or(0);
+            let _ = request_tx.send(String::from_utf8_lossy(&buf[..n]).into_owned());
+            let reply = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\
+                 Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            let _ = stream.write_all(reply.as_bytes()).await;
+        });
+        (port, request_rx)
+    }
+
+    #[tokio::test]
+    async fn service_worker_lookup_uses_the_given_client() {
+        let (port, request) =
+            serve_capturing_request(|port| sw_target_list("127.0.0.1", port)).await;
+
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert("x-cdp-caller", "custom".parse().unwrap());
+        let client = reqwest::Client::builder()
+            .no_proxy()
+            .default_headers(headers)
+            .build()
+            .unwrap();
+
+        let ws = find_service_worker_target(&client, "127.0.0.1", port, "abc")
+            .await
+            .expect("target should be found");
+        assert_eq!(ws, format!("ws://127.0.0.1:{}/devtools/page/ABC", port));
+
+        let request = request.await.unwrap().to_lowercase();
+        assert!(request.starts_with("get /json/list "), "unexpected request: {}", request);
+        assert!(request.contains("x-cdp-caller: custom"), "unexpected request: {}", request);
+    }
+
+    /// A `/json/list` body with one Actionbook service worker whose
+    /// WebSocket URL points at `ws_host:ws_port`.
+    fn sw_target_list(ws_host: &str, ws_port: u16) -> String {
+        serde_json::json!([{
+            "type": "service_worker",
+            "url": "chrome-extension://abc/background.js",
+            "webSocketDebuggerUrl": format!("ws://{}:{}/devtools/page/ABC", ws_host, ws_port),
+        }])
+        .to_string()
+    }
+
+    #[tokio::test]
+    async fn service_worker_on_another_host_is_rejected() {
+        let (port, _request) =
+            serve_capturing_request(|port| sw_target_list("attacker.example", port)).await;
+
+        let err = find_service_worker_target(shared_client(), "127.0.0.1", port, "abc")
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, ActionbookError::CdpUnexpectedWebSocketUrl { .. }),
+            "unexpected error: {}",
+            err
+        );
+        assert!(err.to_string().contains("attacker.example"), "unexpected error: {}", err);
+    }
+
+    #[test]
+    fn ws_url_must_match_the_cdp_endpoint() {
+        let ok = |url, host, port| check_ws_url_endpoint(url, host, port).is_ok();
+
+        assert!(ok("ws://127.0.0.1:9222/devtools/page/A", "127.0.0.1", 9222));
+        assert!(ok("ws://LOCALHOST:9222/devtools/page/A", "localhost", 9222));
+        assert!(ok("ws://[::1]:9222/devtools/page/A", "::1", 9222));
+        assert!(ok("wss://chrome-sidecar/devtools/page/A", "chrome-sidecar", 443));
+
+        assert!(!ok("ws://evil.example:9222/devtools/page/A", "127.0.0.1", 9222));
+        assert!(!ok("ws://127.0.0.1:9333/devtools/page/A", "127.0.0.1", 9222));
+        assert!(!ok("http://127.0.0.1:9222/devtools/page/A", "127.0.0.1", 9222));
+        assert!(!ok("not a url", "127.0.0.1", 9222));
+    }
+
+    #[test]
+    fn cdp_version_deserializes_json_version_payload() {
+        let payload = r#"{
+            "Browser": "Chrome/131.0.6778.85",
+            "Protocol-Version": "1.3",
+            "User-Agent": "Mozilla/5.0 (X11; Linux x86_64) Chrome/131.0.6778.85 Safari/537.36",
+            "V8-Version": "13.1.201.13",
+            "WebKit-Version": "537.36 (@7a3a3a1fa1e4e4c5a9b1b52d8d0c6a1a0a8e6d1c)",
+            "webSocketDebuggerUrl": "ws://127.0.0.1:9222/devtools/browser/5f0e-42"
+        }"#;
+        let version: CdpVersion = serde_json::from_str(payload).unwrap();
+        assert_eq!(version.browser, "Chrome/131.0.6778.85");
+        assert_eq!(version.protocol_version, "1.3");
+        assert!(version.user_agent.contains("Linux x86_64"));
+        assert_eq!(version.v8_version, "13.1.201.13");
+        assert_eq!(
+            version.web_socket_debugger_url,
+            "ws://127.0.0.1:9222/devtools/browser/5f0e-42"
+        );
+
+        // Older or embedded builds may leave out the optional fields
+        let minimal: CdpVersion =
+            serde_json::from_str(r#"{"Browser": "Chrome/120.0", "Protocol-Version": "1.3"}"#)
+                .unwrap();
+        assert!(minimal.web_socket_debugger_url.is_empty());
+    }
+
+    #[tokio::test]
+    async fn get_version_reports_http_errors() {
+        let port = serve_responses(vec![("404 Not Found", "{}")]).await;
+        let err = get_version("127.0.0.1", port).await.unwrap_err().to_string();
+        assert!(err.contains("404"), "unexpected error: {}", err);
+    }
+
+    /// Accept one CDP connection and answer its first command with `reply`
+    /// (its `id` filled in). Returns the WebSocket URL.
+    async fn mock_cdp_target(reply: serde_json::Value) -> String {
+        use futures::{SinkExt, StreamExt};
+        use tokio_tungstenite::tungstenite::Message;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            while let Some(Ok(msg)) = ws.next().await {
+                if let Message::Text(text) = msg {
+                    let cmd: serde_json::Value = serde_json::from_str(text.as_str()).unwrap();
+                    let mut reply = reply.clone();
+                    reply["id"] = cmd["id"].clone();
+                    let _ = ws.send(Message::Text(reply.to_string().into())).await;
+                }
+            }
+        });
+        format!("ws://127.0.0.1:{}/devtools/page/MOCK", port)
+    }
+
+    #[tokio::test]
+    async fn evaluate_returns_plain_value() {
+        let ws_url = mock_cdp_target(serde_json::json!({
+            "result": { "result": { "type": "number", "value": 2, "description": "2" } }
+        }))
+        .await;
+        let value = evaluate_in_target(&ws_url, "1+1", false).await.unwrap();
+        assert_eq!(value, serde_json::json!(2));
+    }
+
+    #[tokio::test]
+    async fn client_issues_distinct_ids_over_one_connection() {
+        use futures::{SinkExt, StreamExt};
+        use tokio_tungstenite::tungstenite::Message;
+
+        // Answer each command with its own id as the value, on one connection
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            while let Some(Ok(msg)) = ws.next().await {
+                if let Message::Text(text) = msg {
+                    let cmd: serde_json::Value = serde_json::from_str(text.as_str()).unwrap();
+                    let event = serde_json::json!({ "method": "Runtime.consoleAPICalled" });
+                    let reply = serde_json::json!({
+                        "id": cmd["id"],
+                        "result": { "result": { "type": "number", "value": cmd["id"] } }
+                    });
+                    let _ = ws.send(Message::Text(event.to_string().into())).await;
+                    let _ = ws.send(Message::Text(reply.to_string().into())).await;
+                }
+            }
+        });
+
+        let mut client = CdpClient::connect(&format!("ws://127.0.0.1:{}/devtools/page/MOCK", port))
+            .await
+            .unwrap();
+        let first = client.evaluate("1", false).await.unwrap();
+        let second = client.evaluate("2", false).await.unwrap();
+        client.close().await;
+
+        assert_eq!(first, serde_json::json!(1));
+        assert_eq!(second, serde_json::json!(2));
+    }
+
+    #[test]
+    fn extension_id_comes_from_service_worker_url() {
+        assert_eq!(
+            extension_id_from_url("chrome-extension://abcdef123456/background.js"),
+            Some("abcdef123456")
+        );
+        assert_eq!(extension_id_from_url("chrome-extension:///background.js"), None);
+        assert_eq!(extension_id_from_url("https://example.com/sw.js"), None);
+    }
+
+    #[tokio::test]
+    async fn reload_evaluates_runtime_reload_and_tolerates_disconnect() {
+        use futures::StreamExt;
+        use tokio_tungstenite::tungstenite::Message;
+
+        // Like a real service worker: take the command, then vanish unanswered
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let (seen_tx, seen_rx) = tokio::sync::oneshot::channel();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            while let Some(Ok(msg)) = ws.next().await {
+                if let Message::Text(text) = msg {
+                    let cmd: serde_json::Value = serde_json::from_str(text.as_str()).unwrap();
+                    let _ = seen_tx.send(cmd);
+                    return;
+                }
+            }
+        });
+
+        trigger_reload(&format!("ws://127.0.0.1:{}/devtools/page/SW", port))
+            .await
+            .unwrap();
+        let cmd = seen_rx.await.unwrap();
+        assert_eq!(cmd["method"], "Runtime.evaluate");
+        assert_eq!(cmd["params"]["expression"], "chrome.runtime.reload()");
+    }
+
+    #[tokio::test]
+    async fn reload_reports_exception_from_service_worker() {
+        let ws_url = mock_cdp_target(serde_json::json!({
+            "result": {
+                "result": { "type": "object", "subtype": "error" },
+                "exceptionDetails": {
+                    "text": "Uncaught",
+                    "exception": { "description": "TypeError: chrome.runtime is undefined" }
+                }
+            }
+        }))
+        .await;
+        let err = trigger_reload(&ws_url).await.unwrap_err();
+        assert!(matches!(err, ActionbookError::JavaScriptError(_)), "{}", err);
+    }
+
+    #[test]
+    fn console_entry_from_console_api_called() {
+        let event = serde_json::json!({
+            "method": "Runtime.consoleAPICalled",
+            "params": {
+                "type": "warning",
+                "args": [
+                    { "type": "string", "value": "[actionbook] pairing failed:" },
+                    { "type": "number", "value": 401, "description": "401" },
+                    { "type": "object", "className": "Error", "description": "Error: bad token" }
+                ],
+                "executionContextId": 1,
+                "timestamp": 1700000000000.0
+            }
+        });
+        assert_eq!(
+            parse_console_entry(&event),
+            Some(ConsoleEntry {
+                level: "warning".to_string(),
+                text: "[actionbook] pairing failed: 401 Error: bad token".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn console_entry_from_log_and_exception_events() {
+        let log = serde_json::json!({
+            "method": "Log.entryAdded",
+            "params": { "entry": { "level": "error", "text": "net::ERR_CONNECTION_REFUSED", "source": "network" } }
+        });
+        assert_eq!(parse_console_entry(&log).unwrap().level, "error");
+
+        let thrown = serde_json::json!({
+            "method": "Runtime.exceptionThrown",
+            "params": { "exceptionDetails": { "text": "Uncaught", "exception": { "description": "TypeError: x is undefined" } } }
+        });
+        assert_eq!(
+            parse_console_entry(&thrown).unwrap().text,
+            "TypeError: x is undefined"
+        );
+
+        let other =
+            serde_json::json!({ "method": "Runtime.executionContextCreated", "params": {} });
+        assert_eq!(parse_console_entry(&other), None);
+    }
+
+    #[tokio::test]
+    async fn evaluate_maps_thrown_error_to_javascript_error() {
+        let ws_url = mock_cdp_target(serde_json::json!({
+            "result": {
+                "result": { "type": "object", "subtype": "error" },
+                "exceptionDetails": {
+                    "text": "Uncaught",
+                    "exception": { "description": "Error: boom\n    at <anonymous>:1:7" }
+                }
+            }
+        }))
+        .await;
+        let err = evaluate_in_target(&ws_url, "throw new Error('boom')", false)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ActionbookError::JavaScriptError(_)));
+        assert!(err.to_string().contains("Error: boom"), "{}", err);
+    }
+
+    #[test]
+    fn sw_filename_follows_installed_manifest() {
+        let tmp = tempfile::tempdir().unwrap();
+
+        // No manifest (or no background entry) → background.js
+        assert_eq!(sw_filename_for(tmp.path()), "background.js");
+
+        std::fs::write(
+            tmp.path().join("manifest.json"),
+            r#"{"version": "0.4.0", "background": {"service_worker": "./dist/worker.js"}}"#,
+        )
+        .unwrap();
+        let sw = sw_filename_for(tmp.path());
+        assert_eq!(sw, "dist/worker.js");
+        assert!(is_extension_sw_url(
+            "chrome-extension://abc/dist/worker.js",
+            &sw
+        ));
+        assert!(!is_extension_sw_url(
+            "chrome-extension://abc/background.js",
+            &sw
+        ));
+    }
 }
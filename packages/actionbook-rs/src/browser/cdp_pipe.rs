@@ -8,20 +8,25 @@ use std::os::unix::io::FromRawFd;
 /// Minimal CDP pipe client for communicating with Chrome over fd 3/4.
 ///
 /// Chrome's pipe transport uses null-terminated (`\0`) JSON-RPC messages.
-/// This is used exclusively to send `Extensions.loadUnpacked` after launch,
-/// then converted to a [`PipeKeepAlive`] handle that must be held for the
+/// This is used to send `Extensions.loadUnpacked` after launch, then
+/// converted to a [`PipeKeepAlive`] handle that must be held for the
 /// duration of the Chrome process.
 pub struct CdpPipe {
     reader: std::fs::File,
     writer: std::fs::File,
 }
 
-/// Opaque handle that keeps the CDP pipe's write end open.
+/// Handle that keeps the CDP pipe's write end open.
 ///
 /// Chrome exits when it detects EOF on its pipe input (fd 3).
 /// This handle keeps the parent's write end open, preventing EOF.
-/// Drop it when Chrome should exit.
-pub struct PipeKeepAlive(#[allow(dead_code)] std::fs::File);
+/// Drop it when Chrome should exit. Holding it is all most callers need;
+/// [`send`](Self::send) issues further commands over the same pipe.
+pub struct PipeKeepAlive {
+    reader: std::fs::File,
+    writer: std::fs::File,
+    next_id: u64,
+}
 
 /// Result of creating pipe pairs for Chrome's `--remote-debugging-pipe`.
 ///
@@ -49,12 +54,18 @@ pub fn create_pipe_pair() -> Result<PipePair> {
     use std::os::unix::io::IntoRawFd;
 
     // Pipe 1: parent -> Chrome (commands). Chrome reads from fd 3.
-    let (cmd_reader, cmd_writer) = os_pipe::pipe()
-        .map_err(|e| ActionbookError::Other(format!("Failed to create command pipe: {}", e)))?;
+    let (cmd_reader, cmd_writer) =
+        os_pipe::pipe().map_err(|source| ActionbookError::CdpPipeIo {
+            op: "command channel setup",
+            source,
+        })?;
 
     // Pipe 2: Chrome -> parent (responses). Chrome writes to fd 4.
-    let (resp_reader, resp_writer) = os_pipe::pipe()
-        .map_err(|e| ActionbookError::Other(format!("Failed to create response pipe: {}", e)))?;
+    let (resp_reader, resp_writer) =
+        os_pipe::pipe().map_err(|source| ActionbookError::CdpPipeIo {
+            op: "response channel setup",
+            source,
+        })?;
 
     let child_read_fd = cmd_reader.into_raw_fd();
     let child_write_fd = resp_writer.into_raw_fd();
@@ -102,129 +113,171 @@ impl CdpPipe {
             mut writer,
         } = self;
 
-        use std::io::Write;
-
-        let abs_path = path.canonicalize().map_err(|e| {
-            ActionbookError::ExtensionError(format!(
-                "Failed to canonicalize extension path {}: {}",
-                path.display(),
-                e
-            ))
-        })?;
-
         let request = serde_json::json!({
             "id": 1,
             "method": "Extensions.loadUnpacked",
             "params": {
-                "path": abs_path.to_string_lossy()
+                "path": extension_path_param(path)?
             }
         });
-
-        let mut msg = serde_json::to_string(&request).map_err(|e| {
-            ActionbookError::Other(format!("Failed to serialize CDP request: {}", e))
-        })?;
-        msg.push('\0');
-
-        tracing::debug!("CDP pipe -> {}", msg.trim_end_matches('\0'));
-
-        writer.write_all(msg.as_bytes()).map_err(|e| {
-            ActionbookError::ExtensionError(format!("Failed to write to CDP pipe: {}", e))
-        })?;
-        writer.flush().map_err(|e| {
-            ActionbookError::ExtensionError(format!("Failed to flush CDP pipe: {}", e))
-        })?;
-
-        let ext_id = read_null_terminated_response(&mut reader)?;
-
-        // Drop reader (no longer needed). Keep writer open so Chrome
-        // doesn't see EOF on fd 3 and exit.
-        drop(reader);
-
-        Ok((ext_id, PipeKeepAlive(writer)))
+        write_null_terminated_message(&mut writer, &request)?;
+
+        let response = read_null_terminated_message(&mut reader)?;
+        let ext_id = Self::parse_load_extension_response(&response)?;
+
+        // Keep writer open so Chrome doesn't see EOF on fd 3 and exit; the
+        // reader is kept for later commands sent through the handle.
+        Ok((
+            ext_id,
+            PipeKeepAlive {
+                reader,
+                writer,
+                next_id: 2,
+            },
+        ))
     }
 
     /// Parse a CDP response JSON string and extract the extension ID or error.
     fn parse_load_extension_response(response_str: &str) -> Result<String> {
-        let response: serde_json::Value =
-            serde_json::from_str(response_str).map_err(|e| {
-                ActionbookError::ExtensionError(format!(
-                    "Failed to parse CDP pipe response: {} (raw: {})",
-                    e, response_str
-                ))
-            })?;
-
+        let response = parse_pipe_message(response_str)?;
         if let Some(error) = response.get("error") {
-            let message = error
-                .get("message")
-                .and_then(|m| m.as_str())
-                .unwrap_or("unknown error");
-            return Err(ActionbookError::ExtensionError(format!(
-                "CDP Extensions.loadUnpacked failed: {}",
-                message
-            )));
+            return Err(command_failed("Extensions.loadUnpacked", error));
         }
 
         let ext_id = response
             .get("result")
             .and_then(|r| r.get("id"))
             .and_then(|id| id.as_str())
-            .ok_or_else(|| {
-                ActionbookError::ExtensionError(format!(
-                    "CDP response missing result.id: {}",
-                    response_str
-                ))
+            .ok_or_else(|| ActionbookError::CdpResponseMissingField {
+                method: "Extensions.loadUnpacked",
+                field: "result.id",
+                response: response_str.to_string(),
             })?;
 
         Ok(ext_id.to_string())
     }
 }
 
+impl PipeKeepAlive {
+    /// Send a CDP command over the retained pipe and return its `result`.
+    ///
+    /// Messages with other ids (events, or responses nobody waited for)
+    /// are skipped. **Blocking**, like [`CdpPipe::load_extension`].
+    #[allow(dead_code)]
+    pub fn send(&mut self, method: &str, params: serde_json::Value) -> Result<serde_json::Value> {
+        let id = self.next_id;
+        self.next_id += 1;
+        let request = serde_json::json!({ "id": id, "method": method, "params": params });
+        write_null_terminated_message(&mut self.writer, &request)?;
+
+        loop {
+            let raw = read_null_terminated_message(&mut self.reader)?;
+            let response = parse_pipe_message(&raw)?;
+            if response.get("id").and_then(|i| i.as_u64()) != Some(id) {
+                continue;
+            }
+            if let Some(error) = response.get("error") {
+                return Err(command_failed(method, error));
+            }
+            return Ok(response
+                .get("result")
+                .cloned()
+                .unwrap_or(serde_json::Value::Null));
+        }
+    }
+}
+
 /// Maximum size for a CDP pipe response (1 MB). A legitimate
 /// `Extensions.loadUnpacked` response is well under 1 KB; this limit
 /// prevents unbounded memory growth from malformed or unexpected data.
 const MAX_PIPE_RESPONSE_SIZE: usize = 1_048_576;
 
-/// Read a single null-terminated response from the pipe and parse the extension ID.
-fn read_null_terminated_response(reader: &mut std::fs::File) -> Result<String> {
+/// Absolute form of `path` as sent in `Extensions.loadUnpacked`. JSON can
+/// only carry UTF-8, so a path that is not valid UTF-8 is rejected rather
+/// than sent with replacement characters.
+fn extension_path_param(path: &Path) -> Result<String> {
+    let abs_path =
+        path.canonicalize()
+            .map_err(|source| ActionbookError::ExtensionPathInaccessible {
+                path: path.to_path_buf(),
+                source,
+            })?;
+    abs_path
+        .into_os_string()
+        .into_string()
+        .map_err(|raw| ActionbookError::ExtensionPathNotUtf8(raw.into()))
+}
+
+/// Parse one message read from the pipe.
+fn parse_pipe_message(raw: &str) -> Result<serde_json::Value> {
+    serde_json::from_str(raw).map_err(|source| ActionbookError::CdpPipeInvalidJson {
+        raw: raw.to_string(),
+        source,
+    })
+}
+
+/// The error for a CDP `error` object answering `method`.
+fn command_failed(method: &str, error: &serde_json::Value) -> ActionbookError {
+    ActionbookError::CdpCommandFailed {
+        method: method.to_string(),
+        message: error
+            .get("message")
+            .and_then(|m| m.as_str())
+            .unwrap_or("unknown error")
+            .to_string(),
+    }
+}
+
+/// Write one request to the pipe, terminated by `\0`.
+fn write_null_terminated_message(
+    writer: &mut std::fs::File,
+    request: &serde_json::Value,
+) -> Result<()> {
+    use std::io::Write;
+
+    let mut msg = serde_json::to_string(request)?;
+    msg.push('\0');
+
+    tracing::debug!("CDP pipe -> {}", msg.trim_end_matches('\0'));
+
+    writer
+        .write_all(msg.as_bytes())
+        .map_err(|source| ActionbookError::CdpPipeIo { op: "write", source })?;
+    writer
+        .flush()
+        .map_err(|source| ActionbookError::CdpPipeIo { op: "flush", source })
+}
+
+/// Read a single null-terminated message from the pipe.
+fn read_null_terminated_message(reader: &mut std::fs::File) -> Result<String> {
     use std::io::Read;
 
     let mut buf = Vec::with_capacity(4096);
     let mut byte = [0u8; 1];
     loop {
         match reader.read(&mut byte) {
-            Ok(0) => {
-                return Err(ActionbookError::ExtensionError(
-                    "CDP pipe closed before receiving response".to_string(),
-                ));
-            }
+            Ok(0) => return Err(ActionbookError::CdpPipeClosed),
             Ok(_) => {
                 if byte[0] == 0 {
                     break;
                 }
                 if buf.len() >= MAX_PIPE_RESPONSE_SIZE {
-                    return Err(ActionbookError::ExtensionError(
-                        "CDP pipe response exceeded maximum size (1 MB)".to_string(),
-                    ));
+                    return Err(ActionbookError::CdpPipeResponseTooLarge {
+                        limit: MAX_PIPE_RESPONSE_SIZE,
+                    });
                 }
                 buf.push(byte[0]);
             }
             Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
-            Err(e) => {
-                return Err(ActionbookError::ExtensionError(format!(
-                    "Failed to read from CDP pipe: {}",
-                    e
-                )));
-            }
+            Err(source) => return Err(ActionbookError::CdpPipeIo { op: "read", source }),
         }
     }
 
-    let response_str = String::from_utf8(buf).map_err(|e| {
-        ActionbookError::ExtensionError(format!("CDP pipe response is not valid UTF-8: {}", e))
-    })?;
+    let response_str = String::from_utf8(buf)?;
 
     tracing::debug!("CDP pipe <- {}", response_str);
 
-    CdpPipe::parse_load_extension_response(&response_str)
+    Ok(response_str)
 }
 
 #[cfg(test)]
@@ -314,6 +367,11 @@ mod tests {
         let resp = r#"{"id":1,"result":{}}"#;
         let err = CdpPipe::parse_load_extension_response(resp).unwrap_err();
         let msg = err.to_string();
+        assert!(
+            matches!(err, ActionbookError::CdpResponseMissingField { field: "result.id", .. }),
+            "unexpected error: {:?}",
+            err
+        );
         assert!(
             msg.contains("missing result.id"),
             "Error should mention missing result.id, got: {}",
@@ -327,8 +385,9 @@ mod tests {
         let resp = "not json";
         let err = CdpPipe::parse_load_extension_response(resp).unwrap_err();
         let msg = err.to_string();
+        assert!(matches!(err, ActionbookError::CdpPipeInvalidJson { .. }));
         assert!(
-            msg.contains("Failed to parse CDP pipe response"),
+            msg.contains("CDP pipe response is not valid JSON"),
             "Error should mention parse failure, got: {}",
             msg
         );
@@ -389,6 +448,124 @@ mod tests {
         handle.join().unwrap();
     }
 
+    /// The request carries the exact path, spaces and non-ASCII included
+    #[test]
+    fn load_extension_sends_exact_unicode_path() {
+        use std::os::unix::io::IntoRawFd;
+
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().join("my ext — Ümlaut 拡張 🧩");
+        std::fs::create_dir(&dir).unwrap();
+        let expected = dir.canonicalize().unwrap().to_str().unwrap().to_string();
+
+        let (cmd_reader, cmd_writer) = os_pipe::pipe().unwrap();
+        let (resp_reader, resp_writer) = os_pipe::pipe().unwrap();
+        let child_read_fd = cmd_reader.into_raw_fd();
+        let child_write_fd = resp_writer.into_raw_fd();
+        let cdp = CdpPipe {
+            writer: unsafe { std::fs::File::from_raw_fd(cmd_writer.into_raw_fd()) },
+            reader: unsafe { std::fs::File::from_raw_fd(resp_reader.into_raw_fd()) },
+        };
+
+        let handle = std::thread::spawn(move || {
+            let mut reader = unsafe { std::fs::File::from_raw_fd(child_read_fd) };
+            let mut writer = unsafe { std::fs::File::from_raw_fd(child_write_fd) };
+            let request = read_null_terminated_message(&mut reader).unwrap();
+            let resp = serde_json::json!({"id": 1, "result": {"id": "test-extension-id"}});
+            write_null_terminated_message(&mut writer, &resp).unwrap();
+            serde_json::from_str::<serde_json::Value>(&request).unwrap()
+        });
+
+        let (ext_id, _keepalive) = cdp.load_extension(&dir).unwrap();
+        assert_eq!(ext_id, "test-extension-id");
+
+        let request = handle.join().unwrap();
+        assert_eq!(request["params"]["path"].as_str(), Some(expected.as_str()));
+    }
+
+    /// A path JSON cannot represent is refused instead of being mangled
+    #[test]
+    fn extension_path_rejects_non_utf8() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().join(std::ffi::OsStr::from_bytes(b"ext-\xff"));
+        std::fs::create_dir(&dir).unwrap();
+
+        let err = extension_path_param(&dir).unwrap_err();
+        match err {
+            ActionbookError::ExtensionPathNotUtf8(path) => {
+                assert_eq!(path, dir.canonicalize().unwrap());
+            }
+            other => panic!("expected ExtensionPathNotUtf8, got {:?}", other),
+        }
+    }
+
+    /// A command sent after load_extension reuses the retained pipe
+    #[test]
+    fn keepalive_sends_follow_up_command() {
+        use std::os::unix::io::IntoRawFd;
+
+        let (cmd_reader, cmd_writer) = os_pipe::pipe().unwrap();
+        let (resp_reader, resp_writer) = os_pipe::pipe().unwrap();
+
+        let child_read_fd = cmd_reader.into_raw_fd();
+        let child_write_fd = resp_writer.into_raw_fd();
+
+        let cdp = CdpPipe {
+            writer: unsafe { std::fs::File::from_raw_fd(cmd_writer.into_raw_fd()) },
+            reader: unsafe { std::fs::File::from_raw_fd(resp_reader.into_raw_fd()) },
+        };
+
+        // Mock Chrome: answer two commands, with an event before the second reply
+        let handle = std::thread::spawn(move || {
+            let mut reader = unsafe { std::fs::File::from_raw_fd(child_read_fd) };
+            let mut writer = unsafe { std::fs::File::from_raw_fd(child_write_fd) };
+            let mut methods = Vec::new();
+            for reply in [
+                r#"{"id":1,"result":{"id":"test-extension-id"}}"#,
+                r#"{"method":"Target.targetCreated","params":{}}"#,
+            ] {
+                let mut buf = Vec::new();
+                let mut byte = [0u8; 1];
+                loop {
+                    reader.read_exact(&mut byte).unwrap();
+                    if byte[0] == 0 {
+                        break;
+                    }
+                    buf.push(byte[0]);
+                }
+                let req: serde_json::Value = serde_json::from_slice(&buf).unwrap();
+                methods.push(req["method"].as_str().unwrap().to_string());
+
+                writer.write_all(reply.as_bytes()).unwrap();
+                writer.write_all(&[0]).unwrap();
+                if methods.len() == 2 {
+                    let resp = serde_json::json!({
+                        "id": req["id"],
+                        "result": {"targetInfos": []}
+                    });
+                    writer.write_all(resp.to_string().as_bytes()).unwrap();
+                    writer.write_all(&[0]).unwrap();
+                }
+                writer.flush().unwrap();
+            }
+            methods
+        });
+
+        let (ext_id, mut keepalive) = cdp.load_extension(Path::new("/tmp")).unwrap();
+        assert_eq!(ext_id, "test-extension-id");
+
+        let result = keepalive
+            .send("Target.getTargets", serde_json::json!({}))
+            .unwrap();
+        assert_eq!(result, serde_json::json!({"targetInfos": []}));
+        assert_eq!(
+            handle.join().unwrap(),
+            ["Extensions.loadUnpacked", "Target.getTargets"]
+        );
+    }
+
     /// Verify PipeKeepAlive keeps the write end open
     #[test]
     fn keepalive_prevents_eof() {
@@ -0,0 +1,118 @@
+//! Isolated browser contexts (incognito-equivalent).
+//!
+//! A context created with `Target.createBrowserContext` has its own cookie
+//! jar and storage. Chrome ties it to the DevTools connection that created
+//! it, so each [`BrowserContext`] keeps its own browser WebSocket open and
+//! the context goes away when the handle is disposed or dropped.
+
+#![allow(dead_code)]
+
+use tokio::sync::Mutex;
+
+use super::cdp_http::CdpClient;
+use crate::error::{ActionbookError, Result};
+
+/// Handle to an isolated browser context. Cookies and storage set through
+/// one context are invisible to the default context and to other contexts.
+pub struct BrowserContext {
+    id: String,
+    conn: Mutex<CdpClient>,
+}
+
+impl BrowserContext {
+    /// The CDP `browserContextId`.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Open a new page in this context and return its target id.
+    pub async fn new_page(&self, url: &str) -> Result<String> {
+        let result = self
+            .conn
+            .lock()
+            .await
+            .send(
+                "Target.createTarget",
+                serde_json::json!({ "url": url, "browserContextId": self.id }),
+            )
+            .await?;
+        result
+            .get("targetId")
+            .and_then(|v| v.as_str())
+            .map(str::to_string)
+            .ok_or_else(|| {
+                ActionbookError::Other("Target.createTarget returned no targetId".to_string())
+            })
+    }
+
+    /// All cookies stored in this context.
+    pub async fn get_cookies(&self) -> Result<Vec<serde_json::Value>> {
+        let result = self
+            .conn
+            .lock()
+            .await
+            .send(
+                "Storage.getCookies",
+                serde_json::json!({ "browserContextId": self.id }),
+            )
+            .await?;
+        Ok(result
+            .get("cookies")
+            .and_then(|v| v.as_array())
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    /// Store a cookie in this context. `cookie` uses the CDP `CookieParam`
+    /// shape, e.g. `{"name", "value", "url"}` or `{"name", "value", "domain"}`.
+    pub async fn set_cookie(&self, cookie: serde_json::Value) -> Result<()> {
+        self.conn
+            .lock()
+            .await
+            .send(
+                "Storage.setCookies",
+                serde_json::json!({ "cookies": [cookie], "browserContextId": self.id }),
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Close every page in the context and discard its cookies and storage.
+    pub async fn dispose(self) -> Result<()> {
+        let mut conn = self.conn.into_inner();
+        conn.send(
+            "Target.disposeBrowserContext",
+            serde_json::json!({ "browserContextId": self.id }),
+        )
+        .await?;
+        conn.close().await;
+        Ok(())
+    }
+}
+
+/// Create a fresh browser context over the browser-level WebSocket
+/// `browser_ws_url`. The context is disposed automatically if the handle is
+/// dropped without calling [`BrowserContext::dispose`].
+pub(crate) async fn create(browser_ws_url: &str) -> Result<BrowserContext> {
+    let mut conn = CdpClient::connect(browser_ws_url).await?;
+    let result = conn
+        .send(
+            "Target.createBrowserContext",
+            serde_json::json!({ "disposeOnDetach": true }),
+        )
+        .await?;
+    let id = result
+        .get("browserContextId")
+        .and_then(|v| v.as_str())
+        .map(str::to_string)
+        .ok_or_else(|| {
+            ActionbookError::Other(
+                "Target.createBrowserContext returned no browserContextId".to_string(),
+            )
+        })?;
+
+    Ok(BrowserContext {
+        id,
+        conn: Mutex::new(conn),
+    })
+}
@@ -1,26 +1,127 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
 use crate::error::{ActionbookError, Result};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum BrowserType {
     Chrome,
     Brave,
     Edge,
     Arc,
     Chromium,
+    Vivaldi,
+    Opera,
+    Thorium,
+    /// A Chromium-based browser we have no probe for, named after its
+    /// executable (only arises from a user-specified path)
+    Other(String),
 }
 
 impl BrowserType {
-    pub fn name(&self) -> &'static str {
+    pub fn name(&self) -> &str {
         match self {
             BrowserType::Chrome => "Google Chrome",
             BrowserType::Brave => "Brave",
             BrowserType::Edge => "Microsoft Edge",
             BrowserType::Arc => "Arc",
             BrowserType::Chromium => "Chromium",
+            BrowserType::Vivaldi => "Vivaldi",
+            BrowserType::Opera => "Opera",
+            BrowserType::Thorium => "Thorium",
+            BrowserType::Other(name) => name,
+        }
+    }
+
+    /// Classify a browser executable by its file name, e.g.
+    /// `/usr/bin/vivaldi-stable` or `...\Application\msedge.exe`.
+    /// Unrecognized executables become [`BrowserType::Other`].
+    pub fn from_path(path: &Path) -> Self {
+        let file_name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let lower = file_name.to_ascii_lowercase();
+        let stem = lower.strip_suffix(".exe").unwrap_or(&lower);
+        match stem {
+            "google chrome"
+            | "google-chrome"
+            | "google-chrome-stable"
+            | "google-chrome-beta"
+            | "google-chrome-unstable"
+            | "google chrome beta"
+            | "google chrome dev"
+            | "google chrome canary"
+            | "chrome" => BrowserType::Chrome,
+            "brave browser" | "brave-browser" | "brave" => BrowserType::Brave,
+            "microsoft edge" | "microsoft-edge" | "microsoft-edge-stable" | "msedge" => {
+                BrowserType::Edge
+            }
+            "arc" => BrowserType::Arc,
+            "chromium" | "chromium-browser" => BrowserType::Chromium,
+            "vivaldi" | "vivaldi-stable" => BrowserType::Vivaldi,
+            "opera" => BrowserType::Opera,
+            "thorium" | "thorium-browser" => BrowserType::Thorium,
+            _ => BrowserType::Other(file_name),
+        }
+    }
+}
+
+/// Release channel of a browser install
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum BrowserChannel {
+    Stable,
+    Beta,
+    Dev,
+    Canary,
+}
+
+impl BrowserChannel {
+    /// Discovery order when the config doesn't set one: stable first
+    pub const DEFAULT_ORDER: [BrowserChannel; 4] = [
+        BrowserChannel::Stable,
+        BrowserChannel::Beta,
+        BrowserChannel::Dev,
+        BrowserChannel::Canary,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            BrowserChannel::Stable => "stable",
+            BrowserChannel::Beta => "beta",
+            BrowserChannel::Dev => "dev",
+            BrowserChannel::Canary => "canary",
+        }
+    }
+
+    /// Detect the channel from an install path, e.g. `google-chrome-beta`,
+    /// `Google Chrome Canary.app` or `Chrome Dev\Application\chrome.exe`.
+    /// Only multi-word path components are considered, so a plain `dev`
+    /// directory doesn't mark a stable build as Dev.
+    pub fn from_path(path: &Path) -> Self {
+        for component in path.components().rev() {
+            let lower = component.as_os_str().to_string_lossy().to_ascii_lowercase();
+            let words: Vec<&str> = lower
+                .split([' ', '-', '_', '.'])
+                .filter(|w| !w.is_empty())
+                .collect();
+            if words.len() < 2 {
+                continue;
+            }
+            for word in words {
+                match word {
+                    "canary" | "sxs" => return BrowserChannel::Canary,
+                    "beta" => return BrowserChannel::Beta,
+                    "dev" | "unstable" => return BrowserChannel::Dev,
+                    _ => {}
+                }
+            }
         }
+        BrowserChannel::Stable
     }
 }
 
@@ -29,14 +130,17 @@ pub struct BrowserInfo {
     pub browser_type: BrowserType,
     pub path: PathBuf,
     pub version: Option<String>,
+    pub channel: BrowserChannel,
 }
 
 impl BrowserInfo {
     pub fn new(browser_type: BrowserType, path: PathBuf) -> Self {
+        let channel = BrowserChannel::from_path(&path);
         Self {
             browser_type,
             path,
             version: None,
+            channel,
         }
     }
 
@@ -46,7 +150,7 @@ impl BrowserInfo {
     }
 }
 
-/// Discover the best available browser on the system
+/// Discover the best available browser on the system, stable channel first
 pub fn discover_browser() -> Result<BrowserInfo> {
     let browsers = discover_all_browsers();
 
@@ -58,24 +162,46 @@ pub fn discover_browser() -> Result<BrowserInfo> {
     Ok(browsers.into_iter().next().unwrap())
 }
 
-/// Discover all available browsers on the system
+/// Discover all available browsers on the system, stable channel first
 pub fn discover_all_browsers() -> Vec<BrowserInfo> {
+    discover_all_browsers_preferring(&BrowserChannel::DEFAULT_ORDER)
+}
+
+/// Discover all available browsers, one install per browser and channel,
+/// ordered by [`sort_by_channel`] with `channels`.
+pub fn discover_all_browsers_preferring(channels: &[BrowserChannel]) -> Vec<BrowserInfo> {
     let candidates = get_browser_candidates();
-    let mut found = Vec::new();
+    let mut found: Vec<BrowserInfo> = Vec::new();
 
     for (browser_type, paths) in candidates {
         for path in paths {
             let path = PathBuf::from(path);
-            if path.exists() {
-                found.push(BrowserInfo::new(browser_type, path).with_version());
-                break; // Found this browser type, move to next
+            let channel = BrowserChannel::from_path(&path);
+            let seen = found
+                .iter()
+                .any(|b| b.browser_type == browser_type && b.channel == channel);
+            if !seen && path.exists() {
+                found.push(BrowserInfo::new(browser_type.clone(), path).with_version());
             }
         }
     }
 
+    sort_by_channel(&mut found, channels);
     found
 }
 
+/// Order browsers by their channel's position in `channels`, keeping the
+/// discovery order among browsers of equally preferred channels. Channels
+/// missing from `channels` go last.
+pub fn sort_by_channel(browsers: &mut [BrowserInfo], channels: &[BrowserChannel]) {
+    browsers.sort_by_key(|b| {
+        channels
+            .iter()
+            .position(|c| *c == b.channel)
+            .unwrap_or(channels.len())
+    });
+}
+
 /// Get browser candidates based on the current platform
 fn get_browser_candidates() -> Vec<(BrowserType, Vec<&'static str>)> {
     #[cfg(target_os = "macos")]
@@ -86,6 +212,9 @@ fn get_browser_candidates() -> Vec<(BrowserType, Vec<&'static str>)> {
                 vec![
                     "/Applications/Google Chrome.app/Contents/MacOS/Google Chrome",
                     "~/Applications/Google Chrome.app/Contents/MacOS/Google Chrome",
+                    "/Applications/Google Chrome Beta.app/Contents/MacOS/Google Chrome Beta",
+                    "/Applications/Google Chrome Dev.app/Contents/MacOS/Google Chrome Dev",
+                    "/Applications/Google Chrome Canary.app/Contents/MacOS/Google Chrome Canary",
                 ],
             ),
             (
@@ -116,6 +245,27 @@ fn get_browser_candidates() -> Vec<(BrowserType, Vec<&'static str>)> {
                     "~/Applications/Chromium.app/Contents/MacOS/Chromium",
                 ],
             ),
+            (
+                BrowserType::Vivaldi,
+                vec![
+                    "/Applications/Vivaldi.app/Contents/MacOS/Vivaldi",
+                    "~/Applications/Vivaldi.app/Contents/MacOS/Vivaldi",
+                ],
+            ),
+            (
+                BrowserType::Opera,
+                vec![
+                    "/Applications/Opera.app/Contents/MacOS/Opera",
+                    "~/Applications/Opera.app/Contents/MacOS/Opera",
+                ],
+            ),
+            (
+                BrowserType::Thorium,
+                vec![
+                    "/Applications/Thorium.app/Contents/MacOS/Thorium",
+                    "~/Applications/Thorium.app/Contents/MacOS/Thorium",
+                ],
+            ),
         ]
     }
 
@@ -128,6 +278,7 @@ fn get_browser_candidates() -> Vec<(BrowserType, Vec<&'static str>)> {
                     "/usr/bin/google-chrome",
                     "/usr/bin/google-chrome-stable",
                     "/usr/bin/google-chrome-beta",
+                    "/usr/bin/google-chrome-unstable",
                     "/snap/bin/chromium",
                 ],
             ),
@@ -143,6 +294,18 @@ fn get_browser_candidates() -> Vec<(BrowserType, Vec<&'static str>)> {
                 BrowserType::Chromium,
                 vec!["/usr/bin/chromium", "/usr/bin/chromium-browser"],
             ),
+            (
+                BrowserType::Vivaldi,
+                vec!["/usr/bin/vivaldi", "/usr/bin/vivaldi-stable"],
+            ),
+            (BrowserType::Opera, vec!["/usr/bin/opera"]),
+            (
+                BrowserType::Thorium,
+                vec![
+                    "/usr/bin/thorium-browser",
+                    "/opt/chromium.org/thorium/thorium",
+                ],
+            ),
         ]
     }
 
@@ -154,6 +317,8 @@ fn get_browser_candidates() -> Vec<(BrowserType, Vec<&'static str>)> {
                 vec![
                     r"C:\Program Files\Google\Chrome\Application\chrome.exe",
                     r"C:\Program Files (x86)\Google\Chrome\Application\chrome.exe",
+                    r"C:\Program Files\Google\Chrome Beta\Application\chrome.exe",
+                    r"C:\Program Files\Google\Chrome Dev\Application\chrome.exe",
                 ],
             ),
             (
@@ -170,6 +335,27 @@ fn get_browser_candidates() -> Vec<(BrowserType, Vec<&'static str>)> {
                     r"C:\Program Files (x86)\Microsoft\Edge\Application\msedge.exe",
                 ],
             ),
+            (
+                BrowserType::Vivaldi,
+                vec![
+                    r"C:\Program Files\Vivaldi\Application\vivaldi.exe",
+                    r"C:\Program Files (x86)\Vivaldi\Application\vivaldi.exe",
+                ],
+            ),
+            (
+                BrowserType::Opera,
+                vec![
+                    r"C:\Program Files\Opera\opera.exe",
+                    r"C:\Program Files (x86)\Opera\opera.exe",
+                ],
+            ),
+            (
+                BrowserType::Thorium,
+                vec![
+                    r"C:\Program Files\Thorium\Application\thorium.exe",
+                    r"C:\Program Files (x86)\Thorium\Application\thorium.exe",
+                ],
+            ),
         ]
     }
 
@@ -211,8 +397,7 @@ mod tests {
     fn test_discover_browser() {
         // This test will pass on machines with a browser installed
         let result = discover_browser();
-        if result.is_ok() {
-            let info = result.unwrap();
+        if let Ok(info) = result {
             println!(
                 "Found browser: {} at {:?} (version: {:?})",
                 info.browser_type.name(),
@@ -234,4 +419,115 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_browser_type_from_path() {
+        let cases = [
+            (
+                "/Applications/Vivaldi.app/Contents/MacOS/Vivaldi",
+                BrowserType::Vivaldi,
+            ),
+            ("/usr/bin/vivaldi-stable", BrowserType::Vivaldi),
+            (
+                "/Applications/Opera.app/Contents/MacOS/Opera",
+                BrowserType::Opera,
+            ),
+            ("/usr/bin/opera", BrowserType::Opera),
+            ("/Applications/Arc.app/Contents/MacOS/Arc", BrowserType::Arc),
+            (
+                "/Applications/Thorium.app/Contents/MacOS/Thorium",
+                BrowserType::Thorium,
+            ),
+            ("/usr/bin/thorium-browser", BrowserType::Thorium),
+            ("/usr/bin/google-chrome-stable", BrowserType::Chrome),
+            ("/usr/bin/brave-browser", BrowserType::Brave),
+            ("/usr/bin/microsoft-edge", BrowserType::Edge),
+            ("/usr/bin/chromium-browser", BrowserType::Chromium),
+        ];
+        for (path, expected) in cases {
+            assert_eq!(
+                BrowserType::from_path(Path::new(path)),
+                expected,
+                "{}",
+                path
+            );
+        }
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_browser_type_from_windows_path() {
+        let cases = [
+            (
+                r"C:\Program Files\Vivaldi\Application\vivaldi.exe",
+                BrowserType::Vivaldi,
+            ),
+            (r"C:\Program Files\Opera\opera.exe", BrowserType::Opera),
+            (
+                r"C:\Program Files\Thorium\Application\thorium.exe",
+                BrowserType::Thorium,
+            ),
+            (
+                r"C:\Program Files\Microsoft\Edge\Application\msedge.exe",
+                BrowserType::Edge,
+            ),
+        ];
+        for (path, expected) in cases {
+            assert_eq!(
+                BrowserType::from_path(Path::new(path)),
+                expected,
+                "{}",
+                path
+            );
+        }
+    }
+
+    #[test]
+    fn test_unknown_executable_is_other() {
+        let browser_type = BrowserType::from_path(Path::new("/opt/yandex/browser/yandex_browser"));
+        assert_eq!(
+            browser_type,
+            BrowserType::Other("yandex_browser".to_string())
+        );
+        assert_eq!(browser_type.name(), "yandex_browser");
+    }
+
+    #[test]
+    fn test_channel_from_path() {
+        let cases = [
+            ("/usr/bin/google-chrome", BrowserChannel::Stable),
+            ("/usr/bin/google-chrome-beta", BrowserChannel::Beta),
+            ("/usr/bin/google-chrome-unstable", BrowserChannel::Dev),
+            (
+                "/Applications/Google Chrome Canary.app/Contents/MacOS/Google Chrome Canary",
+                BrowserChannel::Canary,
+            ),
+            ("/home/me/dev/chrome/chrome", BrowserChannel::Stable),
+        ];
+        for (path, channel) in cases {
+            assert_eq!(BrowserChannel::from_path(Path::new(path)), channel, "{}", path);
+        }
+    }
+
+    #[test]
+    fn test_preferred_channel_sorts_first() {
+        let mut browsers: Vec<BrowserInfo> = [
+            "/usr/bin/google-chrome",
+            "/usr/bin/google-chrome-beta",
+            "/usr/bin/google-chrome-unstable",
+        ]
+        .iter()
+        .map(|p| BrowserInfo::new(BrowserType::Chrome, PathBuf::from(p)))
+        .collect();
+
+        sort_by_channel(&mut browsers, &BrowserChannel::DEFAULT_ORDER);
+        assert_eq!(browsers[0].channel, BrowserChannel::Stable);
+
+        sort_by_channel(&mut browsers, &[BrowserChannel::Beta]);
+        let order: Vec<_> = browsers.iter().map(|b| b.channel).collect();
+        assert_eq!(
+            order,
+            [BrowserChannel::Beta, BrowserChannel::Stable, BrowserChannel::Dev]
+        );
+    }
 }
@@ -1,19 +1,60 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::io::{IsTerminal, Write};
 use std::net::SocketAddr;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use futures::{SinkExt, StreamExt};
 use rand::Rng;
+use serde::{Deserialize, Serialize};
 use subtle::ConstantTimeEq;
 use tokio::net::{TcpListener, TcpStream};
 use tokio::sync::{mpsc, oneshot, Mutex};
 use tokio_tungstenite::tungstenite::http::StatusCode;
 use tokio_tungstenite::tungstenite::Message;
 
+use super::native_messaging;
+use crate::clock::{Clock, SystemClock};
 use crate::error::{ActionbookError, Result};
 
+/// `println!` for human-readable bridge status. With `--json`, stdout is
+/// reserved for machine-readable events, so the line goes to stderr instead.
+/// The line is also copied to the `--log-file`, if one is open.
+macro_rules! status_println {
+    ($json:expr) => {{
+        crate::log_file::tee(format_args!(""));
+        if $json {
+            eprintln!()
+        } else {
+            crate::browser::extension_bridge::clear_live_line();
+            println!()
+        }
+    }};
+    ($json:expr, $($arg:tt)*) => {{
+        crate::log_file::tee(format_args!($($arg)*));
+        if $json {
+            eprintln!($($arg)*)
+        } else {
+            crate::browser::extension_bridge::clear_live_line();
+            println!($($arg)*)
+        }
+    }};
+}
+pub(crate) use status_println;
+
+/// Whether the `serve --live` summary currently occupies the last stdout line
+static LIVE_LINE: AtomicBool = AtomicBool::new(false);
+
+/// Erase the `serve --live` summary line, if shown, so a status line can take
+/// its place; the next refresh redraws the summary below it.
+pub(crate) fn clear_live_line() {
+    if LIVE_LINE.swap(false, Ordering::SeqCst) {
+        print!("\r\x1b[2K");
+    }
+}
+
 /// CDP method risk levels for the command allowlist.
 /// L1 = read-only, L2 = page modification, L3 = high risk.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -78,6 +119,57 @@ const TOKEN_TTL_SECS: u64 = 30 * 60;
 /// Minimum protocol version we accept in hello handshake.
 const PROTOCOL_VERSION: &str = "0.2.0";
 
+/// Wire protocol revision this bridge speaks by default.
+/// Exchanged as `proto` in hello / hello_ack; bump when the message shape changes.
+pub const BRIDGE_PROTO: u32 = 1;
+
+/// Error returned to CLI clients when no extension is connected but one was
+/// recently rejected for presenting a token other than the bridge's current one.
+pub const EXTENSION_TOKEN_MISMATCH: &str =
+    "Extension connected but authenticated with a different token";
+
+/// How long a rejected extension token is reported to CLI clients.
+const TOKEN_MISMATCH_WINDOW_SECS: u64 = 5 * 60;
+
+/// How long shutdown keeps serving in-flight requests before closing anyway.
+const SHUTDOWN_DRAIN_SECS: u64 = 5;
+
+/// How long the bridge waits for the extension to answer a command.
+const COMMAND_TIMEOUT_SECS: u64 = 30;
+
+/// How long a timed-out request id is remembered, so a response arriving
+/// after the deadline is reported as late rather than unknown.
+const LATE_RESPONSE_WINDOW_SECS: u64 = 5 * 60;
+
+/// Cap on remembered timed-out request ids.
+const MAX_TIMED_OUT_TRACKED: usize = 256;
+
+/// Close reason sent to connections that arrive after shutdown has begun.
+pub const SHUTTING_DOWN_REASON: &str = "shutting down";
+
+/// Default cap on the serialized `params` of a forwarded command. Larger
+/// payloads (e.g. multi-MB data: URLs) can overwhelm the extension.
+pub const DEFAULT_MAX_PARAMS_BYTES: usize = 4 * 1024 * 1024;
+
+/// Error code for a command whose `params` exceed the configured limit.
+pub const PARAMS_TOO_LARGE_CODE: i64 = -32003;
+
+/// All wire protocol revisions this bridge can speak.
+const SUPPORTED_PROTOS: &[u32] = &[1];
+
+/// Pick the protocol revision for a client's hello.
+/// Clients that predate the `proto` field are treated as revision 1;
+/// a `proto` that is present but not a number is rejected.
+fn negotiate_proto(client_proto: Option<&serde_json::Value>) -> Option<u32> {
+    match client_proto {
+        None => Some(1),
+        Some(p) => p
+            .as_u64()
+            .and_then(|p| u32::try_from(p).ok())
+            .filter(|p| SUPPORTED_PROTOS.contains(p)),
+    }
+}
+
 /// Generate a new session token: `abk_` + 32 random hex characters.
 pub fn generate_token() -> String {
     let mut rng = rand::thread_rng();
@@ -95,9 +187,13 @@ pub fn token_file_path() -> Result<PathBuf> {
 }
 
 /// Write the session token to disk with mode 0600.
-/// Uses atomic write pattern: write to temp file with restricted permissions, then rename.
 pub async fn write_token_file(token: &str) -> Result<()> {
-    let path = token_file_path()?;
+    write_private_file(&token_file_path()?, token).await
+}
+
+/// Write `contents` to `path` with mode 0600.
+/// Uses atomic write pattern: write to temp file with restricted permissions, then rename.
+async fn write_private_file(path: &Path, contents: &str) -> Result<()> {
     if let Some(parent) = path.parent() {
         tokio::fs::create_dir_all(parent).await?;
     }
@@ -109,21 +205,109 @@ pub async fn write_token_file(token: &str) -> Result<()> {
         let mut opts = tokio::fs::OpenOptions::new();
         opts.write(true).create(true).truncate(true).mode(0o600);
         let mut file = opts.open(&tmp_path).await?;
-        tokio::io::AsyncWriteExt::write_all(&mut file, token.as_bytes()).await?;
+        tokio::io::AsyncWriteExt::write_all(&mut file, contents.as_bytes()).await?;
         tokio::io::AsyncWriteExt::flush(&mut file).await?;
         drop(file);
         // Atomic rename
-        tokio::fs::rename(&tmp_path, &path).await?;
+        tokio::fs::rename(&tmp_path, path).await?;
     }
 
     #[cfg(not(unix))]
     {
-        tokio::fs::write(&path, token).await?;
+        tokio::fs::write(path, contents).await?;
     }
 
     Ok(())
 }
 
+/// Path to the stable token file: `~/.local/share/actionbook/bridge-token.stable`
+///
+/// Unlike `bridge-token`, this outlives the bridge so `serve --stable-token`
+/// can hand the same token to an already-configured extension after a restart.
+pub fn stable_token_file_path() -> Result<PathBuf> {
+    let data_dir = dirs::data_local_dir().ok_or_else(|| {
+        ActionbookError::Other("Cannot determine local data directory".to_string())
+    })?;
+    Ok(data_dir.join("actionbook").join("bridge-token.stable"))
+}
+
+/// Token for `serve --stable-token`: the one saved by a previous run, or a
+/// freshly generated one (saved for next time) when there is none, it is
+/// malformed or readable by other users, or `rotate` is set.
+pub async fn load_or_create_stable_token(rotate: bool) -> Result<String> {
+    load_or_create_stable_token_at(&stable_token_file_path()?, rotate).await
+}
+
+async fn load_or_create_stable_token_at(path: &Path, rotate: bool) -> Result<String> {
+    if !rotate {
+        if let Some(token) = read_stable_token(path).await {
+            return Ok(token);
+        }
+    }
+    let token = generate_token();
+    write_private_file(path, &token).await?;
+    Ok(token)
+}
+
+async fn read_stable_token(path: &Path) -> Option<String> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = tokio::fs::metadata(path).await.ok()?.permissions().mode();
+        if mode & 0o077 != 0 {
+            tracing::warn!(
+                "Ignoring {} (mode {:o}); generating a new stable token",
+                path.display(),
+                mode & 0o777
+            );
+            return None;
+        }
+    }
+    let token = tokio::fs::read_to_string(path)
+        .await
+        .ok()?
+        .trim()
+        .to_string();
+    is_well_formed_token(&token).then_some(token)
+}
+
+/// Whether `token` looks like one made by [`generate_token`].
+fn is_well_formed_token(token: &str) -> bool {
+    token
+        .strip_prefix(TOKEN_PREFIX)
+        .is_some_and(|hex| hex.len() == 32 && hex.bytes().all(|b| b.is_ascii_hexdigit()))
+}
+
+/// Token for `serve --token-stdin`: the first line of `input`, which must
+/// look like one made by [`generate_token`].
+pub fn read_supplied_token(mut input: impl std::io::BufRead) -> Result<String> {
+    let mut line = String::new();
+    input.read_line(&mut line)?;
+    let token = line.trim();
+    if token.is_empty() {
+        return Err(ActionbookError::ExtensionError(
+            "--token-stdin: no token on stdin".to_string(),
+        ));
+    }
+    if !is_well_formed_token(token) {
+        return Err(ActionbookError::ExtensionError(format!(
+            "--token-stdin: token must be {} followed by 32 hex characters",
+            TOKEN_PREFIX
+        )));
+    }
+    Ok(token.to_string())
+}
+
+/// How the serve banners show the session token: the token itself, or a
+/// placeholder when the caller supplied it and it must not be echoed.
+pub fn banner_token(token: &str, supplied: bool) -> String {
+    if supplied {
+        "(supplied on stdin)".to_string()
+    } else {
+        token.to_string()
+    }
+}
+
 /// Delete the token file if it exists.
 pub async fn delete_token_file() {
     if let Ok(path) = token_file_path() {
@@ -131,6 +315,31 @@ pub async fn delete_token_file() {
     }
 }
 
+/// Bridge port when none is given and no port file points elsewhere.
+pub const DEFAULT_BRIDGE_PORT: u16 = 19222;
+
+/// Port for CLI commands that talk to an existing bridge. An explicit
+/// `--port` wins; otherwise use the port recorded by a running bridge
+/// (standard, then isolated), then the configured `browser.bridge_port`,
+/// then any recorded port, then the default.
+pub async fn resolve_bridge_port(explicit: Option<u16>, configured: Option<u16>) -> u16 {
+    if let Some(port) = explicit {
+        return port;
+    }
+    let recorded: Vec<u16> = [read_port_file().await, read_isolated_port_file().await]
+        .into_iter()
+        .flatten()
+        .collect();
+    for &port in &recorded {
+        if is_bridge_running(port).await {
+            return port;
+        }
+    }
+    configured
+        .or_else(|| recorded.first().copied())
+        .unwrap_or(DEFAULT_BRIDGE_PORT)
+}
+
 /// Path to the bridge port file: `~/.local/share/actionbook/bridge-port`
 pub fn port_file_path() -> Result<PathBuf> {
     let data_dir = dirs::data_local_dir().ok_or_else(|| {
@@ -180,31 +389,8 @@ pub fn isolated_token_file_path() -> Result<PathBuf> {
 }
 
 /// Write the isolated session token to disk with mode 0600.
-/// Uses the same atomic write pattern as [`write_token_file`].
 pub async fn write_isolated_token_file(token: &str) -> Result<()> {
-    let path = isolated_token_file_path()?;
-    if let Some(parent) = path.parent() {
-        tokio::fs::create_dir_all(parent).await?;
-    }
-
-    #[cfg(unix)]
-    {
-        let tmp_path = path.with_extension("tmp");
-        let mut opts = tokio::fs::OpenOptions::new();
-        opts.write(true).create(true).truncate(true).mode(0o600);
-        let mut file = opts.open(&tmp_path).await?;
-        tokio::io::AsyncWriteExt::write_all(&mut file, token.as_bytes()).await?;
-        tokio::io::AsyncWriteExt::flush(&mut file).await?;
-        drop(file);
-        tokio::fs::rename(&tmp_path, &path).await?;
-    }
-
-    #[cfg(not(unix))]
-    {
-        tokio::fs::write(&path, token).await?;
-    }
-
-    Ok(())
+    write_private_file(&isolated_token_file_path()?, token).await
 }
 
 /// Read the isolated token from file. Returns None if file doesn't exist.
@@ -239,7 +425,6 @@ pub async fn write_isolated_port_file(port: u16) -> Result<()> {
 }
 
 /// Read the isolated bridge port from file. Returns None if file doesn't exist or is invalid.
-#[allow(dead_code)]
 pub async fn read_isolated_port_file() -> Option<u16> {
     let path = isolated_port_file_path().ok()?;
     let content = tokio::fs::read_to_string(&path).await.ok()?;
@@ -300,6 +485,11 @@ pub async fn write_pid_file(port: u16) -> Result<()> {
 pub async fn read_pid_file() -> Option<(u32, u16)> {
     let path = pid_file_path().ok()?;
     let content = tokio::fs::read_to_string(&path).await.ok()?;
+    parse_pid_file(&content)
+}
+
+/// Parse `PID:PORT` PID file contents.
+fn parse_pid_file(content: &str) -> Option<(u32, u16)> {
     let (pid_str, port_str) = content.trim().split_once(':')?;
     Some((pid_str.parse().ok()?, port_str.parse().ok()?))
 }
@@ -356,8 +546,7 @@ pub async fn write_isolated_pid_file(port: u16) -> Result<()> {
 pub async fn read_isolated_pid_file() -> Option<(u32, u16)> {
     let path = isolated_pid_file_path().ok()?;
     let content = tokio::fs::read_to_string(&path).await.ok()?;
-    let (pid_str, port_str) = content.trim().split_once(':')?;
-    Some((pid_str.parse().ok()?, port_str.parse().ok()?))
+    parse_pid_file(&content)
 }
 
 /// Delete the isolated PID file if it exists.
@@ -367,63 +556,501 @@ pub async fn delete_isolated_pid_file() {
     }
 }
 
+// --- Stale file reconciliation ---
+
+/// Which set of state files (token, port, PID) a bridge process owns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BridgeMode {
+    /// `extension serve`: `bridge-token`, `bridge-port`, `bridge-pid`
+    Standard,
+    /// `extension serve --isolated`: the `.isolated` variants
+    Isolated,
+}
+
+impl BridgeMode {
+    fn file_suffix(self) -> &'static str {
+        match self {
+            BridgeMode::Standard => "",
+            BridgeMode::Isolated => ".isolated",
+        }
+    }
+}
+
+/// Remove bridge state files left behind by ungraceful shutdowns, before this
+/// process starts a bridge in `my_mode` on `my_port`.
+///
+/// Each mode's token/port/PID files are deleted only when their owner is
+/// confirmed dead: the PID file is missing or unparseable (legacy PID-only
+/// format), or names a process that is no longer running. Files of a live
+/// peer bridge, in either mode, are never touched.
+pub async fn reconcile_stale_files(my_mode: BridgeMode, my_port: u16) {
+    let Some(data_dir) = dirs::data_local_dir() else {
+        return;
+    };
+    reconcile_stale_files_in(&data_dir.join("actionbook"), my_mode, my_port).await;
+}
+
+async fn reconcile_stale_files_in(dir: &Path, my_mode: BridgeMode, my_port: u16) {
+    for mode in [BridgeMode::Standard, BridgeMode::Isolated] {
+        let suffix = mode.file_suffix();
+        let owner = tokio::fs::read_to_string(dir.join(format!("bridge-pid{}", suffix)))
+            .await
+            .ok()
+            .and_then(|content| parse_pid_file(&content));
+
+        if is_owner_live(owner, mode == my_mode, my_port) {
+            tracing::debug!("Keeping {:?} bridge files of live owner {:?}", mode, owner);
+            continue;
+        }
+
+        for name in ["bridge-token", "bridge-port", "bridge-pid"] {
+            let _ = tokio::fs::remove_file(dir.join(format!("{}{}", name, suffix))).await;
+        }
+    }
+}
+
+/// Whether the `PID:PORT` owner of a set of bridge files is a running bridge.
+/// This process's own entry for the slot it is about to take counts as stale.
+fn is_owner_live(owner: Option<(u32, u16)>, is_my_mode: bool, my_port: u16) -> bool {
+    match owner {
+        None => false,
+        Some((pid, port)) if pid == std::process::id() => !(is_my_mode && port == my_port),
+        Some((pid, _port)) => is_pid_alive(pid),
+    }
+}
+
+/// One mode's bridge state files, as found by [`scan_bridge_files`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BridgeFiles {
+    pub mode: BridgeMode,
+    /// `PID:PORT` from the PID file, when it holds one
+    pub owner: Option<(u32, u16)>,
+    /// Whether `owner` is a running process, whose files must be left alone
+    pub live: bool,
+    /// The token, port and PID files that exist
+    pub paths: Vec<PathBuf>,
+}
+
+/// Find the token, port and PID files of both bridge modes, for
+/// `actionbook reset`. The stable token is not runtime state and is left out.
+pub async fn scan_bridge_files() -> Vec<BridgeFiles> {
+    let Some(data_dir) = dirs::data_local_dir() else {
+        return Vec::new();
+    };
+    scan_bridge_files_in(&data_dir.join("actionbook")).await
+}
+
+async fn scan_bridge_files_in(dir: &Path) -> Vec<BridgeFiles> {
+    let mut found = Vec::new();
+    for mode in [BridgeMode::Standard, BridgeMode::Isolated] {
+        let suffix = mode.file_suffix();
+        let owner = tokio::fs::read_to_string(dir.join(format!("bridge-pid{}", suffix)))
+            .await
+            .ok()
+            .and_then(|content| parse_pid_file(&content));
+        let paths = ["bridge-token", "bridge-port", "bridge-pid"]
+            .iter()
+            .map(|name| dir.join(format!("{}{}", name, suffix)))
+            .filter(|path| path.symlink_metadata().is_ok())
+            .collect();
+        found.push(BridgeFiles {
+            mode,
+            owner,
+            live: owner.is_some_and(|(pid, _)| is_pid_alive(pid)),
+            paths,
+        });
+    }
+    found
+}
+
+/// Delete the files of every entry in `found` whose owner is not running,
+/// returning the paths that were removed.
+pub async fn remove_stale_bridge_files(found: &[BridgeFiles]) -> Vec<PathBuf> {
+    let mut removed = Vec::new();
+    for files in found.iter().filter(|files| !files.live) {
+        for path in &files.paths {
+            match tokio::fs::remove_file(path).await {
+                Ok(()) => removed.push(path.clone()),
+                Err(e) => tracing::warn!("Failed to remove {}: {}", path.display(), e),
+            }
+        }
+    }
+    removed
+}
+
+/// Instance id of an extension whose hello carries no `instance_id`
+pub const DEFAULT_INSTANCE_ID: &str = "default";
+
+/// A connected extension
+struct ExtensionConn {
+    /// Channel to send commands to the extension
+    tx: mpsc::UnboundedSender<String>,
+    /// Protocol revision negotiated with the extension
+    proto: u32,
+}
+
+/// Why a CLI command could not be routed to an extension
+#[derive(Debug, PartialEq, Eq)]
+enum RouteError {
+    /// No extension is connected
+    NoneConnected,
+    /// `target_instance` names no connected extension
+    UnknownInstance(String),
+    /// Several extensions are connected and no `target_instance` was given
+    Ambiguous(Vec<String>),
+}
+
+/// A CLI request forwarded to the extension and awaiting its response
+struct PendingRequest {
+    /// Random nonce sent as `gen` with the command; a response echoing a
+    /// different value is stale and must not resolve this request
+    generation: u64,
+    /// Extension instance the command was forwarded to
+    instance: String,
+    sender: oneshot::Sender<String>,
+}
+
+/// A request whose CLI caller was answered with a timeout error
+struct TimedOutRequest {
+    generation: u64,
+    /// When the bridge gave up waiting
+    deadline: Instant,
+}
+
+/// What an extension response with a given id/generation belongs to
+enum ResponseTarget {
+    /// An outstanding request, removed from the pending map
+    Pending(PendingRequest),
+    /// A request that already timed out, with how far past its deadline
+    /// the response arrived
+    Late(std::time::Duration),
+    /// No known request
+    Unmatched,
+}
+
 /// Shared state for the bridge server
 struct BridgeState {
     /// Session token that clients must present in the hello handshake
     token: String,
-    /// Channel to send commands to the connected extension
-    extension_tx: Option<mpsc::UnboundedSender<String>>,
+    /// Connected extensions, keyed by the `instance_id` from their hello
+    extensions: HashMap<String, ExtensionConn>,
     /// Pending CLI requests waiting for extension responses, keyed by request id
-    pending: HashMap<u64, oneshot::Sender<String>>,
+    pending: HashMap<u64, PendingRequest>,
     /// Monotonically increasing request id counter
     next_id: u64,
     /// Last activity timestamp (any message from any client resets this)
     last_activity: Instant,
+    /// When an extension last failed the hello because of a stale token
+    extension_token_rejected_at: Option<Instant>,
+    /// Whether any extension has completed its hello since the bridge started
+    extension_seen: bool,
+    /// Send status lines to stderr (stdout carries JSON events only)
+    json: bool,
+    /// Extension responses dropped because their id or generation matched
+    /// no outstanding request
+    mismatched_responses: u64,
+    /// Recently timed-out requests, keyed by request id
+    timed_out: HashMap<u64, TimedOutRequest>,
+    /// Extension responses that arrived after their request timed out
+    late_responses: u64,
+    /// Largest serialized `params` forwarded to the extension, in bytes
+    max_params_bytes: usize,
+    /// Method of the most recently forwarded command, and when it was sent
+    last_command: Option<(String, Instant)>,
+    /// When each command forwarded in the last minute was sent
+    recent_commands: VecDeque<Instant>,
+    /// Time source for idle and expiry tracking
+    clock: Arc<dyn Clock>,
+}
+
+/// Snapshot of bridge activity, as shown by `serve --live`
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct BridgeMetrics {
+    /// Instance ids of the connected extensions
+    extension_instances: Vec<String>,
+    /// Commands forwarded and not yet answered
+    in_flight: usize,
+    /// Method of the most recent command and how long ago it was sent
+    last_command: Option<(String, Duration)>,
+    /// Commands forwarded in the last 60 seconds
+    requests_per_min: usize,
 }
 
 impl BridgeState {
     fn new(token: String) -> Self {
+        Self::with_clock(token, Arc::new(SystemClock))
+    }
+
+    fn with_clock(token: String, clock: Arc<dyn Clock>) -> Self {
         Self {
             token,
-            extension_tx: None,
+            extensions: HashMap::new(),
             pending: HashMap::new(),
             next_id: 1,
-            last_activity: Instant::now(),
+            last_activity: clock.now(),
+            extension_token_rejected_at: None,
+            extension_seen: false,
+            json: false,
+            mismatched_responses: 0,
+            timed_out: HashMap::new(),
+            late_responses: 0,
+            max_params_bytes: DEFAULT_MAX_PARAMS_BYTES,
+            last_command: None,
+            recent_commands: VecDeque::new(),
+            clock,
+        }
+    }
+
+    /// Register a connected extension, replacing any earlier connection with
+    /// the same instance id.
+    fn add_extension(&mut self, instance_id: String, conn: ExtensionConn) {
+        self.extensions.insert(instance_id, conn);
+        self.extension_seen = true;
+    }
+
+    /// Note a command forwarded to an extension, for [`BridgeState::metrics`].
+    fn record_command(&mut self, method: &str) {
+        let now = self.clock.now();
+        self.last_command = Some((method.to_string(), now));
+        self.recent_commands.push_back(now);
+        self.prune_recent_commands();
+    }
+
+    fn prune_recent_commands(&mut self) {
+        while let Some(&sent) = self.recent_commands.front() {
+            if self.since(sent) < Duration::from_secs(60) {
+                break;
+            }
+            self.recent_commands.pop_front();
+        }
+    }
+
+    fn metrics(&mut self) -> BridgeMetrics {
+        self.prune_recent_commands();
+        BridgeMetrics {
+            extension_instances: self.instance_ids(),
+            in_flight: self.pending.len(),
+            last_command: self
+                .last_command
+                .as_ref()
+                .map(|(method, sent)| (method.clone(), self.since(*sent))),
+            requests_per_min: self.recent_commands.len(),
         }
     }
 
     fn touch(&mut self) {
-        self.last_activity = Instant::now();
+        self.last_activity = self.clock.now();
+    }
+
+    /// Time since `then` on the monotonic clock (zero if `then` is later)
+    fn since(&self, then: Instant) -> std::time::Duration {
+        self.clock.now().saturating_duration_since(then)
+    }
+
+    /// Whether the token has gone unused for [`TOKEN_TTL_SECS`].
+    /// Idle time is measured on the monotonic clock, so wall-clock changes
+    /// (NTP corrections, manual adjustments) neither shorten nor extend it.
+    fn token_idle(&self) -> bool {
+        self.since(self.last_activity).as_secs() >= TOKEN_TTL_SECS
+    }
+
+    /// Whether an extension was rejected for a token mismatch recently enough
+    /// that it explains the missing extension connection.
+    fn recent_extension_token_mismatch(&self) -> bool {
+        self.extension_token_rejected_at
+            .is_some_and(|t| self.since(t).as_secs() < TOKEN_MISMATCH_WINDOW_SECS)
+    }
+
+    /// Pick the extension a command goes to: `target` if given, otherwise
+    /// the sole connected extension.
+    fn route(
+        &self,
+        target: Option<&str>,
+    ) -> std::result::Result<(&str, &ExtensionConn), RouteError> {
+        if let Some(target) = target {
+            return self
+                .extensions
+                .get_key_value(target)
+                .map(|(id, conn)| (id.as_str(), conn))
+                .ok_or_else(|| RouteError::UnknownInstance(target.to_string()));
+        }
+        let mut connected = self.extensions.iter();
+        match (connected.next(), connected.next()) {
+            (None, _) => Err(RouteError::NoneConnected),
+            (Some((id, conn)), None) => Ok((id.as_str(), conn)),
+            _ => Err(RouteError::Ambiguous(self.instance_ids())),
+        }
+    }
+
+    /// Ids of the connected extensions, sorted
+    fn instance_ids(&self) -> Vec<String> {
+        let mut ids: Vec<String> = self.extensions.keys().cloned().collect();
+        ids.sort();
+        ids
+    }
+
+    /// Drop a pending request whose caller has been told it timed out, and
+    /// remember it so a late response can be recognised.
+    fn record_timeout(&mut self, request_id: u64) {
+        let Some(pending) = self.pending.remove(&request_id) else {
+            return;
+        };
+        let now = self.clock.now();
+        self.timed_out.retain(|_, t| {
+            now.saturating_duration_since(t.deadline).as_secs() < LATE_RESPONSE_WINDOW_SECS
+        });
+        if self.timed_out.len() >= MAX_TIMED_OUT_TRACKED {
+            if let Some(oldest) = self
+                .timed_out
+                .iter()
+                .min_by_key(|(_, t)| t.deadline)
+                .map(|(id, _)| *id)
+            {
+                self.timed_out.remove(&oldest);
+            }
+        }
+        self.timed_out.insert(
+            request_id,
+            TimedOutRequest {
+                generation: pending.generation,
+                deadline: now,
+            },
+        );
+    }
+
+    /// Match an extension response to the request it answers. Extensions
+    /// that predate `gen` echo nothing; only an explicit mismatch marks the
+    /// response as stale.
+    fn take_response_target(&mut self, id: u64, generation: Option<u64>) -> ResponseTarget {
+        let matches = |expected: u64| generation.is_none_or(|g| g == expected);
+        if self.pending.get(&id).is_some_and(|p| matches(p.generation)) {
+            if let Some(pending) = self.pending.remove(&id) {
+                return ResponseTarget::Pending(pending);
+            }
+        }
+        if self
+            .timed_out
+            .get(&id)
+            .is_some_and(|t| matches(t.generation))
+        {
+            if let Some(timed_out) = self.timed_out.remove(&id) {
+                return ResponseTarget::Late(self.since(timed_out.deadline));
+            }
+        }
+        ResponseTarget::Unmatched
     }
 }
 
 /// Start the bridge WebSocket server on the given port with the given session token.
 /// This function blocks until the server is shut down.
+#[allow(dead_code)]
 pub async fn serve(port: u16, token: String) -> Result<()> {
+    serve_with_options(port, token, ServeOptions::default()).await
+}
+
+/// Console, file and limit behaviour for a bridge run.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ServeOptions {
+    /// Skip global token/port files (the token is injected via CDP instead)
+    pub isolated: bool,
+    /// Print a single JSON `ready` event on stdout once the listener is
+    /// accepting, and send all human-readable output to stderr
+    pub json: bool,
+    /// Largest serialized `params` forwarded to the extension, in bytes
+    /// (defaults to [`DEFAULT_MAX_PARAMS_BYTES`])
+    pub max_params_bytes: Option<usize>,
+    /// The token came from the stable token file; keep that file in step
+    /// when the idle timeout rotates the token
+    pub stable_token: bool,
+    /// The caller supplied the token (`--token-stdin`): leave it out of the
+    /// `ready` event and don't print its idle-timeout replacement
+    pub token_supplied: bool,
+    /// Record `PID:PORT` in this mode's PID file once the listener is bound,
+    /// so the file carries the OS-assigned port when serving on port 0
+    pub pid_file: bool,
+    /// Report once whether an extension connects within
+    /// [`EXTENSION_CONNECT_WAIT`] (see [`wait_for_extension`])
+    pub report_extension: bool,
+    /// Print [`NO_EXTENSION_HINT`] if no extension has connected this long
+    /// after the listener is bound
+    pub connect_hint_after: Option<Duration>,
+    /// The bridge `extension restart` stopped before starting this one; the
+    /// `ready` event reports it, so `--json` callers get a single object
+    pub replaced: Option<StoppedBridge>,
+    /// Keep a refreshing activity summary on the last line of stdout.
+    /// Ignored with `json`, or when stdout is not a terminal.
+    pub live: bool,
+    /// The global `--timeout`: shut down as on a signal once this long has
+    /// passed, then fail with [`ActionbookError::GlobalTimeout`]. Honoured by
+    /// [`serve_with_options`] and the isolated launcher, which own the signal
+    /// handling; the lower-level `serve_*` functions ignore it.
+    pub deadline: Option<Duration>,
+}
+
+/// A bridge process that was stopped to make way for a new one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StoppedBridge {
+    pub pid: u32,
+    /// It ignored the graceful shutdown and had to be killed
+    pub forced: bool,
+}
+
+/// Resolve once `deadline` has passed; never, without one.
+pub(crate) async fn deadline_elapsed(deadline: Option<Duration>) {
+    match deadline {
+        Some(after) => tokio::time::sleep(after).await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Like [`serve`], with explicit [`ServeOptions`]. Stops on SIGINT/SIGTERM,
+/// or once `options.deadline` has passed.
+pub async fn serve_with_options(port: u16, token: String, options: ServeOptions) -> Result<()> {
     let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel();
 
-    // Handle SIGINT/SIGTERM by sending on the oneshot
-    tokio::spawn(async move {
-        #[cfg(unix)]
-        {
-            use tokio::signal::unix::{signal, SignalKind};
-            let mut sigint =
-                signal(SignalKind::interrupt()).expect("Failed to register SIGINT handler");
-            let mut sigterm =
-                signal(SignalKind::terminate()).expect("Failed to register SIGTERM handler");
-            tokio::select! {
-                _ = sigint.recv() => tracing::info!("Received SIGINT"),
-                _ = sigterm.recv() => tracing::info!("Received SIGTERM"),
+    // Handle SIGINT/SIGTERM (or the deadline) by sending on the oneshot;
+    // the flag is set before the send, as the bridge can wind down before
+    // this task has finished
+    let deadline = options.deadline;
+    let timed_out = Arc::new(AtomicBool::new(false));
+    let stopper_timed_out = Arc::clone(&timed_out);
+    let stopper = tokio::spawn(async move {
+        let hit_deadline = tokio::select! {
+            _ = async {
+                #[cfg(unix)]
+                {
+                    use tokio::signal::unix::{signal, SignalKind};
+                    let mut sigint = signal(SignalKind::interrupt())
+                        .expect("Failed to register SIGINT handler");
+                    let mut sigterm = signal(SignalKind::terminate())
+                        .expect("Failed to register SIGTERM handler");
+                    tokio::select! {
+                        _ = sigint.recv() => tracing::info!("Received SIGINT"),
+                        _ = sigterm.recv() => tracing::info!("Received SIGTERM"),
+                    }
+                }
+                #[cfg(not(unix))]
+                {
+                    tokio::signal::ctrl_c().await.ok();
+                }
+            } => false,
+            _ = deadline_elapsed(deadline) => {
+                tracing::info!("Global timeout reached, shutting down");
+                true
             }
-        }
-        #[cfg(not(unix))]
-        {
-            tokio::signal::ctrl_c().await.ok();
-        }
+        };
+        stopper_timed_out.store(hit_deadline, Ordering::SeqCst);
         let _ = shutdown_tx.send(());
     });
 
-    serve_with_shutdown(port, token, shutdown_rx, false).await
+    let result = serve_with_shutdown_options(port, token, shutdown_rx, options).await;
+    stopper.abort();
+    match result {
+        Ok(()) if timed_out.load(Ordering::SeqCst) => {
+            Err(ActionbookError::GlobalTimeout(deadline.unwrap_or_default().as_secs()))
+        }
+        result => result,
+    }
 }
 
 /// Start the bridge WebSocket server with an externally-controlled shutdown channel.
@@ -436,12 +1063,63 @@ pub async fn serve(port: u16, token: String) -> Result<()> {
 /// When `isolated` is true, global file writes (token file, port file) are skipped.
 /// In isolated mode, the token is injected directly via CDP so no global files should
 /// be created that could be read by other Chrome instances.
+#[allow(dead_code)]
 pub async fn serve_with_shutdown(
     port: u16,
     token: String,
     shutdown_rx: tokio::sync::oneshot::Receiver<()>,
     isolated: bool,
 ) -> Result<()> {
+    let options = ServeOptions {
+        isolated,
+        ..Default::default()
+    };
+    serve_with_shutdown_options(port, token, shutdown_rx, options).await
+}
+
+/// Like [`serve_with_shutdown`], with explicit [`ServeOptions`].
+pub async fn serve_with_shutdown_options(
+    port: u16,
+    token: String,
+    shutdown_rx: tokio::sync::oneshot::Receiver<()>,
+    options: ServeOptions,
+) -> Result<()> {
+    let listener = bind_bridge(port).await?;
+    serve_on_listener(listener, token, shutdown_rx, options).await
+}
+
+/// Bind the bridge's loopback listener on `port` (0 lets the OS pick one).
+pub async fn bind_bridge(port: u16) -> Result<TcpListener> {
+    let addr = SocketAddr::from(([127, 0, 0, 1], port));
+    TcpListener::bind(&addr)
+        .await
+        .map_err(|source| ActionbookError::BridgeBind { addr, source })
+}
+
+/// Like [`serve_with_shutdown_options`], on a listener bound up front with
+/// [`bind_bridge`], so callers can find out the port is taken before doing
+/// anything else.
+pub async fn serve_on_listener(
+    listener: TcpListener,
+    token: String,
+    shutdown_rx: tokio::sync::oneshot::Receiver<()>,
+    options: ServeOptions,
+) -> Result<()> {
+    let ServeOptions {
+        isolated,
+        json,
+        max_params_bytes,
+        stable_token,
+        token_supplied,
+        pid_file,
+        report_extension,
+        connect_hint_after,
+        replaced,
+        live,
+        // Enforced by the callers that own the shutdown signal
+        deadline: _,
+    } = options;
+
     // Clean up stale port file from a previous ungraceful shutdown before starting.
     // Only clean the current mode's file to avoid disrupting the other mode.
     if isolated {
@@ -450,15 +1128,67 @@ pub async fn serve_with_shutdown(
         delete_port_file().await;
     }
 
-    let addr = SocketAddr::from(([127, 0, 0, 1], port));
-    let listener = TcpListener::bind(&addr).await.map_err(|e| {
-        ActionbookError::Other(format!("Failed to bind to {}: {}", addr, e))
-    })?;
+    // Port 0 asks the OS for a free port; everything below records the real one
+    let port = listener.local_addr()?.port();
 
-    let state = Arc::new(Mutex::new(BridgeState::new(token)));
+    if json {
+        let token = (!token_supplied).then_some(token.as_str());
+        crate::output::print_json(ready_event(port, token, isolated, replaced));
+    }
+
+    let report_handle = report_extension.then(|| {
+        let token = token.clone();
+        tokio::spawn(async move {
+            if wait_for_extension(port, &token, EXTENSION_CONNECT_WAIT).await {
+                status_println!(json, "  Extension connected {}", colored::Colorize::green("✓"));
+            } else {
+                status_println!(
+                    json,
+                    "  {} Extension not yet connected after {}s",
+                    colored::Colorize::yellow("!"),
+                    EXTENSION_CONNECT_WAIT.as_secs()
+                );
+            }
+        })
+    });
+
+    let mut bridge_state = BridgeState::new(token);
+    bridge_state.json = json;
+    bridge_state.max_params_bytes = max_params_bytes.unwrap_or(DEFAULT_MAX_PARAMS_BYTES);
+    let state = Arc::new(Mutex::new(bridge_state));
+
+    let hint_handle = connect_hint_after.map(|after| {
+        let hint_state = Arc::clone(&state);
+        tokio::spawn(async move {
+            if no_extension_after(&hint_state, after).await {
+                status_println!(
+                    json,
+                    "\n  {} {}\n",
+                    colored::Colorize::yellow("!"),
+                    colored::Colorize::bold(NO_EXTENSION_HINT)
+                );
+            }
+        })
+    });
+
+    status_println!(json, "Bridge server listening on ws://127.0.0.1:{}", port);
+    status_println!(json, "Waiting for extension connection...");
 
-    println!("Bridge server listening on ws://127.0.0.1:{}", port);
-    println!("Waiting for extension connection...");
+    let live_handle = (live && !json && std::io::stdout().is_terminal()).then(|| {
+        let live_state = Arc::clone(&state);
+        tokio::spawn(async move {
+            loop {
+                let metrics = live_state.lock().await.metrics();
+                {
+                    let mut stdout = std::io::stdout().lock();
+                    let _ = write!(stdout, "\r\x1b[2K{}", format_live_summary(&metrics));
+                    let _ = stdout.flush();
+                    LIVE_LINE.store(true, Ordering::SeqCst);
+                }
+                tokio::time::sleep(LIVE_REFRESH).await;
+            }
+        })
+    });
 
     // Write port file so native messaging can discover the actual port.
     // In isolated mode, write to .isolated file instead of global file.
@@ -469,61 +1199,91 @@ pub async fn serve_with_shutdown(
     } else {
         if let Err(e) = write_port_file(port).await {
             tracing::warn!("Failed to write port file: {}. Native messaging auto-pairing may not work.", e);
-            eprintln!(
-                "  Warning: Failed to write port file: {}. Auto-pairing may not work.",
-                e
+            crate::output::print_warning(
+                json,
+                format!("Failed to write port file: {}. Auto-pairing may not work.", e),
             );
         }
     }
 
+    if pid_file {
+        let written = if isolated {
+            write_isolated_pid_file(port).await
+        } else {
+            write_pid_file(port).await
+        };
+        if let Err(e) = written {
+            crate::output::print_warning(json, format!("Failed to write PID file: {}", e));
+        }
+    }
+
     // Spawn TTL watchdog
     let ttl_state = Arc::clone(&state);
     let ttl_handle = tokio::spawn(async move {
         loop {
             tokio::time::sleep(std::time::Duration::from_secs(60)).await;
             let mut s = ttl_state.lock().await;
-            if s.last_activity.elapsed().as_secs() >= TOKEN_TTL_SECS {
+            if s.token_idle() {
                 tracing::warn!("Token idle timeout reached ({}min). Generating new token.", TOKEN_TTL_SECS / 60);
                 let new_token = generate_token();
                 // Send token_expired notification before closing
-                if let Some(ext_tx) = s.extension_tx.take() {
+                for (_id, conn) in s.extensions.drain() {
                     let expire_msg = serde_json::json!({
                         "type": "token_expired",
                         "message": "Session token expired due to inactivity"
                     });
-                    let _ = ext_tx.send(expire_msg.to_string());
-                    drop(ext_tx);
+                    let _ = conn.tx.send(expire_msg.to_string());
                 }
                 // Notify all pending CLI requests with their original IDs
-                for (id, sender) in s.pending.drain() {
+                for (id, pending) in s.pending.drain() {
                     let err_msg = serde_json::json!({
                         "id": id,
                         "error": { "code": -32000, "message": "Session token expired" }
                     });
-                    let _ = sender.send(err_msg.to_string());
+                    let _ = pending.sender.send(err_msg.to_string());
+                }
+                if s.json {
+                    let shown = (!token_supplied).then_some(new_token.as_str());
+                    crate::output::print_json(token_rotated_event(port, shown));
+                }
+                if token_supplied {
+                    status_println!(
+                        s.json,
+                        "\n  {} Token expired due to inactivity. \
+                         The new token is in the token file\n",
+                        colored::Colorize::yellow("!")
+                    );
+                } else {
+                    status_println!(
+                        s.json,
+                        "\n  {} Token expired due to inactivity. New token: {}\n",
+                        colored::Colorize::yellow("!"),
+                        new_token
+                    );
                 }
-                println!(
-                    "\n  {} Token expired due to inactivity. New token: {}\n",
-                    colored::Colorize::yellow("!"),
-                    new_token
-                );
                 // Write new token file to the appropriate location
                 if isolated {
                     let _ = write_isolated_token_file(&new_token).await;
                 } else {
                     let _ = write_token_file(&new_token).await;
                 }
+                if stable_token {
+                    if let Ok(path) = stable_token_file_path() {
+                        let _ = write_private_file(&path, &new_token).await;
+                    }
+                }
                 s.token = new_token;
-                s.last_activity = Instant::now();
+                s.touch();
             }
         }
     });
 
     let accept_loop = async {
         loop {
-            let (stream, peer) = listener.accept().await.map_err(|e| {
-                ActionbookError::Other(format!("Accept failed: {}", e))
-            })?;
+            let (stream, peer) = listener
+                .accept()
+                .await
+                .map_err(ActionbookError::BridgeAccept)?;
 
             tracing::debug!("New connection from {}", peer);
 
@@ -537,7 +1297,7 @@ pub async fn serve_with_shutdown(
             }
 
             let state = Arc::clone(&state);
-            tokio::spawn(handle_connection(stream, state));
+            tokio::spawn(handle_connection(stream, peer, state));
         }
     };
 
@@ -548,6 +1308,9 @@ pub async fn serve_with_shutdown(
             Ok(())
         }
     };
+    if result.is_ok() {
+        drain_in_flight(&listener, &state).await;
+    }
 
     // Cleanup always runs, whether shutdown was graceful or the loop exited.
     // Only delete files owned by this mode to avoid interfering with a
@@ -559,8 +1322,112 @@ pub async fn serve_with_shutdown(
         delete_port_file().await;
     }
     ttl_handle.abort();
-    result
-}
+    if let Some(handle) = report_handle {
+        handle.abort();
+    }
+    if let Some(handle) = hint_handle {
+        handle.abort();
+    }
+    if let Some(handle) = live_handle {
+        handle.abort();
+        clear_live_line();
+    }
+    result
+}
+
+/// How often `serve --live` redraws its summary
+const LIVE_REFRESH: Duration = Duration::from_secs(1);
+
+/// One-line `serve --live` summary of `metrics`.
+fn format_live_summary(metrics: &BridgeMetrics) -> String {
+    let extension = if metrics.extension_instances.is_empty() {
+        "not connected".to_string()
+    } else {
+        format!("connected ({})", metrics.extension_instances.join(", "))
+    };
+    let last = match &metrics.last_command {
+        Some((method, ago)) => format!("{} ({}s ago)", method, ago.as_secs()),
+        None => "none".to_string(),
+    };
+    format!(
+        "extension: {} | in flight: {} | last: {} | {} req/min",
+        extension, metrics.in_flight, last, metrics.requests_per_min
+    )
+}
+
+/// Wait (up to [`SHUTDOWN_DRAIN_SECS`]) for pending requests to finish after
+/// shutdown began. Connections arriving meanwhile are closed right away with
+/// [`SHUTTING_DOWN_REASON`] instead of being accepted and dropped.
+async fn drain_in_flight(listener: &TcpListener, state: &Arc<Mutex<BridgeState>>) {
+    let drained = async {
+        while !state.lock().await.pending.is_empty() {
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        }
+    };
+    let reject_new = async {
+        while let Ok((stream, _peer)) = listener.accept().await {
+            tokio::spawn(reject_shutting_down(stream));
+        }
+    };
+    let _ = tokio::time::timeout(std::time::Duration::from_secs(SHUTDOWN_DRAIN_SECS), async {
+        tokio::select! {
+            _ = drained => {}
+            _ = reject_new => {}
+        }
+    })
+    .await;
+}
+
+/// Complete the WebSocket upgrade only to send a "going away" close frame.
+async fn reject_shutting_down(stream: TcpStream) {
+    let Ok(mut ws) = tokio_tungstenite::accept_async(stream).await else {
+        return;
+    };
+    let _ = ws
+        .close(Some(tokio_tungstenite::tungstenite::protocol::CloseFrame {
+            code: tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode::Away,
+            reason: SHUTTING_DOWN_REASON.into(),
+        }))
+        .await;
+}
+
+/// The one-line `ready` event for `--json` callers, printed and flushed so a
+/// parent process can block on a single stdout line instead of polling.
+/// A caller-supplied token is left out; a restart adds the bridge it stopped.
+fn ready_event(
+    port: u16,
+    token: Option<&str>,
+    isolated: bool,
+    replaced: Option<StoppedBridge>,
+) -> serde_json::Value {
+    let mut event = serde_json::json!({
+        "event": "ready",
+        "port": port,
+        "mode": if isolated { "isolated" } else { "standard" },
+    });
+    if let Some(token) = token {
+        event["token"] = serde_json::json!(token);
+    }
+    if let Some(stopped) = replaced {
+        event["replaced"] = serde_json::json!({ "pid": stopped.pid, "forced": stopped.forced });
+    }
+    event
+}
+
+/// The `token_rotated` event printed for `--json` callers when the idle
+/// timeout replaces the session token. `token` is `None` when the caller
+/// supplied the original, as in [`ready_event`].
+fn token_rotated_event(port: u16, token: Option<&str>) -> serde_json::Value {
+    let mut event = serde_json::json!({
+        "event": "token_rotated",
+        "port": port,
+        "reason": "idle_timeout",
+    });
+    if let Some(token) = token {
+        event["token"] = serde_json::json!(token);
+    }
+    event
+}
 
 /// Parse an origin string into (scheme, host, optional_port).
 fn parse_origin(origin: &str) -> Option<(&str, &str, Option<&str>)> {
@@ -623,10 +1490,12 @@ fn is_origin_allowed(origin: Option<&str>) -> bool {
     }
 }
 
-/// Handle a single incoming WebSocket connection.
+/// Handle a single incoming WebSocket connection from `peer`.
 /// Performs origin validation during the upgrade, then does the hello handshake.
-async fn handle_connection(stream: TcpStream, state: Arc<Mutex<BridgeState>>) {
-    // Use accept_hdr_async to inspect upgrade request headers for origin validation
+async fn handle_connection(stream: TcpStream, peer: SocketAddr, state: Arc<Mutex<BridgeState>>) {
+    // Use accept_hdr_async to inspect upgrade request headers for origin validation.
+    // The callback signature (and its large `Err` response type) is fixed by tungstenite.
+    #[allow(clippy::result_large_err)]
     let ws = match tokio_tungstenite::accept_hdr_async(
         stream,
         |req: &tokio_tungstenite::tungstenite::http::Request<()>,
@@ -725,12 +1594,57 @@ async fn handle_connection(stream: TcpStream, state: Arc<Mutex<BridgeState>>) {
         }
     }
 
+    // Negotiate wire protocol revision
+    let proto = match negotiate_proto(parsed.get("proto")) {
+        Some(p) => p,
+        None => {
+            let requested = parsed.get("proto").cloned().unwrap_or_default();
+            tracing::warn!(
+                "Rejected {} client with proto {} (supported: {:?})",
+                client_role,
+                requested,
+                SUPPORTED_PROTOS
+            );
+            let message = format!(
+                "Bridge protocol {} is not supported. This bridge speaks {:?}; update the extension or CLI so both match.",
+                requested, SUPPORTED_PROTOS
+            );
+            let err_msg = serde_json::json!({
+                "type": "hello_error",
+                "error": "proto_mismatch",
+                "message": message,
+                "supported_proto": SUPPORTED_PROTOS,
+            });
+            let _ = write
+                .send(Message::Text(err_msg.to_string().into()))
+                .await;
+            let _ = write
+                .send(Message::Close(Some(
+                    tokio_tungstenite::tungstenite::protocol::CloseFrame {
+                        code: tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode::Protocol,
+                        reason: format!("unsupported proto {}", requested).into(),
+                    },
+                )))
+                .await;
+            return;
+        }
+    };
+
     // Validate token (constant-time to prevent timing side-channels)
     {
-        let s = state.lock().await;
+        let mut s = state.lock().await;
         let token_match = client_token.as_bytes().ct_eq(s.token.as_bytes());
         if token_match.unwrap_u8() != 1 {
             tracing::warn!("Invalid token from {} client", client_role);
+            if client_role == "extension" {
+                s.extension_token_rejected_at = Some(s.clock.now());
+                status_println!(
+                    s.json,
+                    "  {} Extension rejected: it authenticated with a different token. \
+                     Reload the extension to re-inject the current token, or restart the bridge.",
+                    colored::Colorize::yellow("!")
+                );
+            }
             let err_msg = serde_json::json!({
                 "type": "hello_error",
                 "error": "invalid_token",
@@ -743,8 +1657,25 @@ async fn handle_connection(stream: TcpStream, state: Arc<Mutex<BridgeState>>) {
         }
     }
 
+    // A CLI hello with `session: true` may idle before its first command;
+    // the ack echoes it so clients can tell apart older bridges that answer
+    // only one command per connection
+    let session = client_role == "cli"
+        && parsed
+            .get("session")
+            .and_then(|s| s.as_bool())
+            .unwrap_or(false);
+
     // Send hello_ack to confirm successful authentication
-    let ack = serde_json::json!({ "type": "hello_ack", "version": PROTOCOL_VERSION });
+    let mut ack = serde_json::json!({
+        "type": "hello_ack",
+        "role": "bridge",
+        "version": PROTOCOL_VERSION,
+        "proto": proto,
+    });
+    if session {
+        ack["session"] = serde_json::json!(true);
+    }
     if write
         .send(Message::Text(ack.to_string().into()))
         .await
@@ -761,16 +1692,65 @@ async fn handle_connection(stream: TcpStream, state: Arc<Mutex<BridgeState>>) {
     }
 
     match client_role {
-        "extension" => handle_extension_client(write, read, state).await,
-        "cli" => handle_cli_client(write, read, state).await,
+        "extension" => {
+            // Flag an extension too old for this CLI, but still let it connect
+            if let Some(warning) = parsed
+                .get("extension_version")
+                .and_then(|v| v.as_str())
+                .and_then(|v| {
+                    native_messaging::check_extension_compat(native_messaging::HOST_VERSION, v)
+                })
+            {
+                let json = state.lock().await.json;
+                crate::output::print_warning(json, warning);
+            }
+            let instance_id = parsed
+                .get("instance_id")
+                .and_then(|i| i.as_str())
+                .filter(|i| !i.is_empty())
+                .unwrap_or(DEFAULT_INSTANCE_ID)
+                .to_string();
+            handle_extension_client(write, read, state, proto, instance_id).await
+        }
+        "cli" => handle_cli_client(write, read, state, peer, session).await,
         other => {
             tracing::warn!("Unknown client role: {}", other);
         }
     }
 }
 
-/// Handle the extension client connection.
+/// Read a numeric field (`id`, `gen`) from an extension response.
+/// Some extension builds echo these back as strings (`"5"`) or floats
+/// (`5.0`); those are accepted when they name an exact non-negative
+/// integer, with a warning so the extension can be fixed.
+fn response_u64(resp: &serde_json::Value, field: &str) -> Option<u64> {
+    let value = resp.get(field)?;
+    if let Some(n) = value.as_u64() {
+        return Some(n);
+    }
+    let coerced = match value {
+        serde_json::Value::String(s) => s.trim().parse::<u64>().ok(),
+        // Beyond 2^53 a float no longer identifies a single integer
+        serde_json::Value::Number(n) => n
+            .as_f64()
+            .filter(|f| f.fract() == 0.0 && *f >= 0.0 && *f <= (1u64 << 53) as f64)
+            .map(|f| f as u64),
+        _ => None,
+    };
+    if let Some(n) = coerced {
+        tracing::warn!(
+            "Extension sent non-canonical {} {}; treating it as {}",
+            field,
+            value,
+            n
+        );
+    }
+    coerced
+}
+
+/// Handle the extension client connection for `instance_id`.
 /// Stores the sender channel and routes responses back to pending CLI requests.
+/// A reconnect with the same instance id replaces the earlier connection.
 async fn handle_extension_client(
     mut write: futures::stream::SplitSink<
         tokio_tungstenite::WebSocketStream<TcpStream>,
@@ -778,15 +1758,30 @@ async fn handle_extension_client(
     >,
     mut read: futures::stream::SplitStream<tokio_tungstenite::WebSocketStream<TcpStream>>,
     state: Arc<Mutex<BridgeState>>,
+    proto: u32,
+    instance_id: String,
 ) {
-    println!("  {} Extension connected", colored::Colorize::green("✓"));
+    let json = state.lock().await.json;
+    status_println!(
+        json,
+        "  {} Extension connected ({})",
+        colored::Colorize::green("✓"),
+        instance_id
+    );
 
     // Create a channel for sending commands to the extension
     let (tx, mut rx) = mpsc::unbounded_channel::<String>();
 
     {
         let mut s = state.lock().await;
-        s.extension_tx = Some(tx);
+        s.add_extension(
+            instance_id.clone(),
+            ExtensionConn {
+                tx: tx.clone(),
+                proto,
+            },
+        );
+        s.extension_token_rejected_at = None;
     }
 
     // Spawn a task to forward commands from the channel to the WebSocket
@@ -820,12 +1815,30 @@ async fn handle_extension_client(
                 let text_str = text.to_string();
                 match serde_json::from_str::<serde_json::Value>(&text_str) {
                     Ok(resp) => {
-                        if let Some(id) = resp.get("id").and_then(|i| i.as_u64()) {
+                        if let Some(id) = response_u64(&resp, "id") {
                             let mut s = state.lock().await;
-                            if let Some(sender) = s.pending.remove(&id) {
-                                let _ = sender.send(text_str);
-                            } else {
-                                tracing::warn!("Response for unknown request id: {}", id);
+                            let generation = response_u64(&resp, "gen");
+                            match s.take_response_target(id, generation) {
+                                ResponseTarget::Pending(pending) => {
+                                    let _ = pending.sender.send(text_str);
+                                }
+                                ResponseTarget::Late(over) => {
+                                    s.late_responses += 1;
+                                    tracing::info!(
+                                        "Late response for request {} arrived {}ms after its {}s deadline",
+                                        id,
+                                        over.as_millis(),
+                                        COMMAND_TIMEOUT_SECS
+                                    );
+                                }
+                                ResponseTarget::Unmatched => {
+                                    s.mismatched_responses += 1;
+                                    tracing::warn!(
+                                        "Dropping response with unmatched id/generation: {} / {:?}",
+                                        id,
+                                        generation
+                                    );
+                                }
                             }
                         } else {
                             tracing::debug!("Extension message without id (event): {}", text_str);
@@ -845,85 +1858,260 @@ async fn handle_extension_client(
         }
     }
 
-    println!(
-        "  {} Extension disconnected",
-        colored::Colorize::yellow("!")
+    status_println!(
+        json,
+        "  {} Extension disconnected ({})",
+        colored::Colorize::yellow("!"),
+        instance_id
     );
 
-    // Clean up: notify all pending requests and clear extension channel
+    // Clean up: fail this instance's pending requests and drop its channel,
+    // unless a reconnect with the same instance id has already replaced it
     {
         let mut s = state.lock().await;
-        for (_id, sender) in s.pending.drain() {
-            let err_msg = serde_json::json!({
-                "id": 0,
-                "error": { "code": -32000, "message": "Extension disconnected" }
-            });
-            let _ = sender.send(err_msg.to_string());
+        if s.extensions
+            .get(&instance_id)
+            .is_some_and(|conn| conn.tx.same_channel(&tx))
+        {
+            s.extensions.remove(&instance_id);
+            let orphaned: Vec<u64> = s
+                .pending
+                .iter()
+                .filter(|(_, p)| p.instance == instance_id)
+                .map(|(id, _)| *id)
+                .collect();
+            for id in orphaned {
+                if let Some(pending) = s.pending.remove(&id) {
+                    let err_msg = serde_json::json!({
+                        "id": 0,
+                        "error": { "code": -32000, "message": "Extension disconnected" }
+                    });
+                    let _ = pending.sender.send(err_msg.to_string());
+                }
+            }
         }
-        s.extension_tx = None;
     }
 
     write_handle.abort();
 }
 
-/// Handle a CLI client connection.
-/// After the hello handshake, the CLI sends commands and receives responses.
+/// The command a CLI client sends after its hello, tagged with the
+/// connection it arrived on.
+#[derive(Debug)]
+struct CliCommand {
+    peer: SocketAddr,
+    id: serde_json::Value,
+    method: String,
+    params: serde_json::Value,
+    /// Extension instance to route to; `None` means the sole connected one
+    target_instance: Option<String>,
+}
+
+impl CliCommand {
+    /// Parse the command frame received from `peer`. Missing `id` and
+    /// `params` default to `0` and `null`; `target_instance` is optional.
+    fn parse(peer: SocketAddr, text: &str) -> Option<Self> {
+        let msg: serde_json::Value = serde_json::from_str(text).ok()?;
+        Some(Self::from_value(peer, &msg))
+    }
+
+    /// Build a command from an already-parsed frame (or batch element).
+    fn from_value(peer: SocketAddr, msg: &serde_json::Value) -> Self {
+        Self {
+            peer,
+            id: msg.get("id").cloned().unwrap_or(serde_json::json!(0)),
+            method: msg
+                .get("method")
+                .and_then(|m| m.as_str())
+                .unwrap_or("")
+                .to_string(),
+            params: msg
+                .get("params")
+                .cloned()
+                .unwrap_or(serde_json::Value::Null),
+            target_instance: msg
+                .get("target_instance")
+                .and_then(|t| t.as_str())
+                .map(str::to_string),
+        }
+    }
+}
+
+impl std::fmt::Display for CliCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} from {}", self.method, self.peer)
+    }
+}
+
+/// Handle a CLI client connection from `peer`.
+///
+/// After the hello handshake the CLI sends commands until it closes the
+/// connection. Each is answered under the client's own `id` as soon as it
+/// completes, possibly out of order; closing the connection cancels whatever
+/// is still pending. A legacy client (no `session` in its hello) sends one
+/// command straight away and disconnects once answered.
+///
+/// A frame holding a JSON array is a batch: its commands run concurrently
+/// and are answered together as one array, in request order.
 async fn handle_cli_client(
-    mut write: futures::stream::SplitSink<
-        tokio_tungstenite::WebSocketStream<TcpStream>,
-        Message,
-    >,
+    mut write: futures::stream::SplitSink<tokio_tungstenite::WebSocketStream<TcpStream>, Message>,
     mut read: futures::stream::SplitStream<tokio_tungstenite::WebSocketStream<TcpStream>>,
     state: Arc<Mutex<BridgeState>>,
+    peer: SocketAddr,
+    session: bool,
 ) {
-    // Read the actual command message (second message after hello)
-    let cmd_msg = match tokio::time::timeout(
-        std::time::Duration::from_secs(5),
-        read.next(),
-    )
-    .await
-    {
-        Ok(Some(Ok(Message::Text(text)))) => text.to_string(),
-        _ => {
-            tracing::warn!("CLI disconnected before sending command");
+    // Only a session may sit idle before its first command
+    let first = if session {
+        None
+    } else {
+        match tokio::time::timeout(std::time::Duration::from_secs(5), read.next()).await {
+            Ok(Some(Ok(Message::Text(text)))) => Some(text.to_string()),
+            _ => {
+                tracing::warn!("CLI {} disconnected before sending command", peer);
+                return;
+            }
+        }
+    };
+
+    let (out_tx, mut out_rx) = mpsc::unbounded_channel::<String>();
+    let (closed_tx, closed_rx) = tokio::sync::watch::channel(false);
+
+    let writer = tokio::spawn(async move {
+        while let Some(text) = out_rx.recv().await {
+            if write.send(Message::Text(text.into())).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let spawn_command = |text: &str| {
+        if text.trim_start().starts_with('[') {
+            let state = Arc::clone(&state);
+            let out_tx = out_tx.clone();
+            let closed_rx = closed_rx.clone();
+            let text = text.to_string();
+            tokio::spawn(async move {
+                if let Some(resp) = execute_cli_batch(peer, &text, &state, closed_rx).await {
+                    let _ = out_tx.send(resp.to_string());
+                }
+            });
             return;
         }
+        let Some(command) = CliCommand::parse(peer, text) else {
+            tracing::warn!("Invalid JSON command from CLI {}", peer);
+            return;
+        };
+        let state = Arc::clone(&state);
+        let out_tx = out_tx.clone();
+        let mut closed_rx = closed_rx.clone();
+        tokio::spawn(async move {
+            let closed = async move {
+                let _ = closed_rx.wait_for(|closed| *closed).await;
+            };
+            if let Some(resp) = execute_cli_command(command, &state, closed).await {
+                let _ = out_tx.send(resp.to_string());
+            }
+        });
     };
 
-    let first_msg: serde_json::Value = match serde_json::from_str(&cmd_msg) {
-        Ok(v) => v,
+    if let Some(text) = first {
+        spawn_command(&text);
+    }
+    // A killed CLI process shows up as an error or end of stream rather
+    // than a Close frame
+    while let Some(frame) = read.next().await {
+        match frame {
+            Ok(Message::Text(text)) => spawn_command(text.as_str()),
+            Ok(Message::Close(_)) | Err(_) => break,
+            Ok(_) => {}
+        }
+    }
+
+    tracing::debug!("CLI {} closed", peer);
+    let _ = closed_tx.send(true);
+    drop(out_tx);
+    let _ = writer.await;
+}
+
+/// Run a batch frame (a JSON array of commands) from `peer` concurrently and
+/// build the array of responses, in request order. Returns `None` when the
+/// CLI went away before the batch finished.
+async fn execute_cli_batch(
+    peer: SocketAddr,
+    text: &str,
+    state: &Arc<Mutex<BridgeState>>,
+    closed_rx: tokio::sync::watch::Receiver<bool>,
+) -> Option<serde_json::Value> {
+    let invalid = |message: &str| {
+        serde_json::json!({
+            "id": null,
+            "error": { "code": -32600, "message": message }
+        })
+    };
+    let elements = match serde_json::from_str::<Vec<serde_json::Value>>(text) {
+        Ok(elements) if elements.is_empty() => return Some(invalid("Empty batch")),
+        Ok(elements) => elements,
         Err(_) => {
-            tracing::warn!("Invalid JSON command from CLI");
-            return;
+            tracing::warn!("Invalid JSON batch from CLI {}", peer);
+            return Some(invalid("Invalid batch"));
         }
     };
+    tracing::debug!("CLI batch of {} commands from {}", elements.len(), peer);
+
+    let responses = elements.into_iter().map(|element| {
+        let mut closed_rx = closed_rx.clone();
+        async move {
+            if !element.is_object() {
+                return invalid("Invalid request");
+            }
+            let command = CliCommand::from_value(peer, &element);
+            let id = command.id.clone();
+            let closed = async move {
+                let _ = closed_rx.wait_for(|closed| *closed).await;
+            };
+            let resp = execute_cli_command(command, state, closed).await;
+            // An unreadable extension response still needs a slot in the array
+            resp.unwrap_or_else(|| {
+                serde_json::json!({
+                    "id": id,
+                    "error": { "code": -32603, "message": "No response from extension" }
+                })
+            })
+        }
+    });
+    let responses = futures::future::join_all(responses).await;
+
+    if *closed_rx.borrow() {
+        return None;
+    }
+    Some(serde_json::Value::Array(responses))
+}
 
+/// Run one CLI command and build the response frame to send back under the
+/// CLI's id. Returns `None` when `closed` resolves first (the CLI went away
+/// and the request was cancelled) or the extension's response is unreadable.
+async fn execute_cli_command(
+    command: CliCommand,
+    state: &Arc<Mutex<BridgeState>>,
+    closed: impl std::future::Future<Output = ()>,
+) -> Option<serde_json::Value> {
     // Update activity
     {
         let mut s = state.lock().await;
         s.touch();
     }
 
-    let method = first_msg
-        .get("method")
-        .and_then(|m| m.as_str())
-        .unwrap_or("");
-    let params = first_msg
-        .get("params")
-        .cloned()
-        .unwrap_or(serde_json::Value::Null);
-    let cli_id = first_msg
-        .get("id")
-        .cloned()
-        .unwrap_or(serde_json::json!(0));
+    let method = command.method.as_str();
+    let params = command.params.clone();
+    let cli_id = command.id.clone();
 
-    tracing::debug!("CLI command: {} {:?}", method, params);
+    tracing::debug!("CLI command: {} {:?}", command, params);
 
     // Enforce CDP method allowlist
     let risk_level = match get_risk_level(method) {
         Some(level) => level,
         None => {
-            tracing::warn!("Rejected unknown CDP method: {}", method);
+            tracing::warn!("Rejected unknown CDP method: {}", command);
             let err = serde_json::json!({
                 "id": cli_id,
                 "error": {
@@ -931,22 +2119,95 @@ async fn handle_cli_client(
                     "message": format!("Method not allowed: {}", method)
                 }
             });
-            let _ = write.send(Message::Text(err.to_string().into())).await;
-            return;
+            return Some(err);
         }
     };
 
     // Log L2+ operations
     match risk_level {
         RiskLevel::L2 => {
-            tracing::info!("L2 operation: {} (page modification)", method);
+            tracing::info!("L2 operation: {} (page modification)", command);
         }
         RiskLevel::L3 => {
-            tracing::warn!("L3 operation: {} (high risk)", method);
+            tracing::warn!("L3 operation: {} (high risk)", command);
         }
         RiskLevel::L1 => {}
     }
 
+    // Reject oversized params before they reach the extension
+    let max_params_bytes = state.lock().await.max_params_bytes;
+    let params_bytes = params.to_string().len();
+    if params_bytes > max_params_bytes {
+        tracing::warn!(
+            "Rejected {}: params are {} bytes (limit {})",
+            command,
+            params_bytes,
+            max_params_bytes
+        );
+        let err = serde_json::json!({
+            "id": cli_id,
+            "error": {
+                "code": PARAMS_TOO_LARGE_CODE,
+                "message": format!(
+                    "params too large ({} bytes, limit {})",
+                    params_bytes, max_params_bytes
+                )
+            }
+        });
+        return Some(err);
+    }
+
+    // Extension.ping is answered by the bridge itself so callers can tell a
+    // missing extension apart from a bridge that is down
+    if method == "Extension.ping" {
+        let (
+            extension_connected,
+            extension_instances,
+            token_mismatch,
+            mismatched_responses,
+            late_responses,
+            pending_requests,
+        ) = {
+            let s = state.lock().await;
+            (
+                !s.extensions.is_empty(),
+                s.instance_ids()
+                    .into_iter()
+                    .map(|id| {
+                        let proto = s.extensions[&id].proto;
+                        serde_json::json!({ "id": id, "proto": proto })
+                    })
+                    .collect::<Vec<_>>(),
+                s.recent_extension_token_mismatch(),
+                s.mismatched_responses,
+                s.late_responses,
+                s.pending.len(),
+            )
+        };
+        let resp = serde_json::json!({
+            "id": cli_id,
+            "result": {
+                "status": "pong",
+                "source": "bridge",
+                "extension_connected": extension_connected,
+                "extension_instances": extension_instances,
+                "extension_token_mismatch": token_mismatch,
+                "mismatched_responses": mismatched_responses,
+                "late_responses": late_responses,
+                "pending_requests": pending_requests,
+                "proto": BRIDGE_PROTO,
+            }
+        });
+        return Some(resp);
+    }
+
+    // Extension.pingExtension is the full round-trip through the extension
+    let forward_method = if method == "Extension.pingExtension" {
+        "Extension.ping"
+    } else {
+        method
+    };
+
     // Allocate a unique id and create a oneshot channel for the response
     let (response_tx, response_rx) = oneshot::channel::<String>();
     let request_id;
@@ -954,76 +2215,119 @@ async fn handle_cli_client(
     {
         let mut s = state.lock().await;
 
-        // Check extension is connected
-        if s.extension_tx.is_none() {
-            let err = serde_json::json!({
-                "id": cli_id,
-                "error": { "code": -32000, "message": "Extension not connected" }
-            });
-            let _ = write.send(Message::Text(err.to_string().into())).await;
-            return;
-        }
+        // Pick the extension to forward to
+        let (instance, ext_tx) = match s.route(command.target_instance.as_deref()) {
+            Ok((instance, conn)) => (instance.to_string(), conn.tx.clone()),
+            Err(e) => {
+                let message = match e {
+                    RouteError::NoneConnected if s.recent_extension_token_mismatch() => {
+                        EXTENSION_TOKEN_MISMATCH.to_string()
+                    }
+                    RouteError::NoneConnected => "Extension not connected".to_string(),
+                    RouteError::UnknownInstance(id) => {
+                        format!("Extension instance '{}' not connected", id)
+                    }
+                    RouteError::Ambiguous(ids) => format!(
+                        "Multiple extensions connected ({}); set target_instance",
+                        ids.join(", ")
+                    ),
+                };
+                let err = serde_json::json!({
+                    "id": cli_id,
+                    "error": { "code": -32000, "message": message }
+                });
+                return Some(err);
+            }
+        };
 
         request_id = s.next_id;
         s.next_id += 1;
-        s.pending.insert(request_id, response_tx);
-
-        // Forward command to extension with bridge-assigned id and risk level
+        s.record_command(forward_method);
+        // Kept within 2^53 so the extension's JS numbers echo it back exactly
+        let generation = u64::from(rand::random::<u32>());
+        s.pending.insert(
+            request_id,
+            PendingRequest {
+                generation,
+                instance: instance.clone(),
+                sender: response_tx,
+            },
+        );
+
+        // Forward command to extension with bridge-assigned id, generation and risk level
         let cmd = serde_json::json!({
             "id": request_id,
-            "method": method,
+            "gen": generation,
+            "method": forward_method,
             "params": params,
             "risk_level": risk_level.as_str(),
         });
 
-        if let Some(ext_tx) = &s.extension_tx {
-            if ext_tx.send(cmd.to_string()).is_err() {
-                s.pending.remove(&request_id);
-                s.extension_tx = None;
-                drop(s);
-                let err = serde_json::json!({
-                    "id": cli_id,
-                    "error": { "code": -32000, "message": "Extension disconnected" }
-                });
-                let _ = write.send(Message::Text(err.to_string().into())).await;
-                return;
-            }
+        if ext_tx.send(cmd.to_string()).is_err() {
+            s.pending.remove(&request_id);
+            s.extensions.remove(&instance);
+            drop(s);
+            let err = serde_json::json!({
+                "id": cli_id,
+                "error": { "code": -32000, "message": "Extension disconnected" }
+            });
+            return Some(err);
         }
     }
 
-    // Wait for response from extension (with timeout)
-    match tokio::time::timeout(std::time::Duration::from_secs(30), response_rx).await {
+    // Wait for response from extension (with timeout), giving up early if the
+    // CLI closes its connection
+    let response = tokio::select! {
+        r = tokio::time::timeout(std::time::Duration::from_secs(COMMAND_TIMEOUT_SECS), response_rx) => r,
+        _ = closed => {
+            cancel_pending(state, request_id).await;
+            return None;
+        }
+    };
+    match response {
         Ok(Ok(resp_str)) => {
             // Rewrite the id to match the CLI's original id
-            if let Ok(mut resp) = serde_json::from_str::<serde_json::Value>(&resp_str) {
-                resp["id"] = cli_id;
-                let _ = write
-                    .send(Message::Text(resp.to_string().into()))
-                    .await;
+            let mut resp = serde_json::from_str::<serde_json::Value>(&resp_str).ok()?;
+            resp["id"] = cli_id;
+            if let Some(obj) = resp.as_object_mut() {
+                obj.remove("gen");
             }
+            Some(resp)
         }
-        Ok(Err(_)) => {
-            let err = serde_json::json!({
-                "id": cli_id,
-                "error": { "code": -32000, "message": "Extension connection lost" }
-            });
-            let _ = write.send(Message::Text(err.to_string().into())).await;
-        }
+        Ok(Err(_)) => Some(serde_json::json!({
+            "id": cli_id,
+            "error": { "code": -32000, "message": "Extension connection lost" }
+        })),
         Err(_) => {
-            // Timeout — clean up pending request
-            let mut s = state.lock().await;
-            s.pending.remove(&request_id);
-            drop(s);
+            // Timeout — clean up pending request, remembering it in case
+            // the extension answers later
+            state.lock().await.record_timeout(request_id);
 
-            let err = serde_json::json!({
+            Some(serde_json::json!({
                 "id": cli_id,
-                "error": { "code": -32000, "message": "Extension command timed out (30s)" }
-            });
-            let _ = write.send(Message::Text(err.to_string().into())).await;
+                "error": {
+                    "code": -32000,
+                    "message": format!("Extension command timed out ({}s)", COMMAND_TIMEOUT_SECS)
+                }
+            }))
         }
     }
 }
 
+/// Drop a pending request whose CLI caller went away and tell the extension
+/// it can stop working on it.
+async fn cancel_pending(state: &Arc<Mutex<BridgeState>>, request_id: u64) {
+    let mut s = state.lock().await;
+    let Some(pending) = s.pending.remove(&request_id) else {
+        return;
+    };
+    tracing::debug!("CLI disconnected, cancelling request {}", request_id);
+    if let Some(conn) = s.extensions.get(&pending.instance) {
+        let cancel = serde_json::json!({ "type": "cancel", "id": request_id });
+        let _ = conn.tx.send(cancel.to_string());
+    }
+}
+
 /// Send a single command to the extension via the bridge and wait for the response.
 /// Used by CLI commands when `--extension` mode is active.
 /// Selects the correct token file based on which PID file's embedded port matches
@@ -1033,11 +2337,71 @@ pub async fn send_command(
     method: &str,
     params: serde_json::Value,
 ) -> Result<serde_json::Value> {
+    let token = resolve_token(port).await?;
+    send_command_with_token(port, method, params, &token).await
+}
+
+/// Like [`send_command`], but returns the full response frame from the extension
+/// (`id`, `result` or `error`, and any extra top-level fields such as `meta`)
+/// instead of just `result`. Error frames are returned as-is, not as `Err`.
+#[allow(dead_code)]
+pub async fn send_command_raw(
+    port: u16,
+    method: &str,
+    params: serde_json::Value,
+) -> Result<serde_json::Value> {
+    let token = resolve_token(port).await?;
+    send_command_raw_with_token(port, method, params, &token).await
+}
+
+/// A browser tab as reported by `Extension.listTabs`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TabInfo {
+    /// Chrome tab id, as accepted by `Extension.attachTab`/`activateTab`
+    pub id: u64,
+    /// Chrome omits url and title for tabs it has not committed yet
+    #[serde(default)]
+    pub url: String,
+    #[serde(default)]
+    pub title: String,
+    /// Whether this is the active tab of its window
+    #[serde(default)]
+    pub active: bool,
+}
+
+/// List the browser's tabs through the bridge on `port`.
+pub async fn list_tabs(port: u16) -> Result<Vec<TabInfo>> {
+    let token = resolve_token(port).await?;
+    list_tabs_with_token(port, &token).await
+}
+
+/// Like [`list_tabs`], authenticating with an explicit token.
+pub async fn list_tabs_with_token(port: u16, token: &str) -> Result<Vec<TabInfo>> {
+    let result =
+        send_command_with_token(port, "Extension.listTabs", serde_json::json!({}), token).await?;
+    parse_tabs(result)
+}
+
+/// Decode the `result` of an `Extension.listTabs` response.
+fn parse_tabs(mut result: serde_json::Value) -> Result<Vec<TabInfo>> {
+    let tabs = result
+        .get_mut("tabs")
+        .map(serde_json::Value::take)
+        .ok_or_else(|| {
+            ActionbookError::ExtensionError("Extension.listTabs response has no tabs".to_string())
+        })?;
+    serde_json::from_value(tabs).map_err(|e| {
+        ActionbookError::ExtensionError(format!("Malformed Extension.listTabs response: {}", e))
+    })
+}
+
+/// Find the session token for the bridge on `port`.
+async fn resolve_token(port: u16) -> Result<String> {
     // Use PID:PORT mapping to select the correct token file for this port.
     let iso_match = read_isolated_pid_file().await.is_some_and(|(_pid, pt)| pt == port);
     let std_match = read_pid_file().await.is_some_and(|(_pid, pt)| pt == port);
 
-    let token = match (iso_match, std_match) {
+    match (iso_match, std_match) {
         (true, false) => read_isolated_token_file().await,
         (false, true) => read_token_file().await,
         // Ambiguous or no match — try both (standard first for backwards compat)
@@ -1048,9 +2412,57 @@ pub async fn send_command(
             "No bridge token found. Is `actionbook extension serve` running?"
                 .to_string(),
         )
+    })
+}
+
+/// A running bridge found by [`discover_bridge`], with the token to reach it.
+#[derive(Debug, Clone)]
+pub struct BridgeEndpoint {
+    pub mode: BridgeMode,
+    pub port: u16,
+    pub token: String,
+}
+
+/// Find a running bridge when no port was given: the isolated bridge first
+/// (its port and token files), then the standard one (its port file, or the
+/// default port). A candidate is used only if its token file exists and
+/// something is listening on its port.
+pub async fn discover_bridge() -> Result<BridgeEndpoint> {
+    let data_dir = dirs::data_local_dir().ok_or_else(|| {
+        ActionbookError::Other("Cannot determine local data directory".to_string())
     })?;
+    discover_bridge_in(&data_dir.join("actionbook")).await
+}
 
-    send_command_with_token(port, method, params, &token).await
+async fn discover_bridge_in(dir: &Path) -> Result<BridgeEndpoint> {
+    for mode in [BridgeMode::Isolated, BridgeMode::Standard] {
+        let suffix = mode.file_suffix();
+        let read = |name: &str| tokio::fs::read_to_string(dir.join(format!("{}{}", name, suffix)));
+        let Ok(token) = read("bridge-token").await else {
+            continue;
+        };
+        let port = match read("bridge-port").await {
+            Ok(content) => content.trim().parse().ok(),
+            Err(_) if mode == BridgeMode::Standard => Some(DEFAULT_BRIDGE_PORT),
+            Err(_) => None,
+        };
+        let Some(port) = port else {
+            continue;
+        };
+        if is_bridge_running(port).await {
+            return Ok(BridgeEndpoint {
+                mode,
+                port,
+                token: token.trim().to_string(),
+            });
+        }
+        tracing::debug!("{:?} bridge on port {} is not running", mode, port);
+    }
+    Err(ActionbookError::ExtensionError(
+        "No running bridge found (checked isolated and standard). \
+         Is `actionbook extension serve` running?"
+            .to_string(),
+    ))
 }
 
 /// Send a single command with an explicit token.
@@ -1060,27 +2472,169 @@ pub async fn send_command_with_token(
     params: serde_json::Value,
     token: &str,
 ) -> Result<serde_json::Value> {
+    let resp = send_command_raw_with_token(port, method, params, token).await?;
+    response_result(resp)
+}
+
+/// The `result` of a response frame, or its `error` as an `Err`:
+/// [`ActionbookError::ExtensionRpc`] when the error carries a numeric code.
+fn response_result(resp: serde_json::Value) -> Result<serde_json::Value> {
+    if let Some(error) = resp.get("error") {
+        let message = error
+            .get("message")
+            .and_then(|m| m.as_str())
+            .unwrap_or("Unknown extension error")
+            .to_string();
+        return Err(match error.get("code").and_then(|c| c.as_i64()) {
+            Some(code) => ActionbookError::ExtensionRpc {
+                code,
+                message,
+                data: error.get("data").cloned(),
+            },
+            None => ActionbookError::ExtensionError(message),
+        });
+    }
+    Ok(resp
+        .get("result")
+        .cloned()
+        .unwrap_or(serde_json::Value::Null))
+}
+
+/// Send a single command with an explicit token and return the full response frame.
+pub async fn send_command_raw_with_token(
+    port: u16,
+    method: &str,
+    params: serde_json::Value,
+    token: &str,
+) -> Result<serde_json::Value> {
+    let mut ws = connect_cli(port, token, false).await?;
+
+    // Send the actual command
+    let msg = serde_json::json!({
+        "id": 1,
+        "method": method,
+        "params": params,
+    });
+
+    ws.send(Message::Text(msg.to_string().into()))
+        .await
+        .map_err(|e| ActionbookError::BridgeConnection {
+            op: "send",
+            source: Box::new(e),
+        })?;
+
+    read_response(&mut ws).await
+}
+
+/// Send several commands to the bridge on `port` as one batch frame.
+/// Results come back in the order of `commands`, each as its `result` or
+/// error; the outer `Err` is for failing to reach the bridge at all.
+#[allow(dead_code)]
+pub async fn send_command_batch(
+    port: u16,
+    commands: Vec<(String, serde_json::Value)>,
+) -> Result<Vec<Result<serde_json::Value>>> {
+    let token = resolve_token(port).await?;
+    send_command_batch_with_token(port, commands, &token).await
+}
+
+/// Like [`send_command_batch`], with an explicit token.
+pub async fn send_command_batch_with_token(
+    port: u16,
+    commands: Vec<(String, serde_json::Value)>,
+    token: &str,
+) -> Result<Vec<Result<serde_json::Value>>> {
+    if commands.is_empty() {
+        return Ok(Vec::new());
+    }
+    let count = commands.len();
+    let batch: Vec<serde_json::Value> = commands
+        .into_iter()
+        .enumerate()
+        .map(|(i, (method, params))| {
+            serde_json::json!({ "id": i + 1, "method": method, "params": params })
+        })
+        .collect();
+
+    let mut ws = connect_cli(port, token, false).await?;
+    ws.send(Message::Text(serde_json::Value::Array(batch).to_string().into()))
+        .await
+        .map_err(|e| ActionbookError::BridgeConnection {
+            op: "send",
+            source: Box::new(e),
+        })?;
+
+    let responses = match read_response(&mut ws).await? {
+        serde_json::Value::Array(responses) if responses.len() == count => responses,
+        other => {
+            return Err(ActionbookError::ExtensionError(format!(
+                "Unexpected batch response: {}",
+                other
+            )))
+        }
+    };
+    Ok(responses.into_iter().map(response_result).collect())
+}
+
+/// Wait for the next text frame on `ws` and parse it as JSON.
+async fn read_response(ws: &mut CliStream) -> Result<serde_json::Value> {
+    while let Some(frame) = ws.next().await {
+        match frame {
+            Ok(Message::Text(text)) => {
+                return Ok(serde_json::from_str(text.as_str())?);
+            }
+            Ok(Message::Close(_)) => break,
+            Ok(_) => continue,
+            Err(e) => {
+                return Err(ActionbookError::BridgeConnection {
+                    op: "read",
+                    source: Box::new(e),
+                });
+            }
+        }
+    }
+
+    Err(ActionbookError::ExtensionError(
+        "Connection closed without response".to_string(),
+    ))
+}
+
+/// A CLI-side WebSocket connection to the bridge
+type CliStream = tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<TcpStream>>;
+
+/// Connect to the bridge on `port` and complete the CLI hello handshake.
+/// With `session`, ask the bridge to keep the connection open for many
+/// commands (see [`BridgeClient`]).
+async fn connect_cli(port: u16, token: &str, session: bool) -> Result<CliStream> {
     use tokio_tungstenite::connect_async;
 
     let url = format!("ws://127.0.0.1:{}", port);
-    let (mut ws, _) = connect_async(&url).await.map_err(|e| {
-        ActionbookError::ExtensionError(format!(
-            "Cannot connect to bridge at {}. Is `actionbook extension serve` running? ({})",
-            url, e
-        ))
-    })?;
+    let (mut ws, _) =
+        connect_async(&url)
+            .await
+            .map_err(|e| ActionbookError::BridgeUnreachable {
+                url: url.clone(),
+                source: Box::new(e),
+            })?;
 
     // Send hello handshake first
-    let hello = serde_json::json!({
+    let mut hello = serde_json::json!({
         "type": "hello",
         "role": "cli",
         "token": token,
         "version": PROTOCOL_VERSION,
+        "proto": BRIDGE_PROTO,
     });
+    if session {
+        hello["session"] = serde_json::json!(true);
+    }
 
     ws.send(Message::Text(hello.to_string().into()))
         .await
-        .map_err(|e| ActionbookError::ExtensionError(format!("Send hello failed: {}", e)))?;
+        .map_err(|e| ActionbookError::BridgeConnection {
+            op: "hello",
+            source: Box::new(e),
+        })?;
 
     // Wait for hello_ack from server
     match tokio::time::timeout(std::time::Duration::from_secs(5), ws.next()).await {
@@ -1088,10 +2642,26 @@ pub async fn send_command_with_token(
             let ack: serde_json::Value =
                 serde_json::from_str(text.as_str()).unwrap_or_default();
             if ack.get("type").and_then(|t| t.as_str()) != Some("hello_ack") {
+                if ack.get("error").and_then(|e| e.as_str()) == Some("proto_mismatch") {
+                    return Err(ActionbookError::ExtensionError(
+                        ack.get("message")
+                            .and_then(|m| m.as_str())
+                            .unwrap_or("Bridge protocol mismatch")
+                            .to_string(),
+                    ));
+                }
                 return Err(ActionbookError::ExtensionError(
                     "Authentication failed: invalid token".to_string(),
                 ));
             }
+            // An older bridge ignores `session` and would answer only the
+            // first command
+            if session && ack.get("session").and_then(|s| s.as_bool()) != Some(true) {
+                return Err(ActionbookError::ExtensionError(
+                    "Bridge does not support command sessions; restart it with a newer actionbook"
+                        .to_string(),
+                ));
+            }
         }
         Ok(Some(Ok(Message::Close(_)))) | Ok(None) => {
             return Err(ActionbookError::ExtensionError(
@@ -1099,10 +2669,10 @@ pub async fn send_command_with_token(
             ));
         }
         Ok(Some(Err(e))) => {
-            return Err(ActionbookError::ExtensionError(format!(
-                "Authentication error: {}",
-                e
-            )));
+            return Err(ActionbookError::BridgeConnection {
+                op: "handshake",
+                source: Box::new(e),
+            });
         }
         Ok(Some(Ok(_))) => {
             // Binary, Ping, Pong, Frame - unexpected during handshake
@@ -1117,47 +2687,112 @@ pub async fn send_command_with_token(
         }
     }
 
-    // Send the actual command
-    let msg = serde_json::json!({
-        "id": 1,
-        "method": method,
-        "params": params,
-    });
+    Ok(ws)
+}
 
-    ws.send(Message::Text(msg.to_string().into()))
-        .await
-        .map_err(|e| ActionbookError::ExtensionError(format!("Send failed: {}", e)))?;
+/// Responses awaited by a [`BridgeClient`], keyed by request id
+#[allow(dead_code)]
+type ClientPending = Arc<std::sync::Mutex<HashMap<u64, oneshot::Sender<serde_json::Value>>>>;
 
-    // Wait for response
-    while let Some(frame) = ws.next().await {
-        match frame {
-            Ok(Message::Text(text)) => {
-                let resp: serde_json::Value = serde_json::from_str(text.as_str())?;
-                if let Some(error) = resp.get("error") {
-                    return Err(ActionbookError::ExtensionError(
-                        error
-                            .get("message")
-                            .and_then(|m| m.as_str())
-                            .unwrap_or("Unknown extension error")
-                            .to_string(),
-                    ));
+/// One authenticated bridge connection reused for a sequence of commands,
+/// instead of the connect-and-hello per command of [`send_command`].
+///
+/// Calls may run concurrently; responses are matched to callers by id.
+#[allow(dead_code)]
+pub struct BridgeClient {
+    sink: Mutex<futures::stream::SplitSink<CliStream, Message>>,
+    pending: ClientPending,
+    next_id: std::sync::atomic::AtomicU64,
+    reader: tokio::task::JoinHandle<()>,
+}
+
+#[allow(dead_code)]
+impl BridgeClient {
+    /// Connect to the bridge on `port`, using the token file that matches it.
+    pub async fn connect(port: u16) -> Result<Self> {
+        let token = resolve_token(port).await?;
+        Self::connect_with_token(port, &token).await
+    }
+
+    /// Connect to the bridge on `port` with an explicit token.
+    pub async fn connect_with_token(port: u16, token: &str) -> Result<Self> {
+        let (sink, mut stream) = connect_cli(port, token, true).await?.split();
+        let pending: ClientPending = Arc::default();
+
+        let reader_pending = Arc::clone(&pending);
+        let reader = tokio::spawn(async move {
+            while let Some(Ok(frame)) = stream.next().await {
+                let Message::Text(text) = frame else {
+                    continue;
+                };
+                let Ok(resp) = serde_json::from_str::<serde_json::Value>(text.as_str()) else {
+                    continue;
+                };
+                let Some(id) = resp.get("id").and_then(|id| id.as_u64()) else {
+                    continue;
+                };
+                if let Some(tx) = reader_pending.lock().unwrap().remove(&id) {
+                    let _ = tx.send(resp);
                 }
-                return Ok(resp.get("result").cloned().unwrap_or(serde_json::Value::Null));
-            }
-            Ok(Message::Close(_)) => break,
-            Ok(_) => continue,
-            Err(e) => {
-                return Err(ActionbookError::ExtensionError(format!(
-                    "WebSocket error: {}",
-                    e
-                )));
             }
+            // Fail every outstanding call by dropping its sender
+            reader_pending.lock().unwrap().clear();
+        });
+
+        Ok(Self {
+            sink: Mutex::new(sink),
+            pending,
+            next_id: std::sync::atomic::AtomicU64::new(1),
+            reader,
+        })
+    }
+
+    /// Send a command and return its `result`, or its `error` as an `Err`.
+    pub async fn call(&self, method: &str, params: serde_json::Value) -> Result<serde_json::Value> {
+        response_result(self.call_raw(method, params).await?)
+    }
+
+    /// Like [`BridgeClient::call`], returning the full response frame.
+    pub async fn call_raw(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        let id = self
+            .next_id
+            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().unwrap().insert(id, tx);
+
+        let msg = serde_json::json!({
+            "id": id,
+            "method": method,
+            "params": params,
+        });
+        if let Err(e) = self
+            .sink
+            .lock()
+            .await
+            .send(Message::Text(msg.to_string().into()))
+            .await
+        {
+            self.pending.lock().unwrap().remove(&id);
+            return Err(ActionbookError::BridgeConnection {
+                op: "send",
+                source: Box::new(e),
+            });
         }
+
+        rx.await.map_err(|_| {
+            ActionbookError::ExtensionError("Connection closed without response".to_string())
+        })
     }
+}
 
-    Err(ActionbookError::ExtensionError(
-        "Connection closed without response".to_string(),
-    ))
+impl Drop for BridgeClient {
+    fn drop(&mut self) {
+        self.reader.abort();
+    }
 }
 
 /// Check if a process with the given PID is still alive.
@@ -1192,9 +2827,323 @@ pub async fn is_bridge_running(port: u16) -> bool {
         .is_ok()
 }
 
+/// Check that a WebSocket server (not just any TCP listener) answers on `port`.
+/// Completes the upgrade and closes straight away, without a hello or command.
+#[allow(dead_code)]
+pub async fn handshake_probe(port: u16) -> bool {
+    let url = format!("ws://127.0.0.1:{}", port);
+    let probe = async {
+        let Ok((mut ws, _)) = tokio_tungstenite::connect_async(&url).await else {
+            return false;
+        };
+        let _ = ws.close(None).await;
+        // Drain until the server acknowledges the close so no connection lingers
+        while let Some(Ok(_)) = ws.next().await {}
+        true
+    };
+    tokio::time::timeout(std::time::Duration::from_secs(2), probe)
+        .await
+        .unwrap_or(false)
+}
+
+/// How long serve waits for an extension before reporting it hasn't connected
+pub const EXTENSION_CONNECT_WAIT: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Printed by serve when no extension connects within
+/// [`ServeOptions::connect_hint_after`]
+pub const NO_EXTENSION_HINT: &str =
+    "No extension connected yet — did you load it in Chrome and enter the token?";
+
+/// Wait `after`, then tell whether no extension has connected to the bridge
+/// in that time. A connection that has since dropped still counts.
+async fn no_extension_after(state: &Mutex<BridgeState>, after: Duration) -> bool {
+    tokio::time::sleep(after).await;
+    !state.lock().await.extension_seen
+}
+
+/// Wait until an extension has completed its hello with the bridge on `port`,
+/// asking the bridge via `Extension.ping` until `timeout` elapses. Returns
+/// `false` if none connected in time or the bridge never answered.
+pub async fn wait_for_extension(port: u16, token: &str, timeout: std::time::Duration) -> bool {
+    let deadline = tokio::time::Instant::now() + timeout;
+    loop {
+        let ping = send_command_with_token(port, "Extension.ping", serde_json::json!({}), token);
+        let remaining = deadline.saturating_duration_since(tokio::time::Instant::now());
+        if let Ok(Ok(result)) = tokio::time::timeout(remaining, ping).await {
+            if result["extension_connected"].as_bool() == Some(true) {
+                return true;
+            }
+        }
+        if tokio::time::Instant::now() >= deadline {
+            return false;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+    }
+}
+
+/// Wait until nothing is listening on `port` any more, polling until
+/// `timeout` elapses. Returns `false` if the port is still taken.
+pub async fn wait_for_port_release(port: u16, timeout: std::time::Duration) -> bool {
+    let deadline = tokio::time::Instant::now() + timeout;
+    loop {
+        if !is_bridge_running(port).await {
+            return true;
+        }
+        if tokio::time::Instant::now() >= deadline {
+            return false;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::clock::FakeClock;
+
+    fn pending_request(state: &mut BridgeState, id: u64, generation: u64) {
+        let (sender, _rx) = oneshot::channel();
+        state.pending.insert(
+            id,
+            PendingRequest {
+                generation,
+                instance: DEFAULT_INSTANCE_ID.to_string(),
+                sender,
+            },
+        );
+    }
+
+    #[test]
+    fn cli_command_carries_peer() {
+        let peer: SocketAddr = "127.0.0.1:54321".parse().unwrap();
+        let command = CliCommand::parse(
+            peer,
+            r#"{"id": 3, "method": "Extension.listTabs", "params": {"a": 1}}"#,
+        )
+        .unwrap();
+
+        assert_eq!(command.peer, peer);
+        assert_eq!(command.id, serde_json::json!(3));
+        assert_eq!(command.method, "Extension.listTabs");
+        assert_eq!(command.params, serde_json::json!({"a": 1}));
+        assert_eq!(
+            command.to_string(),
+            "Extension.listTabs from 127.0.0.1:54321"
+        );
+    }
+
+    #[test]
+    fn cli_command_defaults_and_rejects_invalid_json() {
+        let peer: SocketAddr = "127.0.0.1:1".parse().unwrap();
+        let command = CliCommand::parse(peer, r#"{"method": "Extension.ping"}"#).unwrap();
+        assert_eq!(command.id, serde_json::json!(0));
+        assert_eq!(command.params, serde_json::Value::Null);
+
+        assert!(CliCommand::parse(peer, "not json").is_none());
+    }
+
+    fn connect_extension(state: &mut BridgeState, instance_id: &str) {
+        let (tx, _rx) = mpsc::unbounded_channel();
+        state.add_extension(instance_id.to_string(), ExtensionConn { tx, proto: 1 });
+    }
+
+    #[tokio::test]
+    async fn connect_hint_fires_only_without_an_extension() {
+        let after = Duration::from_millis(50);
+        let state = Arc::new(Mutex::new(BridgeState::new("abk_test".to_string())));
+        assert!(no_extension_after(&state, after).await);
+
+        // An extension connecting within the window suppresses the hint
+        let hint = tokio::spawn({
+            let state = Arc::clone(&state);
+            async move { no_extension_after(&state, after).await }
+        });
+        connect_extension(&mut *state.lock().await, "default");
+        assert!(!hint.await.unwrap());
+
+        // ... even if it has disconnected again by the time the window ends
+        state.lock().await.extensions.clear();
+        assert!(!no_extension_after(&state, after).await);
+    }
+
+    #[test]
+    fn route_picks_target_or_sole_extension() {
+        let mut state = BridgeState::new("abk_test".to_string());
+        assert_eq!(state.route(None).err(), Some(RouteError::NoneConnected));
+
+        connect_extension(&mut state, "work");
+        assert_eq!(state.route(None).map(|(id, _)| id).ok(), Some("work"));
+        assert_eq!(
+            state.route(Some("home")).err(),
+            Some(RouteError::UnknownInstance("home".to_string()))
+        );
+
+        connect_extension(&mut state, "home");
+        assert_eq!(
+            state.route(None).err(),
+            Some(RouteError::Ambiguous(vec![
+                "home".to_string(),
+                "work".to_string()
+            ]))
+        );
+        assert_eq!(
+            state.route(Some("home")).map(|(id, _)| id).ok(),
+            Some("home")
+        );
+    }
+
+    #[test]
+    fn cli_command_reads_target_instance() {
+        let peer: SocketAddr = "127.0.0.1:1".parse().unwrap();
+        let command = CliCommand::parse(
+            peer,
+            r#"{"method": "Extension.listTabs", "target_instance": "work"}"#,
+        )
+        .unwrap();
+        assert_eq!(command.target_instance.as_deref(), Some("work"));
+    }
+
+    #[test]
+    fn response_after_timeout_is_late_not_unknown() {
+        let mut state = BridgeState::new("abk_test".to_string());
+        pending_request(&mut state, 7, 42);
+        state.record_timeout(7);
+        assert!(state.pending.is_empty());
+
+        assert!(matches!(
+            state.take_response_target(7, Some(42)),
+            ResponseTarget::Late(_)
+        ));
+        // Each late id is reported once; a repeat is unknown
+        assert!(matches!(
+            state.take_response_target(7, Some(42)),
+            ResponseTarget::Unmatched
+        ));
+        assert!(matches!(
+            state.take_response_target(8, None),
+            ResponseTarget::Unmatched
+        ));
+    }
+
+    #[test]
+    fn late_response_with_wrong_generation_is_unmatched() {
+        let mut state = BridgeState::new("abk_test".to_string());
+        pending_request(&mut state, 7, 42);
+        state.record_timeout(7);
+
+        assert!(matches!(
+            state.take_response_target(7, Some(43)),
+            ResponseTarget::Unmatched
+        ));
+    }
+
+    fn state_with_fake_clock() -> (BridgeState, Arc<FakeClock>) {
+        let clock = Arc::new(FakeClock::new());
+        let state = BridgeState::with_clock("abk_test".to_string(), clock.clone());
+        (state, clock)
+    }
+
+    #[test]
+    fn token_expiry_follows_monotonic_idle_time() {
+        let (mut state, clock) = state_with_fake_clock();
+        let ttl = Duration::from_secs(TOKEN_TTL_SECS);
+
+        clock.advance(ttl - Duration::from_secs(1));
+        // A wall clock stepped back (or forward) a day leaves idle time alone
+        clock.set_system_now(clock.system_now() - Duration::from_secs(24 * 3600));
+        assert!(!state.token_idle());
+        state.touch();
+        clock.set_system_now(clock.system_now() + Duration::from_secs(24 * 3600));
+        clock.advance(ttl - Duration::from_secs(1));
+        assert!(!state.token_idle());
+
+        clock.advance(Duration::from_secs(1));
+        assert!(state.token_idle());
+    }
+
+    #[test]
+    fn live_summary_formats_metrics() {
+        let idle = BridgeMetrics {
+            extension_instances: vec![],
+            in_flight: 0,
+            last_command: None,
+            requests_per_min: 0,
+        };
+        assert_eq!(
+            format_live_summary(&idle),
+            "extension: not connected | in flight: 0 | last: none | 0 req/min"
+        );
+
+        let busy = BridgeMetrics {
+            extension_instances: vec!["default".to_string(), "work".to_string()],
+            in_flight: 2,
+            last_command: Some(("Page.navigate".to_string(), Duration::from_millis(3400))),
+            requests_per_min: 17,
+        };
+        assert_eq!(
+            format_live_summary(&busy),
+            "extension: connected (default, work) | in flight: 2 \
+             | last: Page.navigate (3s ago) | 17 req/min"
+        );
+    }
+
+    #[test]
+    fn metrics_count_commands_in_the_last_minute() {
+        let (mut state, clock) = state_with_fake_clock();
+        state.record_command("Page.navigate");
+        clock.advance(Duration::from_secs(30));
+        state.record_command("Runtime.evaluate");
+        pending_request(&mut state, 1, 1);
+
+        let metrics = state.metrics();
+        assert_eq!(metrics.requests_per_min, 2);
+        assert_eq!(metrics.in_flight, 1);
+        assert_eq!(
+            metrics.last_command,
+            Some(("Runtime.evaluate".to_string(), Duration::ZERO))
+        );
+
+        clock.advance(Duration::from_secs(31));
+        assert_eq!(state.metrics().requests_per_min, 1);
+    }
+
+    #[test]
+    fn expiry_windows_follow_the_clock() {
+        let (mut state, clock) = state_with_fake_clock();
+
+        state.extension_token_rejected_at = Some(clock.now());
+        assert!(state.recent_extension_token_mismatch());
+        clock.advance(Duration::from_secs(TOKEN_MISMATCH_WINDOW_SECS));
+        assert!(!state.recent_extension_token_mismatch());
+
+        pending_request(&mut state, 1, 1);
+        state.record_timeout(1);
+        clock.advance(Duration::from_secs(3));
+        match state.take_response_target(1, Some(1)) {
+            ResponseTarget::Late(late_by) => assert_eq!(late_by, Duration::from_secs(3)),
+            _ => panic!("expected a late response"),
+        }
+
+        pending_request(&mut state, 2, 1);
+        state.record_timeout(2);
+        clock.advance(Duration::from_secs(LATE_RESPONSE_WINDOW_SECS));
+        pending_request(&mut state, 3, 1);
+        state.record_timeout(3);
+        assert!(matches!(
+            state.take_response_target(2, Some(1)),
+            ResponseTarget::Unmatched
+        ));
+    }
+
+    #[test]
+    fn timed_out_tracking_is_bounded() {
+        let mut state = BridgeState::new("abk_test".to_string());
+        for id in 0..(MAX_TIMED_OUT_TRACKED as u64 + 10) {
+            pending_request(&mut state, id, 1);
+            state.record_timeout(id);
+        }
+        assert_eq!(state.timed_out.len(), MAX_TIMED_OUT_TRACKED);
+    }
 
     #[test]
     fn test_origin_allowed() {
@@ -1260,4 +3209,273 @@ mod tests {
         assert!(token.starts_with(TOKEN_PREFIX));
         assert_eq!(token.len(), 4 + 32); // "abk_" + 32 hex chars
     }
+
+    #[test]
+    fn response_ids_are_coerced_when_unambiguous() {
+        let id = |resp: serde_json::Value| response_u64(&resp, "id");
+
+        assert_eq!(id(serde_json::json!({"id": 5})), Some(5));
+        assert_eq!(id(serde_json::json!({"id": "5"})), Some(5));
+        assert_eq!(id(serde_json::json!({"id": 5.0})), Some(5));
+
+        assert_eq!(id(serde_json::json!({"id": 5.5})), None);
+        assert_eq!(id(serde_json::json!({"id": -1})), None);
+        assert_eq!(id(serde_json::json!({"id": "five"})), None);
+        assert_eq!(id(serde_json::json!({"id": 1e300})), None);
+        assert_eq!(id(serde_json::json!({"id": null})), None);
+        assert_eq!(id(serde_json::json!({"type": "event"})), None);
+    }
+
+    #[test]
+    fn token_rotated_event_leaves_out_supplied_tokens() {
+        let event = token_rotated_event(19222, Some("abk_new"));
+        assert_eq!(event["event"], "token_rotated");
+        assert_eq!(event["port"], 19222);
+        assert_eq!(event["token"], "abk_new");
+
+        assert!(token_rotated_event(19222, None).get("token").is_none());
+    }
+
+    #[test]
+    fn test_negotiate_proto() {
+        let proto = |v: serde_json::Value| negotiate_proto(Some(&v));
+        assert_eq!(negotiate_proto(None), Some(1));
+        assert_eq!(proto(serde_json::json!(1)), Some(1));
+        assert_eq!(proto(serde_json::json!(2)), None);
+        assert_eq!(proto(serde_json::json!(u64::MAX)), None);
+        assert_eq!(proto(serde_json::json!("1")), None);
+        assert_eq!(proto(serde_json::json!(1.5)), None);
+        assert_eq!(proto(serde_json::json!(null)), None);
+    }
+
+    fn write_bridge_files(dir: &Path, mode: BridgeMode, pid: u32, port: u16) {
+        let suffix = mode.file_suffix();
+        std::fs::write(dir.join(format!("bridge-token{}", suffix)), "abk_test").unwrap();
+        std::fs::write(dir.join(format!("bridge-port{}", suffix)), port.to_string()).unwrap();
+        std::fs::write(
+            dir.join(format!("bridge-pid{}", suffix)),
+            format!("{}:{}", pid, port),
+        )
+        .unwrap();
+    }
+
+    fn bridge_files_exist(dir: &Path, mode: BridgeMode) -> bool {
+        let suffix = mode.file_suffix();
+        ["bridge-token", "bridge-port", "bridge-pid"]
+            .iter()
+            .all(|name| dir.join(format!("{}{}", name, suffix)).exists())
+    }
+
+    #[cfg(unix)]
+    fn dead_pid() -> u32 {
+        let mut child = std::process::Command::new("true").spawn().unwrap();
+        let pid = child.id();
+        child.wait().unwrap();
+        pid
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn reset_removes_only_stale_bridge_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut live = std::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .unwrap();
+        write_bridge_files(dir.path(), BridgeMode::Standard, live.id(), 19222);
+        write_bridge_files(dir.path(), BridgeMode::Isolated, dead_pid(), 19333);
+        std::fs::write(dir.path().join("bridge-token.stable"), "abk_test").unwrap();
+
+        let found = scan_bridge_files_in(dir.path()).await;
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].mode, BridgeMode::Standard);
+        assert!(found[0].live);
+        assert_eq!(found[0].owner, Some((live.id(), 19222)));
+        assert_eq!(found[1].mode, BridgeMode::Isolated);
+        assert!(!found[1].live);
+        assert_eq!(found[1].paths.len(), 3);
+
+        let removed = remove_stale_bridge_files(&found).await;
+        assert_eq!(removed, found[1].paths);
+        assert!(bridge_files_exist(dir.path(), BridgeMode::Standard));
+        assert!(!dir.path().join("bridge-pid.isolated").exists());
+        assert!(dir.path().join("bridge-token.stable").exists());
+
+        let _ = live.kill();
+        let _ = live.wait();
+    }
+
+    #[tokio::test]
+    async fn reset_treats_ownerless_files_as_stale() {
+        let dir = tempfile::tempdir().unwrap();
+        // A token and a legacy PID-only file, with no port file
+        std::fs::write(dir.path().join("bridge-token"), "abk_test").unwrap();
+        std::fs::write(dir.path().join("bridge-pid"), "12345").unwrap();
+
+        let found = scan_bridge_files_in(dir.path()).await;
+        assert_eq!(found[0].owner, None);
+        assert!(!found[0].live);
+        assert_eq!(found[0].paths.len(), 2);
+        assert!(found[1].paths.is_empty());
+
+        assert_eq!(remove_stale_bridge_files(&found).await.len(), 2);
+        assert!(scan_bridge_files_in(dir.path())
+            .await
+            .iter()
+            .all(|files| files.paths.is_empty()));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_reconcile_keeps_live_peer_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut peer = std::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .unwrap();
+
+        // A live isolated bridge must survive a standard bridge starting up,
+        // and a live standard bridge must survive an isolated one
+        write_bridge_files(dir.path(), BridgeMode::Isolated, peer.id(), 19333);
+        write_bridge_files(dir.path(), BridgeMode::Standard, peer.id(), 19222);
+        reconcile_stale_files_in(dir.path(), BridgeMode::Standard, 19444).await;
+        reconcile_stale_files_in(dir.path(), BridgeMode::Isolated, 19555).await;
+
+        assert!(bridge_files_exist(dir.path(), BridgeMode::Isolated));
+        assert!(bridge_files_exist(dir.path(), BridgeMode::Standard));
+
+        let _ = peer.kill();
+        let _ = peer.wait();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_reconcile_cleans_dead_peer_files() {
+        let dir = tempfile::tempdir().unwrap();
+        write_bridge_files(dir.path(), BridgeMode::Isolated, dead_pid(), 19333);
+        write_bridge_files(dir.path(), BridgeMode::Standard, dead_pid(), 19222);
+
+        reconcile_stale_files_in(dir.path(), BridgeMode::Standard, 19222).await;
+
+        for mode in [BridgeMode::Standard, BridgeMode::Isolated] {
+            let suffix = mode.file_suffix();
+            for name in ["bridge-token", "bridge-port", "bridge-pid"] {
+                assert!(!dir.path().join(format!("{}{}", name, suffix)).exists());
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_cleans_ownerless_and_own_slot_files() {
+        let dir = tempfile::tempdir().unwrap();
+
+        // Token/port without a PID file, and a legacy PID-only file
+        std::fs::write(dir.path().join("bridge-token"), "abk_test").unwrap();
+        std::fs::write(dir.path().join("bridge-port"), "19222").unwrap();
+        std::fs::write(dir.path().join("bridge-pid"), "12345").unwrap();
+        // This process's own entry for the slot it is about to take
+        write_bridge_files(dir.path(), BridgeMode::Isolated, std::process::id(), 19333);
+
+        reconcile_stale_files_in(dir.path(), BridgeMode::Isolated, 19333).await;
+
+        assert!(!dir.path().join("bridge-token").exists());
+        assert!(!dir.path().join("bridge-port").exists());
+        assert!(!dir.path().join("bridge-pid").exists());
+        assert!(!dir.path().join("bridge-token.isolated").exists());
+    }
+
+    #[tokio::test]
+    async fn test_discover_bridge_prefers_isolated() {
+        let dir = tempfile::tempdir().unwrap();
+        let isolated = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let standard = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let isolated_port = isolated.local_addr().unwrap().port();
+        let standard_port = standard.local_addr().unwrap().port();
+        write_bridge_files(
+            dir.path(),
+            BridgeMode::Isolated,
+            std::process::id(),
+            isolated_port,
+        );
+        write_bridge_files(
+            dir.path(),
+            BridgeMode::Standard,
+            std::process::id(),
+            standard_port,
+        );
+
+        let endpoint = discover_bridge_in(dir.path()).await.unwrap();
+        assert_eq!(endpoint.mode, BridgeMode::Isolated);
+        assert_eq!(endpoint.port, isolated_port);
+        assert_eq!(endpoint.token, "abk_test");
+
+        // Falls back to the standard bridge once the isolated one is gone
+        drop(isolated);
+        let endpoint = discover_bridge_in(dir.path()).await.unwrap();
+        assert_eq!(endpoint.mode, BridgeMode::Standard);
+        assert_eq!(endpoint.port, standard_port);
+
+        drop(standard);
+        assert!(discover_bridge_in(dir.path()).await.is_err());
+    }
+
+    #[test]
+    fn test_parse_pid_file() {
+        assert_eq!(parse_pid_file("123:19222\n"), Some((123, 19222)));
+        assert_eq!(parse_pid_file("123"), None);
+        assert_eq!(parse_pid_file("abc:19222"), None);
+    }
+
+    #[tokio::test]
+    async fn test_stable_token_reused_until_rotated() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("bridge-token.stable");
+
+        let first = load_or_create_stable_token_at(&path, false).await.unwrap();
+        assert!(is_well_formed_token(&first));
+        assert_eq!(
+            load_or_create_stable_token_at(&path, false).await.unwrap(),
+            first
+        );
+
+        let rotated = load_or_create_stable_token_at(&path, true).await.unwrap();
+        assert_ne!(rotated, first);
+        assert_eq!(
+            load_or_create_stable_token_at(&path, false).await.unwrap(),
+            rotated
+        );
+
+        // A corrupted file is replaced rather than trusted
+        std::fs::write(&path, "garbage").unwrap();
+        let replaced = load_or_create_stable_token_at(&path, false).await.unwrap();
+        assert!(is_well_formed_token(&replaced));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_stable_token_ignored_when_readable_by_others() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("bridge-token.stable");
+        let exposed = generate_token();
+        std::fs::write(&path, &exposed).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+
+        let token = load_or_create_stable_token_at(&path, false).await.unwrap();
+        assert_ne!(token, exposed);
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    #[test]
+    fn test_supplied_token_is_validated() {
+        let token = generate_token();
+        let input = format!("{}\n", token);
+        assert_eq!(read_supplied_token(input.as_bytes()).unwrap(), token);
+
+        for bad in ["", "\n", "secret\n", "abk_1234\n", "abk_zzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzz\n"] {
+            assert!(read_supplied_token(bad.as_bytes()).is_err(), "accepted {:?}", bad);
+        }
+    }
 }
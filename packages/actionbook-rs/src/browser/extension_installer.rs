@@ -6,6 +6,7 @@ use std::time::Duration;
 use crate::error::{ActionbookError, Result};
 
 const GITHUB_REPO: &str = "actionbook/actionbook";
+const GITHUB_API_BASE: &str = "https://api.github.com";
 const RELEASE_TAG_PREFIX: &str = "actionbook-extension-v";
 const USER_AGENT: &str = concat!("actionbook-cli/", env!("CARGO_PKG_VERSION"));
 /// Maximum download size for the extension zip (10 MB compressed)
@@ -15,26 +16,140 @@ const MAX_UNCOMPRESSED_SIZE: u64 = 50 * 1024 * 1024;
 /// Allowed download hosts (GitHub asset CDN)
 const ALLOWED_DOWNLOAD_HOSTS: &[&str] = &["github.com", "githubusercontent.com"];
 
-/// Returns the extension install directory: ~/.config/actionbook/extension/
-pub fn extension_dir() -> Result<PathBuf> {
-    let config_dir = dirs::config_dir().ok_or_else(|| {
-        ActionbookError::ExtensionError(
-            "Could not determine config directory".to_string(),
-        )
+/// Written into the install dir by [`install_zip`]: each installed file's
+/// length and SHA-256, the reference [`verify_installed`] compares against.
+pub const INSTALL_RECORD_FILE: &str = ".install-record.json";
+
+/// Environment variable that relocates the extension install directory.
+pub const EXTENSION_DIR_ENV: &str = "ACTIONBOOK_EXTENSION_DIR";
+
+/// Returns the extension install directory.
+///
+/// Resolution order: `ACTIONBOOK_EXTENSION_DIR`, then `configured` (the
+/// `browser.extension_dir` config field), then ~/.config/actionbook/extension/.
+/// Overrides must be absolute paths.
+pub fn extension_dir(configured: Option<&str>) -> Result<PathBuf> {
+    if let Some(dir) = std::env::var_os(EXTENSION_DIR_ENV).filter(|v| !v.is_empty()) {
+        return absolute_override(PathBuf::from(dir), EXTENSION_DIR_ENV);
+    }
+    if let Some(dir) = configured {
+        return absolute_override(PathBuf::from(dir), "browser.extension_dir");
+    }
+
+    let config_dir = crate::config::config_home().ok_or_else(|| {
+        ActionbookError::ExtensionError(format!(
+            "Could not determine config directory; set {} or XDG_CONFIG_HOME",
+            crate::config::CONFIG_HOME_ENV
+        ))
     })?;
     Ok(config_dir.join("actionbook").join("extension"))
 }
 
-/// Check if the extension is installed (manifest.json exists on disk)
-pub fn is_installed() -> bool {
-    extension_dir()
-        .map(|dir| dir.join("manifest.json").exists())
-        .unwrap_or(false)
+fn absolute_override(dir: PathBuf, source: &str) -> Result<PathBuf> {
+    if dir.is_absolute() {
+        Ok(dir)
+    } else {
+        Err(ActionbookError::ExtensionError(format!(
+            "{} must be an absolute path, got '{}'",
+            source,
+            dir.display()
+        )))
+    }
+}
+
+/// Where extension releases are listed and downloaded from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReleaseSource {
+    /// Base URL of a GitHub-compatible REST API, without trailing slash
+    api_base: String,
+    /// `owner/repo` holding the `actionbook-extension-v*` releases
+    repo: String,
+}
+
+impl Default for ReleaseSource {
+    fn default() -> Self {
+        Self {
+            api_base: GITHUB_API_BASE.to_string(),
+            repo: GITHUB_REPO.to_string(),
+        }
+    }
 }
 
-/// Read the installed extension version from the on-disk manifest.json
-pub fn installed_version() -> Option<String> {
-    let dir = extension_dir().ok()?;
+impl ReleaseSource {
+    /// Build a source from optional overrides, falling back to the public
+    /// GitHub repository. The base must be an https URL and the repo must be
+    /// `owner/repo`.
+    pub fn new(api_base: Option<&str>, repo: Option<&str>) -> Result<Self> {
+        let mut source = Self::default();
+        if let Some(base) = api_base {
+            let parsed = reqwest::Url::parse(base).map_err(|e| {
+                ActionbookError::ExtensionError(format!(
+                    "Invalid extension release base URL '{}': {}",
+                    base, e
+                ))
+            })?;
+            if parsed.scheme() != "https" {
+                return Err(ActionbookError::ExtensionError(format!(
+                    "Extension release base URL must use HTTPS, got '{}'",
+                    base
+                )));
+            }
+            if parsed.host_str().is_none() {
+                return Err(ActionbookError::ExtensionError(format!(
+                    "Extension release base URL has no host: '{}'",
+                    base
+                )));
+            }
+            source.api_base = base.trim_end_matches('/').to_string();
+        }
+        if let Some(repo) = repo {
+            let valid = matches!(
+                repo.split('/').collect::<Vec<_>>().as_slice(),
+                [owner, name] if !owner.is_empty() && !name.is_empty()
+            );
+            if !valid {
+                return Err(ActionbookError::ExtensionError(format!(
+                    "Extension release repo must be 'owner/repo', got '{}'",
+                    repo
+                )));
+            }
+            source.repo = repo.to_string();
+        }
+        Ok(source)
+    }
+
+    /// API endpoint listing the most recent releases.
+    pub fn releases_url(&self) -> String {
+        format!("{}/repos/{}/releases?per_page=20", self.api_base, self.repo)
+    }
+
+    /// Host of a non-GitHub API base; its assets are allowed for download.
+    fn mirror_host(&self) -> Option<String> {
+        if self.api_base == GITHUB_API_BASE {
+            return None;
+        }
+        reqwest::Url::parse(&self.api_base)
+            .ok()
+            .and_then(|u| u.host_str().map(str::to_string))
+    }
+
+    /// Where a human can browse the releases when automatic install fails.
+    fn releases_page(&self) -> String {
+        if self.api_base == GITHUB_API_BASE {
+            format!("https://github.com/{}/releases", self.repo)
+        } else {
+            format!("{}/repos/{}/releases", self.api_base, self.repo)
+        }
+    }
+}
+
+/// Check if the extension is installed in `dir` (manifest.json exists on disk)
+pub fn is_installed(dir: &Path) -> bool {
+    dir.join("manifest.json").exists()
+}
+
+/// Read the version of the extension installed in `dir` from its manifest.json
+pub fn installed_version(dir: &Path) -> Option<String> {
     let manifest_path = dir.join("manifest.json");
     let content = fs::read_to_string(manifest_path).ok()?;
     let parsed: serde_json::Value = serde_json::from_str(&content).ok()?;
@@ -44,11 +159,198 @@ pub fn installed_version() -> Option<String> {
         .map(|s| s.to_string())
 }
 
-/// Remove the installed extension directory
-pub fn uninstall() -> Result<()> {
-    let dir = extension_dir()?;
+/// Warning when the extension Chrome is running (`running`, as reported by
+/// `Extension.version`) differs from the one on disk (`installed`), which
+/// happens after an install or update until Chrome reloads the extension.
+pub fn loaded_version_mismatch(installed: &str, running: &str) -> Option<String> {
+    let same = match (
+        semver::Version::parse(installed),
+        semver::Version::parse(running),
+    ) {
+        (Ok(installed), Ok(running)) => installed.cmp_precedence(&running).is_eq(),
+        _ => installed.trim() == running.trim(),
+    };
+    (!same).then(|| {
+        format!(
+            "Chrome is running extension v{} but v{} is installed; reload it \
+             (actionbook extension reload) or restart Chrome to pick up the installed version",
+            running.trim(),
+            installed.trim()
+        )
+    })
+}
+
+/// Chrome extension ID for a manifest `key` (base64 DER public key): the
+/// first 16 bytes of its SHA-256, hex-encoded with digits mapped to `a`-`p`.
+pub fn extension_id_from_key(key: &str) -> Result<String> {
+    use base64::Engine;
+
+    let der = base64::engine::general_purpose::STANDARD
+        .decode(key.trim())
+        .map_err(|e| ActionbookError::ExtensionError(format!("Invalid manifest key: {}", e)))?;
+    let digest = ring::digest::digest(&ring::digest::SHA256, &der);
+    Ok(digest.as_ref()[..16]
+        .iter()
+        .flat_map(|b| [b >> 4, b & 0x0f])
+        .map(|nibble| (b'a' + nibble) as char)
+        .collect())
+}
+
+/// Extension ID pinned by the `key` in `dir/manifest.json`, if it has one.
+/// Without a key Chrome derives the ID from the load path instead.
+pub fn expected_extension_id(dir: &Path) -> Result<Option<String>> {
+    let content = fs::read_to_string(dir.join("manifest.json"))?;
+    let manifest: serde_json::Value = serde_json::from_str(&content)?;
+    manifest
+        .get("key")
+        .and_then(|k| k.as_str())
+        .map(extension_id_from_key)
+        .transpose()
+}
+
+/// Service worker script declared by `background.service_worker` in
+/// `dir/manifest.json`, relative to the extension root.
+pub fn service_worker_path(dir: &Path) -> Option<String> {
+    let content = fs::read_to_string(dir.join("manifest.json")).ok()?;
+    let manifest: serde_json::Value = serde_json::from_str(&content).ok()?;
+    manifest
+        .get("background")?
+        .get("service_worker")?
+        .as_str()
+        .map(|p| {
+            p.trim_start_matches("./")
+                .trim_start_matches('/')
+                .to_string()
+        })
+        .filter(|p| !p.is_empty())
+}
+
+/// Compare the ID Chrome assigned on load with the one the manifest pins.
+/// Returns a warning message on mismatch; `None` when they agree or the
+/// manifest has no key.
+pub fn check_extension_id(dir: &Path, assigned: &str) -> Result<Option<String>> {
+    Ok(expected_extension_id(dir)?
+        .filter(|expected| expected != assigned)
+        .map(|expected| {
+            format!(
+                "Chrome assigned extension ID {} but the manifest key pins {}",
+                assigned, expected
+            )
+        }))
+}
+
+/// Length and SHA-256 of one installed file.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+struct FileDigest {
+    len: u64,
+    sha256: String,
+}
+
+/// Differences between the extension dir and its install record.
+#[derive(Debug, Default, Clone, PartialEq, serde::Serialize)]
+pub struct VerifyReport {
+    /// Recorded files that are no longer on disk
+    pub missing: Vec<String>,
+    /// Files on disk that the install did not create
+    pub extra: Vec<String>,
+    /// Files whose length or contents changed since install
+    pub modified: Vec<String>,
+}
+
+impl VerifyReport {
+    /// True when the installed tree matches the record exactly.
+    pub fn is_ok(&self) -> bool {
+        self.missing.is_empty() && self.extra.is_empty() && self.modified.is_empty()
+    }
+}
+
+/// Compare every file under `dir` with `dir/.install-record.json`.
+pub fn verify_dir(dir: &Path) -> Result<VerifyReport> {
+    let record_path = dir.join(INSTALL_RECORD_FILE);
+    let content = fs::read_to_string(&record_path).map_err(|_| {
+        ActionbookError::ExtensionError(format!(
+            "No install record at {}. Reinstall with: actionbook extension install --force",
+            record_path.display()
+        ))
+    })?;
+    let recorded: std::collections::BTreeMap<String, FileDigest> = serde_json::from_str(&content)
+        .map_err(|e| {
+        ActionbookError::ExtensionError(format!(
+            "Install record {} is invalid: {}",
+            record_path.display(),
+            e
+        ))
+    })?;
+    let actual = digest_tree(dir)?;
+
+    let mut report = VerifyReport::default();
+    for (name, digest) in &recorded {
+        match actual.get(name) {
+            None => report.missing.push(name.clone()),
+            Some(found) if found != digest => report.modified.push(name.clone()),
+            Some(_) => {}
+        }
+    }
+    report.extra = actual
+        .keys()
+        .filter(|name| !recorded.contains_key(*name))
+        .cloned()
+        .collect();
+    Ok(report)
+}
+
+/// Digest every file under `dir` (except the install record), keyed by its
+/// `/`-separated path relative to `dir`.
+fn digest_tree(dir: &Path) -> Result<std::collections::BTreeMap<String, FileDigest>> {
+    let mut digests = std::collections::BTreeMap::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(current) = pending.pop() {
+        for entry in fs::read_dir(&current)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                pending.push(path);
+                continue;
+            }
+            let name = path
+                .strip_prefix(dir)
+                .unwrap_or(&path)
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            if name == INSTALL_RECORD_FILE {
+                continue;
+            }
+            let bytes = fs::read(&path)?;
+            let sha256 = ring::digest::digest(&ring::digest::SHA256, &bytes)
+                .as_ref()
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect();
+            digests.insert(
+                name,
+                FileDigest {
+                    len: bytes.len() as u64,
+                    sha256,
+                },
+            );
+        }
+    }
+    Ok(digests)
+}
+
+/// Write the install record for the freshly extracted tree at `dir`.
+fn write_install_record(dir: &Path) -> Result<()> {
+    let record = serde_json::to_string_pretty(&digest_tree(dir)?)?;
+    fs::write(dir.join(INSTALL_RECORD_FILE), record).map_err(|e| {
+        ActionbookError::ExtensionError(format!("Failed to write install record: {}", e))
+    })
+}
+
+/// Remove the extension installed in `dir`
+pub fn uninstall(dir: &Path) -> Result<()> {
     if dir.exists() {
-        fs::remove_dir_all(&dir).map_err(|e| {
+        fs::remove_dir_all(dir).map_err(|e| {
             ActionbookError::ExtensionError(format!(
                 "Failed to remove {}: {}",
                 dir.display(),
@@ -74,19 +376,50 @@ fn build_http_client() -> Result<reqwest::Client> {
         })
 }
 
-/// Download the latest extension release from GitHub and install it.
+/// Download the latest extension release from `source` and install it in `dir`.
 ///
 /// Returns the installed version string on success.
 /// If `force` is false and the extension is already installed at the same or newer
 /// version, returns an error.
-pub async fn download_and_install(force: bool) -> Result<String> {
-    let dir = extension_dir()?;
+#[allow(dead_code)]
+pub async fn download_and_install(
+    dir: &Path,
+    source: &ReleaseSource,
+    force: bool,
+) -> Result<String> {
+    download_and_install_with_progress(dir, source, force, |_| {}).await
+}
 
-    // Fetch latest extension release info from GitHub
-    let (version, asset_url) = fetch_latest_release().await?;
+/// Bytes received so far for the extension download, and the expected
+/// total when the server sent a `Content-Length`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DownloadProgress {
+    pub downloaded: u64,
+    pub total: Option<u64>,
+}
 
-    if is_installed() && !force {
-        let current = installed_version().unwrap_or_default();
+impl DownloadProgress {
+    /// Percent complete (0-100), if the total size is known.
+    pub fn percent(&self) -> Option<u8> {
+        self.total
+            .filter(|total| *total > 0)
+            .map(|total| (self.downloaded.min(total) * 100 / total) as u8)
+    }
+}
+
+/// [`download_and_install`], calling `on_progress` after every chunk of the
+/// release asset is received.
+pub async fn download_and_install_with_progress(
+    dir: &Path,
+    source: &ReleaseSource,
+    force: bool,
+    mut on_progress: impl FnMut(DownloadProgress),
+) -> Result<String> {
+    // Fetch latest extension release info from GitHub (or the configured mirror)
+    let (version, asset_url) = fetch_latest_release(source).await?;
+
+    if is_installed(dir) && !force {
+        let current = installed_version(dir).unwrap_or_default();
         let current_semver = semver::Version::parse(&current).map_err(|e| {
             ActionbookError::ExtensionError(format!(
                 "Installed version '{}' is not valid semver: {}. Use --force to reinstall",
@@ -109,11 +442,26 @@ pub async fn download_and_install(force: bool) -> Result<String> {
     }
 
     // Download the zip asset
-    let zip_bytes = download_asset(&asset_url).await?;
+    let zip_bytes = download_asset(&asset_url, &mut on_progress).await?;
+
+    install_zip(&zip_bytes, dir, Some(&version))?;
+
+    Ok(version)
+}
 
-    // Extract to a temporary directory first (atomic: don't destroy existing install
-    // until we've verified the new one is valid)
-    let parent = dir.parent().ok_or_else(|| {
+/// Extract an extension zip and atomically install it at `target_dir`.
+///
+/// The archive is unpacked into a unique temp dir next to `target_dir`, its
+/// manifest is checked (against `expected_version`, if given), and only then is
+/// it renamed into place. Concurrent installs into the same target never mix
+/// files: the final directory is always one complete extraction. If the swap
+/// fails, the previous install is restored. Returns the installed version.
+pub fn install_zip(
+    bytes: &[u8],
+    target_dir: &Path,
+    expected_version: Option<&str>,
+) -> Result<String> {
+    let parent = target_dir.parent().ok_or_else(|| {
         ActionbookError::ExtensionError("Cannot determine parent of extension dir".to_string())
     })?;
     fs::create_dir_all(parent).map_err(|e| {
@@ -123,16 +471,16 @@ pub async fn download_and_install(force: bool) -> Result<String> {
             e
         ))
     })?;
-    let tmp_dir = tempfile::tempdir_in(parent).map_err(|e| {
-        ActionbookError::ExtensionError(format!(
-            "Failed to create temp directory: {}",
-            e
-        ))
-    })?;
+    let tmp_dir = tempfile::Builder::new()
+        .prefix(".extension-new-")
+        .tempdir_in(parent)
+        .map_err(|e| {
+            ActionbookError::ExtensionError(format!("Failed to create temp directory: {}", e))
+        })?;
 
-    extract_zip(&zip_bytes, tmp_dir.path())?;
+    extract_zip(bytes, tmp_dir.path())?;
 
-    // Verify extracted manifest version matches the release
+    // Verify the extracted manifest before touching the existing install
     let tmp_manifest = tmp_dir.path().join("manifest.json");
     let manifest_content = fs::read_to_string(&tmp_manifest).map_err(|_| {
         ActionbookError::ExtensionError(
@@ -152,38 +500,78 @@ pub async fn download_and_install(force: bool) -> Result<String> {
             ActionbookError::ExtensionError(
                 "Extracted manifest.json is missing 'version' field".to_string(),
             )
-        })?;
-    if extracted_version != version {
-        return Err(ActionbookError::ExtensionError(format!(
-            "Version mismatch after extraction: expected v{}, got v{}. Release may be corrupted",
-            version, extracted_version
-        )));
-    }
-
-    // Verification passed — now swap: remove old dir, move new into place
-    match fs::remove_dir_all(&dir) {
-        Ok(_) => {}
-        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
-        Err(e) => {
+        })?
+        .to_string();
+    if let Some(expected) = expected_version {
+        if extracted_version != expected {
             return Err(ActionbookError::ExtensionError(format!(
-                "Failed to clean existing install at {}: {}",
-                dir.display(),
-                e
+                "Version mismatch after extraction: expected v{}, got v{}. Release may be corrupted",
+                expected, extracted_version
             )));
         }
     }
 
-    // Persist the temp dir (prevent auto-cleanup) and rename into place
-    let tmp_path = tmp_dir.keep();
-    fs::rename(&tmp_path, &dir).map_err(|e| {
-        ActionbookError::ExtensionError(format!(
-            "Failed to move extracted extension to {}: {}",
-            dir.display(),
-            e
-        ))
-    })?;
+    write_install_record(tmp_dir.path())?;
+    swap_into_place(tmp_dir, target_dir, parent)?;
+    Ok(extracted_version)
+}
 
-    Ok(version)
+/// Rename the extracted `new_dir` onto `target_dir`.
+///
+/// The current install is first moved aside into a unique backup dir (renames
+/// are atomic, so a concurrent install either sees the old tree or none). If a
+/// racing install lands in between, it is moved aside too and the rename is
+/// retried; the last successful rename wins with a complete tree.
+fn swap_into_place(new_dir: tempfile::TempDir, target_dir: &Path, parent: &Path) -> Result<()> {
+    const MAX_ATTEMPTS: usize = 8;
+
+    let mut previous: Option<tempfile::TempDir> = None;
+    let mut last_err = None;
+
+    for _ in 0..MAX_ATTEMPTS {
+        match fs::rename(new_dir.path(), target_dir) {
+            Ok(()) => {
+                // Ownership moved to target_dir; don't let the TempDir delete it
+                let _ = new_dir.keep();
+                return Ok(());
+            }
+            Err(e) => last_err = Some(e),
+        }
+
+        // Something is in the way: move it into a backup dir and retry
+        let backup = tempfile::Builder::new()
+            .prefix(".extension-old-")
+            .tempdir_in(parent)
+            .map_err(|e| {
+                ActionbookError::ExtensionError(format!("Failed to create temp directory: {}", e))
+            })?;
+        match fs::rename(target_dir, backup.path().join("extension")) {
+            Ok(()) => {
+                if previous.is_none() {
+                    previous = Some(backup);
+                }
+            }
+            // A concurrent install already moved it; just retry
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => {
+                return Err(ActionbookError::ExtensionError(format!(
+                    "Failed to clean existing install at {}: {}",
+                    target_dir.display(),
+                    e
+                )));
+            }
+        }
+    }
+
+    // Roll back to the install we displaced, if the slot is still free
+    if let Some(backup) = previous {
+        let _ = fs::rename(backup.path().join("extension"), target_dir);
+    }
+    Err(ActionbookError::ExtensionError(format!(
+        "Failed to move extracted extension to {}: {}",
+        target_dir.display(),
+        last_err.map(|e| e.to_string()).unwrap_or_default()
+    )))
 }
 
 /// Fetch the latest actionbook-extension release from GitHub API.
@@ -193,11 +581,8 @@ pub async fn download_and_install(force: bool) -> Result<String> {
 /// NOTE: Only fetches the first page of releases (20 items). This is sufficient
 /// because extension releases are recent, but if the repo accumulates many
 /// non-extension releases, pagination may be needed (Link header).
-async fn fetch_latest_release() -> Result<(String, String)> {
-    let url = format!(
-        "https://api.github.com/repos/{}/releases?per_page=20",
-        GITHUB_REPO
-    );
+async fn fetch_latest_release(source: &ReleaseSource) -> Result<(String, String)> {
+    let url = source.releases_url();
 
     let client = build_http_client()?;
 
@@ -209,8 +594,8 @@ async fn fetch_latest_release() -> Result<(String, String)> {
         .await
         .map_err(|e| {
             ActionbookError::ExtensionError(format!(
-                "Failed to fetch releases from GitHub: {}. Check your network connection",
-                e
+                "Failed to fetch releases from {}: {}. Check your network connection",
+                url, e
             ))
         })?;
 
@@ -218,17 +603,15 @@ async fn fetch_latest_release() -> Result<(String, String)> {
         let status = resp.status();
         let body = resp.text().await.unwrap_or_default();
         return Err(ActionbookError::ExtensionError(format!(
-            "GitHub API returned {}: {}. If rate-limited, try again later or download manually from https://github.com/{}/releases",
-            status, body, GITHUB_REPO
+            "{} returned {}: {}. If rate-limited, try again later or download manually from {}",
+            url,
+            status,
+            body,
+            source.releases_page()
         )));
     }
 
-    let releases: Vec<serde_json::Value> = resp.json().await.map_err(|e| {
-        ActionbookError::ExtensionError(format!(
-            "Failed to parse GitHub releases response: {}",
-            e
-        ))
-    })?;
+    let releases: Vec<serde_json::Value> = resp.json().await?;
 
     // Find the latest release with an actionbook-extension-v* tag
     for release in &releases {
@@ -270,7 +653,7 @@ async fn fetch_latest_release() -> Result<(String, String)> {
                     })?;
 
                 // Validate download URL host
-                validate_download_url(download_url)?;
+                validate_download_url(download_url, source.mirror_host().as_deref())?;
 
                 return Ok((version, download_url.to_string()));
             }
@@ -278,13 +661,14 @@ async fn fetch_latest_release() -> Result<(String, String)> {
     }
 
     Err(ActionbookError::ExtensionError(format!(
-        "No extension release found. Check https://github.com/{}/releases for available versions",
-        GITHUB_REPO
+        "No extension release found. Check {} for available versions",
+        source.releases_page()
     )))
 }
 
-/// Validate that a download URL points to an allowed GitHub host.
-fn validate_download_url(url: &str) -> Result<()> {
+/// Validate that a download URL points to an allowed GitHub host, or to the
+/// host of the configured release mirror.
+fn validate_download_url(url: &str, mirror_host: Option<&str>) -> Result<()> {
     let parsed = reqwest::Url::parse(url).map_err(|e| {
         ActionbookError::ExtensionError(format!("Invalid download URL: {}", e))
     })?;
@@ -296,10 +680,17 @@ fn validate_download_url(url: &str) -> Result<()> {
     }
 
     let host = parsed.host_str().unwrap_or("");
+    if mirror_host == Some(host) {
+        return Ok(());
+    }
     if !ALLOWED_DOWNLOAD_HOSTS.iter().any(|&allowed| host == allowed || host.ends_with(&format!(".{}", allowed))) {
+        let expected = match mirror_host {
+            Some(mirror) => format!("GitHub or {}", mirror),
+            None => "GitHub".to_string(),
+        };
         return Err(ActionbookError::ExtensionError(format!(
-            "Download URL host '{}' is not allowed (expected GitHub)",
-            host
+            "Download URL host '{}' is not allowed (expected {})",
+            host, expected
         )));
     }
 
@@ -309,7 +700,10 @@ fn validate_download_url(url: &str) -> Result<()> {
 /// Download a file from a URL, returning the bytes.
 ///
 /// Enforces a maximum download size to prevent resource exhaustion.
-async fn download_asset(url: &str) -> Result<Vec<u8>> {
+async fn download_asset(
+    url: &str,
+    on_progress: &mut dyn FnMut(DownloadProgress),
+) -> Result<Vec<u8>> {
     let client = build_http_client()?;
 
     let resp = client
@@ -319,8 +713,8 @@ async fn download_asset(url: &str) -> Result<Vec<u8>> {
         .await
         .map_err(|e| {
             ActionbookError::ExtensionError(format!(
-                "Failed to download extension: {}",
-                e
+                "Failed to download extension from {}: {}",
+                url, e
             ))
         })?;
 
@@ -332,8 +726,18 @@ async fn download_asset(url: &str) -> Result<Vec<u8>> {
         )));
     }
 
+    read_download(resp, on_progress).await
+}
+
+/// Read a download body chunk by chunk, reporting progress after each chunk
+/// and enforcing `MAX_DOWNLOAD_SIZE` as bytes arrive.
+async fn read_download(
+    mut resp: reqwest::Response,
+    on_progress: &mut dyn FnMut(DownloadProgress),
+) -> Result<Vec<u8>> {
     // Check content-length if available
-    if let Some(content_length) = resp.content_length() {
+    let total = resp.content_length();
+    if let Some(content_length) = total {
         if content_length > MAX_DOWNLOAD_SIZE as u64 {
             return Err(ActionbookError::ExtensionError(format!(
                 "Extension download too large ({} bytes, max {} bytes). This may indicate a corrupted release",
@@ -342,22 +746,28 @@ async fn download_asset(url: &str) -> Result<Vec<u8>> {
         }
     }
 
-    let bytes = resp.bytes().await.map_err(|e| {
+    let mut bytes = Vec::with_capacity(total.unwrap_or(0) as usize);
+    while let Some(chunk) = resp.chunk().await.map_err(|e| {
         ActionbookError::ExtensionError(format!(
             "Failed to read download response: {}",
             e
         ))
-    })?;
-
-    if bytes.len() > MAX_DOWNLOAD_SIZE {
-        return Err(ActionbookError::ExtensionError(format!(
-            "Extension download too large ({} bytes, max {} bytes)",
-            bytes.len(),
-            MAX_DOWNLOAD_SIZE
-        )));
+    })? {
+        bytes.extend_from_slice(&chunk);
+        if bytes.len() > MAX_DOWNLOAD_SIZE {
+            return Err(ActionbookError::ExtensionError(format!(
+                "Extension download too large ({} bytes, max {} bytes)",
+                bytes.len(),
+                MAX_DOWNLOAD_SIZE
+            )));
+        }
+        on_progress(DownloadProgress {
+            downloaded: bytes.len() as u64,
+            total,
+        });
     }
 
-    Ok(bytes.to_vec())
+    Ok(bytes)
 }
 
 /// Extract a zip archive to a target directory.
@@ -459,12 +869,51 @@ pub fn extract_zip(bytes: &[u8], target_dir: &Path) -> Result<()> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_absolute_override() {
+        let tmp = tempfile::tempdir().unwrap();
+        assert_eq!(
+            absolute_override(tmp.path().to_path_buf(), EXTENSION_DIR_ENV).unwrap(),
+            tmp.path()
+        );
+
+        let err = absolute_override(PathBuf::from("relative/ext"), EXTENSION_DIR_ENV)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("ACTIONBOOK_EXTENSION_DIR must be an absolute path"));
+    }
+
+    #[test]
+    fn test_loaded_version_mismatch() {
+        let warning = loaded_version_mismatch("0.3.0", "0.2.1").unwrap();
+        assert!(warning.contains("running extension v0.2.1 but v0.3.0 is installed"));
+        assert!(warning.contains("actionbook extension reload"));
+
+        assert_eq!(loaded_version_mismatch("0.3.0", "0.3.0"), None);
+        // Build metadata does not make two versions differ
+        assert_eq!(loaded_version_mismatch("0.3.0+abc", "0.3.0+def"), None);
+        // Non-semver versions fall back to a plain comparison
+        assert_eq!(loaded_version_mismatch("dev", " dev"), None);
+        assert!(loaded_version_mismatch("dev", "0.3.0").is_some());
+    }
+
     #[test]
     fn test_extension_dir_is_under_config() {
-        let dir = extension_dir().expect("should resolve config dir");
+        let dir = extension_dir(None).expect("should resolve config dir");
         assert!(dir.ends_with("actionbook/extension"));
     }
 
+    #[test]
+    fn test_extension_dir_uses_configured_dir() {
+        let tmp = tempfile::tempdir().unwrap();
+        let configured = tmp.path().join("packaged");
+        assert_eq!(
+            extension_dir(configured.to_str()).unwrap(),
+            configured
+        );
+        assert!(extension_dir(Some("relative/ext")).is_err());
+    }
+
     #[test]
     fn test_extract_zip() {
         let tmp = tempfile::tempdir().expect("should create temp dir");
@@ -609,20 +1058,306 @@ mod tests {
         );
     }
 
+    /// Build an extension zip whose every file records `version`.
+    fn versioned_zip(version: &str, extra_file: Option<&str>) -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        let options = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Stored);
+
+        let manifest = format!("{{\"manifest_version\":3,\"version\":\"{}\"}}", version);
+        writer.start_file("manifest.json", options).unwrap();
+        std::io::Write::write_all(&mut writer, manifest.as_bytes()).unwrap();
+        for name in ["background.js", "popup.js", "icons/icon-16.png"]
+            .into_iter()
+            .chain(extra_file)
+        {
+            writer.start_file(name, options).unwrap();
+            std::io::Write::write_all(&mut writer, version.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_install_zip_replaces_existing_install() {
+        let tmp = tempfile::tempdir().unwrap();
+        let target = tmp.path().join("extension");
+
+        install_zip(&versioned_zip("1.0.0", Some("old-only.js")), &target, None).unwrap();
+        let version = install_zip(&versioned_zip("2.0.0", None), &target, Some("2.0.0")).unwrap();
+
+        assert_eq!(version, "2.0.0");
+        assert_eq!(
+            fs::read_to_string(target.join("popup.js")).unwrap(),
+            "2.0.0"
+        );
+        assert!(!target.join("old-only.js").exists());
+        // Only the installed dir remains; temp and backup dirs are cleaned up
+        assert_eq!(fs::read_dir(tmp.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_install_zip_version_mismatch_keeps_existing_install() {
+        let tmp = tempfile::tempdir().unwrap();
+        let target = tmp.path().join("extension");
+
+        install_zip(&versioned_zip("1.0.0", None), &target, None).unwrap();
+        let err = install_zip(&versioned_zip("2.0.0", None), &target, Some("3.0.0"));
+
+        assert!(err.is_err());
+        assert_eq!(
+            fs::read_to_string(target.join("popup.js")).unwrap(),
+            "1.0.0"
+        );
+        assert_eq!(fs::read_dir(tmp.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_concurrent_install_zip_leaves_one_complete_tree() {
+        let zips = [
+            versioned_zip("1.0.0", None),
+            versioned_zip("2.0.0", Some("only-in-2.js")),
+        ];
+
+        for _ in 0..20 {
+            let tmp = tempfile::tempdir().unwrap();
+            let target = tmp.path().join("extension");
+            install_zip(
+                &versioned_zip("0.9.0", Some("only-in-0.9.js")),
+                &target,
+                None,
+            )
+            .unwrap();
+
+            let barrier = std::sync::Barrier::new(zips.len());
+            std::thread::scope(|scope| {
+                for zip in &zips {
+                    let (barrier, target) = (&barrier, &target);
+                    scope.spawn(move || {
+                        barrier.wait();
+                        install_zip(zip, target, None).expect("concurrent install should succeed");
+                    });
+                }
+            });
+
+            let version = installed_version_at(&target);
+            assert!(version == "1.0.0" || version == "2.0.0", "got {}", version);
+            for name in ["background.js", "popup.js", "icons/icon-16.png"] {
+                assert_eq!(
+                    fs::read_to_string(target.join(name)).unwrap(),
+                    version,
+                    "{} comes from a different install than manifest.json",
+                    name
+                );
+            }
+            assert_eq!(target.join("only-in-2.js").exists(), version == "2.0.0");
+            assert!(!target.join("only-in-0.9.js").exists());
+            assert_eq!(fs::read_dir(tmp.path()).unwrap().count(), 1);
+        }
+    }
+
+    #[test]
+    fn test_verify_intact_install_is_ok() {
+        let tmp = tempfile::tempdir().unwrap();
+        let target = tmp.path().join("extension");
+        install_zip(&versioned_zip("1.0.0", None), &target, None).unwrap();
+
+        let report = verify_dir(&target).unwrap();
+        assert!(report.is_ok(), "unexpected drift: {:?}", report);
+    }
+
+    #[test]
+    fn test_verify_reports_tampered_install() {
+        let tmp = tempfile::tempdir().unwrap();
+        let target = tmp.path().join("extension");
+        install_zip(&versioned_zip("1.0.0", None), &target, None).unwrap();
+
+        fs::write(target.join("popup.js"), "1.0.1").unwrap();
+        fs::remove_file(target.join("icons/icon-16.png")).unwrap();
+        fs::write(target.join("icons/injected.js"), "x").unwrap();
+
+        let report = verify_dir(&target).unwrap();
+        assert_eq!(report.modified, vec!["popup.js"]);
+        assert_eq!(report.missing, vec!["icons/icon-16.png"]);
+        assert_eq!(report.extra, vec!["icons/injected.js"]);
+        assert!(!report.is_ok());
+    }
+
+    #[test]
+    fn test_verify_without_record_errors() {
+        let tmp = tempfile::tempdir().unwrap();
+        extract_zip(&versioned_zip("1.0.0", None), tmp.path()).unwrap();
+
+        let err = verify_dir(tmp.path()).unwrap_err().to_string();
+        assert!(err.contains("No install record"), "got: {}", err);
+    }
+
+    #[tokio::test]
+    async fn read_download_reports_increasing_progress() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 1024];
+            let _ = stream.read(&mut buf).await;
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 4096\r\n\r\n")
+                .await
+                .unwrap();
+            for _ in 0..4 {
+                stream.write_all(&[7u8; 1024]).await.unwrap();
+                stream.flush().await.unwrap();
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+        });
+
+        let resp = reqwest::Client::builder()
+            .no_proxy()
+            .build()
+            .unwrap()
+            .get(format!("http://127.0.0.1:{}/ext.zip", port))
+            .send()
+            .await
+            .unwrap();
+        let mut seen = Vec::new();
+        let bytes = read_download(resp, &mut |p| seen.push(p)).await.unwrap();
+
+        assert_eq!(bytes.len(), 4096);
+        assert!(!seen.is_empty());
+        assert!(
+            seen.windows(2).all(|w| w[0].downloaded < w[1].downloaded),
+            "progress must increase: {:?}",
+            seen
+        );
+        let last = seen.last().unwrap();
+        assert_eq!(last.downloaded, 4096);
+        assert_eq!(last.total, Some(4096));
+        assert_eq!(last.percent(), Some(100));
+    }
+
+    #[test]
+    fn download_progress_percent() {
+        let p = |downloaded, total| DownloadProgress { downloaded, total };
+        assert_eq!(p(0, Some(200)).percent(), Some(0));
+        assert_eq!(p(50, Some(200)).percent(), Some(25));
+        assert_eq!(p(10, None).percent(), None);
+        assert_eq!(p(0, Some(0)).percent(), None);
+    }
+
+    fn installed_version_at(dir: &Path) -> String {
+        let manifest: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(dir.join("manifest.json")).unwrap()).unwrap();
+        manifest["version"].as_str().unwrap().to_string()
+    }
+
     #[test]
     fn test_validate_download_url_accepts_github() {
         assert!(validate_download_url(
-            "https://github.com/actionbook/actionbook/releases/download/v0.2.0/ext.zip"
+            "https://github.com/actionbook/actionbook/releases/download/v0.2.0/ext.zip",
+            None,
         ).is_ok());
         assert!(validate_download_url(
-            "https://objects.githubusercontent.com/some-path/ext.zip"
+            "https://objects.githubusercontent.com/some-path/ext.zip",
+            None,
         ).is_ok());
     }
 
     #[test]
     fn test_validate_download_url_rejects_non_github() {
-        assert!(validate_download_url("https://evil.com/ext.zip").is_err());
-        assert!(validate_download_url("http://github.com/ext.zip").is_err());
-        assert!(validate_download_url("https://not-github.com/ext.zip").is_err());
+        assert!(validate_download_url("https://evil.com/ext.zip", None).is_err());
+        assert!(validate_download_url("http://github.com/ext.zip", None).is_err());
+        assert!(validate_download_url("https://not-github.com/ext.zip", None).is_err());
+    }
+
+    #[test]
+    fn test_validate_download_url_names_the_mirror() {
+        let err = validate_download_url("https://evil.com/ext.zip", Some("mirror.example"))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("expected GitHub or mirror.example"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn fetch_errors_name_the_configured_source() {
+        // Nothing listens on port 1, so the request fails before any TLS
+        let source = ReleaseSource::new(Some("https://127.0.0.1:1"), None).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let err = download_and_install(dir.path(), &source, false)
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("https://127.0.0.1:1/repos/"), "{}", err);
+        assert!(!err.contains("GitHub"), "{}", err);
+    }
+
+    const MANIFEST_KEY: &str = "MIIBIjANBgkqhkiG9w0BAQEFAAOCAQ8AMIIBCgKCAQEA5xhsOXoi029BKiQTCV7UTZd/f/nzgW6JerV8XfbJLOEr+gHAVNU6J+2Yq3DvTE7+Tnx9EW9jQNGtE4ZXXaGpkvpkcP2ch3ggQQFpjOvHdlVGljepRB2gJivGWR5ooQ1QPWAyxwDLeA09/w2oZ54W9RMXeuzfjv1KRceq9FHlmkIIGaaqYfLzrQbbE7GSV1DSeRG1kG0f7Km2wUsuNDCINI6XyBbhM+662Clurs1GdP7S+Gw/+N/97YEY8Ir2smotGTknHmHuUl5N2XXJjhxfaCT85DkaMV0Kn9D9pVczK4xgqGypplCna5I61YjNDMrymA25qLNKQv2nf/mv7Y7l9wIDAQAB";
+
+    #[test]
+    fn test_extension_id_from_key() {
+        // The published manifest key pins the Web Store extension ID
+        assert_eq!(
+            extension_id_from_key(MANIFEST_KEY).unwrap(),
+            crate::browser::native_messaging::EXTENSION_ID
+        );
+        assert!(extension_id_from_key("not base64!").is_err());
+    }
+
+    #[test]
+    fn test_check_extension_id() {
+        let tmp = tempfile::tempdir().unwrap();
+        let manifest = serde_json::json!({ "version": "0.3.0", "key": MANIFEST_KEY });
+        fs::write(tmp.path().join("manifest.json"), manifest.to_string()).unwrap();
+        let expected = extension_id_from_key(MANIFEST_KEY).unwrap();
+
+        assert_eq!(check_extension_id(tmp.path(), &expected).unwrap(), None);
+        let warning = check_extension_id(tmp.path(), "abcdefghijklmnopabcdefghijklmnop")
+            .unwrap()
+            .expect("mismatched ID should warn");
+        assert!(warning.contains(&expected));
+
+        // Without a key Chrome picks a path-derived ID, so nothing to compare
+        fs::write(tmp.path().join("manifest.json"), r#"{"version": "0.3.0"}"#).unwrap();
+        assert_eq!(
+            check_extension_id(tmp.path(), "abcdefghijklmnopabcdefghijklmnop").unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_custom_release_source_urls() {
+        let source = ReleaseSource::new(
+            Some("https://ghe.corp.example/api/v3/"),
+            Some("corp/actionbook"),
+        )
+        .unwrap();
+        assert_eq!(
+            source.releases_url(),
+            "https://ghe.corp.example/api/v3/repos/corp/actionbook/releases?per_page=20"
+        );
+
+        let mirror = source.mirror_host();
+        assert_eq!(mirror.as_deref(), Some("ghe.corp.example"));
+        assert!(validate_download_url(
+            "https://ghe.corp.example/corp/actionbook/releases/download/actionbook-extension-v0.3.0/actionbook-extension-v0.3.0.zip",
+            mirror.as_deref(),
+        )
+        .is_ok());
+        assert!(validate_download_url("https://evil.com/ext.zip", mirror.as_deref()).is_err());
+
+        let default = ReleaseSource::new(None, None).unwrap();
+        assert_eq!(
+            default.releases_url(),
+            "https://api.github.com/repos/actionbook/actionbook/releases?per_page=20"
+        );
+        assert_eq!(default.mirror_host(), None);
+    }
+
+    #[test]
+    fn test_release_source_validation() {
+        assert!(ReleaseSource::new(Some("http://mirror.example/api"), None).is_err());
+        assert!(ReleaseSource::new(Some("not a url"), None).is_err());
+        assert!(ReleaseSource::new(None, Some("actionbook")).is_err());
+        assert!(ReleaseSource::new(None, Some("a/b/c")).is_err());
     }
 }
@@ -0,0 +1,284 @@
+//! Session-wide request interception and header injection.
+//!
+//! CDP network overrides (`Network.setExtraHTTPHeaders`, `Fetch.enable`) only
+//! live as long as the DevTools connection that installed them. Each override
+//! therefore keeps its own page WebSocket open in a background task, and the
+//! returned [`InterceptionHandle`] tears it down again.
+
+use std::collections::HashMap;
+
+use futures::{SinkExt, StreamExt};
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::error::{ActionbookError, Result};
+
+/// What to do with a request matched by a [`RequestRule`].
+#[derive(Debug, Clone)]
+pub enum RequestAction {
+    /// Fail the request with `BlockedByClient`.
+    Block,
+    /// Add or override request headers, then let the request continue.
+    SetHeaders(HashMap<String, String>),
+}
+
+/// A URL pattern plus the action to apply to matching requests.
+///
+/// `url_pattern` uses CDP `Fetch.RequestPattern` syntax: `*` matches zero or
+/// more characters, `?` matches exactly one.
+#[derive(Debug, Clone)]
+pub struct RequestRule {
+    pub url_pattern: String,
+    pub action: RequestAction,
+}
+
+impl RequestRule {
+    /// Block every request whose URL matches `url_pattern`.
+    #[allow(dead_code)]
+    pub fn block(url_pattern: impl Into<String>) -> Self {
+        Self {
+            url_pattern: url_pattern.into(),
+            action: RequestAction::Block,
+        }
+    }
+
+    /// Add or override `headers` on every request whose URL matches `url_pattern`.
+    #[allow(dead_code)]
+    pub fn set_headers(url_pattern: impl Into<String>, headers: HashMap<String, String>) -> Self {
+        Self {
+            url_pattern: url_pattern.into(),
+            action: RequestAction::SetHeaders(headers),
+        }
+    }
+}
+
+/// Keeps a network override active. Call [`InterceptionHandle::disable`] to
+/// turn it off; dropping the handle also closes the connection.
+pub struct InterceptionHandle {
+    shutdown: Option<oneshot::Sender<()>>,
+    task: Option<JoinHandle<()>>,
+}
+
+impl InterceptionHandle {
+    /// Whether the background connection is still running.
+    #[allow(dead_code)]
+    pub fn is_active(&self) -> bool {
+        self.task
+            .as_ref()
+            .map(|t| !t.is_finished())
+            .unwrap_or(false)
+    }
+
+    /// Disable the override and wait for the connection to close.
+    #[allow(dead_code)]
+    pub async fn disable(mut self) -> Result<()> {
+        if let Some(tx) = self.shutdown.take() {
+            let _ = tx.send(());
+        }
+        if let Some(task) = self.task.take() {
+            task.await
+                .map_err(|e| ActionbookError::Other(format!("Interception task failed: {}", e)))?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for InterceptionHandle {
+    fn drop(&mut self) {
+        if let Some(tx) = self.shutdown.take() {
+            let _ = tx.send(());
+        }
+    }
+}
+
+/// Connect to a page target, run the `setup` commands in order, and keep the
+/// connection alive (answering `Fetch.requestPaused` for `rule`, if any) until
+/// the returned handle is disabled.
+pub(crate) async fn start(
+    ws_url: &str,
+    setup: Vec<(&'static str, serde_json::Value)>,
+    rule: Option<RequestRule>,
+) -> Result<InterceptionHandle> {
+    let (mut ws, _) = tokio_tungstenite::connect_async(ws_url)
+        .await
+        .map_err(|e| {
+            ActionbookError::CdpConnectionFailed(format!("WebSocket connection failed: {}", e))
+        })?;
+
+    let mut next_id: u64 = 1;
+    for (method, params) in setup {
+        let id = next_id;
+        next_id += 1;
+        let cmd = serde_json::json!({ "id": id, "method": method, "params": params });
+        ws.send(Message::Text(cmd.to_string().into()))
+            .await
+            .map_err(|e| ActionbookError::Other(format!("Failed to send command: {}", e)))?;
+
+        // Wait for the matching response, skipping any events in between
+        loop {
+            match ws.next().await {
+                Some(Ok(Message::Text(text))) => {
+                    let response: serde_json::Value = serde_json::from_str(text.as_str())?;
+                    if response.get("id").and_then(|v| v.as_u64()) != Some(id) {
+                        continue;
+                    }
+                    if let Some(error) = response.get("error") {
+                        return Err(ActionbookError::Other(format!(
+                            "CDP error in {}: {}",
+                            method, error
+                        )));
+                    }
+                    break;
+                }
+                Some(Ok(_)) => continue,
+                Some(Err(e)) => {
+                    return Err(ActionbookError::Other(format!("WebSocket error: {}", e)))
+                }
+                None => {
+                    return Err(ActionbookError::Other(format!(
+                        "Connection closed before {} completed",
+                        method
+                    )))
+                }
+            }
+        }
+    }
+
+    let (shutdown_tx, mut shutdown_rx) = oneshot::channel::<()>();
+
+    let task = tokio::spawn(async move {
+        loop {
+            tokio::select! {
+                _ = &mut shutdown_rx => break,
+                msg = ws.next() => {
+                    let text = match msg {
+                        Some(Ok(Message::Text(text))) => text,
+                        Some(Ok(_)) => continue,
+                        Some(Err(_)) | None => return,
+                    };
+                    let Ok(event) = serde_json::from_str::<serde_json::Value>(text.as_str()) else {
+                        continue;
+                    };
+                    if event.get("method").and_then(|m| m.as_str()) != Some("Fetch.requestPaused") {
+                        continue;
+                    }
+                    let Some(rule) = rule.as_ref() else {
+                        continue;
+                    };
+                    let (method, params) = paused_request_reply(rule, &event["params"]);
+                    let cmd = serde_json::json!({ "id": next_id, "method": method, "params": params });
+                    next_id += 1;
+                    if ws.send(Message::Text(cmd.to_string().into())).await.is_err() {
+                        return;
+                    }
+                }
+            }
+        }
+
+        if rule.is_some() {
+            let cmd = serde_json::json!({ "id": next_id, "method": "Fetch.disable", "params": {} });
+            let _ = ws.send(Message::Text(cmd.to_string().into())).await;
+        }
+        let _ = ws.close(None).await;
+    });
+
+    Ok(InterceptionHandle {
+        shutdown: Some(shutdown_tx),
+        task: Some(task),
+    })
+}
+
+/// Build the CDP command that resumes a `Fetch.requestPaused` event.
+fn paused_request_reply(
+    rule: &RequestRule,
+    params: &serde_json::Value,
+) -> (&'static str, serde_json::Value) {
+    let request_id = params
+        .get("requestId")
+        .cloned()
+        .unwrap_or(serde_json::Value::Null);
+
+    match &rule.action {
+        RequestAction::Block => (
+            "Fetch.failRequest",
+            serde_json::json!({ "requestId": request_id, "errorReason": "BlockedByClient" }),
+        ),
+        RequestAction::SetHeaders(overrides) => {
+            let mut headers: Vec<(String, String)> = params
+                .pointer("/request/headers")
+                .and_then(|h| h.as_object())
+                .map(|h| {
+                    h.iter()
+                        .filter(|(name, _)| !overrides.keys().any(|k| k.eq_ignore_ascii_case(name)))
+                        .map(|(name, value)| {
+                            (name.clone(), value.as_str().unwrap_or_default().to_string())
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            headers.extend(overrides.iter().map(|(k, v)| (k.clone(), v.clone())));
+
+            let headers: Vec<serde_json::Value> = headers
+                .into_iter()
+                .map(|(name, value)| serde_json::json!({ "name": name, "value": value }))
+                .collect();
+            (
+                "Fetch.continueRequest",
+                serde_json::json!({ "requestId": request_id, "headers": headers }),
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn block_rule_fails_request() {
+        let rule = RequestRule::block("*://ads.example.com/*");
+        let (method, params) =
+            paused_request_reply(&rule, &serde_json::json!({ "requestId": "interception-1" }));
+        assert_eq!(method, "Fetch.failRequest");
+        assert_eq!(params["requestId"], "interception-1");
+        assert_eq!(params["errorReason"], "BlockedByClient");
+    }
+
+    #[test]
+    fn set_headers_rule_overrides_case_insensitively() {
+        let mut overrides = HashMap::new();
+        overrides.insert("user-agent".to_string(), "custom-agent".to_string());
+        overrides.insert("Authorization".to_string(), "Bearer abc".to_string());
+        let rule = RequestRule::set_headers("*", overrides);
+
+        let event = serde_json::json!({
+            "requestId": "interception-2",
+            "request": {
+                "headers": {
+                    "User-Agent": "HeadlessChrome",
+                    "Accept": "text/html"
+                }
+            }
+        });
+        let (method, params) = paused_request_reply(&rule, &event);
+        assert_eq!(method, "Fetch.continueRequest");
+
+        let headers: HashMap<String, String> = params["headers"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|h| {
+                (
+                    h["name"].as_str().unwrap().to_string(),
+                    h["value"].as_str().unwrap().to_string(),
+                )
+            })
+            .collect();
+        assert_eq!(headers.len(), 3);
+        assert_eq!(headers["user-agent"], "custom-agent");
+        assert_eq!(headers["Authorization"], "Bearer abc");
+        assert_eq!(headers["Accept"], "text/html");
+        assert!(!headers.contains_key("User-Agent"));
+    }
+}
@@ -1,16 +1,52 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
 use colored::Colorize;
 
 use crate::browser::cdp_http;
-use crate::browser::cdp_pipe::PipeKeepAlive;
-use crate::browser::extension_bridge;
+use crate::browser::cdp_pipe::{CdpPipe, PipeKeepAlive};
+use crate::browser::extension_bridge::{self, status_println};
 use crate::browser::extension_installer;
 use crate::browser::launcher::BrowserLauncher;
-use crate::config::{Config, ProfileConfig};
+use crate::browser::process::{self, TerminationOutcome};
+use crate::config::{Config, ProfileConfig, DEFAULT_CDP_HOST};
 use crate::error::{ActionbookError, Result};
+use crate::output;
 
 /// CDP port used internally for the isolated Chrome instance.
 /// Distinct from the default 9222 to avoid conflicts.
-const ISOLATED_CDP_PORT: u16 = 9333;
+pub const ISOLATED_CDP_PORT: u16 = 9333;
+
+/// First Chrome major version with the CDP `Extensions.loadUnpacked` method
+/// used to load the extension over the debugging pipe.
+const MIN_EXTENSION_LOAD_CHROME_MAJOR: u32 = 126;
+
+/// How often [`wait_for_bridge`] re-checks whether the bridge is listening.
+const BRIDGE_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How the isolated bridge hands its session token to the extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TokenDelivery {
+    /// Write the token into the extension's storage over CDP
+    CdpInjection,
+    /// Leave the token in the isolated token file for manual entry in the
+    /// extension popup
+    TokenFile,
+}
+
+impl TokenDelivery {
+    /// CDP injection unless setup recorded that the native messaging host
+    /// could not be registered, in which case the extension cannot complete
+    /// its automatic pairing and the user has to enter the token.
+    pub(crate) fn for_config(config: &Config) -> Self {
+        if config.browser.native_messaging_ok {
+            Self::CdpInjection
+        } else {
+            Self::TokenFile
+        }
+    }
+}
 
 /// Why the main event loop exited.
 enum ShutdownReason {
@@ -20,25 +56,133 @@ enum ShutdownReason {
     ChromeExited,
     /// User sent SIGINT / SIGTERM.
     Signal,
+    /// The global `--timeout` passed (`ServeOptions::deadline`).
+    Deadline,
+}
+
+/// SIGINT / SIGTERM, kept registered for the whole shutdown so a second
+/// Ctrl+C is seen rather than swallowed.
+struct ShutdownSignals {
+    #[cfg(unix)]
+    sigint: tokio::signal::unix::Signal,
+    #[cfg(unix)]
+    sigterm: tokio::signal::unix::Signal,
+}
+
+impl ShutdownSignals {
+    fn new() -> Self {
+        #[cfg(unix)]
+        {
+            use tokio::signal::unix::{signal, SignalKind};
+            Self {
+                sigint: signal(SignalKind::interrupt())
+                    .expect("Failed to register SIGINT handler"),
+                sigterm: signal(SignalKind::terminate())
+                    .expect("Failed to register SIGTERM handler"),
+            }
+        }
+        #[cfg(not(unix))]
+        {
+            Self {}
+        }
+    }
+
+    /// Wait for the next signal.
+    async fn recv(&mut self) {
+        #[cfg(unix)]
+        {
+            tokio::select! {
+                _ = self.sigint.recv() => tracing::info!("Received SIGINT"),
+                _ = self.sigterm.recv() => tracing::info!("Received SIGTERM"),
+            }
+        }
+        #[cfg(not(unix))]
+        {
+            tokio::signal::ctrl_c().await.ok();
+        }
+    }
+}
+
+/// Set by a second signal during shutdown: Chrome is killed at once instead
+/// of being given its grace period.
+#[derive(Default)]
+struct FastExit {
+    requested: AtomicBool,
+    notify: tokio::sync::Notify,
+}
+
+impl FastExit {
+    fn request(&self) {
+        self.requested.store(true, Ordering::SeqCst);
+        self.notify.notify_waiters();
+    }
+
+    fn is_requested(&self) -> bool {
+        self.requested.load(Ordering::SeqCst)
+    }
+
+    /// Resolve once [`request`](Self::request) has been called.
+    async fn wait(&self) {
+        loop {
+            // Registered before the check so a request in between is not missed
+            let notified = self.notify.notified();
+            if self.is_requested() {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
+
+/// Request a fast exit when `next_signal` (the second Ctrl+C) arrives.
+async fn escalate_on_signal(
+    next_signal: impl std::future::Future<Output = ()>,
+    fast_exit: &FastExit,
+    json: bool,
+) {
+    next_signal.await;
+    status_println!(
+        json,
+        "  {}  Interrupted again, killing Chrome without waiting",
+        "!".yellow()
+    );
+    fast_exit.request();
 }
 
 /// Start an isolated Chrome instance with the extension pre-loaded and run the bridge server.
 ///
 /// This orchestrates:
-/// 1. Extension installation check
+/// 1. Extension installation check and bridge port binding
 /// 2. Chrome launch with isolated profile
 /// 3. Bridge server startup (before extension loading so the extension can auto-connect)
 /// 4. Extension loading via CDP pipe
 /// 5. Bridge lifecycle management
 /// 6. Cleanup on exit
-pub async fn serve_isolated(config: &Config, bridge_port: u16) -> Result<()> {
+///
+/// `supplied_token` (from `serve --token-stdin`) replaces the generated session token.
+pub async fn serve_isolated(
+    config: &Config,
+    bridge_port: u16,
+    supplied_token: Option<String>,
+    options: extension_bridge::ServeOptions,
+) -> Result<()> {
+    let json = options.json;
+    let delivery = TokenDelivery::for_config(config);
+
     // 1. Pre-check: extension must be installed
-    if !extension_installer::is_installed() {
+    let ext_dir = extension_installer::extension_dir(config.browser.extension_dir.as_deref())?;
+    if !extension_installer::is_installed(&ext_dir) {
         return Err(ActionbookError::ExtensionError(
             "Extension not installed. Run 'actionbook extension install' first.".to_string(),
         ));
     }
-    let ext_dir = extension_installer::extension_dir()?;
+
+    // 1b. Bind the bridge port before Chrome is launched, so a port still held
+    //     (e.g. by a surviving earlier run) fails here instead of leaving a new
+    //     Chrome orphaned. The port is handed to the extension before the
+    //     bridge serves, so port 0 (any free port) is resolved here too.
+    let listener = extension_bridge::bind_bridge(bridge_port).await?;
+    let bridge_port = listener.local_addr()?.port();
 
     // 2. Build profile config for isolated mode
     let profile = ProfileConfig {
@@ -52,9 +196,29 @@ pub async fn serve_isolated(config: &Config, bridge_port: u16) -> Result<()> {
     let launcher =
         BrowserLauncher::from_profile("extension", &profile)?.with_load_extension(ext_dir.clone());
 
-    // 4. Check if *our* isolated Chrome is already running (profile lock + CDP)
+    // 4. Check if *our* isolated Chrome is already running (profile lock + CDP),
+    //    refusing to guess when the two disagree about who owns the profile
     let profile_dir = BrowserLauncher::default_user_data_dir("extension");
-    let already_running = is_isolated_chrome_running(ISOLATED_CDP_PORT, &profile_dir).await;
+    let state = isolated_chrome_state(
+        cdp_http::shared_client(),
+        &profile.cdp_host,
+        ISOLATED_CDP_PORT,
+        &profile_dir,
+    )
+    .await;
+    let already_running = match state {
+        IsolatedChromeState::NotRunning => false,
+        IsolatedChromeState::Running => true,
+        IsolatedChromeState::Ambiguous(reason) => {
+            return Err(ActionbookError::ExtensionError(format!(
+                "Cannot tell which Chrome owns the isolated profile {}: {}. \
+                 Close the other Chrome instance (or remove a stale SingletonLock) \
+                 and try again.",
+                profile_dir.display(),
+                reason
+            )));
+        }
+    };
 
     // 5. Launch Chrome (but don't load extension yet — bridge must be ready first).
     //    _pipe_keepalive must live until shutdown — Chrome exits when the pipe closes.
@@ -64,44 +228,56 @@ pub async fn serve_isolated(config: &Config, bridge_port: u16) -> Result<()> {
     let mut ext_id_for_injection: Option<String> = None;
 
     let child = if already_running {
-        println!(
+        status_println!(
+            json,
             "  {}  Isolated Chrome already running on CDP port {}",
             "◆".cyan(),
             ISOLATED_CDP_PORT
         );
         None
     } else {
-        println!(
+        // Fail before launching rather than deep inside pipe-based loading.
+        // An explicit executable has no version from discovery yet.
+        let info = launcher.browser_info();
+        let version = info
+            .version
+            .clone()
+            .or_else(|| info.clone().with_version().version);
+        check_extension_loading_support(version.as_deref())?;
+
+        status_println!(
+            json,
             "  {}  Launching isolated Chrome (CDP port {})...",
             "◆".cyan(),
             ISOLATED_CDP_PORT
         );
         let (mut launch_result, cdp_url) = launcher.launch_and_wait().await?;
-        println!("  {}  Chrome ready: {}", "✓".green(), cdp_url.dimmed());
+        status_println!(
+            json,
+            "  {}  Chrome ready: {}",
+            "✓".green(),
+            cdp_url.dimmed()
+        );
 
-        // Stash the CDP pipe for later — we'll load the extension after the bridge is up
-        cdp_pipe_for_ext = launch_result.cdp_pipe.take();
+        // Stash the CDP pipe for later — we'll load the extension after the bridge is up.
+        // Without it the extension would silently never load, so stop here instead.
+        match require_extension_pipe(launch_result.cdp_pipe.take()) {
+            Ok(pipe) => cdp_pipe_for_ext = Some(pipe),
+            Err(e) => {
+                shutdown_chrome(launch_result.child.id(), json, &FastExit::default()).await;
+                return Err(e);
+            }
+        }
 
         Some(launch_result.child)
     };
 
-    // 6. Clean up stale isolated-mode bridge files from previous runs.
-    extension_bridge::delete_isolated_port_file().await;
-    extension_bridge::delete_isolated_token_file().await;
-
-    // Clean up stale standard-mode files — but only if the standard bridge
-    // process is confirmed dead. This prevents `send_command` from picking up
-    // an outdated standard token while preserving files of a running bridge.
-    let standard_alive = extension_bridge::read_pid_file()
-        .await
-        .is_some_and(|(pid, _port)| extension_bridge::is_pid_alive(pid));
-    if !standard_alive {
-        extension_bridge::delete_port_file().await;
-        extension_bridge::delete_token_file().await;
-        extension_bridge::delete_pid_file().await;
-    }
+    // 6. Clean up bridge files from previous ungraceful shutdowns (both modes),
+    //    leaving any live bridge's files in place.
+    extension_bridge::reconcile_stale_files(extension_bridge::BridgeMode::Isolated, bridge_port)
+        .await;
 
-    let token = extension_bridge::generate_token();
+    let token = supplied_token.unwrap_or_else(extension_bridge::generate_token);
 
     // 6b. Write isolated token file so CLI commands (ping, browser open, etc.) can discover it.
     //     This is safe because the file is at bridge-token.isolated, not the global bridge-token,
@@ -110,11 +286,7 @@ pub async fn serve_isolated(config: &Config, bridge_port: u16) -> Result<()> {
 
     // 6c. Write isolated PID file so `extension stop` can find this process.
     if let Err(e) = extension_bridge::write_isolated_pid_file(bridge_port).await {
-        eprintln!(
-            "  {} Failed to write PID file: {}",
-            "!".yellow(),
-            e
-        );
+        output::print_warning(json, format!("Failed to write PID file: {}", e));
     }
 
     // 7. Create shutdown channel and start bridge server BEFORE loading extension.
@@ -123,19 +295,22 @@ pub async fn serve_isolated(config: &Config, bridge_port: u16) -> Result<()> {
     let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel();
     let token_for_bridge = token.clone();
     let bridge_handle = tokio::spawn(async move {
-        extension_bridge::serve_with_shutdown(bridge_port, token_for_bridge, shutdown_rx, true).await
+        let options = extension_bridge::ServeOptions {
+            isolated: true,
+            ..options
+        };
+        extension_bridge::serve_on_listener(listener, token_for_bridge, shutdown_rx, options)
+            .await
     });
 
     // 8. Wait for the bridge to be ready (accepting connections) before loading
     //    the extension, so the extension's first connect attempt succeeds.
-    wait_for_bridge(bridge_port).await?;
+    let ready_timeout = Duration::from_millis(config.browser.bridge_ready_timeout_ms);
+    wait_for_bridge(bridge_port, ready_timeout).await?;
 
     // 9. NOW load extension via CDP pipe — bridge + token are ready.
     if let Some(cdp_pipe) = cdp_pipe_for_ext {
-        println!(
-            "  {}  Loading extension via CDP pipe...",
-            "◆".cyan(),
-        );
+        status_println!(json, "  {}  Loading extension via CDP pipe...", "◆".cyan());
         let ext_dir_owned = ext_dir.clone();
         let load_result = tokio::time::timeout(
             std::time::Duration::from_secs(30),
@@ -145,9 +320,7 @@ pub async fn serve_isolated(config: &Config, bridge_port: u16) -> Result<()> {
 
         let ext_result = match load_result {
             Ok(Ok(Ok(pair))) => Ok(pair),
-            Ok(Ok(Err(e))) => Err(ActionbookError::ExtensionError(format!(
-                "Failed to load extension via CDP pipe: {}", e
-            ))),
+            Ok(Ok(Err(e))) => Err(ActionbookError::ExtensionLoadFailed(Box::new(e))),
             Ok(Err(join_err)) => Err(ActionbookError::ExtensionError(format!(
                 "Extension loading task panicked: {}", join_err
             ))),
@@ -167,47 +340,71 @@ pub async fn serve_isolated(config: &Config, bridge_port: u16) -> Result<()> {
                 extension_bridge::delete_isolated_port_file().await;
                 extension_bridge::delete_isolated_pid_file().await;
                 if let Some(pid) = child.as_ref().map(|c| c.id()) {
-                    terminate_chrome(pid).await;
+                    shutdown_chrome(pid, json, &FastExit::default()).await;
                 }
                 return Err(e);
             }
         };
         _pipe_keepalive = Some(keepalive);
-        println!(
+        status_println!(
+            json,
             "  {}  Extension loaded (ID: {})",
             "✓".green(),
             ext_id.dimmed()
         );
+        match extension_installer::check_extension_id(&ext_dir, &ext_id) {
+            Ok(Some(warning)) => output::print_warning(json, warning),
+            Ok(None) => {}
+            Err(e) => tracing::debug!("Could not derive expected extension ID: {}", e),
+        }
         ext_id_for_injection = Some(ext_id);
     }
 
     // 10. Inject token directly into extension via CDP (isolated mode only).
     //     This bypasses global files entirely — only the isolated Chrome receives the token.
-    if let Some(ref ext_id) = ext_id_for_injection {
-        println!(
-            "  {}  Injecting token via CDP...",
-            "◆".cyan(),
+    //     Skipped when native messaging is unavailable: the token file is the
+    //     source and the user pastes the token into the popup.
+    if delivery == TokenDelivery::TokenFile {
+        status_println!(
+            json,
+            "  {}  Native messaging unavailable; skipping CDP token injection",
+            "!".yellow()
         );
+    } else if let Some(ref ext_id) = ext_id_for_injection {
+        status_println!(json, "  {}  Injecting token via CDP...", "◆".cyan());
         if let Err(e) = cdp_http::inject_token_via_cdp(
-            ISOLATED_CDP_PORT, ext_id, &token, bridge_port,
-        ).await {
-            eprintln!("  {} CDP token injection failed: {}", "!".yellow(), e);
+            &profile.cdp_host,
+            ISOLATED_CDP_PORT,
+            ext_id,
+            &token,
+            bridge_port,
+        )
+        .await
+        {
+            output::print_warning(json, format!("CDP token injection failed: {}", e));
             // Non-fatal: user can still enter token manually via popup
         } else {
-            println!("  {}  Token injected via CDP", "✓".green());
+            status_println!(json, "  {}  Token injected via CDP", "✓".green());
         }
     } else if already_running {
         // Chrome is already running — find the extension's SW without knowing ext_id
-        println!(
+        status_println!(
+            json,
             "  {}  Injecting token into existing extension via CDP...",
             "◆".cyan(),
         );
         if let Err(e) = cdp_http::inject_token_existing(
-            ISOLATED_CDP_PORT, &token, bridge_port,
-        ).await {
-            eprintln!("  {} CDP token injection failed: {}", "!".yellow(), e);
+            &profile.cdp_host,
+            ISOLATED_CDP_PORT,
+            &ext_dir,
+            &token,
+            bridge_port,
+        )
+        .await
+        {
+            output::print_warning(json, format!("CDP token injection failed: {}", e));
         } else {
-            println!("  {}  Token injected via CDP", "✓".green());
+            status_println!(json, "  {}  Token injected via CDP", "✓".green());
         }
     }
 
@@ -215,41 +412,66 @@ pub async fn serve_isolated(config: &Config, bridge_port: u16) -> Result<()> {
     let extension_path = format!(
         "{}{}",
         ext_dir.display(),
-        extension_installer::installed_version()
+        extension_installer::installed_version(&ext_dir)
             .map(|v| format!(" (v{})", v))
             .unwrap_or_default()
     );
 
-    println!();
-    println!("  {}", "Actionbook Extension Bridge (Isolated)".bold());
-    println!("  {}", "─".repeat(45).dimmed());
-    println!();
-    println!(
+    status_println!(json);
+    status_println!(
+        json,
+        "  {}",
+        "Actionbook Extension Bridge (Isolated)".bold()
+    );
+    status_println!(json, "  {}", "─".repeat(45).dimmed());
+    status_println!(json);
+    status_println!(
+        json,
         "  {}  WebSocket server on ws://127.0.0.1:{}",
         "◆".cyan(),
         bridge_port
     );
-    println!("  {}  Extension: {}", "◆".cyan(), extension_path);
-    println!(
+    status_println!(json, "  {}  Extension: {}", "◆".cyan(), extension_path);
+    status_println!(
+        json,
         "  {}  Profile: {} (isolated)",
         "◆".cyan(),
         profile_dir.display().to_string().dimmed()
     );
-    println!();
-    println!("  \u{1f511}  Session token: {}", token.bold());
-    println!(
+    status_println!(json);
+    status_println!(
+        json,
+        "  \u{1f511}  Session token: {}",
+        extension_bridge::banner_token(&token, options.token_supplied).bold()
+    );
+    let delivery_label = match delivery {
+        TokenDelivery::CdpInjection => "CDP injection (no global files)".to_string(),
+        TokenDelivery::TokenFile => format!(
+            "token file {} (enter it in the extension popup)",
+            extension_bridge::isolated_token_file_path()
+                .map(|p| p.display().to_string())
+                .unwrap_or_default()
+        ),
+    };
+    status_println!(
+        json,
         "  {}  Token delivery: {}",
         "◆".cyan(),
-        "CDP injection (no global files)".dimmed()
+        delivery_label.dimmed()
     );
-    println!();
-    println!(
+    status_println!(json);
+    status_println!(
+        json,
         "  {}  Extension auto-loaded in isolated Chrome",
         "ℹ".dimmed()
     );
-    println!("  {}  Token expires after 30min of inactivity", "ℹ".dimmed());
-    println!("  {}  Press Ctrl+C to stop", "ℹ".dimmed());
-    println!();
+    status_println!(
+        json,
+        "  {}  Token expires after 30min of inactivity",
+        "ℹ".dimmed()
+    );
+    status_println!(json, "  {}  Press Ctrl+C to stop", "ℹ".dimmed());
+    status_println!(json);
 
     // 12. Save Chrome PID before moving child into monitor task
     let chrome_pid = child.as_ref().map(|c| c.id());
@@ -265,24 +487,7 @@ pub async fn serve_isolated(config: &Config, bridge_port: u16) -> Result<()> {
     }
 
     // 14. Set up signal handler
-    let signal_handler = async {
-        #[cfg(unix)]
-        {
-            use tokio::signal::unix::{signal, SignalKind};
-            let mut sigint =
-                signal(SignalKind::interrupt()).expect("Failed to register SIGINT handler");
-            let mut sigterm =
-                signal(SignalKind::terminate()).expect("Failed to register SIGTERM handler");
-            tokio::select! {
-                _ = sigint.recv() => tracing::info!("Received SIGINT"),
-                _ = sigterm.recv() => tracing::info!("Received SIGTERM"),
-            }
-        }
-        #[cfg(not(unix))]
-        {
-            tokio::signal::ctrl_c().await.ok();
-        }
-    };
+    let mut signals = ShutdownSignals::new();
 
     // 15. Select between bridge, Chrome exit, and signal — track reason
     let reason = tokio::select! {
@@ -292,19 +497,29 @@ pub async fn serve_isolated(config: &Config, bridge_port: u16) -> Result<()> {
         }
         _ = async { chrome_exit_rx.await.ok(); } => {
             tracing::info!("Chrome exited, shutting down bridge...");
-            println!("\n  {} Chrome exited", "!".yellow());
+            status_println!(json, "\n  {} Chrome exited", "!".yellow());
             let _ = shutdown_tx.send(());
             ShutdownReason::ChromeExited
         }
-        _ = signal_handler => {
+        _ = signals.recv() => {
             tracing::info!("Signal received, shutting down...");
             let _ = shutdown_tx.send(());
             ShutdownReason::Signal
         }
+        _ = extension_bridge::deadline_elapsed(options.deadline) => {
+            tracing::info!("Global timeout reached, shutting down...");
+            let _ = shutdown_tx.send(());
+            ShutdownReason::Deadline
+        }
     };
 
-    // 16. Cleanup
-    println!("\n  {}  Cleaning up...", "◆".cyan());
+    // 16. Cleanup; a further signal from here on skips Chrome's grace period
+    let fast_exit = Arc::new(FastExit::default());
+    let escalation = tokio::spawn({
+        let fast_exit = fast_exit.clone();
+        async move { escalate_on_signal(signals.recv(), &fast_exit, json).await }
+    });
+    status_println!(json, "\n  {}  Cleaning up...", "◆".cyan());
 
     // Delete only isolated token, port, and PID files — leave global files untouched
     // so a concurrently-running personal-Chrome bridge is not affected.
@@ -317,98 +532,414 @@ pub async fn serve_isolated(config: &Config, bridge_port: u16) -> Result<()> {
     // recycled PID.
     if !matches!(reason, ShutdownReason::ChromeExited) {
         if let Some(pid) = chrome_pid {
-            terminate_chrome(pid).await;
+            shutdown_chrome(pid, json, &fast_exit).await;
         }
     }
-
-    println!("  {}  Shutdown complete", "✓".green());
-
-    // Propagate bridge errors so callers see a non-zero exit code
-    if let ShutdownReason::BridgeExited(result) = reason {
-        return match result {
-            Ok(inner) => inner,
-            Err(join_err) => Err(ActionbookError::Other(format!(
-                "Bridge task panicked: {}",
-                join_err
-            ))),
-        };
+    escalation.abort();
+
+    status_println!(json, "  {}  Shutdown complete", "✓".green());
+
+    // Propagate bridge errors (and the timeout) so callers see a non-zero exit code
+    match reason {
+        ShutdownReason::BridgeExited(Ok(inner)) => inner,
+        ShutdownReason::BridgeExited(Err(join_err)) => Err(ActionbookError::Other(format!(
+            "Bridge task panicked: {}",
+            join_err
+        ))),
+        ShutdownReason::Deadline => Err(ActionbookError::GlobalTimeout(
+            options.deadline.unwrap_or_default().as_secs(),
+        )),
+        ShutdownReason::ChromeExited | ShutdownReason::Signal => Ok(()),
     }
+}
 
+/// Ensure a browser reporting `version` (e.g. "120.0.6099.109") supports
+/// `Extensions.loadUnpacked`. An unknown or unparseable version is let
+/// through, since it cannot be shown to be too old.
+fn check_extension_loading_support(version: Option<&str>) -> Result<()> {
+    let Some(major) = version
+        .and_then(|v| v.split('.').next())
+        .and_then(|m| m.parse::<u32>().ok())
+    else {
+        return Ok(());
+    };
+    if major < MIN_EXTENSION_LOAD_CHROME_MAJOR {
+        return Err(ActionbookError::ExtensionError(format!(
+            "Chrome v{}+ required for isolated extension loading; detected v{}",
+            MIN_EXTENSION_LOAD_CHROME_MAJOR, major
+        )));
+    }
     Ok(())
 }
 
-/// Wait for the bridge server to start accepting connections.
-/// Polls with short intervals, fails after a timeout.
-async fn wait_for_bridge(port: u16) -> Result<()> {
-    for _ in 0..20 {
+/// Wait for the bridge server to start accepting connections, polling
+/// every [`BRIDGE_POLL_INTERVAL`] until `timeout` (the configured
+/// `browser.bridge_ready_timeout_ms`) elapses.
+async fn wait_for_bridge(port: u16, timeout: Duration) -> Result<()> {
+    let started = Instant::now();
+    loop {
         if extension_bridge::is_bridge_running(port).await {
             return Ok(());
         }
-        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        if started.elapsed() >= timeout {
+            return Err(ActionbookError::Other(format!(
+                "Timeout waiting for bridge server to start on port {} ({}ms elapsed; \
+                 raise browser.bridge_ready_timeout_ms on slow machines)",
+                port,
+                started.elapsed().as_millis()
+            )));
+        }
+        tokio::time::sleep(BRIDGE_POLL_INTERVAL).await;
     }
-    Err(ActionbookError::Other(
-        "Timeout waiting for bridge server to start".to_string(),
-    ))
 }
 
-/// Terminate a Chrome process by PID using direct syscalls (unix) or taskkill (windows).
-///
-/// Uses `libc::kill` instead of shelling out to `/bin/kill` to avoid PATH-hijacking
-/// risks. Sends SIGTERM first, then SIGKILL only if the process is still alive.
-async fn terminate_chrome(pid: u32) {
-    #[cfg(unix)]
-    {
-        let pid = pid as libc::pid_t;
-        // SAFETY: Sending signals to a PID we obtained from our own Child.
-        // The caller already verified Chrome hasn't exited (ShutdownReason check),
-        // so PID reuse risk is minimal.
-        unsafe {
-            libc::kill(pid, libc::SIGTERM);
+/// Terminate Chrome, noting when it had to be force-killed. Once
+/// `fast_exit` is requested, Chrome is killed without (further) waiting.
+async fn shutdown_chrome(pid: u32, json: bool, fast_exit: &FastExit) {
+    let result = tokio::select! {
+        biased;
+        _ = fast_exit.wait() => process::kill(pid).await,
+        result = process::terminate(pid, process::DEFAULT_TERMINATION_GRACE) => result,
+    };
+    match result {
+        Ok(TerminationOutcome::Killed) if fast_exit.is_requested() => {
+            tracing::info!("Chrome killed on repeated interrupt");
         }
-        // Give Chrome time to shut down gracefully
-        tokio::time::sleep(std::time::Duration::from_secs(2)).await;
-        // Force kill only if still running (kill(pid, 0) probes without sending a signal)
-        unsafe {
-            if libc::kill(pid, 0) == 0 {
-                libc::kill(pid, libc::SIGKILL);
-            }
+        Ok(TerminationOutcome::Killed) => {
+            tracing::warn!("Chrome force-killed after timeout");
+            status_println!(
+                json,
+                "  {}  Chrome force-killed after timeout",
+                "!".yellow()
+            );
         }
-    }
-    #[cfg(not(unix))]
-    {
-        let _ = std::process::Command::new("taskkill")
-            .args(["/PID", &pid.to_string(), "/F"])
-            .status();
+        Ok(outcome) => tracing::debug!("Chrome terminated: {:?}", outcome),
+        Err(e) => tracing::warn!("Failed to terminate Chrome (PID {}): {}", pid, e),
     }
 }
 
-/// Check if an isolated Chrome instance is likely running.
-///
-/// Verifies both the Chrome profile lock file (proving a Chrome instance
-/// is using *our* isolated profile directory) and the CDP endpoint (proving
-/// it is accepting debugging connections). This avoids mistakenly reusing
-/// a different Chrome instance that happens to listen on the same port.
-async fn is_isolated_chrome_running(port: u16, profile_dir: &std::path::Path) -> bool {
-    // Check profile lock file first (cheap filesystem check).
-    // Chrome creates SingletonLock in the user-data-dir while running.
-    // On macOS this is a dangling symlink (target = "hostname-PID"), so
-    // Path::exists() returns false.  Use symlink_metadata() instead.
+/// Chrome's profile lock in a user data dir
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ProfileLock {
+    /// No `SingletonLock`: no Chrome is using the profile
+    Absent,
+    /// A lock whose owner can't be read (e.g. Windows, where it is a plain file)
+    Unknown,
+    /// A `hostname-PID` symlink, with whether that PID is still running
+    Held { pid: u32, alive: bool },
+}
+
+/// Who owns the isolated profile, from its lock and the CDP endpoint
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum IsolatedChromeState {
+    NotRunning,
+    /// Exactly one live Chrome holds the profile and answers on the CDP port
+    Running,
+    /// The signals disagree, so reusing or launching could attach to the
+    /// wrong Chrome
+    Ambiguous(String),
+}
+
+/// Read the profile lock. Chrome creates `SingletonLock` in the user data
+/// dir while running; on Unix it is a dangling symlink (target
+/// `hostname-PID`), so `Path::exists()` is false and `symlink_metadata()`
+/// is used instead.
+fn read_profile_lock(profile_dir: &std::path::Path) -> ProfileLock {
     let lock_file = profile_dir.join("SingletonLock");
     if lock_file.symlink_metadata().is_err() {
-        return false;
+        return ProfileLock::Absent;
     }
+    let pid = std::fs::read_link(&lock_file).ok().and_then(|target| {
+        let target = target.to_string_lossy().into_owned();
+        target.rsplit_once('-')?.1.parse::<u32>().ok()
+    });
+    match pid {
+        Some(pid) => ProfileLock::Held {
+            pid,
+            alive: process::is_alive(pid),
+        },
+        None => ProfileLock::Unknown,
+    }
+}
+
+/// Combine the profile lock with whether a browser answers `/json/version`
+/// on the isolated CDP port.
+fn classify_isolated_chrome(lock: &ProfileLock, cdp_answers: bool) -> IsolatedChromeState {
+    match (lock, cdp_answers) {
+        (ProfileLock::Absent, false) => IsolatedChromeState::NotRunning,
+        (ProfileLock::Absent, true) => IsolatedChromeState::Ambiguous(
+            "a browser answers on the CDP port but none holds the profile lock".to_string(),
+        ),
+        (ProfileLock::Unknown, true) | (ProfileLock::Held { alive: true, .. }, true) => {
+            IsolatedChromeState::Running
+        }
+        (ProfileLock::Unknown, false) => IsolatedChromeState::NotRunning,
+        (ProfileLock::Held { alive: false, .. }, false) => IsolatedChromeState::NotRunning,
+        (ProfileLock::Held { pid, alive: false }, true) => IsolatedChromeState::Ambiguous(
+            format!(
+                "the profile lock names exited process {} but a browser answers on the CDP port",
+                pid
+            ),
+        ),
+        (ProfileLock::Held { pid, alive: true }, false) => IsolatedChromeState::Ambiguous(
+            format!(
+                "process {} holds the profile lock but nothing answers on the CDP port",
+                pid
+            ),
+        ),
+    }
+}
+
+/// Check whether *our* isolated Chrome is running: the profile lock proves a
+/// Chrome instance is using the isolated profile directory, and the CDP
+/// endpoint proves it accepts debugging connections. Requiring both avoids
+/// reusing a different Chrome that happens to listen on the same port.
+async fn isolated_chrome_state(
+    client: &reqwest::Client,
+    host: &str,
+    port: u16,
+    profile_dir: &std::path::Path,
+) -> IsolatedChromeState {
+    let lock = read_profile_lock(profile_dir);
+    let cdp_answers = cdp_http::get_version_with(client, host, port).await.is_ok();
+    classify_isolated_chrome(&lock, cdp_answers)
+}
+
+/// The CDP pipe a freshly launched isolated Chrome must have for the
+/// extension to be loaded into it.
+fn require_extension_pipe(pipe: Option<CdpPipe>) -> Result<CdpPipe> {
+    pipe.ok_or(ActionbookError::CdpPipeUnavailable)
+}
 
-    // Then verify CDP endpoint responds
-    let url = format!("http://127.0.0.1:{}/json/version", port);
-    let client = reqwest::Client::builder()
-        .no_proxy()
-        .timeout(std::time::Duration::from_secs(2))
-        .build()
-        .unwrap_or_else(|_| reqwest::Client::new());
-    client
-        .get(&url)
-        .send()
+/// PID of the isolated Chrome, for `actionbook reset` to stop it once its
+/// bridge has died. Only a Chrome that holds the isolated profile lock and
+/// answers on the isolated CDP port counts, so a recycled PID in a stale
+/// lock is never mistaken for it.
+pub async fn isolated_chrome_pid() -> Option<u32> {
+    let profile_dir = BrowserLauncher::default_user_data_dir("extension");
+    let lock = read_profile_lock(&profile_dir);
+    let cdp_answers = cdp_http::get_version(DEFAULT_CDP_HOST, ISOLATED_CDP_PORT)
         .await
-        .map(|r| r.status().is_success())
-        .unwrap_or(false)
+        .is_ok();
+    running_chrome_pid(&lock, cdp_answers)
+}
+
+fn running_chrome_pid(lock: &ProfileLock, cdp_answers: bool) -> Option<u32> {
+    match (lock, classify_isolated_chrome(lock, cdp_answers)) {
+        (ProfileLock::Held { pid, .. }, IsolatedChromeState::Running) => Some(*pid),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn running_chrome_pid_needs_lock_and_cdp() {
+        let live = ProfileLock::Held {
+            pid: 4242,
+            alive: true,
+        };
+        let dead = ProfileLock::Held {
+            pid: 4242,
+            alive: false,
+        };
+        assert_eq!(running_chrome_pid(&live, true), Some(4242));
+        assert_eq!(running_chrome_pid(&live, false), None);
+        assert_eq!(running_chrome_pid(&dead, true), None);
+        assert_eq!(running_chrome_pid(&ProfileLock::Unknown, true), None);
+        assert_eq!(running_chrome_pid(&ProfileLock::Absent, false), None);
+    }
+
+    #[test]
+    fn launch_without_cdp_pipe_is_an_error() {
+        let Err(err) = require_extension_pipe(None) else {
+            panic!("a launch without a CDP pipe should be rejected");
+        };
+        assert!(matches!(err, ActionbookError::CdpPipeUnavailable));
+        assert!(
+            err.to_string().contains("--remote-debugging-pipe"),
+            "unexpected error: {}",
+            err
+        );
+    }
+
+    #[test]
+    fn isolated_chrome_state_flags_conflicting_signals() {
+        let live = ProfileLock::Held {
+            pid: 4242,
+            alive: true,
+        };
+        let dead = ProfileLock::Held {
+            pid: 4242,
+            alive: false,
+        };
+        assert_eq!(
+            classify_isolated_chrome(&live, true),
+            IsolatedChromeState::Running
+        );
+        assert_eq!(
+            classify_isolated_chrome(&ProfileLock::Unknown, true),
+            IsolatedChromeState::Running
+        );
+        assert_eq!(
+            classify_isolated_chrome(&ProfileLock::Absent, false),
+            IsolatedChromeState::NotRunning
+        );
+        assert_eq!(
+            classify_isolated_chrome(&dead, false),
+            IsolatedChromeState::NotRunning
+        );
+        for (lock, cdp) in [(&ProfileLock::Absent, true), (&dead, true), (&live, false)] {
+            assert!(
+                matches!(
+                    classify_isolated_chrome(lock, cdp),
+                    IsolatedChromeState::Ambiguous(_)
+                ),
+                "{:?} with CDP answering={} should be ambiguous",
+                lock,
+                cdp
+            );
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn profile_lock_reads_owner_pid() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(read_profile_lock(dir.path()), ProfileLock::Absent);
+
+        let pid = std::process::id();
+        let lock_file = dir.path().join("SingletonLock");
+        std::os::unix::fs::symlink(format!("my-host.local-{}", pid), lock_file).unwrap();
+        assert_eq!(
+            read_profile_lock(dir.path()),
+            ProfileLock::Held { pid, alive: true }
+        );
+    }
+
+    #[test]
+    fn extension_loading_requires_minimum_chrome() {
+        let err = check_extension_loading_support(Some("120.0.6099.109")).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "Extension error: Chrome v{}+ required for isolated extension loading; detected v120",
+                MIN_EXTENSION_LOAD_CHROME_MAJOR
+            )
+        );
+
+        let minimum = format!("{}.0.0.0", MIN_EXTENSION_LOAD_CHROME_MAJOR);
+        assert!(check_extension_loading_support(Some(&minimum)).is_ok());
+        assert!(check_extension_loading_support(Some("131.0.6778.85")).is_ok());
+    }
+
+    #[tokio::test]
+    async fn wait_for_bridge_reports_elapsed_time_on_timeout() {
+        // Bind and drop to get a port nothing listens on
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+
+        let err = wait_for_bridge(port, Duration::from_millis(300))
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Timeout waiting for bridge server"), "{}", err);
+        assert!(err.contains("ms elapsed"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn wait_for_bridge_tolerates_slow_start() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+
+        // Comes up well after the old fixed 2s budget would have run out
+        let slow_bridge = tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(2500)).await;
+            let listener = tokio::net::TcpListener::bind(("127.0.0.1", port))
+                .await
+                .unwrap();
+            tokio::time::sleep(Duration::from_secs(2)).await;
+            drop(listener);
+        });
+
+        wait_for_bridge(port, Duration::from_secs(10))
+            .await
+            .unwrap();
+        slow_bridge.abort();
+    }
+
+    /// Stands in for a Chrome that is slow to honour SIGTERM.
+    #[cfg(unix)]
+    fn spawn_stubborn_chrome() -> std::process::Child {
+        use std::io::BufRead;
+        use std::process::{Command, Stdio};
+
+        let mut chrome = Command::new("sh")
+            .args(["-c", "trap '' TERM; echo ready; exec sleep 30"])
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        // Wait until the trap is installed before signalling
+        let mut line = String::new();
+        std::io::BufReader::new(chrome.stdout.take().unwrap())
+            .read_line(&mut line)
+            .unwrap();
+        chrome
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn second_signal_cuts_chrome_grace_period_short() {
+        let mut chrome = spawn_stubborn_chrome();
+        let fast_exit = FastExit::default();
+        let (second_signal, next_signal) = tokio::sync::oneshot::channel::<()>();
+        let started = Instant::now();
+        let escalation = escalate_on_signal(
+            async {
+                next_signal.await.ok();
+            },
+            &fast_exit,
+            true,
+        );
+        let interrupt = async {
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            second_signal.send(()).unwrap();
+        };
+        tokio::join!(shutdown_chrome(chrome.id(), true, &fast_exit), escalation, interrupt);
+
+        assert!(fast_exit.is_requested());
+        assert!(
+            started.elapsed() < process::DEFAULT_TERMINATION_GRACE,
+            "waited {:?}",
+            started.elapsed()
+        );
+        assert!(!process::is_alive(chrome.id()));
+        let _ = chrome.wait();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn fast_exit_requested_up_front_kills_chrome_at_once() {
+        let mut chrome = spawn_stubborn_chrome();
+        let fast_exit = FastExit::default();
+        fast_exit.request();
+
+        let started = Instant::now();
+        shutdown_chrome(chrome.id(), true, &fast_exit).await;
+        assert!(started.elapsed() < process::DEFAULT_TERMINATION_GRACE);
+        assert!(!process::is_alive(chrome.id()));
+        let _ = chrome.wait();
+    }
+
+    #[test]
+    fn unknown_chrome_version_is_allowed() {
+        assert!(check_extension_loading_support(None).is_ok());
+        assert!(check_extension_loading_support(Some("dev")).is_ok());
+    }
 }
@@ -1,14 +1,17 @@
 use std::path::PathBuf;
 use std::process::{Child, Command, Stdio};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use serde_json::{json, Value};
 use tokio::time::sleep;
 
+use super::cdp_http::cdp_http_url;
 use super::cdp_pipe::CdpPipe;
 use super::discovery::{discover_browser, BrowserInfo};
-use crate::config::ProfileConfig;
-use crate::error::{ActionbookError, Result};
+use crate::config::{
+    is_loopback_host, ProfileConfig, DEFAULT_CDP_HOST, DEFAULT_CDP_READY_TIMEOUT_MS,
+};
+use crate::error::{ActionbookError, LaunchErrorKind, Result};
 
 /// Result of launching a browser, optionally including a CDP pipe
 /// for post-launch extension loading (Chrome 137+).
@@ -22,11 +25,15 @@ pub struct BrowserLauncher {
     browser_info: BrowserInfo,
     profile_name: String,
     cdp_port: u16,
+    cdp_host: String,
+    cdp_bind_remote: bool,
     headless: bool,
+    #[cfg(feature = "stealth")]
     stealth: bool,
     user_data_dir: PathBuf,
     extra_args: Vec<String>,
     load_extension_path: Option<PathBuf>,
+    cdp_ready_timeout: Duration,
 }
 
 impl BrowserLauncher {
@@ -56,11 +63,15 @@ impl BrowserLauncher {
             browser_info,
             profile_name: Self::ACTIONBOOK_PROFILE_NAME.to_string(),
             cdp_port: 9222,
+            cdp_host: DEFAULT_CDP_HOST.to_string(),
+            cdp_bind_remote: false,
             headless: false,
+            #[cfg(feature = "stealth")]
             stealth: false,
             user_data_dir: data_dir,
             extra_args: Vec::new(),
             load_extension_path: None,
+            cdp_ready_timeout: Duration::from_millis(DEFAULT_CDP_READY_TIMEOUT_MS),
         })
     }
 
@@ -73,10 +84,8 @@ impl BrowserLauncher {
             )));
         }
 
-        let browser_info = BrowserInfo::new(
-            super::discovery::BrowserType::Chrome, // Assume Chrome-compatible
-            path,
-        );
+        // Anything unrecognized is assumed to be Chrome-compatible
+        let browser_info = BrowserInfo::new(super::discovery::BrowserType::from_path(&path), path);
 
         let data_dir = Self::default_user_data_dir(Self::ACTIONBOOK_PROFILE_NAME);
 
@@ -84,16 +93,24 @@ impl BrowserLauncher {
             browser_info,
             profile_name: Self::ACTIONBOOK_PROFILE_NAME.to_string(),
             cdp_port: 9222,
+            cdp_host: DEFAULT_CDP_HOST.to_string(),
+            cdp_bind_remote: false,
             headless: false,
+            #[cfg(feature = "stealth")]
             stealth: false,
             user_data_dir: data_dir,
             extra_args: Vec::new(),
             load_extension_path: None,
+            cdp_ready_timeout: Duration::from_millis(DEFAULT_CDP_READY_TIMEOUT_MS),
         })
     }
 
     /// Create a launcher from profile configuration
     pub fn from_profile(profile_name: &str, profile: &ProfileConfig) -> Result<Self> {
+        profile.validate_cdp_endpoint().map_err(|reason| {
+            ActionbookError::ConfigError(format!("Profile '{}': {}", profile_name, reason))
+        })?;
+
         let mut launcher = if let Some(ref path) = profile.browser_path {
             Self::with_browser_path(PathBuf::from(path))?
         } else {
@@ -102,7 +119,10 @@ impl BrowserLauncher {
 
         launcher.profile_name = profile_name.to_string();
         launcher.cdp_port = profile.cdp_port;
+        launcher.cdp_host = profile.cdp_host.clone();
+        launcher.cdp_bind_remote = profile.cdp_bind_remote;
         launcher.headless = profile.headless;
+        launcher.cdp_ready_timeout = Duration::from_millis(profile.cdp_ready_timeout_ms);
         launcher.user_data_dir =
             Self::resolve_user_data_dir(profile_name, profile.user_data_dir.as_deref());
 
@@ -110,11 +130,19 @@ impl BrowserLauncher {
     }
 
     /// Enable stealth mode (anti-detection Chrome flags)
+    #[cfg(feature = "stealth")]
     pub fn with_stealth(mut self, stealth: bool) -> Self {
         self.stealth = stealth;
         self
     }
 
+    /// Set how long `launch_and_wait` waits for the CDP endpoint
+    #[allow(dead_code)]
+    pub fn with_cdp_ready_timeout(mut self, timeout: Duration) -> Self {
+        self.cdp_ready_timeout = timeout;
+        self
+    }
+
     /// Load a Chrome extension from the given directory on launch
     pub fn with_load_extension(mut self, path: PathBuf) -> Self {
         self.load_extension_path = Some(path);
@@ -158,6 +186,11 @@ impl BrowserLauncher {
             "--no-default-browser-check".to_string(),
         ];
 
+        // Chrome only listens on loopback unless the profile opts in
+        if self.cdp_bind_remote && !is_loopback_host(&self.cdp_host) {
+            args.push(format!("--remote-debugging-address={}", self.cdp_host));
+        }
+
         // Anti-detection flags — skip when loading extensions because they
         // interfere with the extension runtime and trigger Chrome's
         // "unsupported command-line flag" warning bar.
@@ -209,8 +242,7 @@ impl BrowserLauncher {
         if let Some(parent) = path.parent() {
             std::fs::create_dir_all(parent)?;
         }
-        let content = serde_json::to_string_pretty(value)
-            .map_err(|e| ActionbookError::Other(format!("Failed to serialize JSON: {}", e)))?;
+        let content = serde_json::to_string_pretty(value)?;
         std::fs::write(path, content)?;
         Ok(())
     }
@@ -301,10 +333,42 @@ impl BrowserLauncher {
         Ok(())
     }
 
+    /// Fail early if another browser process already holds this user data dir.
+    ///
+    /// Chrome marks a profile in use with a `SingletonLock` symlink pointing at
+    /// `<hostname>-<pid>`. Launching against a locked profile hands the request
+    /// to the existing process, so CDP would never come up on our port.
+    fn check_profile_lock(&self) -> Result<()> {
+        let lock_path = self.user_data_dir.join("SingletonLock");
+        let Ok(target) = std::fs::read_link(&lock_path) else {
+            return Ok(());
+        };
+        let target = target.to_string_lossy();
+        let pid = target
+            .rsplit('-')
+            .next()
+            .and_then(|p| p.parse::<u32>().ok());
+
+        match pid {
+            Some(pid) if super::extension_bridge::is_pid_alive(pid) => {
+                Err(ActionbookError::launch(
+                    LaunchErrorKind::ProfileLocked,
+                    format!(
+                        "{} is in use by another browser process (PID {})",
+                        self.user_data_dir.display(),
+                        pid
+                    ),
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+
     /// Launch the browser and return the process handle with optional CDP pipe.
     pub fn launch(&self) -> Result<LaunchResult> {
         // Ensure user data directory exists
         std::fs::create_dir_all(&self.user_data_dir)?;
+        self.check_profile_lock()?;
         if let Err(e) = self.ensure_actionbook_profile_display_name() {
             tracing::warn!("Failed to set actionbook profile display name: {}", e);
         }
@@ -332,11 +396,14 @@ impl BrowserLauncher {
             .stderr(Stdio::null())
             .spawn()
             .map_err(|e| {
-                ActionbookError::BrowserLaunchFailed(format!(
-                    "Failed to launch {}: {}",
-                    self.browser_info.browser_type.name(),
-                    e
-                ))
+                ActionbookError::launch(
+                    LaunchErrorKind::Spawn,
+                    format!(
+                        "Failed to launch {}: {}",
+                        self.browser_info.browser_type.name(),
+                        e
+                    ),
+                )
             })?;
 
         Ok(LaunchResult {
@@ -412,11 +479,14 @@ impl BrowserLauncher {
                 libc::close(child_read_fd);
                 libc::close(child_write_fd);
             }
-            ActionbookError::BrowserLaunchFailed(format!(
-                "Failed to launch {} with CDP pipe: {}",
-                self.browser_info.browser_type.name(),
-                e
-            ))
+            ActionbookError::launch(
+                LaunchErrorKind::Spawn,
+                format!(
+                    "Failed to launch {} with CDP pipe: {}",
+                    self.browser_info.browser_type.name(),
+                    e
+                ),
+            )
         })?;
 
         // Child process inherited the child-side fds via dup2.
@@ -461,9 +531,9 @@ impl BrowserLauncher {
         Ok((result, cdp_url))
     }
 
-    /// Wait for CDP endpoint to be ready
+    /// Wait for CDP endpoint to be ready, polling until `cdp_ready_timeout` elapses
     async fn wait_for_cdp(&self) -> Result<String> {
-        let url = format!("http://127.0.0.1:{}/json/version", self.cdp_port);
+        let url = self.version_url();
 
         // Build client with NO_PROXY for localhost
         let client = reqwest::Client::builder()
@@ -471,18 +541,25 @@ impl BrowserLauncher {
             .build()
             .unwrap_or_else(|_| reqwest::Client::new());
 
-        // Try for up to 10 seconds
-        for i in 0..20 {
-            sleep(Duration::from_millis(500)).await;
+        let poll_interval = Duration::from_millis(500);
+        let start = Instant::now();
+        let mut attempt = 0;
 
-            match client.get(&url).send().await {
+        loop {
+            let remaining = self.cdp_ready_timeout.saturating_sub(start.elapsed());
+            if remaining.is_zero() {
+                break;
+            }
+            sleep(poll_interval.min(remaining)).await;
+            attempt += 1;
+
+            let remaining = self
+                .cdp_ready_timeout
+                .saturating_sub(start.elapsed())
+                .max(Duration::from_millis(50));
+            match client.get(&url).timeout(remaining).send().await {
                 Ok(response) if response.status().is_success() => {
-                    let json: serde_json::Value = response.json().await.map_err(|e| {
-                        ActionbookError::CdpConnectionFailed(format!(
-                            "Failed to parse CDP response: {}",
-                            e
-                        ))
-                    })?;
+                    let json: serde_json::Value = response.json().await?;
 
                     if let Some(ws_url) = json.get("webSocketDebuggerUrl").and_then(|v| v.as_str())
                     {
@@ -491,23 +568,31 @@ impl BrowserLauncher {
                     }
                 }
                 Ok(_) => {
-                    tracing::debug!("CDP not ready yet (attempt {})", i + 1);
+                    tracing::debug!("CDP not ready yet (attempt {})", attempt);
                 }
                 Err(e) => {
-                    tracing::debug!("CDP connection attempt {} failed: {}", i + 1, e);
+                    tracing::debug!("CDP connection attempt {} failed: {}", attempt, e);
                 }
             }
         }
 
-        Err(ActionbookError::CdpConnectionFailed(
-            "Timeout waiting for CDP to be ready".to_string(),
+        Err(ActionbookError::launch(
+            LaunchErrorKind::CdpTimeout,
+            format!(
+                "Timeout waiting for CDP to be ready on port {} ({}) after {}ms (limit {}ms, {} attempts)",
+                self.cdp_port,
+                self.cdp_host,
+                start.elapsed().as_millis(),
+                self.cdp_ready_timeout.as_millis(),
+                attempt
+            ),
         ))
     }
 
     /// Get the CDP WebSocket URL for an already running browser
     #[allow(dead_code)]
     pub async fn get_cdp_url(&self) -> Result<String> {
-        let url = format!("http://127.0.0.1:{}/json/version", self.cdp_port);
+        let url = self.version_url();
 
         // Build client with NO_PROXY for localhost
         let client = reqwest::Client::builder()
@@ -523,9 +608,7 @@ impl BrowserLauncher {
             return Err(ActionbookError::BrowserNotRunning);
         }
 
-        let json: serde_json::Value = response.json().await.map_err(|e| {
-            ActionbookError::CdpConnectionFailed(format!("Failed to parse CDP response: {}", e))
-        })?;
+        let json: serde_json::Value = response.json().await?;
 
         json.get("webSocketDebuggerUrl")
             .and_then(|v| v.as_str())
@@ -535,8 +618,12 @@ impl BrowserLauncher {
             })
     }
 
+    /// The `/json/version` endpoint this launcher polls for readiness
+    fn version_url(&self) -> String {
+        cdp_http_url(&self.cdp_host, self.cdp_port, "/json/version")
+    }
+
     /// Get browser info
-    #[allow(dead_code)]
     pub fn browser_info(&self) -> &BrowserInfo {
         &self.browser_info
     }
@@ -545,6 +632,11 @@ impl BrowserLauncher {
     pub fn get_cdp_port(&self) -> u16 {
         self.cdp_port
     }
+
+    /// Get CDP host
+    pub fn get_cdp_host(&self) -> &str {
+        &self.cdp_host
+    }
 }
 
 impl Default for BrowserLauncher {
@@ -559,16 +651,32 @@ mod tests {
     use crate::browser::BrowserType;
     use std::path::PathBuf;
 
+    #[test]
+    fn unreadable_profile_json_is_an_io_error() {
+        let dir = tempfile::tempdir().unwrap();
+        // A directory where the JSON file should be cannot be read as text
+        let err = BrowserLauncher::read_json_or_default(dir.path()).unwrap_err();
+        assert!(
+            matches!(err, crate::error::ActionbookError::IoError(_)),
+            "{:?}",
+            err
+        );
+    }
+
     fn test_launcher_with_user_data_dir(dir: PathBuf) -> BrowserLauncher {
         BrowserLauncher {
             browser_info: BrowserInfo::new(BrowserType::Chrome, PathBuf::new()),
             profile_name: BrowserLauncher::ACTIONBOOK_PROFILE_NAME.to_string(),
             cdp_port: 9222,
+            cdp_host: DEFAULT_CDP_HOST.to_string(),
+            cdp_bind_remote: false,
             headless: false,
+            #[cfg(feature = "stealth")]
             stealth: false,
             user_data_dir: dir,
             extra_args: Vec::new(),
             load_extension_path: None,
+            cdp_ready_timeout: Duration::from_millis(DEFAULT_CDP_READY_TIMEOUT_MS),
         }
     }
 
@@ -686,11 +794,15 @@ mod tests {
             browser_info: BrowserInfo::new(BrowserType::Chrome, PathBuf::new()),
             profile_name: "test".to_string(),
             cdp_port: 9222,
+            cdp_host: DEFAULT_CDP_HOST.to_string(),
+            cdp_bind_remote: false,
             headless: false,
+            #[cfg(feature = "stealth")]
             stealth: false,
             user_data_dir: dir,
             extra_args: Vec::new(),
             load_extension_path: Some(ext_path),
+            cdp_ready_timeout: Duration::from_millis(DEFAULT_CDP_READY_TIMEOUT_MS),
         };
         let args = launcher.build_args();
 
@@ -764,4 +876,174 @@ mod tests {
             "disable-infobars should be set in normal mode"
         );
     }
+
+    #[test]
+    fn launch_with_missing_binary_is_spawn_error() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut launcher = test_launcher_with_user_data_dir(tmp.path().join("profile"));
+        launcher.browser_info =
+            BrowserInfo::new(BrowserType::Chrome, tmp.path().join("no-such-browser"));
+
+        match launcher.launch() {
+            Err(ActionbookError::LaunchError { kind, .. }) => {
+                assert_eq!(kind, LaunchErrorKind::Spawn)
+            }
+            Err(e) => panic!("expected spawn LaunchError, got {}", e),
+            Ok(_) => panic!("expected spawn LaunchError, got Ok"),
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn live_singleton_lock_is_profile_locked_error() {
+        let tmp = tempfile::tempdir().unwrap();
+        let launcher = test_launcher_with_user_data_dir(tmp.path().to_path_buf());
+        std::os::unix::fs::symlink(
+            format!("test-host-{}", std::process::id()),
+            tmp.path().join("SingletonLock"),
+        )
+        .unwrap();
+
+        match launcher.check_profile_lock() {
+            Err(ActionbookError::LaunchError { kind, .. }) => {
+                assert_eq!(kind, LaunchErrorKind::ProfileLocked)
+            }
+            other => panic!("expected ProfileLocked, got {:?}", other.err()),
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn stale_singleton_lock_is_ignored() {
+        let tmp = tempfile::tempdir().unwrap();
+        let launcher = test_launcher_with_user_data_dir(tmp.path().to_path_buf());
+        // PID well above any default pid_max, so it cannot be alive
+        std::os::unix::fs::symlink("test-host-999999999", tmp.path().join("SingletonLock"))
+            .unwrap();
+
+        assert!(launcher.check_profile_lock().is_ok());
+    }
+
+    #[tokio::test]
+    async fn wait_for_cdp_times_out_with_elapsed_info() {
+        // Mock endpoint that accepts connections but never reports ready
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            use tokio::io::AsyncWriteExt;
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let _ = stream
+                    .write_all(b"HTTP/1.1 503 Service Unavailable\r\nContent-Length: 0\r\n\r\n")
+                    .await;
+            }
+        });
+
+        let mut launcher = test_launcher_with_user_data_dir(PathBuf::from("/tmp/test-profile"))
+            .with_cdp_ready_timeout(Duration::from_millis(300));
+        launcher.cdp_port = port;
+
+        let start = Instant::now();
+        let err = launcher.wait_for_cdp().await.unwrap_err();
+        assert!(start.elapsed() < Duration::from_secs(2));
+
+        match err {
+            ActionbookError::LaunchError { kind, detail } => {
+                assert_eq!(kind, LaunchErrorKind::CdpTimeout);
+                assert!(detail.contains("limit 300ms"), "detail: {}", detail);
+                assert!(
+                    detail.contains(&format!("port {}", port)),
+                    "detail: {}",
+                    detail
+                );
+                assert!(detail.contains("after "), "detail: {}", detail);
+            }
+            other => panic!("expected CdpTimeout, got {}", other),
+        }
+    }
+
+    /// A launcher for `profile` that doesn't need a browser installed
+    fn launcher_for_profile(profile: &ProfileConfig) -> Result<BrowserLauncher> {
+        let profile = ProfileConfig {
+            browser_path: Some(std::env::current_exe().unwrap().display().to_string()),
+            ..profile.clone()
+        };
+        BrowserLauncher::from_profile("test", &profile)
+    }
+
+    #[test]
+    fn custom_cdp_host_flows_into_version_url_only() {
+        let profile = ProfileConfig {
+            cdp_host: "chrome-sidecar".to_string(),
+            ..ProfileConfig::with_cdp_port(9555)
+        };
+        let launcher = launcher_for_profile(&profile).unwrap();
+
+        assert_eq!(
+            launcher.version_url(),
+            "http://chrome-sidecar:9555/json/version"
+        );
+        assert!(!launcher
+            .build_args()
+            .iter()
+            .any(|a| a.starts_with("--remote-debugging-address")));
+    }
+
+    #[test]
+    fn cdp_bind_remote_makes_chrome_listen_on_the_cdp_host() {
+        let profile = ProfileConfig {
+            cdp_host: "chrome-sidecar".to_string(),
+            cdp_bind_remote: true,
+            ..ProfileConfig::with_cdp_port(9555)
+        };
+        let launcher = launcher_for_profile(&profile).unwrap();
+
+        assert!(launcher
+            .build_args()
+            .contains(&"--remote-debugging-address=chrome-sidecar".to_string()));
+    }
+
+    #[test]
+    fn default_cdp_host_is_loopback() {
+        let launcher = launcher_for_profile(&ProfileConfig::with_cdp_port(9555)).unwrap();
+
+        assert_eq!(launcher.version_url(), "http://127.0.0.1:9555/json/version");
+        assert!(!launcher
+            .build_args()
+            .iter()
+            .any(|a| a.starts_with("--remote-debugging-address")));
+    }
+
+    #[test]
+    fn invalid_cdp_endpoint_is_rejected() {
+        for (host, port) in [
+            ("http://chrome", 9222),
+            ("chrome:9222", 9222),
+            ("chrome/json", 9222),
+            ("", 9222),
+            ("chrome", 0),
+        ] {
+            let profile = ProfileConfig {
+                cdp_host: host.to_string(),
+                ..ProfileConfig::with_cdp_port(port)
+            };
+            let err = launcher_for_profile(&profile).err().unwrap().to_string();
+            assert!(
+                err.contains("Profile 'test'"),
+                "{}:{} -> {}",
+                host,
+                port,
+                err
+            );
+        }
+    }
+
+    #[test]
+    fn cdp_ready_timeout_defaults_when_missing_from_config() {
+        let profile: ProfileConfig = toml::from_str("cdp_port = 9444").unwrap();
+        assert_eq!(profile.cdp_ready_timeout_ms, DEFAULT_CDP_READY_TIMEOUT_MS);
+
+        let profile: ProfileConfig =
+            toml::from_str("cdp_port = 9444\ncdp_ready_timeout_ms = 45000").unwrap();
+        assert_eq!(profile.cdp_ready_timeout_ms, 45_000);
+    }
 }
@@ -1,19 +1,29 @@
 mod discovery;
 pub(crate) mod cdp_http;
 pub(crate) mod cdp_pipe;
+pub mod context;
 pub mod extension_installer;
 pub mod extension_bridge;
+pub mod interception;
 pub mod isolated_extension;
 pub mod launcher;
 pub mod native_messaging;
+pub mod process;
 mod session;
+#[cfg(feature = "stealth")]
 pub mod stealth;
 
 #[allow(unused_imports)]
-pub use discovery::{discover_all_browsers, BrowserInfo, BrowserType};
-pub use session::{SessionManager, SessionStatus, StealthConfig};
-pub use stealth::{build_stealth_profile, stealth_status};
-
-// Re-export stealth page application for external use
+pub use discovery::{
+    discover_all_browsers, discover_all_browsers_preferring, BrowserChannel, BrowserInfo,
+    BrowserType,
+};
+#[allow(unused_imports)]
+pub use context::BrowserContext;
+#[allow(unused_imports)]
+pub use interception::{InterceptionHandle, RequestAction, RequestRule};
+pub use session::{SessionManager, SessionStatus};
+#[cfg(feature = "stealth")]
+pub use session::StealthConfig;
 #[cfg(feature = "stealth")]
-pub use stealth::apply_stealth_to_page;
+pub use stealth::{apply_stealth_to_page, build_stealth_profile, stealth_status};
@@ -17,32 +17,54 @@ pub const NATIVE_HOST_NAME: &str = "com.actionbook.bridge";
 /// The stable extension ID derived from the public key in manifest.json.
 pub const EXTENSION_ID: &str = "dpfioflkmnkklgjldmaggkodhlidkdcd";
 
-/// Default bridge port (must match extension's BRIDGE_URL and CLI default).
-const DEFAULT_BRIDGE_PORT: u16 = 19222;
+/// Version of this native host, reported to the extension as `host_version`.
+pub const HOST_VERSION: &str = env!("CARGO_PKG_VERSION");
 
-/// Read one native messaging message from stdin.
-fn read_message() -> io::Result<serde_json::Value> {
-    let stdin = io::stdin();
-    let mut handle = stdin.lock();
+/// Oldest extension each native host release line works with, as
+/// `(host version requirement, minimum extension version)`. The first entry
+/// whose requirement matches the host applies.
+const EXTENSION_COMPAT: &[(&str, &str)] = &[(">=0.6.0", "0.1.0")];
 
+/// Check an extension version against what `host_version` needs.
+///
+/// Returns a message telling the user which extension version to update to
+/// when it is too old. Versions that don't parse (e.g. extensions from
+/// before versions were reported) are not flagged.
+pub fn check_extension_compat(host_version: &str, extension_version: &str) -> Option<String> {
+    let host = semver::Version::parse(host_version).ok()?;
+    let extension = semver::Version::parse(extension_version).ok()?;
+    let (_, minimum) = EXTENSION_COMPAT.iter().find(|(req, _)| {
+        semver::VersionReq::parse(req)
+            .map(|req| req.matches(&host))
+            .unwrap_or(false)
+    })?;
+    let minimum = semver::Version::parse(minimum).ok()?;
+    (extension < minimum).then(|| {
+        format!(
+            "Extension v{} is too old for actionbook v{}; update the extension to v{} or newer \
+             (actionbook extension install --force)",
+            extension, host, minimum
+        )
+    })
+}
+
+/// Read one native messaging message from `reader` (stdin when run by Chrome).
+fn read_message(reader: &mut impl Read) -> crate::error::Result<serde_json::Value> {
     // Read 4-byte little-endian length prefix
     let mut len_bytes = [0u8; 4];
-    handle.read_exact(&mut len_bytes)?;
+    reader.read_exact(&mut len_bytes)?;
     let len = u32::from_le_bytes(len_bytes) as usize;
 
     // Sanity check: Chrome caps at 1MB
     if len > 1_048_576 {
-        return Err(io::Error::new(
-            io::ErrorKind::InvalidData,
-            "Message too large",
-        ));
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "Message too large").into());
     }
 
     // Read JSON payload
     let mut buf = vec![0u8; len];
-    handle.read_exact(&mut buf)?;
+    reader.read_exact(&mut buf)?;
 
-    serde_json::from_slice(&buf).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    Ok(serde_json::from_slice(&buf)?)
 }
 
 /// Write one native messaging message to stdout.
@@ -64,19 +86,19 @@ fn write_message(msg: &serde_json::Value) -> io::Result<()> {
 ///
 /// Reads one request from stdin, processes it, writes one response to stdout, then exits.
 pub async fn run() -> crate::error::Result<()> {
-    let msg = read_message().map_err(|e| {
-        crate::error::ActionbookError::Other(format!("Failed to read native message: {}", e))
-    })?;
+    let msg = read_message(&mut io::stdin().lock())?;
 
     let msg_type = msg
         .get("type")
         .and_then(|t| t.as_str())
         .unwrap_or("");
 
-    let response = match msg_type {
+    let mut response = match msg_type {
         "get_token" => {
             let token = extension_bridge::read_token_file().await;
-            let port = extension_bridge::read_port_file().await.unwrap_or(DEFAULT_BRIDGE_PORT);
+            let port = extension_bridge::read_port_file()
+                .await
+                .unwrap_or(extension_bridge::DEFAULT_BRIDGE_PORT);
             let bridge_running = extension_bridge::is_bridge_running(port).await;
 
             match token {
@@ -110,9 +132,16 @@ pub async fn run() -> crate::error::Result<()> {
         }),
     };
 
-    write_message(&response).map_err(|e| {
-        crate::error::ActionbookError::Other(format!("Failed to write native message: {}", e))
-    })?;
+    response["host_version"] = HOST_VERSION.into();
+    if let Some(warning) = msg
+        .get("extension_version")
+        .and_then(|v| v.as_str())
+        .and_then(|v| check_extension_compat(HOST_VERSION, v))
+    {
+        response["compat_warning"] = warning.into();
+    }
+
+    write_message(&response)?;
 
     Ok(())
 }
@@ -199,9 +228,7 @@ pub fn install_manifest() -> crate::error::Result<std::path::PathBuf> {
         })?;
     }
 
-    let content = serde_json::to_string_pretty(&manifest).map_err(|e| {
-        crate::error::ActionbookError::Other(format!("Failed to serialize manifest: {}", e))
-    })?;
+    let content = serde_json::to_string_pretty(&manifest)?;
 
     std::fs::write(&manifest_path, content).map_err(|e| {
         crate::error::ActionbookError::Other(format!(
@@ -214,6 +241,58 @@ pub fn install_manifest() -> crate::error::Result<std::path::PathBuf> {
     Ok(manifest_path)
 }
 
+/// What was found at a native messaging host manifest path
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestStatus {
+    pub path: std::path::PathBuf,
+    pub exists: bool,
+    /// Why an existing manifest is unusable (unreadable, bad JSON, wrong shape)
+    pub error: Option<String>,
+    /// Whether `allowed_origins` lets the extension launch this host
+    pub origin_allowed: bool,
+}
+
+impl ManifestStatus {
+    /// Present, well-formed, and allows the extension.
+    pub fn is_ok(&self) -> bool {
+        self.exists && self.error.is_none() && self.origin_allowed
+    }
+}
+
+/// Inspect the manifest at `path` for `extension_id`.
+pub fn manifest_status(path: &std::path::Path, extension_id: &str) -> ManifestStatus {
+    let mut status = ManifestStatus {
+        path: path.to_path_buf(),
+        exists: path.exists(),
+        error: None,
+        origin_allowed: false,
+    };
+    if !status.exists {
+        return status;
+    }
+
+    let manifest = std::fs::read_to_string(path)
+        .map_err(|e| e.to_string())
+        .and_then(|content| {
+            serde_json::from_str::<serde_json::Value>(&content).map_err(|e| e.to_string())
+        });
+    let origins = match manifest {
+        Ok(manifest) => manifest.get("allowed_origins").and_then(|o| o.as_array()).cloned(),
+        Err(e) => {
+            status.error = Some(e);
+            return status;
+        }
+    };
+    match origins {
+        Some(origins) => {
+            let origin = format!("chrome-extension://{}/", extension_id);
+            status.origin_allowed = origins.iter().any(|o| o.as_str() == Some(origin.as_str()));
+        }
+        None => status.error = Some("missing allowed_origins array".to_string()),
+    }
+    status
+}
+
 /// Remove the native messaging host manifest.
 pub fn uninstall_manifest() -> crate::error::Result<()> {
     let manifest_path = native_host_manifest_path()?;
@@ -245,11 +324,78 @@ mod tests {
         assert!(origins[0].as_str().unwrap().contains(EXTENSION_ID));
     }
 
+    #[test]
+    fn read_message_keeps_error_kinds() {
+        use crate::error::ActionbookError;
+
+        let mut framed = 7u32.to_le_bytes().to_vec();
+        framed.extend_from_slice(br#"{"a":1}"#);
+        assert_eq!(
+            read_message(&mut framed.as_slice()).unwrap(),
+            serde_json::json!({"a": 1})
+        );
+
+        // Chrome closed stdin mid-message
+        let truncated = [7u8, 0, 0, 0, b'{'];
+        match read_message(&mut truncated.as_slice()) {
+            Err(ActionbookError::IoError(e)) => {
+                assert_eq!(e.kind(), io::ErrorKind::UnexpectedEof)
+            }
+            other => panic!("expected an I/O error, got {:?}", other),
+        }
+
+        let mut garbled = 3u32.to_le_bytes().to_vec();
+        garbled.extend_from_slice(b"{{{");
+        assert!(matches!(
+            read_message(&mut garbled.as_slice()),
+            Err(ActionbookError::JsonError(_))
+        ));
+    }
+
+    #[test]
+    fn extension_compat_flags_only_too_old_extensions() {
+        assert_eq!(check_extension_compat("0.6.1", "0.1.0"), None);
+        assert_eq!(check_extension_compat("0.6.1", "0.3.2"), None);
+
+        let warning = check_extension_compat("0.6.1", "0.0.9").unwrap();
+        assert!(warning.contains("update the extension to v0.1.0"), "{}", warning);
+
+        // Unknown versions and hosts outside the matrix are not flagged
+        assert_eq!(check_extension_compat("0.6.1", "unknown"), None);
+        assert_eq!(check_extension_compat("0.5.0", "0.0.1"), None);
+    }
+
+    #[test]
+    fn manifest_status_covers_present_absent_and_malformed() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let absent = manifest_status(&dir.path().join("absent.json"), EXTENSION_ID);
+        assert!(!absent.exists);
+        assert!(!absent.is_ok());
+
+        let present = dir.path().join("present.json");
+        std::fs::write(&present, generate_manifest("/usr/bin/actionbook").to_string()).unwrap();
+        let status = manifest_status(&present, EXTENSION_ID);
+        assert!(status.is_ok(), "{:?}", status);
+        assert!(!manifest_status(&present, "abcdefghijklmnopabcdefghijklmnop").origin_allowed);
+
+        let malformed = dir.path().join("malformed.json");
+        std::fs::write(&malformed, "{ not json").unwrap();
+        let status = manifest_status(&malformed, EXTENSION_ID);
+        assert!(status.exists);
+        assert!(status.error.is_some());
+
+        let no_origins = dir.path().join("no-origins.json");
+        std::fs::write(&no_origins, r#"{"name": "com.actionbook.bridge"}"#).unwrap();
+        let status = manifest_status(&no_origins, EXTENSION_ID);
+        assert_eq!(status.error.as_deref(), Some("missing allowed_origins array"));
+    }
+
     #[test]
     fn test_extension_id_format() {
         // Extension IDs are 32 lowercase characters a-p
         assert_eq!(EXTENSION_ID.len(), 32);
-        assert!(EXTENSION_ID.chars().all(|c| c >= 'a' && c <= 'p'));
+        assert!(EXTENSION_ID.chars().all(|c| ('a'..='p').contains(&c)));
     }
 
     #[test]
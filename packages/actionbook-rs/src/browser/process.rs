@@ -0,0 +1,228 @@
+//! Terminating processes we launched or recorded a PID for.
+//!
+//! Shared by `extension stop` (bridge processes) and the isolated-mode
+//! cleanup (Chrome), so both escalate from a graceful shutdown to a forced
+//! kill the same way.
+
+use std::time::Duration;
+
+/// How long a process gets to exit after SIGTERM before it is force-killed.
+pub const DEFAULT_TERMINATION_GRACE: Duration = Duration::from_secs(2);
+
+/// How often liveness is re-checked while waiting for a process to exit.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// How long to wait for a force-killed process to disappear.
+const KILL_SETTLE_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// How a [`terminate`] call ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TerminationOutcome {
+    /// Exited within the grace period after SIGTERM
+    GracefulExit,
+    /// Still running after the grace period and force-killed
+    Killed,
+    /// Had already exited before any signal was sent
+    AlreadyDead,
+}
+
+/// Terminate `pid`, escalating to a forced kill if it outlives `grace`.
+///
+/// On unix this uses `libc::kill` instead of shelling out to `/bin/kill` to
+/// avoid PATH-hijacking risks: SIGTERM first, then SIGKILL only if the
+/// process is still alive once `grace` has elapsed. On Windows the same
+/// escalation goes through `taskkill` and `taskkill /F`.
+///
+/// Callers are responsible for guarding against PID recycling (e.g. checking
+/// the process is still the one they launched) before calling this.
+/// Returns an error only when the initial signal could not be delivered for
+/// a reason other than the process being gone.
+pub async fn terminate(pid: u32, grace: Duration) -> std::io::Result<TerminationOutcome> {
+    if !is_alive(pid) {
+        return Ok(TerminationOutcome::AlreadyDead);
+    }
+
+    if let Err(e) = request_exit(pid) {
+        if !is_alive(pid) {
+            return Ok(TerminationOutcome::AlreadyDead);
+        }
+        return Err(e);
+    }
+
+    if wait_for_exit(pid, grace).await {
+        return Ok(TerminationOutcome::GracefulExit);
+    }
+
+    let _ = force_kill(pid);
+    wait_for_exit(pid, KILL_SETTLE_TIMEOUT).await;
+    Ok(TerminationOutcome::Killed)
+}
+
+/// Force-kill `pid` right away, without a graceful shutdown request.
+///
+/// Same PID-recycling caveat as [`terminate`]. Returns an error only when
+/// the kill could not be delivered for a reason other than the process
+/// being gone.
+pub async fn kill(pid: u32) -> std::io::Result<TerminationOutcome> {
+    if !is_alive(pid) {
+        return Ok(TerminationOutcome::AlreadyDead);
+    }
+
+    if let Err(e) = force_kill(pid) {
+        if !is_alive(pid) {
+            return Ok(TerminationOutcome::AlreadyDead);
+        }
+        return Err(e);
+    }
+
+    wait_for_exit(pid, KILL_SETTLE_TIMEOUT).await;
+    Ok(TerminationOutcome::Killed)
+}
+
+/// Whether `pid` is still running.
+///
+/// Reaps the process if it is an exited child of ours, since a zombie still
+/// answers `kill(pid, 0)`. Otherwise (not our child, or already reaped
+/// elsewhere) falls back to probing with signal 0.
+#[cfg(unix)]
+pub fn is_alive(pid: u32) -> bool {
+    let pid = pid as libc::pid_t;
+    let mut status = 0;
+    // SAFETY: WNOHANG never blocks; `status` is a valid out pointer.
+    match unsafe { libc::waitpid(pid, &mut status, libc::WNOHANG) } {
+        0 => true,
+        r if r == pid => false,
+        // kill(pid, 0) probes without sending a signal
+        _ => unsafe { libc::kill(pid, 0) == 0 },
+    }
+}
+
+#[cfg(not(unix))]
+pub fn is_alive(pid: u32) -> bool {
+    super::extension_bridge::is_pid_alive(pid)
+}
+
+/// Poll until `pid` exits or `timeout` elapses. Returns whether it exited.
+async fn wait_for_exit(pid: u32, timeout: Duration) -> bool {
+    let deadline = tokio::time::Instant::now() + timeout;
+    loop {
+        if !is_alive(pid) {
+            return true;
+        }
+        if tokio::time::Instant::now() >= deadline {
+            return false;
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+#[cfg(unix)]
+fn request_exit(pid: u32) -> std::io::Result<()> {
+    // SAFETY: plain signal delivery; the caller vouches for the PID.
+    if unsafe { libc::kill(pid as libc::pid_t, libc::SIGTERM) } == 0 {
+        Ok(())
+    } else {
+        Err(std::io::Error::last_os_error())
+    }
+}
+
+#[cfg(unix)]
+fn force_kill(pid: u32) -> std::io::Result<()> {
+    // SAFETY: see request_exit
+    if unsafe { libc::kill(pid as libc::pid_t, libc::SIGKILL) } == 0 {
+        Ok(())
+    } else {
+        Err(std::io::Error::last_os_error())
+    }
+}
+
+#[cfg(not(unix))]
+fn request_exit(pid: u32) -> std::io::Result<()> {
+    let status = std::process::Command::new("taskkill")
+        .args(["/PID", &pid.to_string()])
+        .status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(std::io::Error::other(format!(
+            "taskkill exited with {}",
+            status
+        )))
+    }
+}
+
+#[cfg(not(unix))]
+fn force_kill(pid: u32) -> std::io::Result<()> {
+    let status = std::process::Command::new("taskkill")
+        .args(["/PID", &pid.to_string(), "/F"])
+        .status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(std::io::Error::other(format!(
+            "taskkill /F exited with {}",
+            status
+        )))
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::io::BufRead;
+    use std::process::{Command, Stdio};
+
+    /// A child that ignores SIGTERM, returned once its trap is installed.
+    fn spawn_ignoring_sigterm() -> std::process::Child {
+        let mut child = Command::new("sh")
+            .args(["-c", "trap '' TERM; echo ready; exec sleep 30"])
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let mut line = String::new();
+        std::io::BufReader::new(child.stdout.take().unwrap())
+            .read_line(&mut line)
+            .unwrap();
+        assert_eq!(line.trim(), "ready");
+        child
+    }
+
+    #[tokio::test]
+    async fn terminate_reports_graceful_exit() {
+        let mut child = Command::new("sleep").arg("30").spawn().unwrap();
+        let outcome = terminate(child.id(), Duration::from_secs(5)).await.unwrap();
+        assert_eq!(outcome, TerminationOutcome::GracefulExit);
+        // terminate already reaped it; this only satisfies the Child API
+        let _ = child.wait();
+    }
+
+    #[tokio::test]
+    async fn terminate_kills_process_ignoring_sigterm() {
+        let mut child = spawn_ignoring_sigterm();
+        let outcome = terminate(child.id(), Duration::from_millis(200))
+            .await
+            .unwrap();
+        assert_eq!(outcome, TerminationOutcome::Killed);
+        assert!(!is_alive(child.id()));
+        let _ = child.wait();
+    }
+
+    #[tokio::test]
+    async fn kill_skips_grace_period() {
+        let mut child = spawn_ignoring_sigterm();
+        let started = std::time::Instant::now();
+        let outcome = kill(child.id()).await.unwrap();
+        assert_eq!(outcome, TerminationOutcome::Killed);
+        assert!(started.elapsed() < DEFAULT_TERMINATION_GRACE);
+        assert!(!is_alive(child.id()));
+        let _ = child.wait();
+    }
+
+    #[tokio::test]
+    async fn terminate_reports_already_dead() {
+        let mut child = Command::new("true").spawn().unwrap();
+        child.wait().unwrap();
+        let outcome = terminate(child.id(), Duration::from_secs(1)).await.unwrap();
+        assert_eq!(outcome, TerminationOutcome::AlreadyDead);
+    }
+}
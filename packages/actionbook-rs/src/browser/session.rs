@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
 use std::time::Duration;
@@ -7,9 +8,13 @@ use chromiumoxide::handler::Handler;
 use futures::StreamExt;
 use serde::{Deserialize, Serialize};
 
+use super::cdp_http;
+use super::context::{self, BrowserContext};
+use super::interception::{self, InterceptionHandle, RequestRule};
 use super::launcher::BrowserLauncher;
+#[cfg(feature = "stealth")]
 use super::stealth::StealthProfile;
-use crate::config::{Config, ProfileConfig};
+use crate::config::{Config, ProfileConfig, DEFAULT_CDP_HOST};
 use crate::error::{ActionbookError, Result};
 
 /// Page info from CDP /json/list endpoint
@@ -29,11 +34,20 @@ pub struct PageInfo {
 struct SessionState {
     profile_name: String,
     cdp_port: u16,
+    /// Host of the CDP endpoint; state files written before this field
+    /// existed are local sessions.
+    #[serde(default = "default_session_cdp_host")]
+    cdp_host: String,
     pid: Option<u32>,
     cdp_url: String,
 }
 
+fn default_session_cdp_host() -> String {
+    DEFAULT_CDP_HOST.to_string()
+}
+
 /// Stealth configuration for session manager
+#[cfg(feature = "stealth")]
 #[derive(Debug, Clone, Default)]
 #[allow(dead_code)]
 pub struct StealthConfig {
@@ -49,6 +63,7 @@ pub struct StealthConfig {
 pub struct SessionManager {
     config: Config,
     sessions_dir: PathBuf,
+    #[cfg(feature = "stealth")]
     stealth_config: Option<StealthConfig>,
 }
 
@@ -62,11 +77,13 @@ impl SessionManager {
         Self {
             config,
             sessions_dir,
+            #[cfg(feature = "stealth")]
             stealth_config: None,
         }
     }
 
     /// Create session manager with stealth configuration
+    #[cfg(feature = "stealth")]
     pub fn with_stealth(config: Config, stealth_config: StealthConfig) -> Self {
         let sessions_dir = dirs::data_dir()
             .unwrap_or_else(|| PathBuf::from("."))
@@ -81,6 +98,7 @@ impl SessionManager {
     }
 
     /// Check if stealth mode is enabled
+    #[cfg(feature = "stealth")]
     pub fn is_stealth_enabled(&self) -> bool {
         self.stealth_config
             .as_ref()
@@ -139,6 +157,7 @@ impl SessionManager {
         let state = SessionState {
             profile_name: profile_name.to_string(),
             cdp_port,
+            cdp_host: default_session_cdp_host(),
             pid: None,
             cdp_url: cdp_url.to_string(),
         };
@@ -148,7 +167,7 @@ impl SessionManager {
     /// Check if a session is still alive
     async fn is_session_alive(&self, state: &SessionState) -> bool {
         // Check if we can connect to the CDP port (bypass proxy for localhost)
-        let url = format!("http://127.0.0.1:{}/json/version", state.cdp_port);
+        let url = cdp_http::cdp_http_url(&state.cdp_host, state.cdp_port, "/json/version");
         let client = reqwest::Client::builder()
             .no_proxy()
             .timeout(Duration::from_secs(5))
@@ -157,10 +176,10 @@ impl SessionManager {
         client.get(&url).send().await.is_ok()
     }
 
-    /// Fetch the current browser WebSocket URL from a CDP port via /json/version.
+    /// Fetch the current browser WebSocket URL from a CDP endpoint via /json/version.
     /// Returns `None` if the port is unreachable or the response is malformed.
-    async fn fetch_browser_ws_url(&self, cdp_port: u16) -> Option<String> {
-        let url = format!("http://127.0.0.1:{}/json/version", cdp_port);
+    async fn fetch_browser_ws_url(&self, cdp_host: &str, cdp_port: u16) -> Option<String> {
+        let url = cdp_http::cdp_http_url(cdp_host, cdp_port, "/json/version");
         let client = reqwest::Client::builder()
             .no_proxy()
             .timeout(Duration::from_secs(5))
@@ -186,7 +205,10 @@ impl SessionManager {
             if self.is_session_alive(&state).await {
                 // Refresh WebSocket URL — the browser may have restarted on the same port,
                 // which generates a new session ID and invalidates the cached URL.
-                if let Some(fresh_url) = self.fetch_browser_ws_url(state.cdp_port).await {
+                if let Some(fresh_url) = self
+                    .fetch_browser_ws_url(&state.cdp_host, state.cdp_port)
+                    .await
+                {
                     if fresh_url != state.cdp_url {
                         tracing::debug!("CDP WebSocket URL changed, updating session");
                         state.cdp_url = fresh_url;
@@ -215,10 +237,9 @@ impl SessionManager {
         profile_name: &str,
         profile: &ProfileConfig,
     ) -> Result<(Browser, Handler)> {
-        let stealth_enabled = self.is_stealth_enabled();
-
-        let launcher =
-            BrowserLauncher::from_profile(profile_name, profile)?.with_stealth(stealth_enabled);
+        let launcher = BrowserLauncher::from_profile(profile_name, profile)?;
+        #[cfg(feature = "stealth")]
+        let launcher = launcher.with_stealth(self.is_stealth_enabled());
 
         let (_launch_result, cdp_url) = launcher.launch_and_wait().await?;
 
@@ -226,6 +247,7 @@ impl SessionManager {
         let state = SessionState {
             profile_name: profile_name.to_string(),
             cdp_port: launcher.get_cdp_port(),
+            cdp_host: launcher.get_cdp_host().to_string(),
             pid: None, // TODO: get actual PID
             cdp_url: cdp_url.clone(),
         };
@@ -275,7 +297,7 @@ impl SessionManager {
         "#;
 
         // Use Page.addScriptToEvaluateOnNewDocument via CDP so it applies to all future pages
-        let pages_url = format!("http://127.0.0.1:{}/json/list", state.cdp_port);
+        let pages_url = cdp_http::cdp_http_url(&state.cdp_host, state.cdp_port, "/json/list");
         let client = reqwest::Client::builder()
             .no_proxy()
             .build()
@@ -389,6 +411,18 @@ impl SessionManager {
         Ok(())
     }
 
+    /// Create an isolated, incognito-equivalent browser context in the
+    /// running browser. It has its own cookies and storage and is disposed
+    /// via [`BrowserContext::dispose`] or when the handle is dropped.
+    #[allow(dead_code)]
+    pub async fn new_context(&self, profile_name: Option<&str>) -> Result<BrowserContext> {
+        let profile_name = self.resolve_profile_name(profile_name);
+        let state = self
+            .load_session_state(&profile_name)
+            .ok_or(ActionbookError::BrowserNotRunning)?;
+        context::create(&state.cdp_url).await
+    }
+
     /// Get list of pages from the browser
     pub async fn get_pages(&self, profile_name: Option<&str>) -> Result<Vec<PageInfo>> {
         let profile_name = self.resolve_profile_name(profile_name);
@@ -396,7 +430,7 @@ impl SessionManager {
             .load_session_state(&profile_name)
             .ok_or(ActionbookError::BrowserNotRunning)?;
 
-        let url = format!("http://127.0.0.1:{}/json/list", state.cdp_port);
+        let url = cdp_http::cdp_http_url(&state.cdp_host, state.cdp_port, "/json/list");
         let client = reqwest::Client::builder()
             .no_proxy()
             .build()
@@ -406,9 +440,7 @@ impl SessionManager {
             ActionbookError::CdpConnectionFailed(format!("Failed to get pages: {}", e))
         })?;
 
-        let pages: Vec<PageInfo> = response.json().await.map_err(|e| {
-            ActionbookError::CdpConnectionFailed(format!("Failed to parse pages: {}", e))
-        })?;
+        let pages: Vec<PageInfo> = response.json().await?;
 
         // Filter to only include actual pages (not extensions, service workers, etc.)
         Ok(pages
@@ -489,6 +521,35 @@ impl SessionManager {
         Err(ActionbookError::Other("No response received".to_string()))
     }
 
+    /// Evaluate `expression` in a page via `Runtime.evaluate` and return its
+    /// value. `target_id` picks a page from [`Self::get_pages`]; `None` uses
+    /// the active page. With `await_promise` a returned promise is awaited.
+    /// A thrown JS error is returned as [`ActionbookError::JavaScriptError`].
+    #[allow(dead_code)]
+    pub async fn evaluate(
+        &self,
+        profile_name: Option<&str>,
+        target_id: Option<&str>,
+        expression: &str,
+        await_promise: bool,
+    ) -> Result<serde_json::Value> {
+        let pages = self.get_pages(profile_name).await?;
+        let page = match target_id {
+            Some(id) => pages
+                .into_iter()
+                .find(|p| p.id == id)
+                .ok_or_else(|| ActionbookError::Other(format!("No page target with id {}", id)))?,
+            None => pages
+                .into_iter()
+                .next()
+                .ok_or(ActionbookError::BrowserNotRunning)?,
+        };
+        let ws_url = page
+            .web_socket_debugger_url
+            .ok_or_else(|| ActionbookError::CdpConnectionFailed("No WebSocket URL".to_string()))?;
+        cdp_http::evaluate_in_target(&ws_url, expression, await_promise).await
+    }
+
     /// Helper to send a CDP command and get response
     async fn send_cdp_command(
         &self,
@@ -1314,6 +1375,62 @@ impl SessionManager {
         Ok(())
     }
 
+    /// Add or override HTTP headers on every request made by the active page.
+    ///
+    /// Uses `Network.setExtraHTTPHeaders`. The headers stay in effect until the
+    /// returned handle is disabled or dropped.
+    #[allow(dead_code)]
+    pub async fn set_extra_headers(
+        &self,
+        profile_name: Option<&str>,
+        headers: HashMap<String, String>,
+    ) -> Result<InterceptionHandle> {
+        let ws_url = self.active_page_ws_url(profile_name).await?;
+        interception::start(
+            &ws_url,
+            vec![
+                ("Network.enable", serde_json::json!({})),
+                (
+                    "Network.setExtraHTTPHeaders",
+                    serde_json::json!({ "headers": headers }),
+                ),
+            ],
+            None,
+        )
+        .await
+    }
+
+    /// Modify or block requests from the active page whose URL matches `rule`.
+    ///
+    /// Uses `Fetch.enable`. Interception stays in effect until the returned
+    /// handle is disabled or dropped.
+    #[allow(dead_code)]
+    pub async fn intercept_requests(
+        &self,
+        profile_name: Option<&str>,
+        rule: RequestRule,
+    ) -> Result<InterceptionHandle> {
+        let ws_url = self.active_page_ws_url(profile_name).await?;
+        let patterns = serde_json::json!([{
+            "urlPattern": rule.url_pattern,
+            "requestStage": "Request"
+        }]);
+        interception::start(
+            &ws_url,
+            vec![("Fetch.enable", serde_json::json!({ "patterns": patterns }))],
+            Some(rule),
+        )
+        .await
+    }
+
+    #[allow(dead_code)]
+    async fn active_page_ws_url(&self, profile_name: Option<&str>) -> Result<String> {
+        self.get_active_page_info(profile_name)
+            .await?
+            .web_socket_debugger_url
+            .ok_or_else(|| ActionbookError::CdpConnectionFailed("No WebSocket URL".to_string()))
+    }
+
     /// Get viewport dimensions
     pub async fn get_viewport(&self, profile_name: Option<&str>) -> Result<(f64, f64)> {
         let js = r#"
@@ -1480,6 +1597,7 @@ mod tests {
         SessionManager {
             config: Config::default(),
             sessions_dir: dir.to_path_buf(),
+            #[cfg(feature = "stealth")]
             stealth_config: None,
         }
     }
@@ -1512,6 +1630,7 @@ mod tests {
         let sm = SessionManager {
             config: Config::default(),
             sessions_dir: sessions_dir.clone(),
+            #[cfg(feature = "stealth")]
             stealth_config: None,
         };
 
@@ -1624,7 +1743,7 @@ mod tests {
         let sm = test_session_manager(dir.path());
 
         // Port 19998 is not listening — should return None, not panic
-        let result = sm.fetch_browser_ws_url(19998).await;
+        let result = sm.fetch_browser_ws_url(DEFAULT_CDP_HOST, 19998).await;
         assert!(result.is_none());
     }
 
@@ -1649,7 +1768,9 @@ mod tests {
 
         // fetch_browser_ws_url returns None since port is not listening,
         // so the URL remains unchanged (no crash)
-        let fresh = sm.fetch_browser_ws_url(state.cdp_port).await;
+        let fresh = sm
+            .fetch_browser_ws_url(&state.cdp_host, state.cdp_port)
+            .await;
         assert!(fresh.is_none());
     }
 
@@ -1661,6 +1782,7 @@ mod tests {
         let sm = SessionManager {
             config,
             sessions_dir: dir.path().to_path_buf(),
+            #[cfg(feature = "stealth")]
             stealth_config: None,
         };
 
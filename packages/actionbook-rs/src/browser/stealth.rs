@@ -1,15 +1,13 @@
 //! Stealth browser automation support using chaser-oxide.
 //!
-//! Enable with `--features stealth` for:
+//! Only compiled with `--features stealth`, which enables:
 //! - No automation banner
 //! - Anti-detection measures
 //! - Hardware fingerprint spoofing
 //! - Human-like mouse/keyboard simulation
 
-#[cfg(feature = "stealth")]
 use chaser_oxide::profiles::{ChaserProfile, ChaserProfileBuilder, Gpu};
 
-#[cfg(feature = "stealth")]
 use crate::error::{ActionbookError, Result};
 
 /// Stealth profile configuration
@@ -73,7 +71,6 @@ pub enum StealthGpu {
     AppleM4Max,
 }
 
-#[cfg(feature = "stealth")]
 impl StealthProfile {
     /// Build a chaser-oxide profile from this configuration
     pub fn to_chaser_profile(&self) -> ChaserProfile {
@@ -107,22 +104,37 @@ impl StealthProfile {
     }
 }
 
+/// Global flag the stealth script sets on its first run in a document, so
+/// running it again (re-navigation, repeated apply) patches nothing twice.
+pub const STEALTH_SENTINEL: &str = "__actionbookStealthApplied";
+
 /// Apply stealth profile to a chromiumoxide page
-/// This wraps the page in ChaserPage and applies anti-detection measures
-#[cfg(feature = "stealth")]
+///
+/// The script is registered for every new document and run on the current
+/// one. It is guarded by [`STEALTH_SENTINEL`], so calling this again, e.g.
+/// on a page recreated after a crash, is safe.
 pub async fn apply_stealth_to_page(
     page: &chromiumoxide::Page,
     profile: &StealthProfile,
 ) -> Result<()> {
-    use chromiumoxide::Page;
+    let script = stealth_script(profile);
 
-    // Convert to ChaserPage and apply profile
-    // Note: ChaserPage requires ownership, so we clone the page handle
-    let chaser_profile = profile.to_chaser_profile();
+    page.evaluate_on_new_document(script.clone())
+        .await
+        .map_err(|e| {
+            ActionbookError::Other(format!("Failed to register stealth script: {}", e))
+        })?;
+    page.evaluate(script)
+        .await
+        .map_err(|e| ActionbookError::Other(format!("Failed to apply stealth script: {}", e)))?;
 
-    // Apply stealth via CDP commands directly
-    // This is done via JavaScript injection to avoid ChaserPage ownership issues
+    tracing::debug!("Applied stealth profile to page: {:?}", profile.os);
+    Ok(())
+}
 
+/// Build the page script for `profile`: navigator and WebGL overrides plus
+/// automation hiding, run at most once per document.
+pub fn stealth_script(profile: &StealthProfile) -> String {
     // 1. Override navigator properties
     let navigator_override = format!(
         r#"
@@ -144,10 +156,6 @@ pub async fn apply_stealth_to_page(
         &profile.locale,
     );
 
-    page.evaluate(navigator_override).await.map_err(|e| {
-        ActionbookError::Other(format!("Failed to apply navigator override: {}", e))
-    })?;
-
     // 2. Override WebGL renderer
     let webgl_override = format!(
         r#"
@@ -179,10 +187,6 @@ pub async fn apply_stealth_to_page(
         },
     );
 
-    page.evaluate(webgl_override)
-        .await
-        .map_err(|e| ActionbookError::Other(format!("Failed to apply WebGL override: {}", e)))?;
-
     // 3. Hide automation indicators
     let automation_hide = r#"
         // Remove webdriver property
@@ -209,21 +213,22 @@ pub async fn apply_stealth_to_page(
         });
     "#;
 
-    page.evaluate(automation_hide)
-        .await
-        .map_err(|e| ActionbookError::Other(format!("Failed to hide automation: {}", e)))?;
-
-    tracing::debug!("Applied stealth profile to page: {:?}", profile.os);
-    Ok(())
+    // The sentinel is non-enumerable so page scripts iterating `window` don't see it
+    format!(
+        r#"(() => {{
+        if (window.{sentinel}) return;
+        Object.defineProperty(window, '{sentinel}', {{ value: true, enumerable: false }});
+        {navigator_override}
+        {webgl_override}
+        {automation_hide}
+    }})();"#,
+        sentinel = STEALTH_SENTINEL,
+    )
 }
 
 /// Get stealth mode status string
 pub fn stealth_status() -> &'static str {
-    if cfg!(feature = "stealth") {
-        "enabled (chaser-oxide)"
-    } else {
-        "disabled (use --features stealth to enable)"
-    }
+    "enabled (chaser-oxide)"
 }
 
 /// Parse OS string from CLI into StealthOs
@@ -271,3 +276,50 @@ pub fn build_stealth_profile(os: Option<&str>, gpu: Option<&str>) -> StealthProf
 
     profile
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stealth_script_is_guarded_by_sentinel() {
+        let script = stealth_script(&StealthProfile::default());
+
+        // The guard runs before any patch, and the sentinel is set exactly once
+        let guard = script
+            .find(&format!("if (window.{}) return;", STEALTH_SENTINEL))
+            .expect("script should check the sentinel");
+        let first_patch = script.find("Object.defineProperty(navigator").unwrap();
+        assert!(guard < first_patch);
+        assert_eq!(
+            script
+                .matches(&format!("Object.defineProperty(window, '{}'", STEALTH_SENTINEL))
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn reapplying_does_not_stack_patches() {
+        let profile = StealthProfile::default();
+        let script = stealth_script(&profile);
+
+        // Each global is patched once per script, and the script is the same
+        // every time, so re-applying only re-runs the guarded no-op
+        assert_eq!(script, stealth_script(&profile));
+        assert_eq!(
+            script
+                .matches("WebGLRenderingContext.prototype.getParameter = ")
+                .count(),
+            1
+        );
+        assert_eq!(
+            script
+                .matches("Object.defineProperty(navigator, 'webdriver'")
+                .count(),
+            1
+        );
+        assert!(script.trim_start().starts_with("(() => {"));
+        assert!(script.trim_end().ends_with("})();"));
+    }
+}
@@ -1,7 +1,11 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
 use clap::{Parser, Subcommand, ValueEnum};
 
 use crate::commands;
-use crate::error::Result;
+use crate::config::Config;
+use crate::error::{ActionbookError, Result};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
 pub enum SetupTarget {
@@ -21,6 +25,17 @@ pub enum BrowserMode {
     System,
 }
 
+/// How `get` prints an action body that is JSON
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum BodyFormat {
+    /// Verbatim, as returned by the API
+    Raw,
+    /// Indented with two spaces
+    Pretty,
+    /// Minified
+    Compact,
+}
+
 /// Actionbook CLI - Browser automation with zero installation
 #[derive(Parser)]
 #[command(name = "actionbook", bin_name = "actionbook")]
@@ -42,7 +57,7 @@ pub struct Cli {
     #[arg(long, env = "ACTIONBOOK_HEADLESS", global = true)]
     pub headless: bool,
 
-    /// Enable stealth mode (requires --features stealth)
+    /// Enable stealth mode (only in builds with the `stealth` feature)
     #[arg(long, env = "ACTIONBOOK_STEALTH", global = true)]
     pub stealth: bool,
 
@@ -67,18 +82,30 @@ pub struct Cli {
     #[arg(long, global = true)]
     pub json: bool,
 
-    /// Use extension mode (route browser commands through Chrome Extension bridge)
+    /// Use extension mode: route `browser` commands and `tabs` through the
+    /// Chrome Extension bridge. `search`, `get` and `sources` read the
+    /// Actionbook API and work the same either way.
     #[arg(long, env = "ACTIONBOOK_EXTENSION", global = true)]
     pub extension: bool,
 
-    /// Extension bridge port
-    #[arg(long, env = "ACTIONBOOK_EXTENSION_PORT", global = true, default_value = "19222")]
-    pub extension_port: u16,
+    /// Extension bridge port (default: the running isolated bridge, then the
+    /// standard one)
+    #[arg(long, env = "ACTIONBOOK_EXTENSION_PORT", global = true)]
+    pub extension_port: Option<u16>,
 
-    /// Enable verbose output
+    /// Enable verbose output: log one level more than configured (debug by
+    /// default). Ignored when RUST_LOG is set.
     #[arg(short, long, global = true)]
     pub verbose: bool,
 
+    /// Config file path (overrides the default location)
+    #[arg(long = "config", value_name = "PATH", global = true)]
+    pub config_path: Option<PathBuf>,
+
+    /// Hard upper bound for the whole command, in seconds (place before the subcommand)
+    #[arg(long, value_name = "SECS", env = "ACTIONBOOK_TIMEOUT")]
+    pub timeout: Option<u64>,
+
     #[command(subcommand)]
     pub command: Commands,
 }
@@ -117,6 +144,10 @@ pub enum Commands {
     Get {
         /// Area ID (e.g., "airbnb.com:/:default")
         area_id: String,
+
+        /// Output format for JSON bodies (non-JSON bodies are always printed as-is)
+        #[arg(long, value_enum, default_value_t = BodyFormat::Raw)]
+        format: BodyFormat,
     },
 
     /// List or search sources
@@ -143,6 +174,18 @@ pub enum Commands {
         command: ExtensionCommands,
     },
 
+    /// List the tabs of the browser connected through the extension bridge
+    Tabs,
+
+    /// Remove bridge token/port/PID files left behind by crashed bridges and
+    /// stop an isolated Chrome whose bridge has died. A running bridge's
+    /// files are never touched.
+    Reset {
+        /// Skip confirmation
+        #[arg(long, short)]
+        yes: bool,
+    },
+
     /// Initial setup wizard
     Setup {
         /// Target platform (skip wizard, run `npx skills add` for specific agent)
@@ -172,6 +215,10 @@ pub enum BrowserCommands {
     /// Show browser status and detection results
     Status,
 
+    /// Show which browser is behind a CDP port (`--cdp <port>`, or the
+    /// profile's CDP port), from Chrome's `/json/version`
+    Version,
+
     /// Open a URL in a new tab
     Open {
         /// URL to open
@@ -390,26 +437,58 @@ pub enum CookiesCommands {
 pub enum ExtensionCommands {
     /// Start the extension bridge WebSocket server
     Serve {
-        /// Port to listen on
-        #[arg(long, default_value = "19222")]
-        port: u16,
+        /// Port to listen on (default: browser.bridge_port, else 19222; 0 lets
+        /// the OS pick a free port)
+        #[arg(long)]
+        port: Option<u16>,
         /// Use an isolated Chrome profile for the extension bridge
         #[arg(long)]
         isolated: bool,
+        /// Reject commands whose params exceed this many bytes (default 4 MB)
+        #[arg(long, value_name = "BYTES")]
+        max_params_bytes: Option<usize>,
+        /// Reuse the token saved by a previous run so a configured extension
+        /// stays paired (opt-in; ignored in isolated mode)
+        #[arg(long)]
+        stable_token: bool,
+        /// Replace the saved stable token with a fresh one
+        #[arg(long, requires = "stable_token")]
+        rotate_token: bool,
+        /// Read the session token from the first line of stdin instead of
+        /// generating one; it is never printed
+        #[arg(long, conflicts_with = "stable_token")]
+        token_stdin: bool,
+        /// Also write status output and log events to this file
+        #[arg(long, value_name = "PATH")]
+        log_file: Option<PathBuf>,
+        /// Keep a live summary of bridge activity (extension connection,
+        /// in-flight and recent commands) on the terminal; ignored with --json
+        #[arg(long)]
+        live: bool,
+        /// Rotate the log file to `<PATH>.1` once it reaches this many bytes
+        #[arg(
+            long,
+            value_name = "BYTES",
+            requires = "log_file",
+            default_value_t = crate::log_file::DEFAULT_MAX_BYTES
+        )]
+        log_max_bytes: u64,
     },
 
     /// Check if the bridge server is running
     Status {
-        /// Bridge server port
-        #[arg(long, default_value = "19222")]
-        port: u16,
+        /// Bridge server port (default: the running bridge's port, else
+        /// browser.bridge_port, else 19222)
+        #[arg(long)]
+        port: Option<u16>,
     },
 
     /// Ping the extension through the bridge
     Ping {
-        /// Bridge server port
-        #[arg(long, default_value = "19222")]
-        port: u16,
+        /// Bridge server port (default: the running bridge's port, else
+        /// browser.bridge_port, else 19222)
+        #[arg(long)]
+        port: Option<u16>,
     },
 
     /// Download and install the Chrome extension from GitHub
@@ -417,20 +496,79 @@ pub enum ExtensionCommands {
         /// Force reinstall even if already installed at same version
         #[arg(long)]
         force: bool,
+
+        /// Exit non-zero (code 3) if the native messaging host cannot be registered
+        #[arg(long)]
+        strict: bool,
+
+        /// Leave the native messaging manifest alone (disables token auto-pairing)
+        #[arg(long, conflicts_with = "strict")]
+        no_native_host: bool,
     },
 
+    /// Check the installed extension files against their install record
+    Verify,
+
     /// Stop the running bridge server
     Stop {
-        /// Bridge server port
-        #[arg(long, default_value = "19222")]
-        port: u16,
+        /// Bridge server port (default: the running bridge's port, else
+        /// browser.bridge_port, else 19222)
+        #[arg(long)]
+        port: Option<u16>,
+        /// Kill the bridge immediately instead of asking it to shut down
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Stop the running bridge server and start a fresh one
+    ///
+    /// In isolated mode this also closes the isolated Chrome and launches a
+    /// new one, as the bridge owns its Chrome.
+    Restart {
+        /// Bridge server port (default: browser.bridge_port, else 19222)
+        #[arg(long)]
+        port: Option<u16>,
+        /// Use an isolated Chrome profile for the extension bridge
+        #[arg(long)]
+        isolated: bool,
+        /// Reject commands whose params exceed this many bytes (default 4 MB)
+        #[arg(long, value_name = "BYTES")]
+        max_params_bytes: Option<usize>,
+        /// Reuse the token saved by a previous run so a configured extension
+        /// stays paired (opt-in; ignored in isolated mode)
+        #[arg(long)]
+        stable_token: bool,
+        /// Replace the saved stable token with a fresh one
+        #[arg(long, requires = "stable_token")]
+        rotate_token: bool,
     },
 
+    /// Stream the extension service worker's console output (until Ctrl+C)
+    Logs {
+        /// CDP port of the Chrome running the extension (isolated mode uses 9333)
+        #[arg(long, default_value = "9333")]
+        cdp_port: u16,
+        /// Host of that CDP endpoint
+        #[arg(long, default_value = "127.0.0.1")]
+        cdp_host: String,
+    },
+
+    /// Reload the unpacked extension in the isolated Chrome and re-pair it
+    /// with the running isolated bridge
+    Reload,
+
     /// Print the extension install directory path
     Path,
 
+    /// Show where the native messaging host manifest is and whether it is valid
+    NativeStatus,
+
     /// Remove the installed extension
-    Uninstall,
+    Uninstall {
+        /// Keep the native messaging manifest in place
+        #[arg(long)]
+        keep_native_host: bool,
+    },
 }
 
 #[derive(Subcommand)]
@@ -447,9 +585,20 @@ pub enum SourcesCommands {
 
 #[derive(Subcommand)]
 pub enum ConfigCommands {
+    /// Write a default configuration file
+    Init {
+        /// Overwrite an existing config file
+        #[arg(long)]
+        force: bool,
+    },
+
     /// Show current configuration
     Show,
 
+    /// Show the configuration commands actually use: defaults, file,
+    /// ACTIONBOOK_* env overrides and global flags, with secrets redacted
+    Effective,
+
     /// Set a configuration value
     Set {
         /// Configuration key
@@ -472,6 +621,12 @@ pub enum ConfigCommands {
 
     /// Reset configuration (delete config file)
     Reset,
+
+    /// Validate configuration (e.g. profiles sharing a CDP port)
+    Validate,
+
+    /// Print a JSON Schema describing the configuration file
+    Schema,
 }
 
 #[derive(Subcommand)]
@@ -503,10 +658,61 @@ pub enum ProfileCommands {
 }
 
 impl Cli {
+    /// Config file in effect: `--config` if given, otherwise the default location.
+    pub fn config_file(&self) -> PathBuf {
+        self.config_path.clone().unwrap_or_else(Config::config_path)
+    }
+
+    /// `extension serve --log-file` path and rotation size, if given.
+    pub fn serve_log_file(&self) -> Option<(&std::path::Path, u64)> {
+        match &self.command {
+            Commands::Extension {
+                command:
+                    ExtensionCommands::Serve {
+                        log_file: Some(path),
+                        log_max_bytes,
+                        ..
+                    },
+            } => Some((path.as_path(), *log_max_bytes)),
+            _ => None,
+        }
+    }
+
+    /// Whether the command runs a bridge server (`extension serve` / `restart`).
+    fn serves_bridge(&self) -> bool {
+        matches!(
+            &self.command,
+            Commands::Extension {
+                command: ExtensionCommands::Serve { .. } | ExtensionCommands::Restart { .. },
+            }
+        )
+    }
+
+    /// Load configuration from [`Cli::config_file`].
+    pub fn load_config(&self) -> Result<Config> {
+        Config::load_from(&self.config_file())
+    }
+
+    /// Run the command, bounded by `--timeout` when given.
     pub async fn run(&self) -> Result<()> {
+        tracing::debug!("run_id {}", crate::output::run_id());
+        match self.timeout {
+            // A long-running bridge treats the timeout as a shutdown signal
+            // (`ServeOptions::deadline`), so its files and Chrome are cleaned up
+            Some(_) if self.serves_bridge() => self.dispatch().await,
+            Some(secs) => tokio::time::timeout(Duration::from_secs(secs), self.dispatch())
+                .await
+                .map_err(|_| ActionbookError::GlobalTimeout(secs))?,
+            None => self.dispatch().await,
+        }
+    }
+
+    async fn dispatch(&self) -> Result<()> {
         match &self.command {
             Commands::Browser { command } => commands::browser::run(self, command).await,
             Commands::Extension { command } => commands::extension::run(self, command).await,
+            Commands::Tabs => commands::tabs::run(self).await,
+            Commands::Reset { yes } => commands::reset::run(self, *yes).await,
             Commands::Search {
                 query,
                 domain,
@@ -524,7 +730,7 @@ impl Cli {
                 )
                 .await
             }
-            Commands::Get { area_id } => commands::get::run(self, area_id).await,
+            Commands::Get { area_id, format } => commands::get::run(self, area_id, *format).await,
             Commands::Sources { command } => commands::sources::run(self, command).await,
             Commands::Config { command } => commands::config::run(self, command).await,
             Commands::Profile { command } => commands::profile::run(self, command).await,
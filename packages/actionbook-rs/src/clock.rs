@@ -0,0 +1,99 @@
+//! Time source for timing-dependent state.
+//!
+//! Code that measures idle time or expiry windows reads the time through a
+//! [`Clock`] instead of calling `Instant::now()` directly, so tests can swap
+//! in a [`FakeClock`] and drive time forward without sleeping.
+
+use std::time::{Instant, SystemTime};
+
+/// Where the current time comes from
+pub trait Clock: Send + Sync {
+    /// Monotonic time, for measuring elapsed durations
+    fn now(&self) -> Instant;
+
+    /// Wall-clock time, for timestamps shown to users or written to disk
+    #[allow(dead_code)]
+    fn system_now(&self) -> SystemTime;
+}
+
+/// The real clock
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn system_now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+/// A clock that only moves when told to.
+#[cfg(test)]
+#[derive(Debug)]
+pub struct FakeClock {
+    now: std::sync::Mutex<(Instant, SystemTime)>,
+}
+
+#[cfg(test)]
+impl FakeClock {
+    /// Start at the real current time.
+    pub fn new() -> Self {
+        Self {
+            now: std::sync::Mutex::new((Instant::now(), SystemTime::now())),
+        }
+    }
+
+    /// Move both clocks forward.
+    pub fn advance(&self, by: std::time::Duration) {
+        let mut now = self.now.lock().unwrap();
+        now.0 += by;
+        now.1 += by;
+    }
+
+    /// Set the wall clock alone, as an NTP correction or a manual change
+    /// would; the monotonic clock is unaffected.
+    pub fn set_system_now(&self, to: SystemTime) {
+        self.now.lock().unwrap().1 = to;
+    }
+}
+
+#[cfg(test)]
+impl Default for FakeClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+impl Clock for FakeClock {
+    fn now(&self) -> Instant {
+        self.now.lock().unwrap().0
+    }
+
+    fn system_now(&self) -> SystemTime {
+        self.now.lock().unwrap().1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn fake_clock_moves_only_when_advanced() {
+        let clock = FakeClock::new();
+        let (start, wall) = (clock.now(), clock.system_now());
+        assert_eq!(clock.now(), start);
+
+        clock.advance(Duration::from_secs(90));
+        assert_eq!(clock.now() - start, Duration::from_secs(90));
+        assert_eq!(clock.system_now().duration_since(wall).unwrap(), Duration::from_secs(90));
+
+        clock.set_system_now(wall - Duration::from_secs(3600));
+        assert_eq!(clock.now() - start, Duration::from_secs(90));
+    }
+}
@@ -7,12 +7,12 @@ use colored::Colorize;
 use futures::StreamExt;
 use tokio::time::timeout;
 
-#[cfg(feature = "stealth")]
-use crate::browser::apply_stealth_to_page;
 use crate::browser::{
-    build_stealth_profile, discover_all_browsers, extension_bridge, stealth_status,
-    SessionManager, SessionStatus, StealthConfig,
+    cdp_http, discover_all_browsers_preferring, extension_bridge, BrowserChannel, SessionManager,
+    SessionStatus,
 };
+#[cfg(feature = "stealth")]
+use crate::browser::{apply_stealth_to_page, build_stealth_profile, stealth_status, StealthConfig};
 use crate::cli::{BrowserCommands, Cli, CookiesCommands};
 use crate::config::Config;
 use crate::error::{ActionbookError, Result};
@@ -24,25 +24,55 @@ async fn extension_send(
     method: &str,
     params: serde_json::Value,
 ) -> Result<serde_json::Value> {
-    let result = extension_bridge::send_command(cli.extension_port, method, params.clone()).await;
+    let (port, token) = bridge_target(cli).await?;
+    let result = bridge_send(port, token.as_deref(), method, params.clone()).await;
 
     // Auto-attach: if a CDP method fails because no tab is attached, attach the active tab and retry
-    if let Err(ActionbookError::ExtensionError(ref msg)) = result {
+    if let Err(
+        ActionbookError::ExtensionRpc { message: ref msg, .. }
+        | ActionbookError::ExtensionError(ref msg),
+    ) = result
+    {
         if msg.contains("No tab attached") && !method.starts_with("Extension.") {
             tracing::debug!("Auto-attaching active tab for {}", method);
-            extension_bridge::send_command(
-                cli.extension_port,
+            bridge_send(
+                port,
+                token.as_deref(),
                 "Extension.attachActiveTab",
                 serde_json::json!({}),
             )
             .await?;
-            return extension_bridge::send_command(cli.extension_port, method, params).await;
+            return bridge_send(port, token.as_deref(), method, params).await;
         }
     }
 
     result
 }
 
+/// Port (and token, when discovered) of the bridge extension-mode commands
+/// go to: `--extension-port` if given, otherwise whichever bridge
+/// [`extension_bridge::discover_bridge`] finds running.
+pub(crate) async fn bridge_target(cli: &Cli) -> Result<(u16, Option<String>)> {
+    if let Some(port) = cli.extension_port {
+        return Ok((port, None));
+    }
+    let endpoint = extension_bridge::discover_bridge().await?;
+    tracing::debug!("Using {:?} bridge on port {}", endpoint.mode, endpoint.port);
+    Ok((endpoint.port, Some(endpoint.token)))
+}
+
+async fn bridge_send(
+    port: u16,
+    token: Option<&str>,
+    method: &str,
+    params: serde_json::Value,
+) -> Result<serde_json::Value> {
+    match token {
+        Some(token) => extension_bridge::send_command_with_token(port, method, params, token).await,
+        None => extension_bridge::send_command(port, method, params).await,
+    }
+}
+
 /// Evaluate JS via the extension bridge and return the result value
 async fn extension_eval(cli: &Cli, expression: &str) -> Result<serde_json::Value> {
     let result = extension_send(
@@ -148,6 +178,7 @@ fn js_resolve_selector(selector: &str) -> String {
 }
 
 /// Create a SessionManager with appropriate stealth configuration from CLI flags
+#[cfg(feature = "stealth")]
 fn create_session_manager(cli: &Cli, config: &Config) -> SessionManager {
     if cli.stealth {
         let stealth_profile =
@@ -165,6 +196,12 @@ fn create_session_manager(cli: &Cli, config: &Config) -> SessionManager {
     }
 }
 
+/// Without the `stealth` feature `--stealth` is rejected up front in [`run`].
+#[cfg(not(feature = "stealth"))]
+fn create_session_manager(_cli: &Cli, config: &Config) -> SessionManager {
+    SessionManager::new(config.clone())
+}
+
 /// Resolve a CDP endpoint string (port number or ws:// URL) into a (port, ws_url) pair.
 /// When given a numeric port, queries `http://127.0.0.1:{port}/json/version` to discover
 /// the current browser WebSocket URL.
@@ -334,17 +371,27 @@ pub async fn run(cli: &Cli, command: &BrowserCommands) -> Result<()> {
         ));
     }
 
-    let config = Config::load()?;
+    #[cfg(not(feature = "stealth"))]
+    if cli.stealth {
+        return Err(ActionbookError::Other(
+            "--stealth is not available: actionbook was built without stealth support. \
+             Rebuild with `--features stealth` or remove --stealth."
+                .to_string(),
+        ));
+    }
+
+    let config = cli.load_config()?;
 
     // When --cdp is set, resolve it to a fresh WebSocket URL and persist it
     // as the active session *before* any command runs. Skip for `connect`
-    // which has its own CDP resolution logic.
-    if !matches!(command, BrowserCommands::Connect { .. }) {
+    // which has its own CDP resolution logic, and `version`, which only reads.
+    if !matches!(command, BrowserCommands::Connect { .. } | BrowserCommands::Version) {
         ensure_cdp_override(cli, &config).await?;
     }
 
     match command {
         BrowserCommands::Status => status(cli, &config).await,
+        BrowserCommands::Version => version(cli, &config).await,
         BrowserCommands::Open { url } => open(cli, &config, url).await,
         BrowserCommands::Goto { url, timeout: t } => goto(cli, &config, url, *t).await,
         BrowserCommands::Back => back(cli, &config).await,
@@ -394,7 +441,8 @@ pub async fn run(cli: &Cli, command: &BrowserCommands) -> Result<()> {
 async fn status(cli: &Cli, config: &Config) -> Result<()> {
     // Show API key status
     println!("{}", "API Key:".bold());
-    let api_key = cli.api_key.as_deref().or(config.api.api_key.as_deref());
+    let configured_key = config.api.resolve_api_key().ok().flatten();
+    let api_key = cli.api_key.as_deref().or(configured_key.as_deref());
     match api_key {
         Some(key) if key.len() > 8 => {
             let masked = format!("{}...{}", &key[..4], &key[key.len() - 4..]);
@@ -414,9 +462,9 @@ async fn status(cli: &Cli, config: &Config) -> Result<()> {
 
     // Show stealth mode status
     println!("{}", "Stealth Mode:".bold());
-    let stealth = stealth_status();
-    if stealth.starts_with("enabled") {
-        println!("  {} {}", "✓".green(), stealth);
+    #[cfg(feature = "stealth")]
+    {
+        println!("  {} {}", "✓".green(), stealth_status());
         if cli.stealth {
             let profile =
                 build_stealth_profile(cli.stealth_os.as_deref(), cli.stealth_gpu.as_deref());
@@ -425,22 +473,27 @@ async fn status(cli: &Cli, config: &Config) -> Result<()> {
             println!("  {} Chrome: v{}", "  ".dimmed(), profile.chrome_version);
             println!("  {} Locale: {}", "  ".dimmed(), profile.locale);
         }
-    } else {
-        println!("  {} {}", "○".dimmed(), stealth);
     }
+    #[cfg(not(feature = "stealth"))]
+    println!("  {} disabled (built without stealth support)", "○".dimmed());
     println!();
 
     // Show detected browsers
     println!("{}", "Detected Browsers:".bold());
-    let browsers = discover_all_browsers();
+    let browsers = discover_all_browsers_preferring(&config.browser.channel_preference);
     if browsers.is_empty() {
         println!("  {} No browsers found", "!".yellow());
     } else {
         for browser in browsers {
+            let channel = match browser.channel {
+                BrowserChannel::Stable => String::new(),
+                other => format!(" {}", other.name()),
+            };
             println!(
-                "  {} {} {}",
+                "  {} {}{} {}",
                 "✓".green(),
                 browser.browser_type.name(),
+                channel,
                 browser
                     .version
                     .map(|v| format!("(v{})", v))
@@ -1686,11 +1739,7 @@ async fn eval(cli: &Cli, config: &Config, code: &str) -> Result<()> {
             .await?
     };
 
-    if cli.json {
-        println!("{}", serde_json::to_string_pretty(&value)?);
-    } else {
-        println!("{}", serde_json::to_string_pretty(&value)?);
-    }
+    println!("{}", serde_json::to_string_pretty(&value)?);
 
     Ok(())
 }
@@ -2938,6 +2987,45 @@ async fn restart(cli: &Cli, config: &Config) -> Result<()> {
     Ok(())
 }
 
+async fn version(cli: &Cli, config: &Config) -> Result<()> {
+    let profile = config.get_profile(effective_profile_name(cli, config))?;
+    let cdp_port = match cli.cdp.as_deref() {
+        Some(cdp) => cdp.trim().parse::<u16>().map_err(|_| {
+            ActionbookError::Other(format!(
+                "browser version expects --cdp to be a port number, got '{}'",
+                cdp
+            ))
+        })?,
+        None => profile.cdp_port,
+    };
+    let version = cdp_http::get_version(&profile.cdp_host, cdp_port).await?;
+
+    if cli.json {
+        println!(
+            "{}",
+            serde_json::json!({
+                "success": true,
+                "cdp_host": profile.cdp_host,
+                "cdp_port": cdp_port,
+                "browser": version.browser,
+                "protocol_version": version.protocol_version,
+                "user_agent": version.user_agent,
+                "v8_version": version.v8_version,
+                "webkit_version": version.webkit_version,
+                "web_socket_debugger_url": version.web_socket_debugger_url
+            })
+        );
+    } else {
+        println!("{} CDP at {}:{}", "✓".green(), profile.cdp_host, cdp_port);
+        println!("  Browser: {}", version.browser);
+        println!("  Protocol-Version: {}", version.protocol_version);
+        println!("  User-Agent: {}", version.user_agent);
+        println!("  WebSocket URL: {}", version.web_socket_debugger_url);
+    }
+
+    Ok(())
+}
+
 async fn connect(cli: &Cli, config: &Config, endpoint: &str) -> Result<()> {
     let profile_name = effective_profile_name(cli, config);
     let (cdp_port, cdp_url) = resolve_cdp_endpoint(endpoint).await?;
@@ -2984,8 +3072,10 @@ mod tests {
             api_key: None,
             json: false,
             extension: false,
-            extension_port: 19222,
+            extension_port: None,
             verbose: false,
+            config_path: None,
+            timeout: None,
             command: Commands::Browser { command },
         }
     }
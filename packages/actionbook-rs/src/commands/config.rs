@@ -1,28 +1,84 @@
 use colored::Colorize;
 use dialoguer::Confirm;
 
+use crate::browser::extension_installer::ReleaseSource;
 use crate::cli::{Cli, ConfigCommands};
 use crate::config::Config;
 use crate::error::{ActionbookError, Result};
 
 pub async fn run(cli: &Cli, command: &ConfigCommands) -> Result<()> {
     match command {
+        ConfigCommands::Init { force } => init(cli, *force).await,
         ConfigCommands::Show => show(cli).await,
+        ConfigCommands::Effective => effective(cli).await,
         ConfigCommands::Set { key, value } => set(cli, key, value).await,
         ConfigCommands::Get { key } => get(cli, key).await,
         ConfigCommands::Edit => edit(cli).await,
         ConfigCommands::Path => path(cli).await,
         ConfigCommands::Reset => reset(cli).await,
+        ConfigCommands::Validate => validate(cli).await,
+        ConfigCommands::Schema => schema().await,
     }
 }
 
+async fn init(cli: &Cli, force: bool) -> Result<()> {
+    let path = cli.config_file();
+    let existed = path.exists();
+
+    if existed && !force {
+        return Err(ActionbookError::ConfigError(format!(
+            "Config file already exists at {}. Use --force to overwrite",
+            path.display()
+        )));
+    }
+
+    Config::default().save_to(&path)?;
+
+    if cli.json {
+        println!(
+            "{}",
+            serde_json::json!({
+                "status": if existed { "overwritten" } else { "created" },
+                "path": path.display().to_string()
+            })
+        );
+    } else {
+        println!(
+            "{} Default config written: {}",
+            "✓".green(),
+            path.display().to_string().dimmed()
+        );
+    }
+
+    Ok(())
+}
+
 async fn show(cli: &Cli) -> Result<()> {
-    let config = Config::load()?;
+    let config = cli.load_config()?;
+    print_config(cli, &config)
+}
+
+async fn effective(cli: &Cli) -> Result<()> {
+    let mut config = cli.load_config()?;
+
+    // The global flags commands apply on top of the loaded configuration
+    if let Some(ref key) = cli.api_key {
+        config.api.api_key = Some(key.clone());
+    }
+    if let Some(profile) = cli.profile.as_deref().map(str::trim).filter(|p| !p.is_empty()) {
+        config.browser.default_profile = profile.to_string();
+    }
+    let level = config.log.level_filter(cli.verbose);
+    config.log.level = Some(level.to_string().to_lowercase());
+
+    print_config(cli, &config.redacted())
+}
 
+fn print_config(cli: &Cli, config: &Config) -> Result<()> {
     if cli.json {
-        println!("{}", serde_json::to_string_pretty(&config)?);
+        println!("{}", serde_json::to_string_pretty(config)?);
     } else {
-        let toml_str = toml::to_string_pretty(&config)
+        let toml_str = toml::to_string_pretty(config)
             .map_err(|e| ActionbookError::ConfigError(e.to_string()))?;
         println!("{}", toml_str);
     }
@@ -30,13 +86,15 @@ async fn show(cli: &Cli) -> Result<()> {
     Ok(())
 }
 
-async fn set(_cli: &Cli, key: &str, value: &str) -> Result<()> {
-    let mut config = Config::load()?;
+async fn set(cli: &Cli, key: &str, value: &str) -> Result<()> {
+    let mut config = cli.load_config()?;
 
     // Simple key-value setting (expand as needed)
     match key {
         "api.base_url" => config.api.base_url = value.to_string(),
         "api.api_key" => config.api.api_key = Some(value.to_string()),
+        "api.key_env" => config.api.key_env = Some(value.to_string()),
+        "api.key_file" => config.api.key_file = Some(value.into()),
         "browser.executable" => config.browser.executable = Some(value.to_string()),
         "browser.default_profile" => config.browser.default_profile = value.to_string(),
         "browser.headless" => {
@@ -44,6 +102,55 @@ async fn set(_cli: &Cli, key: &str, value: &str) -> Result<()> {
                 ActionbookError::ConfigError("headless must be true or false".to_string())
             })?
         }
+        "browser.extension_dir" => {
+            if !std::path::Path::new(value).is_absolute() {
+                return Err(ActionbookError::ConfigError(
+                    "extension_dir must be an absolute path".to_string(),
+                ));
+            }
+            config.browser.extension_dir = Some(value.to_string())
+        }
+        "browser.extension_release_base" => {
+            ReleaseSource::new(Some(value), None)
+                .map_err(|e| ActionbookError::ConfigError(e.to_string()))?;
+            config.browser.extension_release_base = Some(value.to_string())
+        }
+        "browser.extension_release_repo" => {
+            ReleaseSource::new(None, Some(value))
+                .map_err(|e| ActionbookError::ConfigError(e.to_string()))?;
+            config.browser.extension_release_repo = Some(value.to_string())
+        }
+        "browser.bridge_ready_timeout_ms" => {
+            config.browser.bridge_ready_timeout_ms =
+                value.parse().ok().filter(|ms| *ms > 0).ok_or_else(|| {
+                    ActionbookError::ConfigError(
+                        "bridge_ready_timeout_ms must be a positive number of milliseconds"
+                            .to_string(),
+                    )
+                })?
+        }
+        "browser.extension_connect_hint_secs" => {
+            config.browser.extension_connect_hint_secs = value.parse().map_err(|_| {
+                ActionbookError::ConfigError(
+                    "extension_connect_hint_secs must be a number of seconds (0 disables)"
+                        .to_string(),
+                )
+            })?
+        }
+        "browser.bridge_port" => {
+            config.browser.bridge_port = Some(value.parse().map_err(|_| {
+                ActionbookError::ConfigError("bridge_port must be a port number".to_string())
+            })?)
+        }
+        "log.level" => {
+            if value.parse::<tracing::level_filters::LevelFilter>().is_err() {
+                return Err(ActionbookError::ConfigError(
+                    "log.level must be one of trace, debug, info, warn, error, off".to_string(),
+                ));
+            }
+            config.log.level = Some(value.to_ascii_lowercase())
+        }
+        "log.dir" => config.log.dir = Some(value.to_string()),
         _ => {
             return Err(ActionbookError::ConfigError(format!(
                 "Unknown config key: {}",
@@ -52,21 +159,39 @@ async fn set(_cli: &Cli, key: &str, value: &str) -> Result<()> {
         }
     }
 
-    config.save()?;
+    config.save_to(&cli.config_file())?;
     println!("{} Set {} = {}", "✓".green(), key, value);
 
     Ok(())
 }
 
 async fn get(cli: &Cli, key: &str) -> Result<()> {
-    let config = Config::load()?;
+    let config = cli.load_config()?;
 
     let value = match key {
         "api.base_url" => Some(config.api.base_url.clone()),
         "api.api_key" => config.api.api_key.clone(),
+        "api.key_env" => config.api.key_env.clone(),
+        "api.key_file" => config
+            .api
+            .key_file
+            .as_ref()
+            .map(|p| p.display().to_string()),
         "browser.executable" => config.browser.executable.clone(),
         "browser.default_profile" => Some(config.browser.default_profile.clone()),
         "browser.headless" => Some(config.browser.headless.to_string()),
+        "browser.extension_dir" => config.browser.extension_dir.clone(),
+        "browser.extension_release_base" => config.browser.extension_release_base.clone(),
+        "browser.extension_release_repo" => config.browser.extension_release_repo.clone(),
+        "browser.bridge_ready_timeout_ms" => {
+            Some(config.browser.bridge_ready_timeout_ms.to_string())
+        }
+        "browser.extension_connect_hint_secs" => {
+            Some(config.browser.extension_connect_hint_secs.to_string())
+        }
+        "browser.bridge_port" => config.browser.bridge_port.map(|p| p.to_string()),
+        "log.level" => config.log.level.clone(),
+        "log.dir" => config.log.dir.clone(),
         _ => {
             return Err(ActionbookError::ConfigError(format!(
                 "Unknown config key: {}",
@@ -93,13 +218,13 @@ async fn get(cli: &Cli, key: &str) -> Result<()> {
     Ok(())
 }
 
-async fn edit(_cli: &Cli) -> Result<()> {
-    let path = Config::config_path();
+async fn edit(cli: &Cli) -> Result<()> {
+    let path = cli.config_file();
 
     // Ensure config file exists
     if !path.exists() {
         let config = Config::default();
-        config.save()?;
+        config.save_to(&path)?;
     }
 
     // Get editor from environment
@@ -116,7 +241,7 @@ async fn edit(_cli: &Cli) -> Result<()> {
 }
 
 async fn reset(cli: &Cli) -> Result<()> {
-    let path = Config::config_path();
+    let path = cli.config_file();
 
     if !path.exists() {
         if cli.json {
@@ -162,7 +287,7 @@ async fn reset(cli: &Cli) -> Result<()> {
 }
 
 async fn path(cli: &Cli) -> Result<()> {
-    let path = Config::config_path();
+    let path = cli.config_file();
 
     if cli.json {
         println!(
@@ -177,3 +302,48 @@ async fn path(cli: &Cli) -> Result<()> {
 
     Ok(())
 }
+
+async fn schema() -> Result<()> {
+    // Always JSON, so the output can be piped straight into an editor setting
+    println!("{}", serde_json::to_string_pretty(&Config::json_schema())?);
+    Ok(())
+}
+
+async fn validate(cli: &Cli) -> Result<()> {
+    let config = cli.load_config()?;
+    let result = config.validate();
+
+    if cli.json {
+        let duplicates: Vec<serde_json::Value> = config
+            .duplicate_cdp_ports()
+            .into_iter()
+            .map(|(port, profiles)| serde_json::json!({ "cdp_port": port, "profiles": profiles }))
+            .collect();
+        let invalid_endpoints: Vec<serde_json::Value> = config
+            .invalid_cdp_endpoints()
+            .into_iter()
+            .map(|(profile, reason)| serde_json::json!({ "profile": profile, "reason": reason }))
+            .collect();
+        println!(
+            "{}",
+            serde_json::json!({
+                "valid": result.is_ok(),
+                "error": result.as_ref().err().map(|e| e.to_string()),
+                "duplicate_cdp_ports": duplicates,
+                "invalid_cdp_endpoints": invalid_endpoints,
+                "port_warnings": config.port_warnings(),
+            })
+        );
+        // The failure is part of the JSON result; only the exit code remains
+        return result.map_err(|e| ActionbookError::AlreadyReported(Box::new(e)));
+    } else {
+        for warning in config.port_warnings() {
+            println!("{} {}", "!".yellow(), warning);
+        }
+        if result.is_ok() {
+            println!("{} Configuration is valid", "✓".green());
+        }
+    }
+
+    result
+}
@@ -1,51 +1,226 @@
 use colored::Colorize;
 
+use crate::browser::cdp_http;
 use crate::browser::extension_installer;
-use crate::browser::extension_bridge;
+use crate::browser::isolated_extension;
+use crate::browser::extension_bridge::{self, status_println};
 use crate::browser::native_messaging;
+use crate::browser::process::{self, TerminationOutcome};
 use crate::cli::{Cli, ExtensionCommands};
-use crate::error::Result;
+use crate::config::{check_bridge_ports, Config, DEFAULT_CDP_HOST};
+use crate::error::{ActionbookError, Result};
+use crate::output;
+
+/// How long `extension restart` waits for the old bridge to release its port.
+const RESTART_RELEASE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
 
 pub async fn run(cli: &Cli, command: &ExtensionCommands) -> Result<()> {
+    let config = cli.load_config();
+    let configured_dir = config
+        .as_ref()
+        .ok()
+        .and_then(|c| c.browser.extension_dir.clone());
+    let configured_dir = configured_dir.as_deref();
+    let configured_port = config.as_ref().ok().and_then(|c| c.browser.bridge_port);
+
     match command {
-        ExtensionCommands::Serve { port, isolated } => {
-            let config = crate::config::Config::load()?;
-            let use_isolated = *isolated || config.browser.extension_isolated_profile;
-            if use_isolated {
-                crate::browser::isolated_extension::serve_isolated(&config, *port).await
+        ExtensionCommands::Serve {
+            port,
+            isolated,
+            max_params_bytes,
+            stable_token,
+            rotate_token,
+            token_stdin,
+            live,
+            // Opened in `main` alongside the tracing setup
+            log_file: _,
+            log_max_bytes: _,
+        } => {
+            let token = if *token_stdin {
+                let supplied = extension_bridge::read_supplied_token(std::io::stdin().lock())?;
+                TokenChoice::Supplied(supplied)
             } else {
-                serve(cli, *port).await
+                TokenChoice::new(*stable_token, *rotate_token)
+            };
+            let config = config?;
+            let port = port.unwrap_or(config.browser.bridge_port());
+            let launch = Launch::Serve { live: *live };
+            start(cli, config, port, *isolated, *max_params_bytes, token, launch).await
+        }
+        ExtensionCommands::Restart {
+            port,
+            isolated,
+            max_params_bytes,
+            stable_token,
+            rotate_token,
+        } => {
+            let config = config?;
+            let port = port.unwrap_or(config.browser.bridge_port());
+            // Under --json the outcome of the stop is folded into the new
+            // bridge's ready event instead of printed on its own
+            let stopped = stop_bridge(cli.json, port, false).await;
+            if stopped["status"] == "error" {
+                if cli.json {
+                    output::print_json(stopped.clone());
+                }
+                let error = stopped["error"].as_str().unwrap_or_default().to_string();
+                return Err(ActionbookError::AlreadyReported(Box::new(
+                    ActionbookError::ExtensionError(error),
+                )));
             }
+            if !extension_bridge::wait_for_port_release(port, RESTART_RELEASE_TIMEOUT).await {
+                return Err(ActionbookError::ExtensionError(format!(
+                    "Port {} is still in use after stopping the bridge; cannot restart",
+                    port
+                )));
+            }
+            let replaced =
+                (stopped["status"] == "stopped").then(|| extension_bridge::StoppedBridge {
+                    pid: stopped["pid"].as_u64().unwrap_or_default() as u32,
+                    forced: stopped["forced"].as_bool().unwrap_or_default(),
+                });
+            let token = TokenChoice::new(*stable_token, *rotate_token);
+            let launch = Launch::Restart { replaced };
+            start(cli, config, port, *isolated, *max_params_bytes, token, launch).await
+        }
+        ExtensionCommands::Status { port } => {
+            status(cli, extension_bridge::resolve_bridge_port(*port, configured_port).await).await
+        }
+        ExtensionCommands::Ping { port } => {
+            let port = extension_bridge::resolve_bridge_port(*port, configured_port).await;
+            ping(cli, port, configured_dir).await
+        }
+        ExtensionCommands::Stop { port, force } => {
+            stop(
+                cli,
+                extension_bridge::resolve_bridge_port(*port, configured_port).await,
+                *force,
+            )
+            .await
+        }
+        ExtensionCommands::Install {
+            force,
+            strict,
+            no_native_host,
+        } => {
+            let browser = config.as_ref().ok().map(|c| &c.browser);
+            let source = extension_installer::ReleaseSource::new(
+                browser.and_then(|b| b.extension_release_base.as_deref()),
+                browser.and_then(|b| b.extension_release_repo.as_deref()),
+            )?;
+            install(cli, configured_dir, &source, *force, *strict, *no_native_host).await
+        }
+        ExtensionCommands::Logs { cdp_host, cdp_port } => {
+            logs(cli, cdp_host, *cdp_port, configured_dir).await
+        }
+        ExtensionCommands::Reload => reload(cli, config.as_ref().ok()).await,
+        ExtensionCommands::Verify => verify(cli, configured_dir).await,
+        ExtensionCommands::Path => path(cli, configured_dir).await,
+        ExtensionCommands::NativeStatus => native_status(cli).await,
+        ExtensionCommands::Uninstall { keep_native_host } => {
+            uninstall(cli, configured_dir, *keep_native_host).await
         }
-        ExtensionCommands::Status { port } => status(cli, *port).await,
-        ExtensionCommands::Ping { port } => ping(cli, *port).await,
-        ExtensionCommands::Stop { port } => stop(cli, *port).await,
-        ExtensionCommands::Install { force } => install(cli, *force).await,
-        ExtensionCommands::Path => path(cli).await,
-        ExtensionCommands::Uninstall => uninstall(cli).await,
     }
 }
 
-async fn serve(_cli: &Cli, port: u16) -> Result<()> {
-    // Clean up stale standard-mode bridge files from previous ungraceful shutdowns.
-    extension_bridge::delete_port_file().await;
-    extension_bridge::delete_token_file().await;
+/// Where a standard-mode bridge gets its session token
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TokenChoice {
+    /// A fresh token for this run (the default)
+    Fresh,
+    /// The stable token saved by a previous run, created if missing
+    Stable,
+    /// A fresh token that also replaces the saved stable token
+    RotateStable,
+    /// A token read from stdin (`--token-stdin`), never printed
+    Supplied(String),
+}
 
-    // Clean up stale isolated-mode files — but only if the isolated bridge
-    // process is confirmed dead. This prevents `send_command` from picking up
-    // an outdated isolated token while preserving files of a running bridge.
-    let isolated_alive = extension_bridge::read_isolated_pid_file()
-        .await
-        .is_some_and(|(pid, _port)| extension_bridge::is_pid_alive(pid));
-    if !isolated_alive {
-        extension_bridge::delete_isolated_port_file().await;
-        extension_bridge::delete_isolated_token_file().await;
-        extension_bridge::delete_isolated_pid_file().await;
+impl TokenChoice {
+    fn new(stable_token: bool, rotate_token: bool) -> Self {
+        match (stable_token, rotate_token) {
+            (false, _) => TokenChoice::Fresh,
+            (true, false) => TokenChoice::Stable,
+            (true, true) => TokenChoice::RotateStable,
+        }
     }
+}
 
-    let extension_path = if extension_installer::is_installed() {
-        let dir = extension_installer::extension_dir()?;
-        let version = extension_installer::installed_version()
+/// Which command is bringing up the bridge
+enum Launch {
+    /// `serve`, with or without the live activity line
+    Serve { live: bool },
+    /// `restart`, after stopping the bridge it names (if one was running)
+    Restart { replaced: Option<extension_bridge::StoppedBridge> },
+}
+
+/// Start the bridge in standard or isolated mode, shared by `serve` and
+/// `restart`. Isolated mode reuses a still-running isolated Chrome; after a
+/// `restart` there is none, as stopping the isolated bridge closes its Chrome.
+async fn start(
+    cli: &Cli,
+    config: Config,
+    port: u16,
+    isolated: bool,
+    max_params_bytes: Option<usize>,
+    token: TokenChoice,
+    launch: Launch,
+) -> Result<()> {
+    let (live, replaced) = match launch {
+        Launch::Serve { live } => (live, None),
+        Launch::Restart { replaced } => (false, replaced),
+    };
+    let use_isolated = isolated || config.browser.extension_isolated_profile;
+    // Isolated mode also drives its own Chrome, whose CDP port must not clash
+    let cdp_port = use_isolated.then_some(isolated_extension::ISOLATED_CDP_PORT);
+    for warning in check_bridge_ports(port, cdp_port).map_err(ActionbookError::ConfigError)? {
+        output::print_warning(cli.json, warning);
+    }
+    let options = extension_bridge::ServeOptions {
+        isolated: use_isolated,
+        json: cli.json,
+        max_params_bytes,
+        // Isolated mode injects a fresh token into its own profile every run
+        stable_token: !use_isolated
+            && matches!(token, TokenChoice::Stable | TokenChoice::RotateStable),
+        token_supplied: matches!(token, TokenChoice::Supplied(_)),
+        // Isolated mode writes its PID file itself before the bridge starts
+        pid_file: !use_isolated,
+        report_extension: true,
+        connect_hint_after: (config.browser.extension_connect_hint_secs > 0)
+            .then(|| std::time::Duration::from_secs(config.browser.extension_connect_hint_secs)),
+        replaced,
+        live,
+        // Serve and restart are exempt from the outer `--timeout` wrapper and
+        // shut down through their own cleanup instead
+        deadline: cli.timeout.map(std::time::Duration::from_secs),
+    };
+    if use_isolated {
+        let supplied = match token {
+            TokenChoice::Supplied(token) => Some(token),
+            _ => None,
+        };
+        isolated_extension::serve_isolated(&config, port, supplied, options).await
+    } else {
+        serve(port, config.browser.extension_dir.as_deref(), options, token).await
+    }
+}
+
+async fn serve(
+    port: u16,
+    configured_dir: Option<&str>,
+    options: extension_bridge::ServeOptions,
+    token_choice: TokenChoice,
+) -> Result<()> {
+    let json = options.json;
+
+    // Clean up bridge files from previous ungraceful shutdowns (both modes),
+    // leaving any live bridge's files in place.
+    extension_bridge::reconcile_stale_files(extension_bridge::BridgeMode::Standard, port).await;
+
+    let dir = extension_installer::extension_dir(configured_dir)?;
+    let extension_path = if extension_installer::is_installed(&dir) {
+        let version = extension_installer::installed_version(&dir)
             .map(|v| format!(" (v{})", v))
             .unwrap_or_default();
         format!("{}{}", dir.display(), version)
@@ -53,39 +228,47 @@ async fn serve(_cli: &Cli, port: u16) -> Result<()> {
         "(not installed - run 'actionbook extension install')".dimmed().to_string()
     };
 
-    // Generate session token
-    let token = extension_bridge::generate_token();
+    let token_supplied = matches!(token_choice, TokenChoice::Supplied(_));
+    let token = match token_choice {
+        TokenChoice::Fresh => extension_bridge::generate_token(),
+        TokenChoice::Supplied(token) => token,
+        TokenChoice::Stable => extension_bridge::load_or_create_stable_token(false).await?,
+        TokenChoice::RotateStable => extension_bridge::load_or_create_stable_token(true).await?,
+    };
 
     // Write token file for CLI auto-read
     if let Err(e) = extension_bridge::write_token_file(&token).await {
-        eprintln!(
-            "  {} Failed to write token file: {}",
-            "!".yellow(),
-            e
-        );
+        output::print_warning(json, format!("Failed to write token file: {}", e));
     }
 
-    println!();
-    println!("  {}", "Actionbook Extension Bridge".bold());
-    println!("  {}", "─".repeat(40).dimmed());
-    println!();
-    println!(
-        "  {}  WebSocket server on ws://127.0.0.1:{}",
-        "◆".cyan(),
-        port
-    );
-    println!(
-        "  {}  Extension: {}",
-        "◆".cyan(),
-        extension_path
-    );
-    println!();
-    println!(
+    status_println!(json);
+    status_println!(json, "  {}", "Actionbook Extension Bridge".bold());
+    status_println!(json, "  {}", "─".repeat(40).dimmed());
+    status_println!(json);
+    if port == 0 {
+        status_println!(
+            json,
+            "  {}  WebSocket server on a port chosen by the OS",
+            "◆".cyan()
+        );
+    } else {
+        status_println!(
+            json,
+            "  {}  WebSocket server on ws://127.0.0.1:{}",
+            "◆".cyan(),
+            port
+        );
+    }
+    status_println!(json, "  {}  Extension: {}", "◆".cyan(), extension_path);
+    status_println!(json);
+    status_println!(
+        json,
         "  {}  Session token: {}",
         "🔑".to_string().as_str(),
-        token.bold()
+        extension_bridge::banner_token(&token, token_supplied).bold()
     );
-    println!(
+    status_println!(
+        json,
         "  {}  Token file: {}",
         "◆".cyan(),
         extension_bridge::token_file_path()
@@ -93,23 +276,25 @@ async fn serve(_cli: &Cli, port: u16) -> Result<()> {
             .unwrap_or_else(|_| "unknown".to_string())
             .dimmed()
     );
-    println!();
-    println!("  {}  Configure the extension with this token", "ℹ".dimmed());
-    println!("  {}  Token expires after 30min of inactivity", "ℹ".dimmed());
-    println!("  {}  Press Ctrl+C to stop", "ℹ".dimmed());
-    println!();
-
-    // Write PID file so `extension stop` can find this process
-    if let Err(e) = extension_bridge::write_pid_file(port).await {
-        eprintln!(
-            "  {} Failed to write PID file: {}",
-            "!".yellow(),
-            e
+    status_println!(json);
+    if !token_supplied {
+        status_println!(
+            json,
+            "  {}  Configure the extension with this token",
+            "ℹ".dimmed()
         );
     }
+    status_println!(
+        json,
+        "  {}  Token expires after 30min of inactivity",
+        "ℹ".dimmed()
+    );
+    status_println!(json, "  {}  Press Ctrl+C to stop", "ℹ".dimmed());
+    status_println!(json);
 
-    // Run the bridge server, cleaning up token file on shutdown
-    let result = extension_bridge::serve(port, token).await;
+    // Run the bridge server (which writes the PID file once it has bound its
+    // port, so `extension stop` can find it), cleaning up token file on shutdown
+    let result = extension_bridge::serve_with_options(port, token, options).await;
 
     // Cleanup token + PID files on exit
     extension_bridge::delete_token_file().await;
@@ -143,34 +328,220 @@ async fn status(_cli: &Cli, port: u16) -> Result<()> {
     Ok(())
 }
 
-async fn ping(_cli: &Cli, port: u16) -> Result<()> {
-    let start = std::time::Instant::now();
-    let result = extension_bridge::send_command(
-        port,
-        "Extension.ping",
-        serde_json::json!({}),
+async fn logs(
+    cli: &Cli,
+    cdp_host: &str,
+    cdp_port: u16,
+    configured_dir: Option<&str>,
+) -> Result<()> {
+    let json = cli.json;
+    let ext_dir = extension_installer::extension_dir(configured_dir)?;
+    status_println!(
+        json,
+        "  {}  Streaming extension console from CDP {}:{} (Ctrl+C to stop)",
+        "◆".cyan(),
+        cdp_host,
+        cdp_port
+    );
+
+    let stream = cdp_http::stream_extension_logs(cdp_host, cdp_port, &ext_dir, |entry| {
+        if json {
+            output::print_json(serde_json::json!({ "level": entry.level, "text": entry.text }));
+            return;
+        }
+        let level = match entry.level.as_str() {
+            "error" | "assert" => entry.level.red(),
+            "warning" | "warn" => entry.level.yellow(),
+            "debug" | "verbose" => entry.level.dimmed(),
+            _ => entry.level.normal(),
+        };
+        println!("  [{}] {}", level, entry.text);
+    });
+
+    tokio::select! {
+        result = stream => {
+            result?;
+            status_println!(json, "  {} Extension service worker went away", "!".yellow());
+        }
+        _ = tokio::signal::ctrl_c() => {}
+    }
+    Ok(())
+}
+
+async fn reload(cli: &Cli, config: Option<&Config>) -> Result<()> {
+    let json = cli.json;
+    let (Some(token), Some(bridge_port)) = (
+        extension_bridge::read_isolated_token_file().await,
+        extension_bridge::read_isolated_port_file().await,
+    ) else {
+        return Err(ActionbookError::ExtensionError(
+            "No isolated bridge is running. Start one with \
+             'actionbook extension serve --isolated'."
+                .to_string(),
+        ));
+    };
+    // Same rule as serve: without native messaging the user pairs by hand
+    let inject = config.is_none_or(|c| {
+        isolated_extension::TokenDelivery::for_config(c)
+            == isolated_extension::TokenDelivery::CdpInjection
+    });
+
+    status_println!(
+        json,
+        "  {}  Reloading extension in isolated Chrome (CDP port {})...",
+        "◆".cyan(),
+        isolated_extension::ISOLATED_CDP_PORT
+    );
+    let configured_dir = config.and_then(|c| c.browser.extension_dir.as_deref());
+    let ext_dir = extension_installer::extension_dir(configured_dir)?;
+    let ext_id = cdp_http::reload_extension(
+        DEFAULT_CDP_HOST,
+        isolated_extension::ISOLATED_CDP_PORT,
+        &ext_dir,
+        inject.then_some((token.as_str(), bridge_port)),
     )
-    .await;
+    .await?;
 
-    match result {
-        Ok(resp) => {
-            let elapsed = start.elapsed();
+    if json {
+        output::print_json(serde_json::json!({
+            "reloaded": true,
+            "extension_id": ext_id,
+            "token_injected": inject,
+        }));
+    } else {
+        println!("  {}  Extension reloaded (ID: {})", "✓".green(), ext_id.dimmed());
+        if inject {
+            println!("  {}  Token re-injected via CDP", "✓".green());
+        } else {
             println!(
-                "  {} Extension responded: {} ({}ms)",
-                "✓".green(),
-                resp,
-                elapsed.as_millis()
+                "  {}  Native messaging unavailable; enter the token in the extension popup",
+                "!".yellow()
             );
         }
+    }
+    Ok(())
+}
+
+async fn ping(cli: &Cli, port: u16, configured_dir: Option<&str>) -> Result<()> {
+    // Bridge-level ping: answered by the bridge, reports extension presence
+    let start = std::time::Instant::now();
+    let bridge =
+        extension_bridge::send_command(port, "Extension.ping", serde_json::json!({})).await;
+    let bridge_ms = start.elapsed().as_millis();
+
+    let bridge = match bridge {
+        Ok(resp) => resp,
         Err(e) => {
-            println!("  {} Ping failed: {}", "✗".red(), e);
+            if cli.json {
+                output::print_json(serde_json::json!({
+                    "bridge": false,
+                    "extension_connected": false,
+                    "error": e.to_string(),
+                }));
+            } else {
+                println!("  {} Bridge ping failed: {}", "✗".red(), e);
+            }
+            return Ok(());
+        }
+    };
+    let extension_connected = bridge
+        .get("extension_connected")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    let token_mismatch = bridge
+        .get("extension_token_mismatch")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+
+    // Full round-trip through the extension, only when one is connected
+    let extension = if extension_connected {
+        let start = std::time::Instant::now();
+        Some(
+            extension_bridge::send_command(port, "Extension.pingExtension", serde_json::json!({}))
+                .await
+                .map(|resp| (resp, start.elapsed().as_millis())),
+        )
+    } else {
+        None
+    };
+    let version_warning = match &extension {
+        Some(Ok(_)) => loaded_version_warning(port, configured_dir).await,
+        _ => None,
+    };
+
+    if cli.json {
+        let mut out = serde_json::json!({
+            "bridge": true,
+            "bridge_ms": bridge_ms as u64,
+            "extension_connected": extension_connected,
+            "extension_token_mismatch": token_mismatch,
+        });
+        match &extension {
+            Some(Ok((_, ms))) => out["extension_ms"] = serde_json::json!(*ms as u64),
+            Some(Err(e)) => out["error"] = serde_json::json!(e.to_string()),
+            None => {}
+        }
+        if let Some(warning) = &version_warning {
+            out["version_warning"] = serde_json::json!(warning);
+        }
+        output::print_json(out);
+        return Ok(());
+    }
+
+    println!(
+        "  {} Bridge responded on port {} ({}ms)",
+        "✓".green(),
+        port,
+        bridge_ms
+    );
+    match extension {
+        Some(Ok((resp, ms))) => {
+            println!("  {} Extension responded: {} ({}ms)", "✓".green(), resp, ms);
+            if let Some(warning) = version_warning {
+                println!("  {} {}", "!".yellow(), warning);
+            }
+        }
+        Some(Err(e)) => {
+            println!("  {} Extension ping failed: {}", "✗".red(), e);
+        }
+        None => {
+            println!("  {} Extension not connected", "✗".red());
+            if token_mismatch {
+                println!(
+                    "  {}  The extension is holding a stale token. Reload the extension to re-inject the current token, or restart with: {}",
+                    "ℹ".dimmed(),
+                    "actionbook extension serve".dimmed()
+                );
+            }
         }
     }
 
     Ok(())
 }
 
-async fn stop(cli: &Cli, port: u16) -> Result<()> {
+/// Compare the extension version Chrome is running against the installed
+/// one. Extensions that predate `Extension.version` are not checked.
+async fn loaded_version_warning(port: u16, configured_dir: Option<&str>) -> Option<String> {
+    let dir = extension_installer::extension_dir(configured_dir).ok()?;
+    let installed = extension_installer::installed_version(&dir)?;
+    let resp = extension_bridge::send_command(port, "Extension.version", serde_json::json!({}))
+        .await
+        .ok()?;
+    let running = resp.get("version").and_then(|v| v.as_str())?;
+    extension_installer::loaded_version_mismatch(&installed, running)
+}
+
+async fn stop(cli: &Cli, port: u16, force: bool) -> Result<()> {
+    let status = stop_bridge(cli.json, port, force).await;
+    if cli.json {
+        output::print_json(status);
+    }
+    Ok(())
+}
+
+/// Stop the bridge on `port`, printing progress unless `json`, and return the
+/// JSON status object `stop` reports (`stopped`, `not_running` or `error`).
+async fn stop_bridge(json: bool, port: u16, force: bool) -> serde_json::Value {
     // Read both PID files — each now contains PID:PORT for deterministic matching.
     let iso = extension_bridge::read_isolated_pid_file().await;
     let std = extension_bridge::read_pid_file().await;
@@ -184,15 +555,7 @@ async fn stop(cli: &Cli, port: u16) -> Result<()> {
                 (false, true) => Some((p2, false)),
                 (true, true) => {
                     // Both alive on same port — ambiguous, refuse
-                    if cli.json {
-                        println!(
-                            "{}",
-                            serde_json::json!({
-                                "status": "error",
-                                "error": "Multiple bridges detected on same port. Stop manually with Ctrl+C."
-                            })
-                        );
-                    } else {
+                    if !json {
                         println!(
                             "  {} Multiple bridges detected on port {}",
                             "!".yellow(),
@@ -203,21 +566,23 @@ async fn stop(cli: &Cli, port: u16) -> Result<()> {
                             "ℹ".dimmed()
                         );
                     }
-                    return Ok(());
+                    return serde_json::json!({
+                        "status": "error",
+                        "error":
+                            "Multiple bridges detected on same port. Stop manually with Ctrl+C."
+                    });
                 }
                 (false, false) => {
                     // Both dead — clean up stale PID files
                     extension_bridge::delete_isolated_pid_file().await;
                     extension_bridge::delete_pid_file().await;
-                    if cli.json {
-                        println!("{}", serde_json::json!({ "status": "not_running" }));
-                    } else {
+                    if !json {
                         println!(
                             "  {} Bridge is not running (cleaned up stale PID files)",
                             "ℹ".dimmed()
                         );
                     }
-                    return Ok(());
+                    return serde_json::json!({ "status": "not_running" });
                 }
             }
         }
@@ -243,12 +608,7 @@ async fn stop(cli: &Cli, port: u16) -> Result<()> {
             // No PID file matches this port — fall back to port check
             let running = extension_bridge::is_bridge_running(port).await;
             if running {
-                if cli.json {
-                    println!(
-                        "{}",
-                        serde_json::json!({ "status": "error", "error": "Bridge is running but no PID file found. Stop it manually with Ctrl+C." })
-                    );
-                } else {
+                if !json {
                     println!(
                         "  {} Bridge is running on port {} but no PID file found",
                         "!".yellow(),
@@ -259,30 +619,32 @@ async fn stop(cli: &Cli, port: u16) -> Result<()> {
                         "ℹ".dimmed()
                     );
                 }
-            } else if cli.json {
-                println!("{}", serde_json::json!({ "status": "not_running" }));
-            } else {
+                return serde_json::json!({
+                    "status": "error",
+                    "error":
+                        "Bridge is running but no PID file found. Stop it manually with Ctrl+C."
+                });
+            }
+            if !json {
                 println!(
                     "  {} Bridge server is not running",
                     "ℹ".dimmed()
                 );
             }
-            return Ok(());
+            return serde_json::json!({ "status": "not_running" });
         }
     };
 
     // Guard against malformed PID files: PID must be positive
     if pid == 0 {
         delete_pid_file(is_isolated).await;
-        if cli.json {
-            println!("{}", serde_json::json!({ "status": "not_running" }));
-        } else {
+        if !json {
             println!(
                 "  {} Invalid PID file (cleaned up)",
                 "ℹ".dimmed()
             );
         }
-        return Ok(());
+        return serde_json::json!({ "status": "not_running" });
     }
 
     // Verify the bridge is actually listening on the expected port before
@@ -295,12 +657,7 @@ async fn stop(cli: &Cli, port: u16) -> Result<()> {
             delete_pid_file(is_isolated).await;
         }
 
-        if cli.json {
-            println!(
-                "{}",
-                serde_json::json!({ "status": "not_running", "stale_pid": pid })
-            );
-        } else {
+        if !json {
             println!(
                 "  {} Bridge is not running on port {}{}",
                 "ℹ".dimmed(),
@@ -312,33 +669,17 @@ async fn stop(cli: &Cli, port: u16) -> Result<()> {
                 }
             );
         }
-        return Ok(());
+        return serde_json::json!({ "status": "not_running", "stale_pid": pid });
     }
 
-    // Send SIGTERM for graceful shutdown.
-    #[cfg(unix)]
-    let kill_ok = {
-        let result = unsafe { libc::kill(pid as i32, libc::SIGTERM) };
-        if result != 0 {
-            let err = std::io::Error::last_os_error();
-            if err.raw_os_error() == Some(libc::ESRCH) {
+    let outcome = match terminate_bridge(pid, force).await {
+        Ok(outcome) => outcome,
+        Err(err) => {
+            // Only delete PID file if process is confirmed dead
+            if !process::is_alive(pid) {
                 delete_pid_file(is_isolated).await;
-                if cli.json {
-                    println!("{}", serde_json::json!({ "status": "not_running" }));
-                } else {
-                    println!(
-                        "  {} Bridge is not running (cleaned up stale PID file)",
-                        "ℹ".dimmed()
-                    );
-                }
-                return Ok(());
             }
-            if cli.json {
-                println!(
-                    "{}",
-                    serde_json::json!({ "status": "error", "error": err.to_string(), "pid": pid })
-                );
-            } else {
+            if !json {
                 println!(
                     "  {} Failed to stop bridge (PID {}): {}",
                     "✗".red(),
@@ -346,74 +687,56 @@ async fn stop(cli: &Cli, port: u16) -> Result<()> {
                     err
                 );
             }
-            false
-        } else {
-            true
+            return serde_json::json!({ "status": "error", "error": err.to_string(), "pid": pid });
         }
     };
 
-    #[cfg(not(unix))]
-    let kill_ok = {
-        let status = std::process::Command::new("taskkill")
-            .args(["/PID", &pid.to_string()])
-            .status();
-        match status {
-            Ok(s) if s.success() => true,
-            Ok(_) | Err(_) => {
-                // Only delete PID file if process is confirmed dead
-                if !extension_bridge::is_pid_alive(pid) {
-                    delete_pid_file(is_isolated).await;
-                }
-                if cli.json {
-                    println!("{}", serde_json::json!({ "status": "error", "error": "Failed to stop bridge process" }));
-                } else {
-                    println!("  {} Failed to stop bridge (PID {})", "✗".red(), pid);
-                }
-                false
-            }
-        }
-    };
-
-    if !kill_ok {
-        return Ok(());
-    }
-
-    // Wait for the process to exit, with SIGKILL escalation
-    tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+    delete_pid_file(is_isolated).await;
 
-    #[cfg(unix)]
-    {
-        let still_running = unsafe { libc::kill(pid as i32, 0) } == 0;
-        if still_running {
-            tokio::time::sleep(std::time::Duration::from_secs(2)).await;
-            let still_running = unsafe { libc::kill(pid as i32, 0) } == 0;
-            if still_running {
-                unsafe { libc::kill(pid as i32, libc::SIGKILL) };
-                tokio::time::sleep(std::time::Duration::from_millis(500)).await;
-            }
+    if outcome == TerminationOutcome::AlreadyDead {
+        if !json {
+            println!(
+                "  {} Bridge is not running (cleaned up stale PID file)",
+                "ℹ".dimmed()
+            );
         }
+        return serde_json::json!({ "status": "not_running" });
     }
 
-    delete_pid_file(is_isolated).await;
-
-    if cli.json {
-        println!(
-            "{}",
-            serde_json::json!({ "status": "stopped", "pid": pid })
-        );
-    } else {
+    let forced = outcome == TerminationOutcome::Killed;
+    if !json {
         println!(
-            "  {} Bridge server stopped (PID {})",
+            "  {} Bridge server stopped (PID {}){}",
             "✓".green(),
-            pid
+            pid,
+            if forced { " after force-kill" } else { "" }
         );
     }
 
-    Ok(())
+    serde_json::json!({ "status": "stopped", "pid": pid, "forced": forced })
 }
 
-async fn install(cli: &Cli, force: bool) -> Result<()> {
-    let dir = extension_installer::extension_dir()?;
+/// Stop the bridge process once it has been confirmed to own the port.
+///
+/// Graceful shutdown by default, escalating to a forced kill if the bridge
+/// hangs; `force` skips straight to the kill.
+async fn terminate_bridge(pid: u32, force: bool) -> std::io::Result<TerminationOutcome> {
+    if force {
+        process::kill(pid).await
+    } else {
+        process::terminate(pid, process::DEFAULT_TERMINATION_GRACE).await
+    }
+}
+
+async fn install(
+    cli: &Cli,
+    configured_dir: Option<&str>,
+    source: &extension_installer::ReleaseSource,
+    force: bool,
+    strict: bool,
+    no_native_host: bool,
+) -> Result<()> {
+    let dir = extension_installer::extension_dir(configured_dir)?;
 
     // Download from GitHub (handles version comparison internally —
     // returns AlreadyUpToDate when installed version >= latest)
@@ -424,44 +747,81 @@ async fn install(cli: &Cli, force: bool) -> Result<()> {
         );
     }
 
-    let result = extension_installer::download_and_install(force).await;
+    let json = cli.json;
+    let mut bar: Option<indicatif::ProgressBar> = None;
+    let mut last_reported: Option<u64> = None;
+    let on_progress = |progress: extension_installer::DownloadProgress| {
+        if json {
+            // One line per percent step, or per 256 KiB when the size is unknown
+            let step = match progress.percent() {
+                Some(pct) => pct as u64,
+                None => progress.downloaded / (256 * 1024),
+            };
+            if last_reported != Some(step) {
+                last_reported = Some(step);
+                output::print_json(serde_json::json!({
+                    "event": "download_progress",
+                    "pct": progress.percent(),
+                    "bytes": progress.downloaded,
+                    "total": progress.total,
+                }));
+            }
+            return;
+        }
+        let bar = bar.get_or_insert_with(|| download_bar(progress.total));
+        bar.set_position(progress.downloaded);
+    };
+    let result =
+        extension_installer::download_and_install_with_progress(&dir, source, force, on_progress)
+            .await;
+    if let Some(bar) = bar {
+        bar.finish_and_clear();
+    }
 
     // Handle "already up to date" as a success case, not an error
-    match &result {
-        Err(crate::error::ActionbookError::ExtensionAlreadyUpToDate {
-            current,
-            latest: _,
-        }) => {
-            if cli.json {
-                println!(
-                    "{}",
-                    serde_json::json!({
-                        "status": "already_installed",
-                        "version": current,
-                        "path": dir.display().to_string()
-                    })
-                );
-            } else {
-                println!(
-                    "  {} Extension v{} is already up to date",
-                    "✓".green(),
-                    current,
-                );
-                println!(
-                    "  {}  Use {} to force reinstall",
-                    "ℹ".dimmed(),
-                    "--force".dimmed()
-                );
-            }
-            return Ok(());
+    if let Err(crate::error::ActionbookError::ExtensionAlreadyUpToDate {
+        current,
+        latest: _,
+    }) = &result
+    {
+        if cli.json {
+            output::print_json(serde_json::json!({
+                "status": "already_installed",
+                "version": current,
+                "path": dir.display().to_string()
+            }));
+        } else {
+            println!(
+                "  {} Extension v{} is already up to date",
+                "✓".green(),
+                current,
+            );
+            println!(
+                "  {}  Use {} to force reinstall",
+                "ℹ".dimmed(),
+                "--force".dimmed()
+            );
         }
-        _ => {}
+        return Ok(());
     }
 
     let version = result?;
 
     // Register native messaging host for automatic token exchange
-    let native_host_result = native_messaging::install_manifest();
+    let native_host_result =
+        register_native_host(no_native_host, native_messaging::install_manifest);
+    if matches!(native_host_result, Some(Ok(_))) {
+        // A successful registration lifts the token-file fallback setup
+        // may have recorded
+        if let Ok(mut config) = cli.load_config() {
+            if !config.browser.native_messaging_ok {
+                config.browser.native_messaging_ok = true;
+                if let Err(e) = config.save_to(&cli.config_file()) {
+                    tracing::warn!("Failed to record native messaging status: {}", e);
+                }
+            }
+        }
+    }
 
     if cli.json {
         let mut result = serde_json::json!({
@@ -470,14 +830,17 @@ async fn install(cli: &Cli, force: bool) -> Result<()> {
             "path": dir.display().to_string()
         });
         match &native_host_result {
-            Ok(p) => {
+            Some(Ok(p)) => {
                 result["native_messaging_host"] = serde_json::json!(p.display().to_string());
             }
-            Err(e) => {
+            Some(Err(e)) => {
                 result["native_messaging_host_error"] = serde_json::json!(e.to_string());
             }
+            None => {
+                result["native_messaging_host_skipped"] = serde_json::json!(true);
+            }
         }
-        println!("{}", result);
+        output::print_json(result);
     } else {
         println!();
         println!(
@@ -488,14 +851,24 @@ async fn install(cli: &Cli, force: bool) -> Result<()> {
         println!("  {}  Path: {}", "◆".cyan(), dir.display());
 
         match &native_host_result {
-            Ok(p) => {
+            Some(Ok(p)) => {
                 println!(
                     "  {} Native messaging host registered",
                     "✓".green()
                 );
                 println!("  {}  Manifest: {}", "◆".cyan(), p.display().to_string().dimmed());
             }
-            Err(e) => {
+            None => {
+                println!(
+                    "  {} Native messaging host not registered (--no-native-host)",
+                    "ℹ".dimmed()
+                );
+                println!(
+                    "  {}  Token auto-pairing is disabled; manual token entry required",
+                    "ℹ".dimmed()
+                );
+            }
+            Some(Err(e)) => {
                 println!(
                     "  {} Failed to register native messaging host: {}",
                     "!".yellow(),
@@ -521,28 +894,113 @@ async fn install(cli: &Cli, force: bool) -> Result<()> {
             "  4. Run {}",
             "actionbook extension serve".cyan()
         );
-        println!(
-            "  5. Extension {} via native messaging",
-            "auto-connects".green().bold()
-        );
+        if native_host_result.is_some() {
+            println!(
+                "  5. Extension {} via native messaging",
+                "auto-connects".green().bold()
+            );
+        } else {
+            println!("  5. Paste the session token into the extension popup");
+        }
         println!();
     }
 
-    Ok(())
+    match &native_host_result {
+        Some(registration) => native_messaging_outcome(strict, registration),
+        None => Ok(()),
+    }
 }
 
-async fn path(cli: &Cli) -> Result<()> {
-    let dir = extension_installer::extension_dir()?;
+/// Register the native messaging host with `register`, unless `skip`
+/// (`--no-native-host`) says the manifest is managed elsewhere.
+fn register_native_host(
+    skip: bool,
+    register: impl FnOnce() -> Result<std::path::PathBuf>,
+) -> Option<Result<std::path::PathBuf>> {
+    (!skip).then(register)
+}
+
+/// Result of an install whose extension files are in place: a failed native
+/// messaging registration is only reported unless `strict` is set.
+fn native_messaging_outcome(
+    strict: bool,
+    registration: &Result<std::path::PathBuf>,
+) -> Result<()> {
+    match registration {
+        Err(e) if strict => Err(ActionbookError::NativeMessagingFailed(e.to_string())),
+        _ => Ok(()),
+    }
+}
+
+async fn verify(cli: &Cli, configured_dir: Option<&str>) -> Result<()> {
+    let dir = extension_installer::extension_dir(configured_dir)?;
+    let report = extension_installer::verify_dir(&dir)?;
 
     if cli.json {
+        output::print_json(serde_json::json!({
+            "status": if report.is_ok() { "ok" } else { "drift" },
+            "missing": report.missing,
+            "extra": report.extra,
+            "modified": report.modified,
+        }));
+    } else if report.is_ok() {
         println!(
-            "{}",
-            serde_json::json!({
-                "path": dir.display().to_string(),
-                "installed": extension_installer::is_installed(),
-                "version": extension_installer::installed_version(),
-            })
+            "  {} Installed extension matches its install record",
+            "✓".green()
         );
+    } else {
+        for (label, files) in [
+            ("missing", &report.missing),
+            ("extra", &report.extra),
+            ("modified", &report.modified),
+        ] {
+            for file in files {
+                println!("  {} {} {}", "!".yellow(), label, file);
+            }
+        }
+    }
+
+    if report.is_ok() {
+        Ok(())
+    } else {
+        Err(ActionbookError::ExtensionError(
+            "Installed extension has drifted. Reinstall with: actionbook extension install --force"
+                .to_string(),
+        ))
+    }
+}
+
+/// Progress bar for the extension download; a byte counter when the
+/// size is unknown.
+fn download_bar(total: Option<u64>) -> indicatif::ProgressBar {
+    let (bar, template) = match total {
+        Some(total) => (
+            indicatif::ProgressBar::new(total),
+            "  {spinner} Downloading [{bar:30}] {bytes}/{total_bytes}",
+        ),
+        None => (
+            indicatif::ProgressBar::new_spinner(),
+            "  {spinner} Downloading {bytes}",
+        ),
+    };
+    bar.set_style(
+        indicatif::ProgressStyle::default_bar()
+            .template(template)
+            .expect("valid progress template")
+            .progress_chars("=> "),
+    );
+    bar
+}
+
+async fn path(cli: &Cli, configured_dir: Option<&str>) -> Result<()> {
+    let dir = extension_installer::extension_dir(configured_dir)?;
+
+    if cli.json {
+        output::print_json(serde_json::json!({
+            "path": dir.display().to_string(),
+            "installed": extension_installer::is_installed(&dir),
+            "version": extension_installer::installed_version(&dir),
+        }));
     } else {
         println!("{}", dir.display());
     }
@@ -550,13 +1008,62 @@ async fn path(cli: &Cli) -> Result<()> {
     Ok(())
 }
 
-async fn uninstall(cli: &Cli) -> Result<()> {
-    if !extension_installer::is_installed() {
+async fn native_status(cli: &Cli) -> Result<()> {
+    let manifests = vec![native_messaging::manifest_status(
+        &native_messaging::native_host_manifest_path()?,
+        native_messaging::EXTENSION_ID,
+    )];
+
+    if cli.json {
+        let entries: Vec<_> = manifests
+            .iter()
+            .map(|m| {
+                serde_json::json!({
+                    "path": m.path.display().to_string(),
+                    "exists": m.exists,
+                    "valid": m.exists && m.error.is_none(),
+                    "error": m.error,
+                    "origin_allowed": m.origin_allowed,
+                })
+            })
+            .collect();
+        output::print_json(serde_json::json!({
+            "extension_id": native_messaging::EXTENSION_ID,
+            "manifests": entries,
+        }));
+    } else {
+        for m in &manifests {
+            let (mark, detail) = if !m.exists {
+                ("✗".red(), "not found".to_string())
+            } else if let Some(ref e) = m.error {
+                ("✗".red(), format!("malformed: {}", e))
+            } else if !m.origin_allowed {
+                (
+                    "!".yellow(),
+                    format!("does not allow extension {}", native_messaging::EXTENSION_ID),
+                )
+            } else {
+                ("✓".green(), "ok".to_string())
+            };
+            println!("  {} {} ({})", mark, m.path.display(), detail);
+        }
+    }
+
+    if manifests.iter().all(|m| m.is_ok()) {
+        Ok(())
+    } else {
+        Err(ActionbookError::ExtensionError(
+            "Native messaging host is not registered correctly. Re-register with: actionbook extension install --force"
+                .to_string(),
+        ))
+    }
+}
+
+async fn uninstall(cli: &Cli, configured_dir: Option<&str>, keep_native_host: bool) -> Result<()> {
+    let dir = extension_installer::extension_dir(configured_dir)?;
+    if !extension_installer::is_installed(&dir) {
         if cli.json {
-            println!(
-                "{}",
-                serde_json::json!({ "status": "not_installed" })
-            );
+            output::print_json(serde_json::json!({ "status": "not_installed" }));
         } else {
             println!(
                 "  {} Extension is not installed",
@@ -566,31 +1073,70 @@ async fn uninstall(cli: &Cli) -> Result<()> {
         return Ok(());
     }
 
-    let dir = extension_installer::extension_dir()?;
-    extension_installer::uninstall()?;
+    extension_installer::uninstall(&dir)?;
 
-    // Also remove native messaging host manifest
-    let _ = native_messaging::uninstall_manifest();
+    // Also remove native messaging host manifest, unless it is managed elsewhere
+    if !keep_native_host {
+        let _ = native_messaging::uninstall_manifest();
+    }
 
     if cli.json {
-        println!(
-            "{}",
-            serde_json::json!({
-                "status": "uninstalled",
-                "path": dir.display().to_string()
-            })
-        );
+        output::print_json(serde_json::json!({
+            "status": "uninstalled",
+            "path": dir.display().to_string(),
+            "native_host_kept": keep_native_host,
+        }));
     } else {
         println!(
             "  {} Extension removed from {}",
             "✓".green(),
             dir.display()
         );
-        println!(
-            "  {} Native messaging host unregistered",
-            "✓".green()
-        );
+        if keep_native_host {
+            println!(
+                "  {} Native messaging host left in place (--keep-native-host)",
+                "ℹ".dimmed()
+            );
+        } else {
+            println!(
+                "  {} Native messaging host unregistered",
+                "✓".green()
+            );
+        }
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strict_install_fails_on_native_messaging_error() {
+        let failed: Result<std::path::PathBuf> =
+            Err(ActionbookError::Other("permission denied".to_string()));
+
+        assert!(native_messaging_outcome(false, &failed).is_ok());
+
+        let err = native_messaging_outcome(true, &failed).unwrap_err();
+        assert!(matches!(err, ActionbookError::NativeMessagingFailed(_)));
+        assert_eq!(err.exit_code(), 3);
+        assert!(err.to_string().contains("permission denied"));
+
+        let registered = Ok(std::path::PathBuf::from("/tmp/host.json"));
+        assert!(native_messaging_outcome(true, &registered).is_ok());
+    }
+
+    #[test]
+    fn no_native_host_skips_manifest_registration() {
+        let skipped = register_native_host(true, || {
+            panic!("manifest registration must not run with --no-native-host")
+        });
+        assert!(skipped.is_none());
+
+        let registered =
+            register_native_host(false, || Ok(std::path::PathBuf::from("/tmp/host.json")));
+        assert!(matches!(registered, Some(Ok(_))));
+    }
+}
@@ -1,19 +1,72 @@
+use std::io::Write;
+
 use crate::api::ApiClient;
-use crate::cli::Cli;
-use crate::config::Config;
+use crate::cli::{BodyFormat, Cli};
 use crate::error::Result;
 
-pub async fn run(cli: &Cli, area_id: &str) -> Result<()> {
-    let mut config = Config::load()?;
-    if let Some(ref key) = cli.api_key {
-        config.api.api_key = Some(key.clone());
+pub async fn run(cli: &Cli, area_id: &str, format: BodyFormat) -> Result<()> {
+    let config = cli.load_config()?;
+    // A per-call --api-key replaces the configured key for this request only
+    let client = match cli.api_key.as_deref() {
+        Some(key) => ApiClient::with_key(&config, key)?,
+        None => ApiClient::from_config(&config)?,
+    };
+
+    // Raw output is copied through as it arrives; reformatting a JSON body
+    // needs all of it
+    if format == BodyFormat::Raw {
+        let mut stdout = std::io::stdout().lock();
+        client.stream_action_by_area_id(area_id, &mut stdout).await?;
+        writeln!(stdout)?;
+        return Ok(());
     }
-    let client = ApiClient::from_config(&config)?;
 
     let result = client.get_action_by_area_id(area_id).await?;
 
-    // Result is plain text, output directly
-    println!("{}", result);
+    // Result is usually plain text; JSON bodies can be reformatted
+    println!("{}", format_body(&result, format));
 
     Ok(())
 }
+
+/// Reformat `body` if it is JSON; anything else is returned unchanged.
+fn format_body(body: &str, format: BodyFormat) -> String {
+    if format == BodyFormat::Raw {
+        return body.to_string();
+    }
+    let Ok(value) = serde_json::from_str::<serde_json::Value>(body) else {
+        return body.to_string();
+    };
+    let formatted = match format {
+        BodyFormat::Pretty => serde_json::to_string_pretty(&value),
+        _ => serde_json::to_string(&value),
+    };
+    formatted.unwrap_or_else(|_| body.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_body_is_reformatted() {
+        let body = r#"{"actions":[1, 2],"url":"https://example.com"}"#;
+        assert_eq!(
+            format_body(body, BodyFormat::Pretty),
+            "{\n  \"actions\": [\n    1,\n    2\n  ],\n  \"url\": \"https://example.com\"\n}"
+        );
+        assert_eq!(
+            format_body(body, BodyFormat::Compact),
+            r#"{"actions":[1,2],"url":"https://example.com"}"#
+        );
+        assert_eq!(format_body(body, BodyFormat::Raw), body);
+    }
+
+    #[test]
+    fn plain_text_body_passes_through() {
+        let body = "Site: example.com\n\n## Actions\n- click [1, 2]";
+        for format in [BodyFormat::Raw, BodyFormat::Pretty, BodyFormat::Compact] {
+            assert_eq!(format_body(body, format), body);
+        }
+    }
+}
@@ -3,6 +3,8 @@ pub mod config;
 pub mod extension;
 pub mod get;
 pub mod profile;
+pub mod reset;
 pub mod search;
 pub mod setup;
 pub mod sources;
+pub mod tabs;
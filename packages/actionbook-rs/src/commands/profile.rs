@@ -1,7 +1,7 @@
 use colored::Colorize;
 
 use crate::cli::{Cli, ProfileCommands};
-use crate::config::{Config, ProfileConfig};
+use crate::config::ProfileConfig;
 use crate::error::Result;
 
 pub async fn run(cli: &Cli, command: &ProfileCommands) -> Result<()> {
@@ -14,7 +14,7 @@ pub async fn run(cli: &Cli, command: &ProfileCommands) -> Result<()> {
 }
 
 async fn list(cli: &Cli) -> Result<()> {
-    let config = Config::load()?;
+    let config = cli.load_config()?;
 
     if cli.json {
         let profiles: Vec<_> = config
@@ -24,6 +24,7 @@ async fn list(cli: &Cli) -> Result<()> {
                 serde_json::json!({
                     "name": name,
                     "cdp_port": profile.cdp_port,
+                    "cdp_host": profile.cdp_host,
                     "headless": profile.headless,
                     "is_remote": profile.is_remote()
                 })
@@ -43,6 +44,9 @@ async fn list(cli: &Cli) -> Result<()> {
 
             println!("  {} {}{}", "●".cyan(), name.bold(), default_marker);
             println!("    CDP Port: {}", profile.cdp_port);
+            if profile.cdp_host != crate::config::DEFAULT_CDP_HOST {
+                println!("    CDP Host: {}", profile.cdp_host);
+            }
 
             if profile.is_remote() {
                 if let Some(ref url) = profile.cdp_url {
@@ -74,7 +78,7 @@ async fn list(cli: &Cli) -> Result<()> {
 }
 
 async fn create(cli: &Cli, name: &str, cdp_port: Option<u16>) -> Result<()> {
-    let mut config = Config::load()?;
+    let mut config = cli.load_config()?;
 
     let profile = if let Some(port) = cdp_port {
         ProfileConfig::with_cdp_port(port)
@@ -90,7 +94,7 @@ async fn create(cli: &Cli, name: &str, cdp_port: Option<u16>) -> Result<()> {
     };
 
     config.set_profile(name, profile.clone());
-    config.save()?;
+    config.save_to(&cli.config_file())?;
 
     if cli.json {
         println!(
@@ -114,9 +118,9 @@ async fn create(cli: &Cli, name: &str, cdp_port: Option<u16>) -> Result<()> {
 }
 
 async fn delete(cli: &Cli, name: &str) -> Result<()> {
-    let mut config = Config::load()?;
+    let mut config = cli.load_config()?;
     config.remove_profile(name)?;
-    config.save()?;
+    config.save_to(&cli.config_file())?;
 
     if cli.json {
         println!(
@@ -134,7 +138,7 @@ async fn delete(cli: &Cli, name: &str) -> Result<()> {
 }
 
 async fn show(cli: &Cli, name: &str) -> Result<()> {
-    let config = Config::load()?;
+    let config = cli.load_config()?;
     let profile = config.get_profile(name)?;
 
     if cli.json {
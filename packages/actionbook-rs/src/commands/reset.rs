@@ -0,0 +1,147 @@
+use colored::Colorize;
+use dialoguer::Confirm;
+
+use crate::browser::extension_bridge::{self, BridgeFiles, BridgeMode};
+use crate::browser::isolated_extension;
+use crate::browser::process::{self, TerminationOutcome};
+use crate::cli::Cli;
+use crate::error::{ActionbookError, Result};
+use crate::output;
+
+pub async fn run(cli: &Cli, yes: bool) -> Result<()> {
+    let found = extension_bridge::scan_bridge_files().await;
+    let stale: Vec<_> = found
+        .iter()
+        .filter(|files| !files.live)
+        .flat_map(|files| &files.paths)
+        .collect();
+    let kept: Vec<_> = found
+        .iter()
+        .filter(|files| files.live && !files.paths.is_empty())
+        .collect();
+
+    // An isolated Chrome only counts as orphaned when the PID file says its
+    // bridge existed and has died; without a PID file it may be starting up
+    let isolated_bridge_died = found
+        .iter()
+        .any(|files| files.mode == BridgeMode::Isolated && files.owner.is_some() && !files.live);
+    let orphaned_chrome = if isolated_bridge_died {
+        isolated_extension::isolated_chrome_pid().await
+    } else {
+        None
+    };
+
+    if stale.is_empty() && orphaned_chrome.is_none() {
+        if cli.json {
+            output::print_json(serde_json::json!({
+                "status": "clean",
+                "removed": [],
+                "kept": kept_json(&kept),
+                "chrome_terminated": null,
+            }));
+        } else {
+            println!("{} Nothing to reset", "✓".green());
+            print_kept(&kept);
+        }
+        return Ok(());
+    }
+
+    if !yes {
+        if cli.json {
+            output::print_json(serde_json::json!({
+                "error": "confirmation_required",
+                "message": "Pass --yes to confirm the reset",
+                "stale_files": stale,
+                "orphaned_chrome": orphaned_chrome,
+            }));
+            // The JSON line already explains; only the exit code remains
+            return Err(ActionbookError::AlreadyReported(Box::new(ActionbookError::Other(
+                "Pass --yes to confirm the reset".to_string(),
+            ))));
+        }
+
+        println!("{} About to:", "!".yellow());
+        for path in &stale {
+            println!("  remove {}", path.display());
+        }
+        if let Some(pid) = orphaned_chrome {
+            println!("  stop the isolated Chrome left running by a dead bridge (PID {})", pid);
+        }
+        let confirm = Confirm::new()
+            .with_prompt("Continue?")
+            .default(false)
+            .interact()
+            .map_err(|e| ActionbookError::Other(format!("Prompt failed: {}", e)))?;
+        if !confirm {
+            println!("  Cancelled.");
+            return Ok(());
+        }
+    }
+
+    let removed = extension_bridge::remove_stale_bridge_files(&found).await;
+
+    let mut chrome_terminated = None;
+    if let Some(pid) = orphaned_chrome {
+        match process::terminate(pid, process::DEFAULT_TERMINATION_GRACE).await {
+            Ok(TerminationOutcome::AlreadyDead) => {}
+            Ok(_) => chrome_terminated = Some(pid),
+            Err(e) => output::print_warning(
+                cli.json,
+                format!("Failed to stop isolated Chrome (PID {}): {}", pid, e),
+            ),
+        }
+    }
+
+    if cli.json {
+        output::print_json(serde_json::json!({
+            "status": "reset",
+            "removed": removed,
+            "kept": kept_json(&kept),
+            "chrome_terminated": chrome_terminated,
+        }));
+        return Ok(());
+    }
+
+    for path in &removed {
+        println!("{} Removed {}", "✓".green(), path.display());
+    }
+    if let Some(pid) = chrome_terminated {
+        println!("{} Stopped orphaned isolated Chrome (PID {})", "✓".green(), pid);
+    }
+    print_kept(&kept);
+
+    Ok(())
+}
+
+fn mode_name(mode: BridgeMode) -> &'static str {
+    match mode {
+        BridgeMode::Standard => "standard",
+        BridgeMode::Isolated => "isolated",
+    }
+}
+
+fn kept_json(kept: &[&BridgeFiles]) -> serde_json::Value {
+    kept.iter()
+        .map(|files| {
+            serde_json::json!({
+                "mode": mode_name(files.mode),
+                "pid": files.owner.map(|(pid, _)| pid),
+                "port": files.owner.map(|(_, port)| port),
+            })
+        })
+        .collect()
+}
+
+fn print_kept(kept: &[&BridgeFiles]) {
+    for files in kept {
+        if let Some((pid, port)) = files.owner {
+            println!(
+                "  {}  Kept the files of the running {} bridge (PID {}, port {})",
+                "ℹ".dimmed(),
+                mode_name(files.mode),
+                pid,
+                port
+            );
+        }
+    }
+}
@@ -2,7 +2,6 @@ use colored::Colorize;
 
 use crate::api::{ApiClient, SearchActionsParams};
 use crate::cli::Cli;
-use crate::config::Config;
 use crate::error::Result;
 
 pub async fn run(
@@ -13,7 +12,7 @@ pub async fn run(
     page: u32,
     page_size: u32,
 ) -> Result<()> {
-    let mut config = Config::load()?;
+    let mut config = cli.load_config()?;
     if let Some(ref key) = cli.api_key {
         config.api.api_key = Some(key.clone());
     }
@@ -209,7 +209,11 @@ fn configure_extension_profile(cli: &Cli, config: &mut Config) -> Result<()> {
         }
 
         // Ensure extension is installed
-        if extension_installer::is_installed() {
+        let installed = extension_installer::extension_dir(
+            config.browser.extension_dir.as_deref(),
+        )
+        .is_ok_and(|dir| extension_installer::is_installed(&dir));
+        if installed {
             println!(
                 "  {}  Extension: installed",
                 "◇".green()
@@ -223,21 +227,14 @@ fn configure_extension_profile(cli: &Cli, config: &mut Config) -> Result<()> {
         }
 
         // Register native messaging host
-        match native_messaging::install_manifest() {
-            Ok(_) => {
-                println!(
-                    "  {}  Native messaging host: registered",
-                    "◇".green()
-                );
-            }
-            Err(e) => {
-                tracing::warn!("Failed to register native messaging host: {}", e);
-                println!(
-                    "  {}  Native messaging: {}",
-                    "◇".dimmed(),
-                    "manual token entry required".dimmed()
-                );
-            }
+        if register_native_messaging(config, native_messaging::install_manifest) {
+            println!("  {}  Native messaging host: registered", "◇".green());
+        } else {
+            println!(
+                "  {}  Native messaging: {}",
+                "◇".dimmed(),
+                "manual token entry required".dimmed()
+            );
         }
 
         println!("  {}  Extension profile: Isolated", "◇".green());
@@ -300,10 +297,25 @@ fn apply_browser_mode(
     Ok(())
 }
 
+/// Run `install` to register the native messaging host and record the
+/// outcome in `browser.native_messaging_ok`. Returns whether it succeeded.
+fn register_native_messaging(
+    config: &mut Config,
+    install: impl FnOnce() -> Result<std::path::PathBuf>,
+) -> bool {
+    let result = install();
+    if let Err(e) = &result {
+        tracing::warn!("Failed to register native messaging host: {}", e);
+    }
+    config.browser.native_messaging_ok = result.is_ok();
+    config.browser.native_messaging_ok
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::browser::{BrowserInfo, BrowserType};
+    use crate::browser::isolated_extension::TokenDelivery;
+    use crate::browser::{BrowserChannel, BrowserInfo, BrowserType};
     use std::path::PathBuf;
 
     fn make_env_with_browsers(browsers: Vec<BrowserInfo>) -> EnvironmentInfo {
@@ -332,8 +344,10 @@ mod tests {
             api_key: None,
             json: false,
             extension: false,
-            extension_port: 19222,
+            extension_port: None,
             verbose: false,
+            config_path: None,
+            timeout: None,
             command: crate::cli::Commands::Config {
                 command: crate::cli::ConfigCommands::Show,
             },
@@ -360,8 +374,10 @@ mod tests {
             api_key: None,
             json: false,
             extension: false,
-            extension_port: 19222,
+            extension_port: None,
             verbose: false,
+            config_path: None,
+            timeout: None,
             command: crate::cli::Commands::Config {
                 command: crate::cli::ConfigCommands::Show,
             },
@@ -386,8 +402,10 @@ mod tests {
             api_key: None,
             json: false,
             extension: false,
-            extension_port: 19222,
+            extension_port: None,
             verbose: false,
+            config_path: None,
+            timeout: None,
             command: crate::cli::Commands::Config {
                 command: crate::cli::ConfigCommands::Show,
             },
@@ -396,6 +414,7 @@ mod tests {
             browser_type: BrowserType::Chrome,
             path: PathBuf::from("/usr/bin/chrome"),
             version: Some("131.0".to_string()),
+            channel: BrowserChannel::Stable,
         };
         let env = make_env_with_browsers(vec![browser]);
         let mut config = Config::default();
@@ -422,8 +441,10 @@ mod tests {
             api_key: None,
             json: false,
             extension: false,
-            extension_port: 19222,
+            extension_port: None,
             verbose: false,
+            config_path: None,
+            timeout: None,
             command: crate::cli::Commands::Config {
                 command: crate::cli::ConfigCommands::Show,
             },
@@ -440,4 +461,29 @@ mod tests {
             "Built-in mode must clear extension_isolated_profile"
         );
     }
+
+    #[test]
+    fn failed_native_messaging_registration_is_recorded() {
+        let mut config = Config::default();
+        assert!(config.browser.native_messaging_ok);
+
+        let ok = register_native_messaging(&mut config, || {
+            Err(ActionbookError::Other("no manifest dir".to_string()))
+        });
+        assert!(!ok);
+        assert!(!config.browser.native_messaging_ok);
+        assert_eq!(
+            TokenDelivery::for_config(&config),
+            TokenDelivery::TokenFile,
+            "isolated serve must fall back to the token file"
+        );
+
+        let ok = register_native_messaging(&mut config, || Ok(PathBuf::from("/tmp/host.json")));
+        assert!(ok);
+        assert!(config.browser.native_messaging_ok);
+        assert_eq!(
+            TokenDelivery::for_config(&config),
+            TokenDelivery::CdpInjection
+        );
+    }
 }
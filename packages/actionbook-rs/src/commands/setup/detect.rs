@@ -1,9 +1,9 @@
+use std::path::Path;
 use std::process::Command;
 
 use colored::Colorize;
 
-use crate::browser::{discover_all_browsers, BrowserInfo};
-use crate::config::Config;
+use crate::browser::{discover_all_browsers_preferring, BrowserChannel, BrowserInfo};
 
 /// Detected environment information used to pre-fill setup defaults
 #[derive(Debug)]
@@ -18,15 +18,16 @@ pub struct EnvironmentInfo {
     pub existing_api_key: Option<String>,
 }
 
-/// Scan the system environment and return detected info
-pub fn detect_environment() -> EnvironmentInfo {
+/// Scan the system environment and return detected info. Browsers are
+/// ordered by `channels`, most preferred first.
+pub fn detect_environment(config_path: &Path, channels: &[BrowserChannel]) -> EnvironmentInfo {
     let os = std::env::consts::OS.to_string();
     let arch = std::env::consts::ARCH.to_string();
     let shell = std::env::var("SHELL").ok();
-    let browsers = discover_all_browsers();
+    let browsers = discover_all_browsers_preferring(channels);
     let npx_available = which::which("npx").is_ok();
     let node_version = detect_node_version();
-    let existing_config = Config::config_path().exists();
+    let existing_config = config_path.exists();
     let existing_api_key = std::env::var("ACTIONBOOK_API_KEY").ok();
 
     EnvironmentInfo {
@@ -51,6 +52,7 @@ pub fn print_environment_report(env: &EnvironmentInfo, json: bool) {
                 serde_json::json!({
                     "name": b.browser_type.name(),
                     "version": b.version,
+                    "channel": b.channel.name(),
                     "path": b.path.display().to_string(),
                 })
             })
@@ -166,23 +168,24 @@ fn detect_node_version() -> Option<String> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::Config;
 
     #[test]
     fn test_detect_environment_returns_valid_struct() {
-        let env = detect_environment();
+        let env = detect_environment(&Config::config_path(), &BrowserChannel::DEFAULT_ORDER);
         assert!(!env.os.is_empty());
         assert!(!env.arch.is_empty());
     }
 
     #[test]
     fn test_print_environment_report_does_not_panic() {
-        let env = detect_environment();
+        let env = detect_environment(&Config::config_path(), &BrowserChannel::DEFAULT_ORDER);
         print_environment_report(&env, false);
     }
 
     #[test]
     fn test_print_environment_report_json_does_not_panic() {
-        let env = detect_environment();
+        let env = detect_environment(&Config::config_path(), &BrowserChannel::DEFAULT_ORDER);
         print_environment_report(&env, true);
     }
 }
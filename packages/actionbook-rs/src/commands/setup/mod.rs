@@ -45,7 +45,7 @@ pub async fn run(cli: &Cli, args: SetupArgs<'_>) -> Result<()> {
         print_step_header(1, "Environment");
     }
     let spinner = create_spinner(cli.json, args.non_interactive, "Scanning environment...");
-    let env = detect::detect_environment();
+    let env = detect::detect_environment(&cli.config_file(), &config.browser.channel_preference);
     finish_spinner(spinner, "Environment detected");
     detect::print_environment_report(&env, cli.json);
     if !cli.json {
@@ -102,7 +102,7 @@ pub async fn run(cli: &Cli, args: SetupArgs<'_>) -> Result<()> {
             println!(
                 "  {}    Path      {}",
                 bar,
-                Config::config_path().display().to_string().dimmed()
+                cli.config_file().display().to_string().dimmed()
             );
             println!("  {}", bar);
 
@@ -139,12 +139,12 @@ pub async fn run(cli: &Cli, args: SetupArgs<'_>) -> Result<()> {
         break config;
     };
 
-    config.save()?;
+    config.save_to(&cli.config_file())?;
     if !cli.json {
         println!(
             "  {}  Configuration saved to {}",
             "◇".green(),
-            Config::config_path().display()
+            cli.config_file().display()
         );
     }
 
@@ -291,14 +291,14 @@ fn handle_existing_config(cli: &Cli, non_interactive: bool, reset: bool) -> Resu
         return Ok(Config::default());
     }
 
-    let config_exists = Config::config_path().exists();
+    let config_exists = cli.config_file().exists();
 
     if !config_exists {
         return Ok(Config::default());
     }
 
     // Load existing config
-    let existing = Config::load()?;
+    let existing = cli.load_config()?;
 
     if non_interactive {
         // Non-interactive: reuse existing config as defaults
@@ -460,7 +460,7 @@ async fn run_health_check(cli: &Cli, config: &Config, non_interactive: bool) {
     }
 
     // Config file check
-    let config_path = Config::config_path();
+    let config_path = cli.config_file();
     if config_path.exists() {
         if cli.json {
             println!(
@@ -485,7 +485,7 @@ fn print_completion(cli: &Cli, config: &Config, skills_result: &mode::SkillsResu
             serde_json::json!({
                 "command": "setup",
                 "status": "complete",
-                "config_path": Config::config_path().display().to_string(),
+                "config_path": cli.config_file().display().to_string(),
                 "browser": config.browser.executable.as_deref().unwrap_or("built-in"),
                 "headless": config.browser.headless,
                 "skills": {
@@ -545,7 +545,7 @@ fn print_completion(cli: &Cli, config: &Config, skills_result: &mode::SkillsResu
     println!(
         "     {}  {}",
         "Config".dimmed(),
-        shorten_home_path(&Config::config_path().display().to_string())
+        shorten_home_path(&cli.config_file().display().to_string())
     );
     println!("     {}  {}", "Key".dimmed(), api_display);
     println!(
@@ -2,7 +2,6 @@ use colored::Colorize;
 
 use crate::api::ApiClient;
 use crate::cli::{Cli, SourcesCommands};
-use crate::config::Config;
 use crate::error::Result;
 
 pub async fn run(cli: &Cli, command: &SourcesCommands) -> Result<()> {
@@ -13,7 +12,7 @@ pub async fn run(cli: &Cli, command: &SourcesCommands) -> Result<()> {
 }
 
 async fn list(cli: &Cli) -> Result<()> {
-    let mut config = Config::load()?;
+    let mut config = cli.load_config()?;
     if let Some(ref key) = cli.api_key {
         config.api.api_key = Some(key.clone());
     }
@@ -74,7 +73,7 @@ async fn list(cli: &Cli) -> Result<()> {
 }
 
 async fn search(cli: &Cli, query: &str) -> Result<()> {
-    let mut config = Config::load()?;
+    let mut config = cli.load_config()?;
     if let Some(ref key) = cli.api_key {
         config.api.api_key = Some(key.clone());
     }
@@ -0,0 +1,43 @@
+use colored::Colorize;
+
+use super::browser::bridge_target;
+use crate::browser::extension_bridge;
+use crate::cli::Cli;
+use crate::error::Result;
+
+pub async fn run(cli: &Cli) -> Result<()> {
+    let (port, token) = bridge_target(cli).await?;
+    let tabs = match token {
+        Some(token) => extension_bridge::list_tabs_with_token(port, &token).await?,
+        None => extension_bridge::list_tabs(port).await?,
+    };
+
+    if cli.json {
+        println!("{}", serde_json::to_string_pretty(&tabs)?);
+    } else if tabs.is_empty() {
+        println!("{} No tabs found", "!".yellow());
+    } else {
+        println!("{} {} tabs open\n", "✓".green(), tabs.len());
+        for (i, tab) in tabs.iter().enumerate() {
+            let title = if tab.title.is_empty() {
+                "(no title)"
+            } else {
+                &tab.title
+            };
+            println!(
+                "{}. {} {}{}",
+                (i + 1).to_string().cyan(),
+                title.bold(),
+                format!("(tab:{})", tab.id).dimmed(),
+                if tab.active {
+                    " [active]".green().to_string()
+                } else {
+                    String::new()
+                }
+            );
+            println!("   {}", tab.url.dimmed());
+        }
+    }
+
+    Ok(())
+}
@@ -1,12 +1,25 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 /// Profile configuration for a browser session
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct ProfileConfig {
     /// CDP port for this profile
     #[serde(default = "default_cdp_port")]
     pub cdp_port: u16,
 
+    /// Host serving the CDP endpoint. Set this to reach a browser in another
+    /// container or machine. Launched browsers still listen on loopback only
+    /// unless `cdp_bind_remote` is set.
+    #[serde(default = "default_cdp_host")]
+    pub cdp_host: String,
+
+    /// Make a launched browser listen for CDP on a non-loopback `cdp_host`.
+    /// The CDP endpoint has no authentication, so only enable this on a
+    /// trusted network.
+    #[serde(default)]
+    pub cdp_bind_remote: bool,
+
     /// User data directory for this profile
     pub user_data_dir: Option<String>,
 
@@ -23,21 +36,42 @@ pub struct ProfileConfig {
     /// Extra browser arguments
     #[serde(default)]
     pub extra_args: Vec<String>,
+
+    /// How long to wait for the CDP endpoint after launching, in milliseconds
+    #[serde(default = "default_cdp_ready_timeout_ms")]
+    pub cdp_ready_timeout_ms: u64,
 }
 
 fn default_cdp_port() -> u16 {
     9222
 }
 
+/// Default CDP host (local loopback)
+pub const DEFAULT_CDP_HOST: &str = "127.0.0.1";
+
+fn default_cdp_host() -> String {
+    DEFAULT_CDP_HOST.to_string()
+}
+
+/// Default CDP readiness timeout (10 seconds)
+pub const DEFAULT_CDP_READY_TIMEOUT_MS: u64 = 10_000;
+
+fn default_cdp_ready_timeout_ms() -> u64 {
+    DEFAULT_CDP_READY_TIMEOUT_MS
+}
+
 impl Default for ProfileConfig {
     fn default() -> Self {
         Self {
             cdp_port: default_cdp_port(),
+            cdp_host: default_cdp_host(),
+            cdp_bind_remote: false,
             user_data_dir: None,
             browser_path: None,
             headless: false,
             cdp_url: None,
             extra_args: Vec::new(),
+            cdp_ready_timeout_ms: default_cdp_ready_timeout_ms(),
         }
     }
 }
@@ -64,4 +98,87 @@ impl ProfileConfig {
     pub fn is_remote(&self) -> bool {
         self.cdp_url.is_some()
     }
+
+    /// Check that `cdp_host` and `cdp_port` form a usable endpoint. The host
+    /// must be a bare hostname or IP address, without scheme, port, or path.
+    pub fn validate_cdp_endpoint(&self) -> std::result::Result<(), String> {
+        if self.cdp_port == 0 {
+            return Err("cdp_port must be between 1 and 65535".to_string());
+        }
+        let host = self.cdp_host.as_str();
+        if host.parse::<std::net::IpAddr>().is_ok() {
+            return Ok(());
+        }
+        let valid_hostname = !host.is_empty()
+            && host.len() <= 253
+            && host.split('.').all(|label| {
+                !label.is_empty()
+                    && label.len() <= 63
+                    && !label.starts_with('-')
+                    && !label.ends_with('-')
+                    && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+            });
+        if valid_hostname {
+            Ok(())
+        } else {
+            Err(format!(
+                "cdp_host '{}' must be a hostname or IP address (no scheme, port, or path)",
+                host
+            ))
+        }
+    }
+}
+
+/// First port of the IANA dynamic range, which operating systems hand out
+/// as ephemeral ports for outgoing connections.
+pub const EPHEMERAL_PORT_START: u16 = 49152;
+
+/// Why `port`, used as the `what` port, may cause trouble: ports below 1024
+/// need elevated privileges and ephemeral ports can be taken by the OS.
+pub fn port_warning(what: &str, port: u16) -> Option<String> {
+    if (1..1024).contains(&port) {
+        Some(format!(
+            "{} {} is below 1024 and may need elevated privileges",
+            what, port
+        ))
+    } else if port >= EPHEMERAL_PORT_START {
+        Some(format!(
+            "{} {} is in the ephemeral range ({}-65535) and may collide with outgoing connections",
+            what, port, EPHEMERAL_PORT_START
+        ))
+    } else {
+        None
+    }
+}
+
+/// Check the ports for an extension bridge and, when it drives its own
+/// browser, that browser's CDP port. A bridge port of 0 lets the OS pick a
+/// free port. A CDP port of 0 and a bridge sharing the CDP port are errors;
+/// anything [`port_warning`] flags is returned as a warning.
+pub fn check_bridge_ports(
+    bridge_port: u16,
+    cdp_port: Option<u16>,
+) -> std::result::Result<Vec<String>, String> {
+    if cdp_port == Some(0) {
+        return Err("CDP port must be between 1 and 65535".to_string());
+    }
+    if cdp_port == Some(bridge_port) {
+        return Err(format!(
+            "bridge port {} is also the CDP port; use a different --port",
+            bridge_port
+        ));
+    }
+    Ok(std::iter::once(port_warning("bridge port", bridge_port))
+        .chain(cdp_port.map(|p| port_warning("CDP port", p)))
+        .flatten()
+        .collect())
+}
+
+/// Whether `host` names the local loopback interface.
+pub fn is_loopback_host(host: &str) -> bool {
+    host.eq_ignore_ascii_case("localhost")
+        || host
+            .parse::<std::net::IpAddr>()
+            .map(|ip| ip.is_loopback())
+            .unwrap_or(false)
 }
@@ -1,5 +1,69 @@
+use std::fmt;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
 use thiserror::Error;
 
+/// WebSocket error from the bridge connection, boxed to keep `Result` small.
+pub type WsError = Box<tokio_tungstenite::tungstenite::Error>;
+
+/// Which stage of a browser launch failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LaunchErrorKind {
+    /// The browser binary could not be spawned.
+    Spawn,
+    /// The browser started but its CDP endpoint never became ready.
+    CdpTimeout,
+    /// The user data directory is locked by another running browser.
+    ProfileLocked,
+}
+
+impl fmt::Display for LaunchErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            LaunchErrorKind::Spawn => "spawn failed",
+            LaunchErrorKind::CdpTimeout => "CDP not ready",
+            LaunchErrorKind::ProfileLocked => "profile locked",
+        };
+        f.write_str(s)
+    }
+}
+
+/// What the JSON-RPC error code of a bridge or extension response means.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(dead_code)]
+pub enum RpcErrorKind {
+    /// -32600: the request frame was rejected
+    InvalidRequest,
+    /// -32601: the method is unknown to the bridge or the extension
+    MethodNotFound,
+    /// -32602: the method exists but its params were rejected
+    InvalidParams,
+    /// -32603: the bridge got no usable response
+    Internal,
+    /// -32003: params exceeded the bridge's `--max-params-bytes`
+    ParamsTooLarge,
+    /// Any other -32000..=-32099 code: timeouts, disconnects, expired tokens
+    Server,
+    /// Any other code, e.g. a browser domain error passed through as is
+    Application,
+}
+
+#[allow(dead_code)]
+impl RpcErrorKind {
+    pub fn from_code(code: i64) -> Self {
+        match code {
+            -32600 => RpcErrorKind::InvalidRequest,
+            -32601 => RpcErrorKind::MethodNotFound,
+            -32602 => RpcErrorKind::InvalidParams,
+            -32603 => RpcErrorKind::Internal,
+            -32003 => RpcErrorKind::ParamsTooLarge,
+            -32099..=-32000 => RpcErrorKind::Server,
+            _ => RpcErrorKind::Application,
+        }
+    }
+}
+
 #[derive(Error, Debug)]
 pub enum ActionbookError {
     #[error("Browser not found. Please install Chrome, Brave, or Edge.")]
@@ -8,9 +72,57 @@ pub enum ActionbookError {
     #[error("Browser launch failed: {0}")]
     BrowserLaunchFailed(String),
 
+    #[error("Browser launch failed ({kind}): {detail}")]
+    LaunchError {
+        kind: LaunchErrorKind,
+        detail: String,
+    },
+
     #[error("CDP connection failed: {0}")]
     CdpConnectionFailed(String),
 
+    #[error("CDP {method} failed: {message}")]
+    CdpCommandFailed { method: String, message: String },
+
+    #[error("CDP {method} response is missing {field}: {response}")]
+    CdpResponseMissingField {
+        method: &'static str,
+        field: &'static str,
+        response: String,
+    },
+
+    #[error("CDP returned WebSocket URL {url}, which is not on {expected}; refusing to connect")]
+    CdpUnexpectedWebSocketUrl { url: String, expected: String },
+
+    #[error("CDP pipe {op} failed: {source}")]
+    CdpPipeIo {
+        op: &'static str,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error(
+        "Chrome was launched without a CDP pipe (--remote-debugging-pipe), \
+         so the extension cannot be loaded"
+    )]
+    CdpPipeUnavailable,
+
+    #[error("CDP pipe closed before a response arrived")]
+    CdpPipeClosed,
+
+    #[error("CDP pipe response exceeded {limit} bytes")]
+    CdpPipeResponseTooLarge { limit: usize },
+
+    #[error("CDP pipe response is not valid UTF-8: {0}")]
+    CdpPipeInvalidUtf8(#[from] std::string::FromUtf8Error),
+
+    #[error("CDP pipe response is not valid JSON: {source} (raw: {raw})")]
+    CdpPipeInvalidJson {
+        raw: String,
+        #[source]
+        source: serde_json::Error,
+    },
+
     #[error("Browser not running. Use 'actionbook browser open <url>' first.")]
     BrowserNotRunning,
 
@@ -33,18 +145,81 @@ pub enum ActionbookError {
     #[error("API error: {0}")]
     ApiError(String),
 
+    #[error("Invalid area ID '{area_id}': {reason}; expected site:path:area")]
+    InvalidAreaId { area_id: String, reason: String },
+
+    #[error("Area not found: {area_id}")]
+    AreaNotFound { area_id: String },
+
     #[error("Setup error: {0}")]
     SetupError(String),
 
     #[error("Extension error: {0}")]
     ExtensionError(String),
 
+    #[error("Extension error: {message}")]
+    ExtensionRpc {
+        code: i64,
+        message: String,
+        data: Option<serde_json::Value>,
+    },
+
+    #[error("Extension path {} is not accessible: {source}", .path.display())]
+    ExtensionPathInaccessible {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("Extension path {} is not valid UTF-8 and cannot be sent over CDP", .0.display())]
+    ExtensionPathNotUtf8(PathBuf),
+
+    #[error("Failed to load extension via CDP pipe: {0}")]
+    ExtensionLoadFailed(#[source] Box<ActionbookError>),
+
+    #[error("Failed to bind bridge to {addr}: {source}")]
+    BridgeBind {
+        addr: SocketAddr,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("Bridge failed to accept a connection: {0}")]
+    BridgeAccept(#[source] std::io::Error),
+
+    #[error(
+        "Cannot connect to bridge at {url}. Is `actionbook extension serve` running? ({source})"
+    )]
+    BridgeUnreachable {
+        url: String,
+        #[source]
+        source: WsError,
+    },
+
+    #[error("Bridge connection {op} failed: {source}")]
+    BridgeConnection {
+        op: &'static str,
+        #[source]
+        source: WsError,
+    },
+
+    #[error("Extension installed, but native messaging registration failed: {0}")]
+    NativeMessagingFailed(String),
+
     #[error("Extension v{current} is already up to date (latest: v{latest})")]
     ExtensionAlreadyUpToDate { current: String, latest: String },
 
     #[error("Timeout: {0}")]
     Timeout(String),
 
+    #[error("Command exceeded the global --timeout of {0}s")]
+    GlobalTimeout(u64),
+
+    /// A failure the command already reported inside its `--json` result;
+    /// `main` exits with its code without printing it again
+    #[error("{0}")]
+    AlreadyReported(Box<ActionbookError>),
+
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
 
@@ -58,4 +233,180 @@ pub enum ActionbookError {
     Other(String),
 }
 
+impl ActionbookError {
+    pub fn launch(kind: LaunchErrorKind, detail: impl Into<String>) -> Self {
+        ActionbookError::LaunchError {
+            kind,
+            detail: detail.into(),
+        }
+    }
+
+    /// How an [`ActionbookError::ExtensionRpc`] failed; `None` for other errors.
+    #[allow(dead_code)]
+    pub fn rpc_kind(&self) -> Option<RpcErrorKind> {
+        match self {
+            ActionbookError::ExtensionRpc { code, .. } => Some(RpcErrorKind::from_code(*code)),
+            _ => None,
+        }
+    }
+
+    /// Process exit code for this error. A global timeout uses 124, matching
+    /// coreutils `timeout`, so CI scripts can tell it apart from other failures.
+    /// A `--strict` install whose native messaging registration failed uses 3,
+    /// since the extension itself did install.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            ActionbookError::GlobalTimeout(_) => 124,
+            ActionbookError::NativeMessagingFailed(_) => 3,
+            ActionbookError::AlreadyReported(inner) => inner.exit_code(),
+            _ => 1,
+        }
+    }
+}
+
 pub type Result<T> = std::result::Result<T, ActionbookError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn launch_error_spawn_display() {
+        let err = ActionbookError::launch(LaunchErrorKind::Spawn, "No such file or directory");
+        assert!(matches!(
+            err,
+            ActionbookError::LaunchError {
+                kind: LaunchErrorKind::Spawn,
+                ..
+            }
+        ));
+        assert_eq!(
+            err.to_string(),
+            "Browser launch failed (spawn failed): No such file or directory"
+        );
+    }
+
+    #[test]
+    fn launch_error_cdp_timeout_display() {
+        let err = ActionbookError::launch(LaunchErrorKind::CdpTimeout, "port 9222");
+        assert!(matches!(
+            err,
+            ActionbookError::LaunchError {
+                kind: LaunchErrorKind::CdpTimeout,
+                ..
+            }
+        ));
+        assert_eq!(
+            err.to_string(),
+            "Browser launch failed (CDP not ready): port 9222"
+        );
+    }
+
+    #[test]
+    fn launch_error_profile_locked_display() {
+        let err = ActionbookError::launch(LaunchErrorKind::ProfileLocked, "/tmp/profile");
+        assert!(matches!(
+            err,
+            ActionbookError::LaunchError {
+                kind: LaunchErrorKind::ProfileLocked,
+                ..
+            }
+        ));
+        assert_eq!(
+            err.to_string(),
+            "Browser launch failed (profile locked): /tmp/profile"
+        );
+    }
+
+    #[test]
+    fn pipe_and_bridge_errors_keep_their_source() {
+        use std::error::Error as _;
+
+        let io = std::io::Error::new(std::io::ErrorKind::BrokenPipe, "broken pipe");
+        let err = ActionbookError::CdpPipeIo { op: "write", source: io };
+        assert_eq!(err.to_string(), "CDP pipe write failed: broken pipe");
+        let source = err.source().unwrap();
+        assert_eq!(
+            source.downcast_ref::<std::io::Error>().unwrap().kind(),
+            std::io::ErrorKind::BrokenPipe
+        );
+
+        let utf8 = String::from_utf8(vec![0xff]).unwrap_err();
+        let err = ActionbookError::from(utf8);
+        assert!(err
+            .to_string()
+            .starts_with("CDP pipe response is not valid UTF-8: "));
+        assert!(err.source().unwrap().is::<std::string::FromUtf8Error>());
+
+        let addr: SocketAddr = "127.0.0.1:19222".parse().unwrap();
+        let in_use = std::io::Error::new(std::io::ErrorKind::AddrInUse, "address in use");
+        let err = ActionbookError::BridgeBind { addr, source: in_use };
+        assert_eq!(
+            err.to_string(),
+            "Failed to bind bridge to 127.0.0.1:19222: address in use"
+        );
+        assert!(err.source().unwrap().is::<std::io::Error>());
+
+        let ws = tokio_tungstenite::tungstenite::Error::ConnectionClosed;
+        let err = ActionbookError::BridgeConnection {
+            op: "send",
+            source: Box::new(ws),
+        };
+        assert_eq!(
+            err.to_string(),
+            "Bridge connection send failed: Connection closed normally"
+        );
+        assert!(err.source().unwrap().is::<WsError>());
+    }
+
+    #[test]
+    fn wrapped_load_failure_renders_its_cause() {
+        use std::error::Error as _;
+
+        let inner = ActionbookError::CdpCommandFailed {
+            method: "Extensions.loadUnpacked".to_string(),
+            message: "Manifest file is missing".to_string(),
+        };
+        let err = ActionbookError::ExtensionLoadFailed(Box::new(inner));
+        assert_eq!(
+            err.to_string(),
+            "Failed to load extension via CDP pipe: \
+             CDP Extensions.loadUnpacked failed: Manifest file is missing"
+        );
+        let cause = err.source().unwrap().downcast_ref::<Box<ActionbookError>>();
+        assert!(matches!(
+            cause.map(|b| &**b),
+            Some(ActionbookError::CdpCommandFailed { .. })
+        ));
+    }
+
+    #[test]
+    fn rpc_codes_are_classified() {
+        assert_eq!(RpcErrorKind::from_code(-32601), RpcErrorKind::MethodNotFound);
+        assert_eq!(RpcErrorKind::from_code(-32003), RpcErrorKind::ParamsTooLarge);
+        assert_eq!(RpcErrorKind::from_code(-32000), RpcErrorKind::Server);
+        assert_eq!(RpcErrorKind::from_code(-32050), RpcErrorKind::Server);
+        assert_eq!(RpcErrorKind::from_code(-31999), RpcErrorKind::Application);
+        assert_eq!(RpcErrorKind::from_code(404), RpcErrorKind::Application);
+
+        let err = ActionbookError::ExtensionRpc {
+            code: -32601,
+            message: "Unknown method: Foo.bar".to_string(),
+            data: None,
+        };
+        assert_eq!(err.rpc_kind(), Some(RpcErrorKind::MethodNotFound));
+        assert_eq!(err.to_string(), "Extension error: Unknown method: Foo.bar");
+        assert_eq!(ActionbookError::ExtensionError("x".to_string()).rpc_kind(), None);
+    }
+
+    #[test]
+    fn global_timeout_has_dedicated_exit_code() {
+        let err = ActionbookError::GlobalTimeout(5);
+        assert_eq!(err.exit_code(), 124);
+        assert_eq!(
+            err.to_string(),
+            "Command exceeded the global --timeout of 5s"
+        );
+        assert_eq!(ActionbookError::Timeout("wait".to_string()).exit_code(), 1);
+    }
+}
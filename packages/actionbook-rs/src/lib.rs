@@ -3,8 +3,14 @@
 
 pub mod browser;
 pub mod cli;
+pub mod clock;
 pub mod config;
 pub mod error;
+pub mod log_file;
+pub mod output;
 
 mod api;
 pub mod commands;
+
+#[cfg(feature = "blocking")]
+pub mod blocking;
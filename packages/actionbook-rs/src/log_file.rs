@@ -0,0 +1,198 @@
+//! Log filter setup and the size-rotated log file for `extension serve
+//! --log-file` (or `log.dir` in the config).
+//!
+//! Tracing events and bridge status lines are both written here, so a bridge
+//! running under a process manager keeps a self-contained log. When a write
+//! would push the file past its size limit, the file is renamed to
+//! `<path>.1` (replacing any previous one) and a fresh file is started.
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use tracing::level_filters::LevelFilter;
+use tracing_subscriber::EnvFilter;
+
+/// Default size at which the log file is rotated (10 MB)
+pub const DEFAULT_MAX_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Name of the log file written into the configured `log.dir`
+pub const LOG_FILE_NAME: &str = "actionbook.log";
+
+/// Tracing filter: `rust_log` (the `RUST_LOG` value) when it is set and
+/// valid, otherwise `level` with chromiumoxide's noisy connection and
+/// handler errors held back. Those errors are harmless - they occur when
+/// Chrome sends CDP events the library doesn't recognize.
+pub fn filter(rust_log: Option<&str>, level: LevelFilter) -> EnvFilter {
+    if let Some(filter) = rust_log
+        .filter(|spec| !spec.is_empty())
+        .and_then(|spec| EnvFilter::try_new(spec).ok())
+    {
+        return filter;
+    }
+    EnvFilter::default()
+        .add_directive(level.into())
+        .add_directive("chromiumoxide::conn=warn".parse().unwrap())
+        .add_directive("chromiumoxide::handler=warn".parse().unwrap())
+}
+
+static LOG_FILE: OnceLock<LogFile> = OnceLock::new();
+
+/// An append-only file that rotates to `<path>.1` once it reaches `max_bytes`.
+pub struct RotatingFile {
+    path: PathBuf,
+    max_bytes: u64,
+    file: File,
+    len: u64,
+}
+
+impl RotatingFile {
+    /// Open (or create) `path` for appending.
+    pub fn open(path: &Path, max_bytes: u64) -> io::Result<Self> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let len = file.metadata()?.len();
+        Ok(Self {
+            path: path.to_path_buf(),
+            max_bytes,
+            file,
+            len,
+        })
+    }
+
+    /// Where the previous file is kept after a rotation.
+    pub fn rotated_path(&self) -> PathBuf {
+        let mut name = self.path.clone().into_os_string();
+        name.push(".1");
+        PathBuf::from(name)
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        std::fs::rename(&self.path, self.rotated_path())?;
+        self.file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        self.len = 0;
+        Ok(())
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.len > 0 && self.len + buf.len() as u64 > self.max_bytes {
+            self.rotate()?;
+        }
+        let written = self.file.write(buf)?;
+        self.len += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// Shared handle to the process-wide [`RotatingFile`]
+#[derive(Clone)]
+pub struct LogFile(Arc<Mutex<RotatingFile>>);
+
+impl Write for LogFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).flush()
+    }
+}
+
+/// Open the process-wide log file. The returned handle is for the tracing
+/// writer; [`tee`] writes to the same file.
+pub fn init(path: &Path, max_bytes: u64) -> io::Result<LogFile> {
+    let file = LogFile(Arc::new(Mutex::new(RotatingFile::open(path, max_bytes)?)));
+    let _ = LOG_FILE.set(file.clone());
+    Ok(file)
+}
+
+/// Append one line to the log file, if one is open. Terminal colour codes
+/// are stripped so the file stays readable.
+pub fn tee(args: std::fmt::Arguments<'_>) {
+    let Some(mut file) = LOG_FILE.get().cloned() else {
+        return;
+    };
+    let mut line = strip_ansi(&args.to_string());
+    line.push('\n');
+    let _ = file.write_all(line.as_bytes());
+}
+
+/// Remove `ESC [ ... <letter>` escape sequences.
+fn strip_ansi(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rotates_when_size_limit_is_reached() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("serve.log");
+        let mut file = RotatingFile::open(&path, 16).unwrap();
+
+        file.write_all(b"first line\n").unwrap();
+        file.write_all(b"second line\n").unwrap();
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "second line\n");
+        assert_eq!(
+            std::fs::read_to_string(file.rotated_path()).unwrap(),
+            "first line\n"
+        );
+    }
+
+    #[test]
+    fn configured_level_applies_unless_rust_log_is_set() {
+        let filter = super::filter(None, LevelFilter::DEBUG);
+        assert_eq!(filter.max_level_hint(), Some(LevelFilter::DEBUG));
+
+        let filter = super::filter(Some(""), LevelFilter::DEBUG);
+        assert_eq!(filter.max_level_hint(), Some(LevelFilter::DEBUG));
+
+        let filter = super::filter(Some("warn"), LevelFilter::DEBUG);
+        assert_eq!(filter.max_level_hint(), Some(LevelFilter::WARN));
+    }
+
+    #[test]
+    fn verbose_default_filter_is_debug_and_keeps_noise_suppressed() {
+        let level = crate::config::LogConfig::default().level_filter(true);
+        let filter = super::filter(None, level);
+        assert_eq!(filter.max_level_hint(), Some(LevelFilter::DEBUG));
+        let directives = filter.to_string();
+        assert!(directives.contains("chromiumoxide::conn=warn"), "{}", directives);
+        assert!(directives.contains("chromiumoxide::handler=warn"), "{}", directives);
+    }
+
+    #[test]
+    fn strips_colour_codes() {
+        assert_eq!(strip_ansi("\x1b[1;32m✓\x1b[0m ready"), "✓ ready");
+        assert_eq!(strip_ansi("plain"), "plain");
+    }
+}
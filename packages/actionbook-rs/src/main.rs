@@ -1,15 +1,19 @@
 mod api;
 mod browser;
 mod cli;
+mod clock;
 mod commands;
 mod config;
 mod error;
+mod log_file;
+mod output;
 
 use clap::Parser;
-use tracing_subscriber::{fmt, prelude::*, EnvFilter};
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
+use tracing_subscriber::{fmt, prelude::*};
 
 use cli::Cli;
-use error::Result;
+use error::{ActionbookError, Result};
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -24,24 +28,55 @@ async fn main() -> Result<()> {
         return browser::native_messaging::run().await;
     }
 
-    // Initialize tracing with filters to suppress noisy chromiumoxide errors
-    // These errors are harmless - they occur when Chrome sends CDP events that
-    // the library doesn't recognize (common with newer Chrome versions)
-    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| {
-        EnvFilter::new("info")
-            .add_directive("chromiumoxide::conn=warn".parse().unwrap())
-            .add_directive("chromiumoxide::handler=warn".parse().unwrap())
+    // With --json, stdout carries machine-readable output only, so logs go to stderr
+    let cli = Cli::parse();
+    let writer = if cli.json {
+        BoxMakeWriter::new(std::io::stderr)
+    } else {
+        BoxMakeWriter::new(std::io::stdout)
+    };
+
+    // RUST_LOG wins; otherwise the configured level, bumped by --verbose.
+    // A config file that fails to load is reported by the command itself.
+    let log_config = cli.load_config().map(|c| c.log).unwrap_or_default();
+    let rust_log = std::env::var("RUST_LOG").ok();
+    let filter = log_file::filter(rust_log.as_deref(), log_config.level_filter(cli.verbose));
+
+    // `extension serve --log-file` also sends every event to a rotating
+    // file, as does `log.dir` for any command. An unusable `log.dir` only
+    // costs the file, not the command.
+    let log_file = match cli.serve_log_file() {
+        Some((path, max_bytes)) => Some(log_file::init(path, max_bytes)?),
+        None => log_config.dir.as_deref().and_then(|dir| {
+            let path = std::path::PathBuf::from(shellexpand::tilde(dir).as_ref())
+                .join(log_file::LOG_FILE_NAME);
+            log_file::init(&path, log_file::DEFAULT_MAX_BYTES)
+                .map_err(|e| {
+                    output::print_warning(
+                        cli.json,
+                        format!("Cannot open log file {}: {}", path.display(), e),
+                    )
+                })
+                .ok()
+        }),
+    };
+    let file_layer = log_file.map(|file| {
+        fmt::layer()
+            .with_ansi(false)
+            .with_writer(move || file.clone())
     });
 
     tracing_subscriber::registry()
-        .with(fmt::layer())
+        .with(fmt::layer().with_writer(writer))
+        .with(file_layer)
         .with(filter)
         .init();
 
-    let cli = Cli::parse();
     if let Err(e) = cli.run().await {
-        eprintln!("Error: {}", e);
-        std::process::exit(1);
+        if !matches!(e, ActionbookError::AlreadyReported(_)) {
+            eprintln!("Error: {}", e);
+        }
+        std::process::exit(e.exit_code());
     }
     Ok(())
 }
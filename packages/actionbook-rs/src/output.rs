@@ -0,0 +1,66 @@
+//! Machine-readable (`--json`) output.
+//!
+//! Every structured line carries the invocation's `run_id`, so consumers
+//! reading interleaved output from several long-running commands can group
+//! lines by the process that printed them.
+
+use std::io::Write;
+use std::sync::OnceLock;
+
+use colored::Colorize;
+
+static RUN_ID: OnceLock<String> = OnceLock::new();
+
+/// This invocation's run ID, generated on first use.
+pub fn run_id() -> &'static str {
+    RUN_ID.get_or_init(|| format!("{:016x}", rand::random::<u64>()))
+}
+
+/// Add `run_id` to a JSON object; other values are returned unchanged.
+pub fn with_run_id(mut value: serde_json::Value) -> serde_json::Value {
+    if let Some(object) = value.as_object_mut() {
+        object.insert("run_id".to_string(), run_id().into());
+    }
+    value
+}
+
+/// Print one JSON line on stdout, tagged with `run_id`, and flush it so a
+/// parent process blocking on the line sees it immediately.
+pub fn print_json(value: serde_json::Value) {
+    let mut stdout = std::io::stdout().lock();
+    let _ = writeln!(stdout, "{}", with_run_id(value));
+    let _ = stdout.flush();
+}
+
+/// Print a non-fatal warning on stderr: a `{"level": "warning", ...}` JSON
+/// line with `--json`, the usual `  ! message` line otherwise.
+pub fn print_warning(json: bool, message: impl std::fmt::Display) {
+    crate::log_file::tee(format_args!("  ! {}", message));
+    let mut stderr = std::io::stderr().lock();
+    if json {
+        let value = serde_json::json!({
+            "level": "warning",
+            "message": message.to_string(),
+        });
+        let _ = writeln!(stderr, "{}", with_run_id(value));
+    } else {
+        let _ = writeln!(stderr, "  {} {}", "!".yellow(), message);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn run_id_is_stable_and_added_to_objects() {
+        let first = with_run_id(serde_json::json!({ "status": "ok" }));
+        let second = with_run_id(serde_json::json!({ "event": "ready" }));
+        assert_eq!(first["run_id"], second["run_id"]);
+        assert_eq!(first["run_id"].as_str(), Some(run_id()));
+        assert_eq!(run_id().len(), 16);
+
+        let array = serde_json::json!([1, 2]);
+        assert_eq!(with_run_id(array.clone()), array);
+    }
+}
@@ -0,0 +1,116 @@
+//! Integration tests for the synchronous `blocking` façade.
+//!
+//! The bridge and a mock extension run on a background thread with their own
+//! runtime, while the test itself stays fully synchronous.
+//!
+//! Run with: cargo test --features blocking --test blocking_test
+
+#![cfg(feature = "blocking")]
+
+use std::time::Duration;
+
+use actionbook::blocking;
+use actionbook::browser::extension_bridge;
+use futures::{SinkExt, StreamExt};
+use tokio_tungstenite::tungstenite::Message;
+
+/// Find a free port by binding to port 0 and reading the assigned port.
+fn free_port() -> u16 {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    listener.local_addr().unwrap().port()
+}
+
+/// Run a bridge plus a mock extension that answers every command with
+/// `{"echo": <method>}` on a dedicated thread.
+fn start_bridge_with_extension(port: u16, token: String) {
+    std::thread::spawn(move || {
+        let rt = tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        rt.block_on(async move {
+            let (_shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel();
+            let t = token.clone();
+            tokio::spawn(async move {
+                let _ = extension_bridge::serve_with_shutdown(port, t, shutdown_rx, false).await;
+            });
+            tokio::time::sleep(Duration::from_millis(100)).await;
+
+            let (mut ws, _) = tokio_tungstenite::connect_async(format!("ws://127.0.0.1:{}", port))
+                .await
+                .unwrap();
+            let hello = serde_json::json!({
+                "type": "hello",
+                "role": "extension",
+                "token": token,
+                "version": "0.2.0"
+            });
+            ws.send(Message::Text(hello.to_string().into()))
+                .await
+                .unwrap();
+
+            while let Some(Ok(frame)) = ws.next().await {
+                let Message::Text(text) = frame else {
+                    continue;
+                };
+                let msg: serde_json::Value = serde_json::from_str(text.as_str()).unwrap();
+                if msg["type"] == "hello_ack" {
+                    continue;
+                }
+                let resp = serde_json::json!({
+                    "id": msg["id"],
+                    "result": { "echo": msg["method"] }
+                });
+                ws.send(Message::Text(resp.to_string().into()))
+                    .await
+                    .unwrap();
+            }
+        });
+    });
+}
+
+#[test]
+fn blocking_calls_round_trip_through_in_process_bridge() {
+    // Keep token/port files away from the real data dir
+    let data_dir = tempfile::tempdir().unwrap();
+    std::env::set_var("XDG_DATA_HOME", data_dir.path());
+
+    let port = free_port();
+    assert!(!blocking::is_bridge_running_blocking(port));
+
+    let token = extension_bridge::generate_token();
+    start_bridge_with_extension(port, token.clone());
+
+    // Wait for the bridge to listen and the mock extension to register
+    for _ in 0..50 {
+        if blocking::is_bridge_running_blocking(port) {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    assert!(blocking::is_bridge_running_blocking(port));
+    std::thread::sleep(Duration::from_millis(200));
+
+    let result = blocking::send_command_with_token_blocking(
+        port,
+        "Extension.pingExtension",
+        serde_json::json!({}),
+        &token,
+    )
+    .unwrap();
+    assert_eq!(result["echo"], "Extension.ping");
+
+    // send_command_blocking discovers the token from the (redirected) data dir
+    if cfg!(target_os = "linux") {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(extension_bridge::write_token_file(&token))
+            .unwrap();
+
+        let result =
+            blocking::send_command_blocking(port, "Extension.listTabs", serde_json::json!({}))
+                .unwrap();
+        assert_eq!(result["echo"], "Extension.listTabs");
+    }
+}
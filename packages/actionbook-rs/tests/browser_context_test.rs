@@ -0,0 +1,65 @@
+//! Integration tests for isolated browser contexts.
+//!
+//! These tests launch a real headless browser and create contexts via
+//! `SessionManager::new_context`. They are skipped when no Chromium-based
+//! browser is installed.
+//!
+//! Run with: cargo test --test browser_context_test
+
+mod common;
+
+fn cookie_names(cookies: &[serde_json::Value]) -> Vec<&str> {
+    cookies
+        .iter()
+        .filter_map(|c| c.get("name").and_then(|n| n.as_str()))
+        .collect()
+}
+
+#[tokio::test]
+async fn contexts_do_not_share_cookies() {
+    if !common::browser_available() {
+        return;
+    }
+
+    let profile = "context-test";
+    let user_data_dir = tempfile::tempdir().unwrap();
+    let manager = common::test_session_manager(profile, user_data_dir.path()).await;
+    let (_browser, mut handler) = manager.get_or_create_session(Some(profile)).await.unwrap();
+    tokio::spawn(async move {
+        use futures::StreamExt;
+        while handler.next().await.is_some() {}
+    });
+
+    let first = manager.new_context(Some(profile)).await.unwrap();
+    let second = manager.new_context(Some(profile)).await.unwrap();
+    assert_ne!(first.id(), second.id());
+
+    first.new_page("about:blank").await.unwrap();
+    second.new_page("about:blank").await.unwrap();
+
+    first
+        .set_cookie(serde_json::json!({
+            "name": "only_in_first",
+            "value": "1",
+            "url": "http://127.0.0.1/"
+        }))
+        .await
+        .unwrap();
+    second
+        .set_cookie(serde_json::json!({
+            "name": "only_in_second",
+            "value": "2",
+            "url": "http://127.0.0.1/"
+        }))
+        .await
+        .unwrap();
+
+    let first_cookies = first.get_cookies().await.unwrap();
+    let second_cookies = second.get_cookies().await.unwrap();
+    assert_eq!(cookie_names(&first_cookies), vec!["only_in_first"]);
+    assert_eq!(cookie_names(&second_cookies), vec!["only_in_second"]);
+
+    first.dispose().await.unwrap();
+    second.dispose().await.unwrap();
+    manager.close_session(Some(profile)).await.unwrap();
+}
@@ -92,6 +92,59 @@ mod get_command {
             .stderr(predicate::str::contains("AREA_ID"));
     }
 
+    #[test]
+    fn get_rejects_malformed_area_id() {
+        actionbook()
+            .args(["get", "airbnb.com"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("Invalid area ID"));
+    }
+
+    /// `get` reads the Actionbook API, which the extension bridge has no
+    /// equivalent for, so `--extension` leaves it on the API path
+    #[test]
+    fn get_uses_the_api_in_extension_mode() {
+        use std::io::{Read, Write};
+
+        let api = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", api.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = api.accept().unwrap();
+            let mut buf = [0u8; 4096];
+            let n = stream.read(&mut buf).unwrap();
+            let body = "Site: airbnb.com";
+            let reply = format!(
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            stream.write_all(reply.as_bytes()).unwrap();
+            String::from_utf8_lossy(&buf[..n]).into_owned()
+        });
+
+        let tmp = tempfile::tempdir().unwrap();
+        let config = tmp.path().join("config.toml");
+        std::fs::write(&config, format!("[api]\nbase_url = \"{}\"\n", base_url)).unwrap();
+        // Nothing listens on the bridge port; routing there would fail
+        let closed = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let bridge_port = closed.local_addr().unwrap().port().to_string();
+        drop(closed);
+
+        actionbook()
+            .env_remove("ACTIONBOOK_API_KEY")
+            .arg("--config")
+            .arg(&config)
+            .args(["--extension", "--extension-port", &bridge_port])
+            .args(["get", "airbnb.com:/:default"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Site: airbnb.com"));
+
+        let request = server.join().unwrap();
+        assert!(request.contains("/api/get_action_by_area_id"), "{}", request);
+    }
+
     #[test]
     fn get_help_shows_usage() {
         actionbook()
@@ -398,6 +451,25 @@ default_profile = "{}"
             .stdout(predicate::str::contains("snapshot"));
     }
 
+    #[test]
+    fn browser_version_reports_unreachable_cdp_port() {
+        let (_tmp, home, config_home, data_home) = setup_config("actionbook");
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+
+        actionbook()
+            .env("HOME", &home)
+            .env("XDG_CONFIG_HOME", &config_home)
+            .env("XDG_DATA_HOME", &data_home)
+            .args(["browser", "version", "--cdp", &port.to_string()])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("Cannot reach CDP"));
+    }
+
     #[test]
     fn browser_cookies_subcommands() {
         actionbook()
@@ -433,6 +505,40 @@ mod config_command {
             .stdout(predicate::str::contains("configuration"));
     }
 
+    #[test]
+    fn config_effective_applies_flags_and_redacts() {
+        let tmp = tempfile::tempdir().unwrap();
+        let config_path = tmp.path().join("config.toml");
+        std::fs::write(&config_path, "[api]\napi_key = \"file-secret\"\n").unwrap();
+
+        let run = |subcommand: &str| {
+            let output = actionbook()
+                .env("ACTIONBOOK_BROWSER_HEADLESS", "true")
+                .env_remove("ACTIONBOOK_API_KEY")
+                .env_remove("ACTIONBOOK_PROFILE")
+                .arg("--config")
+                .arg(&config_path)
+                .args(["--json", "--profile", "work", "config", subcommand])
+                .output()
+                .unwrap();
+            assert!(output.status.success(), "config {} failed", subcommand);
+            serde_json::from_slice::<serde_json::Value>(&output.stdout).unwrap()
+        };
+
+        // Both apply env overrides; only effective applies the flags and
+        // redacts secrets
+        let shown = run("show");
+        assert_eq!(shown["browser"]["headless"], true);
+        assert_eq!(shown["browser"]["default_profile"], "actionbook");
+        assert_eq!(shown["api"]["api_key"], "file-secret");
+
+        let effective = run("effective");
+        assert_eq!(effective["browser"]["headless"], true);
+        assert_eq!(effective["browser"]["default_profile"], "work");
+        assert_eq!(effective["api"]["api_key"], "REDACTED");
+        assert_eq!(effective["log"]["level"], "info");
+    }
+
     #[test]
     fn config_set_requires_key_value() {
         actionbook()
@@ -442,6 +548,21 @@ mod config_command {
             .stderr(predicate::str::contains("KEY"));
     }
 
+    #[test]
+    fn config_schema_describes_top_level_keys() {
+        let output = actionbook().args(["config", "schema"]).output().unwrap();
+        assert!(output.status.success());
+        let schema: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        let properties = schema["properties"].as_object().unwrap();
+        for key in ["api", "browser", "profiles"] {
+            assert!(properties.contains_key(key), "missing {key}");
+        }
+        assert_eq!(
+            schema["$defs"]["BrowserConfig"]["properties"]["default_profile"]["default"],
+            "actionbook"
+        );
+    }
+
     #[test]
     fn config_get_requires_key() {
         actionbook()
@@ -450,6 +571,121 @@ mod config_command {
             .failure()
             .stderr(predicate::str::contains("KEY"));
     }
+
+    #[test]
+    fn config_flag_reads_custom_path() {
+        let tmp = tempfile::tempdir().unwrap();
+        let custom = tmp.path().join("custom.toml");
+        std::fs::write(
+            &custom,
+            "[api]\nbase_url = \"https://custom.example.test\"\n",
+        )
+        .unwrap();
+
+        actionbook()
+            .env("XDG_CONFIG_HOME", tmp.path().join("default-config"))
+            .env_remove("ACTIONBOOK_API_BASE_URL")
+            .arg("--config")
+            .arg(&custom)
+            .args(["config", "get", "api.base_url"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("https://custom.example.test"));
+    }
+
+    #[test]
+    fn config_flag_changes_reported_path() {
+        let tmp = tempfile::tempdir().unwrap();
+        let custom = tmp.path().join("custom.toml");
+
+        actionbook()
+            .arg("--config")
+            .arg(&custom)
+            .args(["config", "path"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("custom.toml"));
+    }
+
+    #[test]
+    fn config_init_writes_default_and_refuses_to_clobber() {
+        let tmp = tempfile::tempdir().unwrap();
+        let custom = tmp.path().join("nested").join("config.toml");
+
+        actionbook()
+            .arg("--config")
+            .arg(&custom)
+            .args(["--json", "config", "init"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(r#""status":"created""#));
+        assert!(custom.exists());
+
+        actionbook()
+            .env_remove("ACTIONBOOK_API_BASE_URL")
+            .arg("--config")
+            .arg(&custom)
+            .args(["config", "get", "api.base_url"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("https://"));
+
+        std::fs::write(
+            &custom,
+            "[api]\nbase_url = \"https://custom.example.test\"\n",
+        )
+        .unwrap();
+        actionbook()
+            .arg("--config")
+            .arg(&custom)
+            .args(["config", "init"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("--force"));
+        assert!(std::fs::read_to_string(&custom)
+            .unwrap()
+            .contains("custom.example.test"));
+
+        actionbook()
+            .arg("--config")
+            .arg(&custom)
+            .args(["--json", "config", "init", "--force"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(r#""status":"overwritten""#));
+        assert!(!std::fs::read_to_string(&custom)
+            .unwrap()
+            .contains("custom.example.test"));
+    }
+
+    #[test]
+    fn config_validate_help() {
+        actionbook()
+            .args(["config", "validate", "--help"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("CDP port"));
+    }
+
+    #[test]
+    fn config_validate_json_reports_failure_once() {
+        let tmp = tempfile::tempdir().unwrap();
+        let custom = tmp.path().join("custom.toml");
+        std::fs::write(&custom, "[profiles.clash]\ncdp_port = 19222\n").unwrap();
+
+        let output = actionbook()
+            .arg("--config")
+            .arg(&custom)
+            .args(["--json", "config", "validate"])
+            .output()
+            .unwrap();
+
+        assert_eq!(output.status.code(), Some(1));
+        let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        assert_eq!(result["valid"], false);
+        assert!(result["error"].as_str().unwrap().contains("clash"));
+        assert!(!String::from_utf8_lossy(&output.stderr).contains("Error:"));
+    }
 }
 
 mod profile_command {
@@ -533,4 +769,572 @@ mod global_flags {
             .assert()
             .success();
     }
+
+    #[test]
+    fn global_timeout_bounds_long_running_command() {
+        let tmp = tempfile::tempdir().unwrap();
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+
+        // `extension serve` runs until interrupted, so only --timeout ends it
+        let start = std::time::Instant::now();
+        actionbook()
+            .env("HOME", tmp.path())
+            .env("XDG_DATA_HOME", tmp.path().join("data"))
+            .env("XDG_CONFIG_HOME", tmp.path().join("config"))
+            .args(["--timeout", "1", "extension", "serve", "--port"])
+            .arg(port.to_string())
+            .timeout(std::time::Duration::from_secs(20))
+            .assert()
+            .code(124)
+            .stderr(predicate::str::contains("global --timeout of 1s"));
+        assert!(start.elapsed() < std::time::Duration::from_secs(10));
+    }
+
+    #[cfg(not(feature = "stealth"))]
+    #[test]
+    fn stealth_flag_rejected_without_stealth_feature() {
+        let tmp = tempfile::tempdir().unwrap();
+        actionbook()
+            .env("HOME", tmp.path())
+            .env("XDG_CONFIG_HOME", tmp.path().join("config"))
+            .args(["--stealth", "browser", "status"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("built without stealth support"));
+    }
+
+    #[test]
+    fn global_timeout_coexists_with_subcommand_timeout() {
+        actionbook()
+            .args(["--timeout", "5", "browser", "wait", "--help"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("--timeout"));
+    }
+}
+
+mod extension_command {
+    use super::*;
+
+    #[test]
+    fn extension_dir_env_relocates_install_and_uninstall() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ext_dir = tmp.path().join("packaged-extension");
+        std::fs::create_dir_all(&ext_dir).unwrap();
+        std::fs::write(ext_dir.join("manifest.json"), r#"{"version": "0.3.1"}"#).unwrap();
+
+        // A default-location install that must be left alone
+        let default_ext = tmp.path().join("config/actionbook/extension");
+        std::fs::create_dir_all(&default_ext).unwrap();
+        std::fs::write(default_ext.join("manifest.json"), r#"{"version": "0.1.0"}"#).unwrap();
+
+        actionbook()
+            .env("HOME", tmp.path())
+            .env("XDG_CONFIG_HOME", tmp.path().join("config"))
+            .env("ACTIONBOOK_EXTENSION_DIR", &ext_dir)
+            .args(["--json", "extension", "path"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("packaged-extension"))
+            .stdout(predicate::str::contains("0.3.1"));
+
+        actionbook()
+            .env("HOME", tmp.path())
+            .env("XDG_CONFIG_HOME", tmp.path().join("config"))
+            .env("ACTIONBOOK_EXTENSION_DIR", &ext_dir)
+            .args(["extension", "uninstall"])
+            .assert()
+            .success();
+
+        assert!(!ext_dir.exists(), "Override dir should be removed");
+        assert!(
+            default_ext.join("manifest.json").exists(),
+            "Default install should be untouched"
+        );
+    }
+
+    #[test]
+    fn extension_dir_resolves_without_home() {
+        let tmp = tempfile::tempdir().unwrap();
+        let expected = tmp.path().join("xdg/actionbook/extension");
+
+        actionbook()
+            .env_remove("HOME")
+            .env_remove("ACTIONBOOK_CONFIG_HOME")
+            .env("XDG_CONFIG_HOME", tmp.path().join("xdg"))
+            .args(["extension", "path"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(expected.display().to_string()));
+    }
+
+    #[test]
+    fn extension_dir_config_field_is_used() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ext_dir = tmp.path().join("from-config");
+        let custom = tmp.path().join("custom.toml");
+        std::fs::write(
+            &custom,
+            format!("[browser]\nextension_dir = \"{}\"\n", ext_dir.display()),
+        )
+        .unwrap();
+
+        actionbook()
+            .env_remove("ACTIONBOOK_EXTENSION_DIR")
+            .arg("--config")
+            .arg(&custom)
+            .args(["extension", "path"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("from-config"));
+    }
+
+    #[test]
+    fn extension_dir_config_field_applies_to_install_and_uninstall() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ext_dir = tmp.path().join("from-config");
+        std::fs::create_dir_all(&ext_dir).unwrap();
+        std::fs::write(ext_dir.join("manifest.json"), r#"{"version": "0.3.1"}"#).unwrap();
+        let default_ext = tmp.path().join("config/actionbook/extension");
+        std::fs::create_dir_all(&default_ext).unwrap();
+        std::fs::write(default_ext.join("manifest.json"), r#"{"version": "0.1.0"}"#).unwrap();
+
+        // The override is resolved before any download, so a relative one
+        // fails install without touching the network
+        let relative = tmp.path().join("relative.toml");
+        std::fs::write(&relative, "[browser]\nextension_dir = \"relative/ext\"\n").unwrap();
+        actionbook()
+            .env_remove("ACTIONBOOK_EXTENSION_DIR")
+            .env("HOME", tmp.path())
+            .env("XDG_CONFIG_HOME", tmp.path().join("config"))
+            .arg("--config")
+            .arg(&relative)
+            .args(["extension", "install"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("browser.extension_dir must be an absolute path"));
+
+        let custom = tmp.path().join("custom.toml");
+        std::fs::write(
+            &custom,
+            format!("[browser]\nextension_dir = \"{}\"\n", ext_dir.display()),
+        )
+        .unwrap();
+        actionbook()
+            .env_remove("ACTIONBOOK_EXTENSION_DIR")
+            .env("HOME", tmp.path())
+            .env("XDG_CONFIG_HOME", tmp.path().join("config"))
+            .arg("--config")
+            .arg(&custom)
+            .args(["extension", "uninstall", "--keep-native-host"])
+            .assert()
+            .success();
+
+        assert!(!ext_dir.exists(), "Configured dir should be removed");
+        assert!(
+            default_ext.join("manifest.json").exists(),
+            "Default install should be untouched"
+        );
+    }
+
+    #[test]
+    fn extension_verify_reports_untracked_files() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ext_dir = tmp.path().join("extension");
+        std::fs::create_dir_all(&ext_dir).unwrap();
+        std::fs::write(ext_dir.join(".install-record.json"), "{}").unwrap();
+        std::fs::write(ext_dir.join("manifest.json"), r#"{"version": "0.1.0"}"#).unwrap();
+
+        actionbook()
+            .env("ACTIONBOOK_EXTENSION_DIR", &ext_dir)
+            .args(["--json", "extension", "verify"])
+            .assert()
+            .failure()
+            .stdout(predicate::str::contains(r#""status":"drift""#))
+            .stdout(predicate::str::contains(r#""extra":["manifest.json"]"#));
+    }
+
+    #[test]
+    fn reload_requires_a_running_isolated_bridge() {
+        let tmp = tempfile::tempdir().unwrap();
+
+        actionbook()
+            .env("HOME", tmp.path())
+            .env("XDG_DATA_HOME", tmp.path().join("data"))
+            .env("XDG_CONFIG_HOME", tmp.path().join("config"))
+            .args(["extension", "reload"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("No isolated bridge is running"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn isolated_serve_checks_bridge_port_before_launching_chrome() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let tmp = tempfile::tempdir().unwrap();
+        let ext_dir = tmp.path().join("extension");
+        std::fs::create_dir_all(&ext_dir).unwrap();
+        std::fs::write(ext_dir.join("manifest.json"), r#"{"version": "0.3.1"}"#).unwrap();
+
+        // Stand-in Chrome that only records that it was launched
+        let launched = tmp.path().join("chrome-launched");
+        let chrome = tmp.path().join("fake-chrome");
+        std::fs::write(
+            &chrome,
+            format!("#!/bin/sh\ntouch '{}'\n", launched.display()),
+        )
+        .unwrap();
+        std::fs::set_permissions(&chrome, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let config = tmp.path().join("config.toml");
+        std::fs::write(
+            &config,
+            format!("[browser]\nexecutable = \"{}\"\n", chrome.display()),
+        )
+        .unwrap();
+
+        let taken = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = taken.local_addr().unwrap().port();
+
+        actionbook()
+            .env("HOME", tmp.path())
+            .env("XDG_DATA_HOME", tmp.path().join("data"))
+            .env("XDG_CONFIG_HOME", tmp.path().join("config"))
+            .env("ACTIONBOOK_EXTENSION_DIR", &ext_dir)
+            .arg("--config")
+            .arg(&config)
+            .args(["extension", "serve", "--isolated", "--port", &port.to_string()])
+            .timeout(std::time::Duration::from_secs(30))
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("Failed to bind bridge"));
+
+        assert!(!launched.exists(), "Chrome must not be launched");
+    }
+
+    #[test]
+    fn serve_json_reports_token_write_failure_as_json() {
+        let tmp = tempfile::tempdir().unwrap();
+        // A regular file where the data directory should be makes every
+        // bridge file write fail.
+        let data_home = tmp.path().join("data");
+        std::fs::write(&data_home, "").unwrap();
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+
+        let output = actionbook()
+            .env("HOME", tmp.path())
+            .env("XDG_DATA_HOME", &data_home)
+            .env("XDG_CONFIG_HOME", tmp.path().join("config"))
+            .args(["--json", "--timeout", "1", "extension", "serve", "--port"])
+            .arg(port.to_string())
+            .timeout(std::time::Duration::from_secs(20))
+            .output()
+            .unwrap();
+
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let line = stderr
+            .lines()
+            .find(|line| line.contains("Failed to write token file"))
+            .expect("token write warning on stderr");
+        let warning: serde_json::Value =
+            serde_json::from_str(line).expect("warning line should be JSON");
+        assert_eq!(warning["level"], "warning");
+        assert!(warning["run_id"].is_string());
+    }
+
+    #[test]
+    fn serve_log_file_receives_status_lines() {
+        let tmp = tempfile::tempdir().unwrap();
+        let log = tmp.path().join("logs/serve.log");
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+
+        actionbook()
+            .env("HOME", tmp.path())
+            .env("XDG_DATA_HOME", tmp.path().join("data"))
+            .env("XDG_CONFIG_HOME", tmp.path().join("config"))
+            .args(["--timeout", "1", "extension", "serve", "--port"])
+            .arg(port.to_string())
+            .arg("--log-file")
+            .arg(&log)
+            .timeout(std::time::Duration::from_secs(20))
+            .assert()
+            .code(124);
+
+        let contents = std::fs::read_to_string(&log).unwrap();
+        let listening = format!("Bridge server listening on ws://127.0.0.1:{}", port);
+        assert!(contents.contains(&listening), "log file: {}", contents);
+        assert!(!contents.contains('\x1b'), "log file should not contain colour codes");
+    }
+
+    /// Contents of `path` once it exists; the ready event can precede the
+    /// bridge's file writes.
+    fn read_when_written(path: &std::path::Path) -> String {
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        loop {
+            match std::fs::read_to_string(path) {
+                Ok(contents) => return contents,
+                Err(e) if std::time::Instant::now() > deadline => {
+                    panic!("{} not written: {}", path.display(), e)
+                }
+                Err(_) => std::thread::sleep(std::time::Duration::from_millis(50)),
+            }
+        }
+    }
+
+    /// Run `--json --timeout 2 extension serve --port 0 <extra>` and return
+    /// its ready event and remaining output, with `inspect` called while the
+    /// bridge is still up. Checks that the timeout ends the run with 124 and
+    /// removes the bridge's token and PID files.
+    fn serve_until_timeout(
+        tmp: &std::path::Path,
+        extra: &[&str],
+        stdin: Option<&str>,
+        inspect: impl FnOnce(&serde_json::Value, &std::path::Path),
+    ) -> (serde_json::Value, std::process::Output) {
+        use std::io::{BufRead, Write};
+
+        let data = tmp.join("data");
+        let mut child = std::process::Command::new(env!("CARGO_BIN_EXE_actionbook"))
+            .env("HOME", tmp)
+            .env("XDG_DATA_HOME", &data)
+            .env("XDG_CONFIG_HOME", tmp.join("config"))
+            .args(["--json", "--timeout", "2", "extension", "serve", "--port", "0"])
+            .args(extra)
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .spawn()
+            .unwrap();
+        let mut input = child.stdin.take().unwrap();
+        if let Some(stdin) = stdin {
+            input.write_all(stdin.as_bytes()).unwrap();
+        }
+        drop(input);
+
+        let mut stdout = std::io::BufReader::new(child.stdout.take().unwrap());
+        let mut line = String::new();
+        stdout.read_line(&mut line).unwrap();
+        let ready: serde_json::Value = serde_json::from_str(line.trim()).unwrap();
+        let dir = data.join("actionbook");
+        inspect(&ready, &dir);
+
+        let output = child.wait_with_output().unwrap();
+        assert_eq!(output.status.code(), Some(124));
+        assert!(!dir.join("bridge-token").exists(), "token file left behind");
+        assert!(!dir.join("bridge-pid").exists(), "PID file left behind");
+        (ready, output)
+    }
+
+    #[test]
+    fn serve_port_zero_records_bound_port() {
+        let tmp = tempfile::tempdir().unwrap();
+
+        serve_until_timeout(tmp.path(), &[], None, |ready, dir| {
+            let port = ready["port"].as_u64().unwrap();
+            assert_ne!(port, 0);
+
+            let port_file = read_when_written(&dir.join("bridge-port"));
+            assert_eq!(port_file.trim(), port.to_string());
+            let pid_file = read_when_written(&dir.join("bridge-pid"));
+            assert!(
+                pid_file.trim().ends_with(&format!(":{}", port)),
+                "pid file: {}",
+                pid_file
+            );
+        });
+    }
+
+    #[test]
+    fn bridge_port_from_config_is_used_without_a_flag() {
+        let tmp = tempfile::tempdir().unwrap();
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let config_path = tmp.path().join("config.toml");
+        std::fs::write(&config_path, format!("[browser]\nbridge_port = {}\n", port)).unwrap();
+        let run = |args: &[&str]| {
+            actionbook()
+                .env("HOME", tmp.path())
+                .env("XDG_DATA_HOME", tmp.path().join("data"))
+                .env("XDG_CONFIG_HOME", tmp.path().join("config"))
+                .arg("--config")
+                .arg(&config_path)
+                .args(args)
+                .timeout(std::time::Duration::from_secs(20))
+                .output()
+                .unwrap()
+        };
+
+        let output = run(&["--json", "--timeout", "1", "extension", "serve"]);
+        assert_eq!(output.status.code(), Some(124));
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        let ready: serde_json::Value =
+            serde_json::from_str(stdout.lines().next().unwrap()).unwrap();
+        assert_eq!(ready["port"], port);
+
+        // With no bridge running, status checks the configured port
+        let output = run(&["extension", "status"]);
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert!(
+            stdout.contains(&format!("not running on port {}", port)),
+            "status output: {}",
+            stdout
+        );
+    }
+
+    #[test]
+    fn serve_token_stdin_uses_the_supplied_token() {
+        let tmp = tempfile::tempdir().unwrap();
+        let token = "abk_0123456789abcdef0123456789abcdef";
+        let stdin = format!("{}\n", token);
+
+        let (ready, output) =
+            serve_until_timeout(tmp.path(), &["--token-stdin"], Some(&stdin), |_, dir| {
+                let token_file = read_when_written(&dir.join("bridge-token"));
+                assert_eq!(token_file.trim(), token);
+            });
+
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(!stdout.contains(token) && !stderr.contains(token), "token was printed");
+        assert_eq!(ready["event"], "ready");
+        assert!(ready.get("token").is_none());
+    }
+
+    #[test]
+    fn serve_token_stdin_rejects_malformed_tokens() {
+        let tmp = tempfile::tempdir().unwrap();
+
+        actionbook()
+            .env("HOME", tmp.path())
+            .env("XDG_DATA_HOME", tmp.path().join("data"))
+            .env("XDG_CONFIG_HOME", tmp.path().join("config"))
+            .args(["extension", "serve", "--port", "0", "--token-stdin"])
+            .write_stdin("hunter2\n")
+            .timeout(std::time::Duration::from_secs(20))
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("32 hex characters"));
+    }
+
+    #[test]
+    fn extension_dir_env_must_be_absolute() {
+        actionbook()
+            .env("ACTIONBOOK_EXTENSION_DIR", "relative/extension")
+            .args(["extension", "path"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("must be an absolute path"));
+    }
+
+    #[test]
+    fn extension_logs_reports_unreachable_cdp_port() {
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+
+        actionbook()
+            .args(["extension", "logs", "--cdp-port", &port.to_string()])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("/json/list"));
+    }
+
+    #[test]
+    fn extension_release_base_must_be_https() {
+        let tmp = tempfile::tempdir().unwrap();
+        let custom = tmp.path().join("custom.toml");
+
+        actionbook()
+            .arg("--config")
+            .arg(&custom)
+            .args([
+                "config",
+                "set",
+                "browser.extension_release_base",
+                "http://mirror.example/api",
+            ])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("must use HTTPS"));
+
+        actionbook()
+            .arg("--config")
+            .arg(&custom)
+            .args([
+                "config",
+                "set",
+                "browser.extension_release_base",
+                "https://mirror.example/api",
+            ])
+            .assert()
+            .success();
+    }
+}
+
+mod reset_command {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn reset_needs_confirmation_then_removes_stale_files() {
+        let tmp = tempfile::tempdir().unwrap();
+        let data_dir = tmp.path().join("data").join("actionbook");
+        std::fs::create_dir_all(&data_dir).unwrap();
+        let mut exited = std::process::Command::new("true").spawn().unwrap();
+        let dead_pid = exited.id();
+        exited.wait().unwrap();
+        std::fs::write(data_dir.join("bridge-token"), "abk_test").unwrap();
+        std::fs::write(data_dir.join("bridge-port"), "19222").unwrap();
+        std::fs::write(data_dir.join("bridge-pid"), format!("{}:19222", dead_pid)).unwrap();
+
+        let reset = || {
+            let mut cmd = actionbook();
+            cmd.env("HOME", tmp.path())
+                .env("XDG_DATA_HOME", tmp.path().join("data"))
+                .env("XDG_CONFIG_HOME", tmp.path().join("config"))
+                .args(["--json", "reset"]);
+            cmd
+        };
+
+        let declined = reset().output().unwrap();
+        assert!(!declined.status.success(), "reset without --yes should fail");
+        let stdout = String::from_utf8_lossy(&declined.stdout);
+        assert!(stdout.contains(r#""error":"confirmation_required""#), "{}", stdout);
+        assert!(!String::from_utf8_lossy(&declined.stderr).contains("Error:"));
+        assert!(data_dir.join("bridge-pid").exists());
+
+        reset()
+            .arg("--yes")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(r#""status":"reset""#))
+            .stdout(predicate::str::contains("bridge-token"));
+        for name in ["bridge-token", "bridge-port", "bridge-pid"] {
+            assert!(!data_dir.join(name).exists(), "{} should be removed", name);
+        }
+
+        reset()
+            .arg("--yes")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(r#""status":"clean""#));
+    }
 }
@@ -0,0 +1,38 @@
+//! Helpers shared by the integration tests that launch a real browser.
+
+use actionbook::browser::{discover_all_browsers, SessionManager};
+use actionbook::config::{Config, ProfileConfig};
+use tokio::net::TcpListener;
+
+/// Whether a Chromium-based browser is installed. Tests that need one
+/// return early (after saying so) when it is not.
+pub fn browser_available() -> bool {
+    if discover_all_browsers().is_empty() {
+        eprintln!("No browser found, skipping");
+        return false;
+    }
+    true
+}
+
+/// Build a session manager with a throwaway headless profile.
+pub async fn test_session_manager(
+    profile: &str,
+    user_data_dir: &std::path::Path,
+) -> SessionManager {
+    let cdp_port = {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        listener.local_addr().unwrap().port()
+    };
+
+    let mut config = Config::default();
+    config.set_profile(
+        profile,
+        ProfileConfig {
+            cdp_port,
+            user_data_dir: Some(user_data_dir.display().to_string()),
+            headless: true,
+            ..Default::default()
+        },
+    );
+    SessionManager::new(config)
+}
@@ -0,0 +1,56 @@
+//! Integration tests for `SessionManager::evaluate`.
+//!
+//! These tests launch a real headless browser. They are skipped when no
+//! Chromium-based browser is installed.
+//!
+//! Run with: cargo test --test evaluate_test
+
+use actionbook::error::ActionbookError;
+
+mod common;
+
+#[tokio::test]
+async fn evaluate_returns_expression_value() {
+    if !common::browser_available() {
+        return;
+    }
+
+    let profile = "evaluate-test";
+    let user_data_dir = tempfile::tempdir().unwrap();
+    let manager = common::test_session_manager(profile, user_data_dir.path()).await;
+    let (_browser, mut handler) = manager.get_or_create_session(Some(profile)).await.unwrap();
+    tokio::spawn(async move {
+        use futures::StreamExt;
+        while handler.next().await.is_some() {}
+    });
+
+    let value = manager
+        .evaluate(Some(profile), None, "1+1", false)
+        .await
+        .unwrap();
+    assert_eq!(value, serde_json::json!(2));
+
+    let page = manager.get_active_page_info(Some(profile)).await.unwrap();
+    let value = manager
+        .evaluate(
+            Some(profile),
+            Some(&page.id),
+            "Promise.resolve('done')",
+            true,
+        )
+        .await
+        .unwrap();
+    assert_eq!(value, serde_json::json!("done"));
+
+    let err = manager
+        .evaluate(Some(profile), None, "throw new Error('boom')", false)
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(&err, ActionbookError::JavaScriptError(msg) if msg.contains("boom")),
+        "Expected a descriptive JavaScriptError, got: {}",
+        err
+    );
+
+    manager.close_session(Some(profile)).await.unwrap();
+}
@@ -6,6 +6,8 @@
 //!
 //! Run with: cargo test --test extension_bridge_test
 
+#![allow(deprecated)]
+
 use std::time::Duration;
 
 use futures::{SinkExt, StreamExt};
@@ -64,6 +66,7 @@ async fn recv_json(
 }
 
 /// Read one text message with a timeout.
+#[allow(clippy::manual_ok_err)]
 async fn recv_json_timeout(
     ws: &mut tokio_tungstenite::WebSocketStream<
         tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>,
@@ -95,6 +98,7 @@ async fn try_recv_json(
 }
 
 /// Try to read with a timeout. Returns None on timeout, close, or error.
+#[allow(clippy::manual_unwrap_or_default)]
 async fn try_recv_json_timeout(
     ws: &mut tokio_tungstenite::WebSocketStream<
         tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>,
@@ -167,123 +171,1213 @@ fn start_bridge(port: u16) -> (tokio::task::JoinHandle<()>, String) {
     (handle, token)
 }
 
-mod bridge_tests {
-    use super::*;
-    use assert_cmd::Command;
-    use predicates::prelude::*;
+mod bridge_tests {
+    use super::*;
+    use assert_cmd::Command;
+    use predicates::prelude::*;
+
+    /// Test: generate_token produces valid format.
+    #[test]
+    fn generate_token_format() {
+        let token = actionbook::browser::extension_bridge::generate_token();
+        assert!(token.starts_with("abk_"), "Token should start with abk_");
+        assert_eq!(token.len(), 4 + 32, "Token should be abk_ + 32 hex chars");
+        // Verify all chars after prefix are hex
+        assert!(token[4..].chars().all(|c| c.is_ascii_hexdigit()));
+    }
+
+    /// Test: generate_token produces unique tokens.
+    #[test]
+    fn generate_token_uniqueness() {
+        let t1 = actionbook::browser::extension_bridge::generate_token();
+        let t2 = actionbook::browser::extension_bridge::generate_token();
+        assert_ne!(t1, t2, "Tokens should be unique");
+    }
+
+    /// Test: Connection with invalid token receives hello_error and is then closed.
+    #[tokio::test]
+    async fn invalid_token_closes_connection() {
+        let port = free_port().await;
+        let (server_handle, _token) = start_bridge(port);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let mut ws = ws_connect(port).await;
+
+        // Send hello with wrong token
+        send_json(
+            &mut ws,
+            serde_json::json!({
+                "type": "hello",
+                "role": "extension",
+                "token": "abk_wrong_token_value_00000000000",
+                "version": "0.2.0"
+            }),
+        )
+        .await;
+
+        // The server should send a hello_error with invalid_token before closing
+        let result = try_recv_json_timeout(&mut ws, 2000).await;
+        assert!(result.is_some(), "Should receive hello_error before connection close");
+        let msg = result.unwrap();
+        assert_eq!(msg["type"], "hello_error");
+        assert_eq!(msg["error"], "invalid_token");
+
+        // Connection should be closed after the error message
+        let after = try_recv_json_timeout(&mut ws, 2000).await;
+        assert!(after.is_none(), "Connection should be closed after hello_error");
+
+        server_handle.abort();
+    }
+
+    /// Test: Connection without hello message is closed.
+    #[tokio::test]
+    async fn no_hello_closes_connection() {
+        let port = free_port().await;
+        let (server_handle, _token) = start_bridge(port);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let mut ws = ws_connect(port).await;
+
+        // Send a non-hello message (old-style extension identification)
+        send_json(
+            &mut ws,
+            serde_json::json!({ "type": "extension" }),
+        )
+        .await;
+
+        // Should be closed
+        let result = try_recv_json_timeout(&mut ws, 2000).await;
+        assert!(result.is_none(), "Connection should be closed without hello");
+
+        server_handle.abort();
+    }
+
+    /// Test: Matching proto version is acknowledged with the bridge's proto.
+    #[tokio::test]
+    async fn matching_proto_is_acknowledged() {
+        let port = free_port().await;
+        let (server_handle, token) = start_bridge(port);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let mut ws = ws_connect(port).await;
+        send_json(
+            &mut ws,
+            serde_json::json!({
+                "type": "hello",
+                "role": "extension",
+                "token": token,
+                "version": "0.2.0",
+                "proto": 1
+            }),
+        )
+        .await;
+
+        let ack = recv_json_timeout(&mut ws, 3000)
+            .await
+            .expect("Should receive hello_ack");
+        assert_eq!(ack["type"], "hello_ack");
+        assert_eq!(ack["role"], "bridge");
+        assert_eq!(ack["proto"], 1);
+
+        server_handle.abort();
+    }
+
+    /// Test: Unsupported proto version gets a descriptive hello_error, then close.
+    #[tokio::test]
+    async fn mismatching_proto_is_rejected() {
+        let port = free_port().await;
+        let (server_handle, token) = start_bridge(port);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let mut ws = ws_connect(port).await;
+        send_json(
+            &mut ws,
+            serde_json::json!({
+                "type": "hello",
+                "role": "extension",
+                "token": token,
+                "version": "0.2.0",
+                "proto": 99
+            }),
+        )
+        .await;
+
+        let msg = try_recv_json_timeout(&mut ws, 2000)
+            .await
+            .expect("Should receive hello_error before connection close");
+        assert_eq!(msg["type"], "hello_error");
+        assert_eq!(msg["error"], "proto_mismatch");
+        assert!(msg["message"].as_str().unwrap().contains("99"));
+        assert_eq!(msg["supported_proto"], serde_json::json!([1]));
+
+        let after = try_recv_json_timeout(&mut ws, 2000).await;
+        assert!(after.is_none(), "Connection should be closed after hello_error");
+
+        server_handle.abort();
+    }
+
+    /// Test: A proto that is present but not a number is rejected, not defaulted.
+    #[tokio::test]
+    async fn non_numeric_proto_is_rejected() {
+        let port = free_port().await;
+        let (server_handle, token) = start_bridge(port);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let mut ws = ws_connect(port).await;
+        send_json(
+            &mut ws,
+            serde_json::json!({
+                "type": "hello",
+                "role": "extension",
+                "token": token,
+                "version": "0.2.0",
+                "proto": "1"
+            }),
+        )
+        .await;
+
+        let msg = try_recv_json_timeout(&mut ws, 2000)
+            .await
+            .expect("Should receive hello_error before connection close");
+        assert_eq!(msg["type"], "hello_error");
+        assert_eq!(msg["error"], "proto_mismatch");
+
+        server_handle.abort();
+    }
+
+    /// Test: CLI command sent without extension connected gets an error response.
+    #[tokio::test]
+    async fn cli_without_extension_gets_error() {
+        let port = free_port().await;
+        let (server_handle, token) = start_bridge(port);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        // Connect as CLI with valid token
+        let mut cli_ws = ws_connect(port).await;
+        hello_cli(&mut cli_ws, &token).await;
+
+        // Send a CLI command
+        send_json(
+            &mut cli_ws,
+            serde_json::json!({
+                "id": 1,
+                "method": "Page.navigate",
+                "params": { "url": "https://example.com" }
+            }),
+        )
+        .await;
+
+        // Should get error response about extension not connected
+        let response = recv_json_timeout(&mut cli_ws, 3000)
+            .await
+            .expect("Should receive response");
+
+        assert!(response.get("error").is_some(), "Should have error field");
+        let error_msg = response["error"]["message"]
+            .as_str()
+            .unwrap_or("");
+        assert!(
+            error_msg.contains("not connected"),
+            "Error should mention extension not connected: {}",
+            error_msg
+        );
+
+        server_handle.abort();
+    }
+
+    /// Test: After an extension is rejected for a stale token, CLI commands
+    /// report the token mismatch instead of a generic "not connected".
+    #[tokio::test]
+    async fn extension_token_mismatch_is_reported_to_cli() {
+        let port = free_port().await;
+        let (server_handle, token) = start_bridge(port);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        // Extension connects with a stale token and is rejected
+        let mut ext_ws = ws_connect(port).await;
+        send_json(
+            &mut ext_ws,
+            serde_json::json!({
+                "type": "hello",
+                "role": "extension",
+                "token": "abk_00000000000000000000000000000000",
+                "version": "0.2.0"
+            }),
+        )
+        .await;
+        let rejected = try_recv_json_timeout(&mut ext_ws, 2000)
+            .await
+            .expect("Should receive hello_error");
+        assert_eq!(rejected["error"], "invalid_token");
+
+        // CLI with the current token sees the specific diagnostic
+        let err = actionbook::browser::extension_bridge::send_command_with_token(
+            port,
+            "Extension.pingExtension",
+            serde_json::json!({}),
+            &token,
+        )
+        .await
+        .expect_err("Ping should fail without an authenticated extension");
+        assert!(
+            err.to_string()
+                .contains(actionbook::browser::extension_bridge::EXTENSION_TOKEN_MISMATCH),
+            "Error should report the token mismatch: {}",
+            err
+        );
+
+        // Once the extension reconnects with the right token the diagnostic clears
+        let mut ext_ws = ws_connect(port).await;
+        hello_extension(&mut ext_ws, &token).await;
+        drop(ext_ws);
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        let err = actionbook::browser::extension_bridge::send_command_with_token(
+            port,
+            "Extension.pingExtension",
+            serde_json::json!({}),
+            &token,
+        )
+        .await
+        .expect_err("Ping should fail after the extension disconnects");
+        assert!(
+            err.to_string().contains("not connected"),
+            "Error should fall back to not connected: {}",
+            err
+        );
+
+        server_handle.abort();
+    }
+
+    /// Test: Extension.ping is answered by the bridge and reports extension presence.
+    #[tokio::test]
+    async fn bridge_answers_extension_ping() {
+        use actionbook::browser::extension_bridge::send_command_with_token;
+
+        let port = free_port().await;
+        let (server_handle, token) = start_bridge(port);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        // No extension yet: the bridge still pongs
+        let pong = send_command_with_token(port, "Extension.ping", serde_json::json!({}), &token)
+            .await
+            .expect("Bridge should answer Extension.ping without an extension");
+        assert_eq!(pong["status"], "pong");
+        assert_eq!(pong["source"], "bridge");
+        assert_eq!(pong["extension_connected"], false);
+
+        // With an extension connected the pong says so, without forwarding
+        let mut ext_ws = ws_connect(port).await;
+        hello_extension(&mut ext_ws, &token).await;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let pong = send_command_with_token(port, "Extension.ping", serde_json::json!({}), &token)
+            .await
+            .unwrap();
+        assert_eq!(pong["extension_connected"], true);
+        assert!(
+            try_recv_json_timeout(&mut ext_ws, 300).await.is_none(),
+            "Extension.ping should not be forwarded to the extension"
+        );
+
+        server_handle.abort();
+    }
+
+    /// Test: a response carrying the right id but a stale generation, or an id
+    /// nobody is waiting for, does not resolve the pending request.
+    #[tokio::test]
+    async fn stale_generation_response_does_not_resolve_request() {
+        use actionbook::browser::extension_bridge::send_command_with_token;
+
+        let port = free_port().await;
+        let (server_handle, token) = start_bridge(port);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let mut ext_ws = ws_connect(port).await;
+        hello_extension(&mut ext_ws, &token).await;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let t = token.clone();
+        let cli = tokio::spawn(async move {
+            send_command_with_token(port, "Extension.listTabs", serde_json::json!({}), &t).await
+        });
+        let cmd = recv_json_timeout(&mut ext_ws, 3000).await.unwrap();
+        let generation = cmd["gen"]
+            .as_u64()
+            .expect("command should carry a generation");
+
+        // Unknown id and stale generation are both dropped
+        send_json(
+            &mut ext_ws,
+            serde_json::json!({ "id": 9999, "gen": generation, "result": { "stale": "id" } }),
+        )
+        .await;
+        send_json(
+            &mut ext_ws,
+            serde_json::json!({
+                "id": cmd["id"],
+                "gen": generation.wrapping_add(1),
+                "result": { "stale": "gen" }
+            }),
+        )
+        .await;
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(
+            !cli.is_finished(),
+            "stale responses must not resolve the request"
+        );
+
+        send_json(
+            &mut ext_ws,
+            serde_json::json!({ "id": cmd["id"], "gen": generation, "result": { "tabs": [] } }),
+        )
+        .await;
+        let result = cli
+            .await
+            .unwrap()
+            .expect("matching response should resolve");
+        assert_eq!(result, serde_json::json!({ "tabs": [] }));
+
+        let pong = send_command_with_token(port, "Extension.ping", serde_json::json!({}), &token)
+            .await
+            .unwrap();
+        assert_eq!(pong["mismatched_responses"], 2);
+
+        server_handle.abort();
+    }
+
+    /// Test: a plain CLI connection can send a second command after the
+    /// first is answered, and each response carries the client's own id.
+    #[tokio::test]
+    async fn cli_connection_accepts_multiple_commands() {
+        let port = free_port().await;
+        let (server_handle, token) = start_bridge(port);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let mut ext_ws = ws_connect(port).await;
+        hello_extension(&mut ext_ws, &token).await;
+        let mut cli_ws = ws_connect(port).await;
+        hello_cli(&mut cli_ws, &token).await;
+
+        for (cli_id, expression) in [(41, "first"), (42, "second")] {
+            send_json(
+                &mut cli_ws,
+                serde_json::json!({
+                    "id": cli_id,
+                    "method": "Runtime.evaluate",
+                    "params": { "expression": expression },
+                }),
+            )
+            .await;
+            let cmd = recv_json_timeout(&mut ext_ws, 3000)
+                .await
+                .expect("each command should reach the extension");
+            assert_eq!(cmd["params"]["expression"], expression);
+            send_json(
+                &mut ext_ws,
+                serde_json::json!({
+                    "id": cmd["id"],
+                    "gen": cmd["gen"],
+                    "result": { "value": expression }
+                }),
+            )
+            .await;
+
+            let resp = recv_json_timeout(&mut cli_ws, 3000)
+                .await
+                .expect("each command should be answered");
+            assert_eq!(resp["id"], cli_id);
+            assert_eq!(resp["result"]["value"], expression);
+        }
+
+        server_handle.abort();
+    }
+
+    /// Test: one `BridgeClient` carries three commands over a single CLI
+    /// connection, including two in flight at once answered out of order.
+    #[tokio::test]
+    async fn bridge_client_reuses_one_connection() {
+        use actionbook::browser::extension_bridge::BridgeClient;
+
+        let port = free_port().await;
+        let (server_handle, token) = start_bridge(port);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let mut ext_ws = ws_connect(port).await;
+        hello_extension(&mut ext_ws, &token).await;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let client = std::sync::Arc::new(
+            BridgeClient::connect_with_token(port, &token)
+                .await
+                .unwrap(),
+        );
+
+        let c = std::sync::Arc::clone(&client);
+        let first = tokio::spawn(async move {
+            c.call("Runtime.evaluate", serde_json::json!({ "expression": "1" }))
+                .await
+        });
+        let cmd1 = recv_json_timeout(&mut ext_ws, 3000).await.unwrap();
+        let c = std::sync::Arc::clone(&client);
+        let second = tokio::spawn(async move {
+            c.call("Runtime.evaluate", serde_json::json!({ "expression": "2" }))
+                .await
+        });
+        let cmd2 = recv_json_timeout(&mut ext_ws, 3000).await.unwrap();
+        assert_eq!(cmd2["params"]["expression"], "2");
+
+        // Answer the second command first
+        for cmd in [&cmd2, &cmd1] {
+            send_json(
+                &mut ext_ws,
+                serde_json::json!({
+                    "id": cmd["id"],
+                    "gen": cmd["gen"],
+                    "result": { "value": cmd["params"]["expression"] }
+                }),
+            )
+            .await;
+        }
+        assert_eq!(
+            second.await.unwrap().unwrap(),
+            serde_json::json!({ "value": "2" })
+        );
+        assert_eq!(
+            first.await.unwrap().unwrap(),
+            serde_json::json!({ "value": "1" })
+        );
+
+        // Third call on the same connection; errors come back as Err
+        let c = std::sync::Arc::clone(&client);
+        let third =
+            tokio::spawn(async move { c.call("Extension.listTabs", serde_json::json!({})).await });
+        let cmd3 = recv_json_timeout(&mut ext_ws, 3000).await.unwrap();
+        send_json(
+            &mut ext_ws,
+            serde_json::json!({
+                "id": cmd3["id"],
+                "gen": cmd3["gen"],
+                "error": { "code": -32000, "message": "tabs unavailable" }
+            }),
+        )
+        .await;
+        let err = third.await.unwrap().unwrap_err();
+        assert!(err.to_string().contains("tabs unavailable"), "{}", err);
+
+        // Bridge-answered commands work on the session too
+        let pong = client
+            .call("Extension.ping", serde_json::json!({}))
+            .await
+            .unwrap();
+        assert_eq!(pong["status"], "pong");
+        assert_eq!(pong["pending_requests"], 0);
+
+        server_handle.abort();
+    }
+
+    /// Test: a two-command batch comes back in request order even when the
+    /// extension answers out of order, with a mix of success and error.
+    #[tokio::test]
+    async fn batch_results_keep_request_order() {
+        use actionbook::browser::extension_bridge::send_command_batch_with_token;
+
+        let port = free_port().await;
+        let (server_handle, token) = start_bridge(port);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let mut ext_ws = ws_connect(port).await;
+        hello_extension(&mut ext_ws, &token).await;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let empty = send_command_batch_with_token(port, vec![], &token).await.unwrap();
+        assert!(empty.is_empty());
+
+        let t = token.clone();
+        let batch = tokio::spawn(async move {
+            send_command_batch_with_token(
+                port,
+                vec![
+                    (
+                        "Runtime.evaluate".to_string(),
+                        serde_json::json!({ "expression": "1" }),
+                    ),
+                    ("Extension.listTabs".to_string(), serde_json::json!({})),
+                ],
+                &t,
+            )
+            .await
+        });
+
+        // Both commands are forwarded before either is answered
+        let cmd_a = recv_json_timeout(&mut ext_ws, 3000).await.unwrap();
+        let cmd_b = recv_json_timeout(&mut ext_ws, 3000).await.unwrap();
+        let (eval, tabs) = if cmd_a["method"] == "Runtime.evaluate" {
+            (cmd_a, cmd_b)
+        } else {
+            (cmd_b, cmd_a)
+        };
+        send_json(
+            &mut ext_ws,
+            serde_json::json!({
+                "id": tabs["id"],
+                "gen": tabs["gen"],
+                "error": { "code": -32000, "message": "tabs unavailable" }
+            }),
+        )
+        .await;
+        send_json(
+            &mut ext_ws,
+            serde_json::json!({ "id": eval["id"], "gen": eval["gen"], "result": { "value": 1 } }),
+        )
+        .await;
+
+        let results = batch.await.unwrap().unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(
+            results[0].as_ref().unwrap(),
+            &serde_json::json!({ "value": 1 })
+        );
+        let err = results[1].as_ref().unwrap_err();
+        assert!(err.to_string().contains("tabs unavailable"), "{}", err);
+
+        server_handle.abort();
+    }
+
+    /// Test: `list_tabs_with_token` decodes the extension's `Extension.listTabs`
+    /// payload into `TabInfo`, tolerating extra and missing fields.
+    #[tokio::test]
+    async fn list_tabs_decodes_tab_info() {
+        use actionbook::browser::extension_bridge::{list_tabs_with_token, TabInfo};
+
+        let port = free_port().await;
+        let (server_handle, token) = start_bridge(port);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let mut ext_ws = ws_connect(port).await;
+        hello_extension(&mut ext_ws, &token).await;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let t = token.clone();
+        let cli = tokio::spawn(async move { list_tabs_with_token(port, &t).await });
+        let cmd = recv_json_timeout(&mut ext_ws, 3000).await.unwrap();
+        assert_eq!(cmd["method"], "Extension.listTabs");
+        send_json(
+            &mut ext_ws,
+            serde_json::json!({
+                "id": cmd["id"],
+                "gen": cmd["gen"],
+                "result": { "tabs": [
+                    {
+                        "id": 7,
+                        "title": "Example",
+                        "url": "https://example.com/",
+                        "active": true,
+                        "windowId": 1
+                    },
+                    { "id": 8, "active": false, "windowId": 1 }
+                ] }
+            }),
+        )
+        .await;
+
+        let tabs = cli.await.unwrap().expect("tabs payload should decode");
+        assert_eq!(
+            tabs,
+            vec![
+                TabInfo {
+                    id: 7,
+                    url: "https://example.com/".to_string(),
+                    title: "Example".to_string(),
+                    active: true,
+                },
+                TabInfo {
+                    id: 8,
+                    url: String::new(),
+                    title: String::new(),
+                    active: false,
+                },
+            ]
+        );
+
+        server_handle.abort();
+    }
+
+    /// Test: with two extensions connected under different instance ids, a
+    /// command with `target_instance` reaches only that extension, and an
+    /// untargeted or unknown-target command is rejected.
+    #[tokio::test]
+    async fn commands_route_to_target_instance() {
+        use actionbook::browser::extension_bridge::send_command_with_token;
+
+        let port = free_port().await;
+        let (server_handle, token) = start_bridge(port);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let mut extensions = Vec::new();
+        for instance_id in ["work", "personal"] {
+            let mut ws = ws_connect(port).await;
+            send_json(
+                &mut ws,
+                serde_json::json!({
+                    "type": "hello",
+                    "role": "extension",
+                    "token": token,
+                    "version": "0.2.0",
+                    "instance_id": instance_id,
+                }),
+            )
+            .await;
+            let ack = recv_json_timeout(&mut ws, 3000).await.unwrap();
+            assert_eq!(ack["type"], "hello_ack");
+            extensions.push(ws);
+        }
+        let (mut work_ws, mut personal_ws) = {
+            let mut it = extensions.into_iter();
+            (it.next().unwrap(), it.next().unwrap())
+        };
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let pong = send_command_with_token(port, "Extension.ping", serde_json::json!({}), &token)
+            .await
+            .unwrap();
+        let instances: Vec<&str> = pong["extension_instances"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|i| i["id"].as_str().unwrap())
+            .collect();
+        assert_eq!(instances, ["personal", "work"]);
+
+        // Targeted command reaches only the named extension
+        let mut cli_ws = ws_connect(port).await;
+        hello_cli(&mut cli_ws, &token).await;
+        send_json(
+            &mut cli_ws,
+            serde_json::json!({
+                "id": 1,
+                "method": "Extension.listTabs",
+                "params": {},
+                "target_instance": "personal",
+            }),
+        )
+        .await;
+        let cmd = recv_json_timeout(&mut personal_ws, 3000)
+            .await
+            .expect("targeted extension should receive the command");
+        assert_eq!(cmd["method"], "Extension.listTabs");
+        assert!(try_recv_json_timeout(&mut work_ws, 200).await.is_none());
+        send_json(
+            &mut personal_ws,
+            serde_json::json!({ "id": cmd["id"], "gen": cmd["gen"], "result": { "tabs": ["p"] } }),
+        )
+        .await;
+        let resp = recv_json_timeout(&mut cli_ws, 3000).await.unwrap();
+        assert_eq!(resp["id"], 1);
+        assert_eq!(resp["result"]["tabs"], serde_json::json!(["p"]));
+
+        // Without a target the choice is ambiguous
+        let err =
+            send_command_with_token(port, "Extension.listTabs", serde_json::json!({}), &token)
+                .await
+                .unwrap_err();
+        assert!(
+            err.to_string().contains("Multiple extensions connected"),
+            "{}",
+            err
+        );
+
+        // An unknown target is rejected
+        let mut cli_ws = ws_connect(port).await;
+        hello_cli(&mut cli_ws, &token).await;
+        send_json(
+            &mut cli_ws,
+            serde_json::json!({
+                "id": 2,
+                "method": "Extension.listTabs",
+                "params": {},
+                "target_instance": "missing",
+            }),
+        )
+        .await;
+        let resp = recv_json_timeout(&mut cli_ws, 3000).await.unwrap();
+        assert!(resp["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("'missing' not connected"));
+
+        // Once one extension leaves, untargeted commands go to the other
+        drop(personal_ws);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let cli = tokio::spawn(async move {
+            send_command_with_token(port, "Extension.listTabs", serde_json::json!({}), &token).await
+        });
+        let cmd = recv_json_timeout(&mut work_ws, 3000)
+            .await
+            .expect("sole remaining extension should receive the command");
+        send_json(
+            &mut work_ws,
+            serde_json::json!({ "id": cmd["id"], "gen": cmd["gen"], "result": { "tabs": ["w"] } }),
+        )
+        .await;
+        let result = cli.await.unwrap().unwrap();
+        assert_eq!(result["tabs"], serde_json::json!(["w"]));
+
+        server_handle.abort();
+    }
+
+    type TestSocket = tokio_tungstenite::WebSocketStream<
+        tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>,
+    >;
+
+    /// Connect an extension and a CLI, and leave one CLI request pending
+    /// with the extension. Returns both sockets and the forwarded command.
+    async fn pending_cli_request(
+        port: u16,
+        token: &str,
+    ) -> (TestSocket, TestSocket, serde_json::Value) {
+        let mut ext_ws = ws_connect(port).await;
+        hello_extension(&mut ext_ws, token).await;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let mut cli_ws = ws_connect(port).await;
+        hello_cli(&mut cli_ws, token).await;
+        send_json(
+            &mut cli_ws,
+            serde_json::json!({ "id": 1, "method": "Extension.listTabs", "params": {} }),
+        )
+        .await;
+        let cmd = recv_json_timeout(&mut ext_ws, 3000).await.unwrap();
+        (ext_ws, cli_ws, cmd)
+    }
+
+    /// Test: closing the CLI connection mid-request drops the pending entry
+    /// right away and forwards a cancel to the extension.
+    #[tokio::test]
+    async fn cli_close_cancels_pending_request() {
+        use actionbook::browser::extension_bridge::send_command_with_token;
+
+        let port = free_port().await;
+        let (server_handle, token) = start_bridge(port);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let (mut ext_ws, mut cli_ws, cmd) = pending_cli_request(port, &token).await;
+
+        cli_ws.close(None).await.unwrap();
+        let cancel = recv_json_timeout(&mut ext_ws, 1000)
+            .await
+            .expect("Extension should be told the request was cancelled");
+        assert_eq!(cancel["type"], "cancel");
+        assert_eq!(cancel["id"], cmd["id"]);
+
+        // The pending entry is gone, so a late reply matches nothing
+        send_json(
+            &mut ext_ws,
+            serde_json::json!({ "id": cmd["id"], "gen": cmd["gen"], "result": {} }),
+        )
+        .await;
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let pong = send_command_with_token(port, "Extension.ping", serde_json::json!({}), &token)
+            .await
+            .unwrap();
+        assert_eq!(pong["mismatched_responses"], 1);
+
+        server_handle.abort();
+    }
+
+    /// Test: a CLI that vanishes mid-flight without a Close frame (e.g. a
+    /// killed process) leaves no pending entry behind.
+    #[tokio::test]
+    async fn dropped_cli_cleans_up_pending_state() {
+        use actionbook::browser::extension_bridge::send_command_with_token;
+
+        let port = free_port().await;
+        let (server_handle, token) = start_bridge(port);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let (mut ext_ws, cli_ws, _cmd) = pending_cli_request(port, &token).await;
+        let pong = send_command_with_token(port, "Extension.ping", serde_json::json!({}), &token)
+            .await
+            .unwrap();
+        assert_eq!(pong["pending_requests"], 1);
 
-    /// Test: generate_token produces valid format.
-    #[test]
-    fn generate_token_format() {
-        let token = actionbook::browser::extension_bridge::generate_token();
-        assert!(token.starts_with("abk_"), "Token should start with abk_");
-        assert_eq!(token.len(), 4 + 32, "Token should be abk_ + 32 hex chars");
-        // Verify all chars after prefix are hex
-        assert!(token[4..].chars().all(|c| c.is_ascii_hexdigit()));
-    }
+        drop(cli_ws);
+        // The cancel forwarded to the extension marks the cleanup as done
+        recv_json_timeout(&mut ext_ws, 1000)
+            .await
+            .expect("Dropped CLI should cancel its request");
 
-    /// Test: generate_token produces unique tokens.
-    #[test]
-    fn generate_token_uniqueness() {
-        let t1 = actionbook::browser::extension_bridge::generate_token();
-        let t2 = actionbook::browser::extension_bridge::generate_token();
-        assert_ne!(t1, t2, "Tokens should be unique");
+        let pong = send_command_with_token(port, "Extension.ping", serde_json::json!({}), &token)
+            .await
+            .unwrap();
+        assert_eq!(pong["pending_requests"], 0);
+
+        server_handle.abort();
     }
 
-    /// Test: Connection with invalid token receives hello_error and is then closed.
+    /// Test: once shutdown begins, new connections get an immediate
+    /// "shutting down" close frame while in-flight requests still complete.
     #[tokio::test]
-    async fn invalid_token_closes_connection() {
+    async fn connection_during_shutdown_gets_close_frame() {
+        use actionbook::browser::extension_bridge::{serve_with_shutdown, SHUTTING_DOWN_REASON};
+
         let port = free_port().await;
-        let (server_handle, _token) = start_bridge(port);
+        let token = actionbook::browser::extension_bridge::generate_token();
+        let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel();
+        let t = token.clone();
+        let server_handle =
+            tokio::spawn(async move { serve_with_shutdown(port, t, shutdown_rx, false).await });
         tokio::time::sleep(Duration::from_millis(100)).await;
 
-        let mut ws = ws_connect(port).await;
+        let mut ext_ws = ws_connect(port).await;
+        hello_extension(&mut ext_ws, &token).await;
+        tokio::time::sleep(Duration::from_millis(50)).await;
 
-        // Send hello with wrong token
+        let mut cli_ws = ws_connect(port).await;
+        hello_cli(&mut cli_ws, &token).await;
         send_json(
-            &mut ws,
+            &mut cli_ws,
+            serde_json::json!({ "id": 1, "method": "Extension.listTabs", "params": {} }),
+        )
+        .await;
+        let cmd = recv_json_timeout(&mut ext_ws, 3000).await.unwrap();
+
+        shutdown_tx.send(()).unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let mut late_ws = ws_connect(port).await;
+        let frame = tokio::time::timeout(Duration::from_secs(2), late_ws.next())
+            .await
+            .expect("late connection should not hang");
+        match frame {
+            Some(Ok(Message::Close(Some(close)))) => {
+                assert_eq!(close.reason.as_str(), SHUTTING_DOWN_REASON)
+            }
+            other => panic!("Expected a shutdown close frame, got {:?}", other),
+        }
+
+        // The in-flight request is still answered during the drain
+        send_json(
+            &mut ext_ws,
+            serde_json::json!({ "id": cmd["id"], "gen": cmd["gen"], "result": { "tabs": [] } }),
+        )
+        .await;
+        let resp = recv_json_timeout(&mut cli_ws, 3000)
+            .await
+            .expect("in-flight request should complete");
+        assert_eq!(resp["result"]["tabs"], serde_json::json!([]));
+
+        tokio::time::timeout(Duration::from_secs(3), server_handle)
+            .await
+            .expect("server should exit once drained")
+            .unwrap()
+            .unwrap();
+    }
+
+    /// Test: `extension restart` stops the running bridge by its PID, waits
+    /// for the port, and brings up a new bridge whose token replaces the old
+    /// one. Under --json it prints a single ready event naming the old bridge.
+    #[tokio::test]
+    async fn restart_replaces_listener_and_token() {
+        use std::io::BufRead;
+
+        let tmp = tempfile::tempdir().unwrap();
+        let port = free_port().await;
+        let spawn = |subcommand: &str| {
+            std::process::Command::new(env!("CARGO_BIN_EXE_actionbook"))
+                .env("HOME", tmp.path())
+                .env("XDG_DATA_HOME", tmp.path().join("data"))
+                .env("XDG_CONFIG_HOME", tmp.path().join("config"))
+                .args(["--json", "extension", subcommand, "--port"])
+                .arg(port.to_string())
+                .stdout(std::process::Stdio::piped())
+                .stderr(std::process::Stdio::null())
+                .spawn()
+                .expect("Should spawn actionbook")
+        };
+        let read_ready = |child: &mut std::process::Child| {
+            let mut stdout = std::io::BufReader::new(child.stdout.take().unwrap());
+            tokio::task::spawn_blocking(move || {
+                let mut line = String::new();
+                stdout.read_line(&mut line).unwrap();
+                // Hand the reader back so the pipe stays open
+                (serde_json::from_str::<serde_json::Value>(line.trim()).unwrap(), stdout)
+            })
+        };
+
+        let mut serve = spawn("serve");
+        let (old_ready, _old_stdout) =
+            tokio::time::timeout(Duration::from_secs(10), read_ready(&mut serve))
+                .await
+                .expect("Ready line should arrive")
+                .unwrap();
+        let old_token = old_ready["token"].as_str().unwrap().to_string();
+        // Reap the old bridge as soon as it exits; as a zombie it would still
+        // look alive to restart's PID check
+        let serve_pid = serve.id();
+        let serve_exit = std::thread::spawn(move || serve.wait());
+
+        let mut restart = spawn("restart");
+        let (ready, _stdout) =
+            tokio::time::timeout(Duration::from_secs(20), read_ready(&mut restart))
+                .await
+                .expect("Restart should print the ready event")
+                .unwrap();
+        assert_eq!(ready["event"], "ready");
+        assert_eq!(ready["replaced"]["pid"], serve_pid);
+        assert_eq!(ready["replaced"]["forced"], false);
+        let new_token = ready["token"].as_str().unwrap().to_string();
+        assert_ne!(old_token, new_token);
+
+        // Restart waits for the old bridge process to exit before starting
+        assert!(serve_exit.is_finished(), "Old bridge should have exited");
+
+        let mut stale_ws = ws_connect(port).await;
+        send_json(
+            &mut stale_ws,
             serde_json::json!({
                 "type": "hello",
-                "role": "extension",
-                "token": "abk_wrong_token_value_00000000000",
+                "role": "cli",
+                "token": old_token,
                 "version": "0.2.0"
             }),
         )
         .await;
+        let rejected = try_recv_json_timeout(&mut stale_ws, 2000)
+            .await
+            .expect("old token should get hello_error");
+        assert_eq!(rejected["type"], "hello_error");
 
-        // The server should send a hello_error with invalid_token before closing
-        let result = try_recv_json_timeout(&mut ws, 2000).await;
-        assert!(result.is_some(), "Should receive hello_error before connection close");
-        let msg = result.unwrap();
-        assert_eq!(msg["type"], "hello_error");
-        assert_eq!(msg["error"], "invalid_token");
+        let mut cli_ws = ws_connect(port).await;
+        hello_cli(&mut cli_ws, &new_token).await;
 
-        // Connection should be closed after the error message
-        let after = try_recv_json_timeout(&mut ws, 2000).await;
-        assert!(after.is_none(), "Connection should be closed after hello_error");
+        let _ = restart.kill();
+        let _ = restart.wait();
+    }
+
+    /// Test: params over the configured size limit are rejected with -32003
+    /// and never reach the extension.
+    #[tokio::test]
+    async fn oversized_params_are_rejected() {
+        use actionbook::browser::extension_bridge::{
+            send_command_raw_with_token, serve_with_shutdown_options, ServeOptions,
+            PARAMS_TOO_LARGE_CODE,
+        };
+
+        let port = free_port().await;
+        let token = actionbook::browser::extension_bridge::generate_token();
+        let (_shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel();
+        let t = token.clone();
+        let options = ServeOptions {
+            max_params_bytes: Some(1024),
+            ..Default::default()
+        };
+        let server_handle = tokio::spawn(async move {
+            serve_with_shutdown_options(port, t, shutdown_rx, options).await
+        });
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let mut ext_ws = ws_connect(port).await;
+        hello_extension(&mut ext_ws, &token).await;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let big = "x".repeat(2048);
+        let resp = send_command_raw_with_token(
+            port,
+            "Page.navigate",
+            serde_json::json!({ "url": format!("data:text/plain,{}", big) }),
+            &token,
+        )
+        .await
+        .unwrap();
+        assert_eq!(resp["error"]["code"], PARAMS_TOO_LARGE_CODE);
+        assert!(resp["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("params too large"));
+        assert!(
+            try_recv_json_timeout(&mut ext_ws, 300).await.is_none(),
+            "oversized command must not be forwarded"
+        );
 
         server_handle.abort();
     }
 
-    /// Test: Connection without hello message is closed.
+    /// Test: Extension.pingExtension makes the full round-trip through the extension.
     #[tokio::test]
-    async fn no_hello_closes_connection() {
+    async fn ping_extension_round_trips_through_extension() {
+        use actionbook::browser::extension_bridge::send_command_with_token;
+
         let port = free_port().await;
-        let (server_handle, _token) = start_bridge(port);
+        let (server_handle, token) = start_bridge(port);
         tokio::time::sleep(Duration::from_millis(100)).await;
 
-        let mut ws = ws_connect(port).await;
+        let err = send_command_with_token(
+            port,
+            "Extension.pingExtension",
+            serde_json::json!({}),
+            &token,
+        )
+        .await
+        .expect_err("pingExtension should fail without an extension");
+        assert!(err.to_string().contains("not connected"), "{}", err);
 
-        // Send a non-hello message (old-style extension identification)
+        let mut ext_ws = ws_connect(port).await;
+        hello_extension(&mut ext_ws, &token).await;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let cli = tokio::spawn(async move {
+            send_command_with_token(
+                port,
+                "Extension.pingExtension",
+                serde_json::json!({}),
+                &token,
+            )
+            .await
+        });
+
+        // The extension sees a plain Extension.ping and answers it
+        let ext_msg = recv_json_timeout(&mut ext_ws, 3000)
+            .await
+            .expect("Extension should receive the forwarded ping");
+        assert_eq!(ext_msg["method"], "Extension.ping");
         send_json(
-            &mut ws,
-            serde_json::json!({ "type": "extension" }),
+            &mut ext_ws,
+            serde_json::json!({ "id": ext_msg["id"], "result": { "status": "pong" } }),
         )
         .await;
 
-        // Should be closed
-        let result = try_recv_json_timeout(&mut ws, 2000).await;
-        assert!(result.is_none(), "Connection should be closed without hello");
+        let result = cli.await.unwrap().expect("pingExtension should succeed");
+        assert_eq!(result["status"], "pong");
 
         server_handle.abort();
     }
 
-    /// Test: CLI command sent without extension connected gets an error response.
+    /// Test: error codes from the bridge and the extension reach the caller
+    /// as structured ExtensionRpc errors.
     #[tokio::test]
-    async fn cli_without_extension_gets_error() {
+    async fn send_command_carries_rpc_error_codes() {
+        use actionbook::browser::extension_bridge::send_command_with_token;
+        use actionbook::error::{ActionbookError, RpcErrorKind};
+
         let port = free_port().await;
         let (server_handle, token) = start_bridge(port);
         tokio::time::sleep(Duration::from_millis(100)).await;
 
-        // Connect as CLI with valid token
-        let mut cli_ws = ws_connect(port).await;
-        hello_cli(&mut cli_ws, &token).await;
+        // Rejected by the bridge itself
+        let err = send_command_with_token(port, "Bogus.method", serde_json::json!({}), &token)
+            .await
+            .unwrap_err();
+        assert_eq!(err.rpc_kind(), Some(RpcErrorKind::MethodNotFound), "{:?}", err);
 
-        // Send a CLI command
+        let mut ext_ws = ws_connect(port).await;
+        hello_extension(&mut ext_ws, &token).await;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let cases = [
+            (
+                serde_json::json!({ "code": -32000, "message": "Debugger detached" }),
+                RpcErrorKind::Server,
+            ),
+            (
+                serde_json::json!({ "code": -32602, "message": "Invalid selector" }),
+                RpcErrorKind::InvalidParams,
+            ),
+            (
+                serde_json::json!({
+                    "code": 4040,
+                    "message": "No node with given id",
+                    "data": { "nodeId": 7 }
+                }),
+                RpcErrorKind::Application,
+            ),
+        ];
+        for (error, kind) in cases {
+            let cli_token = token.clone();
+            let cli = tokio::spawn(async move {
+                send_command_with_token(
+                    port,
+                    "Runtime.evaluate",
+                    serde_json::json!({ "expression": "1" }),
+                    &cli_token,
+                )
+                .await
+            });
+            let ext_msg = recv_json_timeout(&mut ext_ws, 3000)
+                .await
+                .expect("Extension should receive command");
+            send_json(
+                &mut ext_ws,
+                serde_json::json!({ "id": ext_msg["id"], "error": error.clone() }),
+            )
+            .await;
+
+            let err = cli.await.unwrap().unwrap_err();
+            assert_eq!(err.rpc_kind(), Some(kind), "{:?}", err);
+            match err {
+                ActionbookError::ExtensionRpc { code, message, data } => {
+                    assert_eq!(code, error["code"].as_i64().unwrap());
+                    assert_eq!(message, error["message"].as_str().unwrap());
+                    assert_eq!(data, error.get("data").cloned());
+                }
+                other => panic!("expected ExtensionRpc, got {:?}", other),
+            }
+        }
+
+        server_handle.abort();
+    }
+
+    /// Test: send_command_raw keeps the whole response frame, while
+    /// send_command_with_token strips it down to `result`.
+    #[tokio::test]
+    async fn raw_send_preserves_extra_top_level_keys() {
+        use actionbook::browser::extension_bridge::{
+            send_command_raw_with_token, send_command_with_token,
+        };
+
+        let port = free_port().await;
+        let (server_handle, token) = start_bridge(port);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let mut ext_ws = ws_connect(port).await;
+        hello_extension(&mut ext_ws, &token).await;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let t = token.clone();
+        let raw = tokio::spawn(async move {
+            send_command_raw_with_token(port, "Extension.listTabs", serde_json::json!({}), &t).await
+        });
+        let cmd = recv_json_timeout(&mut ext_ws, 3000).await.unwrap();
         send_json(
-            &mut cli_ws,
+            &mut ext_ws,
             serde_json::json!({
-                "id": 1,
-                "method": "Page.navigate",
-                "params": { "url": "https://example.com" }
+                "id": cmd["id"],
+                "result": { "tabs": [] },
+                "meta": { "tab_id": 7 }
             }),
         )
         .await;
-
-        // Should get error response about extension not connected
-        let response = recv_json_timeout(&mut cli_ws, 3000)
-            .await
-            .expect("Should receive response");
-
-        assert!(response.get("error").is_some(), "Should have error field");
-        let error_msg = response["error"]["message"]
-            .as_str()
-            .unwrap_or("");
-        assert!(
-            error_msg.contains("not connected"),
-            "Error should mention extension not connected: {}",
-            error_msg
-        );
+        let raw = raw.await.unwrap().expect("raw send should succeed");
+        assert_eq!(raw["id"], 1, "id should be rewritten to the CLI's id");
+        assert_eq!(raw["result"]["tabs"], serde_json::json!([]));
+        assert_eq!(raw["meta"]["tab_id"], 7);
+
+        let t = token.clone();
+        let plain = tokio::spawn(async move {
+            send_command_with_token(port, "Extension.listTabs", serde_json::json!({}), &t).await
+        });
+        let cmd = recv_json_timeout(&mut ext_ws, 3000).await.unwrap();
+        send_json(
+            &mut ext_ws,
+            serde_json::json!({
+                "id": cmd["id"],
+                "result": { "tabs": [] },
+                "meta": { "tab_id": 7 }
+            }),
+        )
+        .await;
+        let plain = plain.await.unwrap().expect("send should succeed");
+        assert_eq!(plain, serde_json::json!({ "tabs": [] }));
 
         server_handle.abort();
     }
@@ -362,6 +1456,57 @@ mod bridge_tests {
         server_handle.abort();
     }
 
+    /// Test: Responses whose id comes back as a string or float still
+    /// resolve the CLI request.
+    #[tokio::test]
+    async fn non_canonical_response_ids_resolve_requests() {
+        let port = free_port().await;
+        let (server_handle, token) = start_bridge(port);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let mut ext_ws = ws_connect(port).await;
+        hello_extension(&mut ext_ws, &token).await;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let mut cli_ws = ws_connect(port).await;
+        hello_cli(&mut cli_ws, &token).await;
+
+        let echoes: [fn(u64) -> serde_json::Value; 3] = [
+            |id| serde_json::json!(id),
+            |id| serde_json::json!(id.to_string()),
+            |id| serde_json::json!(id as f64),
+        ];
+        for (cli_id, echo) in echoes.iter().enumerate() {
+            send_json(
+                &mut cli_ws,
+                serde_json::json!({
+                    "id": cli_id,
+                    "method": "Runtime.evaluate",
+                    "params": { "expression": "1+1" }
+                }),
+            )
+            .await;
+            let ext_msg = recv_json_timeout(&mut ext_ws, 3000)
+                .await
+                .expect("Extension should receive command");
+            let bridge_id = ext_msg["id"].as_u64().unwrap();
+
+            let mut reply = serde_json::json!({ "id": echo(bridge_id), "result": {} });
+            if let Some(gen) = ext_msg.get("gen") {
+                reply["gen"] = gen.clone();
+            }
+            send_json(&mut ext_ws, reply).await;
+
+            let cli_response = recv_json_timeout(&mut cli_ws, 3000)
+                .await
+                .expect("CLI should receive response");
+            assert_eq!(cli_response["id"].as_u64(), Some(cli_id as u64));
+            assert!(cli_response.get("result").is_some(), "{}", cli_response);
+        }
+
+        server_handle.abort();
+    }
+
     /// Test: Extension error response is forwarded to CLI.
     #[tokio::test]
     async fn extension_error_forwarded_to_cli() {
@@ -647,7 +1792,7 @@ mod bridge_tests {
             &mut cli_ws,
             serde_json::json!({
                 "id": 1,
-                "method": "Extension.ping",
+                "method": "Extension.listTabs",
                 "params": {}
             }),
         )
@@ -655,9 +1800,9 @@ mod bridge_tests {
 
         let ext_msg = recv_json_timeout(&mut ext_ws, 3000)
             .await
-            .expect("Extension should receive Extension.ping");
+            .expect("Extension should receive Extension.listTabs");
 
-        assert_eq!(ext_msg["method"].as_str(), Some("Extension.ping"));
+        assert_eq!(ext_msg["method"].as_str(), Some("Extension.listTabs"));
         assert_eq!(
             ext_msg["risk_level"].as_str(),
             Some("L1"),
@@ -727,6 +1872,65 @@ mod bridge_tests {
         assert!(!running, "Bridge should not be detected as running");
     }
 
+    /// Test: handshake_probe accepts a real bridge but not a bare TCP listener.
+    #[tokio::test]
+    async fn handshake_probe_requires_websocket_bridge() {
+        use actionbook::browser::extension_bridge::handshake_probe;
+
+        let port = free_port().await;
+        let (server_handle, _token) = start_bridge(port);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(handshake_probe(port).await, "Real bridge should pass the probe");
+        server_handle.abort();
+
+        // Accepts TCP, then hangs up without speaking WebSocket
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let raw_port = listener.local_addr().unwrap().port();
+        let raw_handle = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                drop(stream);
+            }
+        });
+        assert!(actionbook::browser::extension_bridge::is_bridge_running(raw_port).await);
+        assert!(
+            !handshake_probe(raw_port).await,
+            "Raw TCP listener should fail the probe"
+        );
+        raw_handle.abort();
+    }
+
+    /// Test: wait_for_extension returns once a delayed extension connects,
+    /// and gives up when none does.
+    #[tokio::test]
+    async fn wait_for_extension_sees_delayed_connect() {
+        use actionbook::browser::extension_bridge::wait_for_extension;
+
+        let port = free_port().await;
+        let (server_handle, token) = start_bridge(port);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        assert!(
+            !wait_for_extension(port, &token, Duration::from_millis(300)).await,
+            "No extension has connected yet"
+        );
+
+        let t = token.clone();
+        let ext_handle = tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(500)).await;
+            let mut ext_ws = ws_connect(port).await;
+            hello_extension(&mut ext_ws, &t).await;
+            // Stay connected until the test is done
+            tokio::time::sleep(Duration::from_secs(10)).await;
+        });
+
+        let started = std::time::Instant::now();
+        assert!(wait_for_extension(port, &token, Duration::from_secs(5)).await);
+        assert!(started.elapsed() >= Duration::from_millis(400));
+
+        ext_handle.abort();
+        server_handle.abort();
+    }
+
     /// Test: send_command_with_token returns error when bridge is not running.
     #[tokio::test]
     async fn send_command_fails_when_bridge_not_running() {
@@ -772,6 +1976,44 @@ mod bridge_tests {
         );
     }
 
+    /// Test: `extension ping` without `--port` talks to the bridge on the
+    /// port recorded in the port file.
+    #[tokio::test]
+    async fn cli_ping_without_port_uses_port_file() {
+        let port = free_port().await;
+        let (server_handle, token) = start_bridge(port);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let home = tempfile::tempdir().unwrap();
+        let data_dir = home.path().join("data");
+        std::fs::create_dir_all(data_dir.join("actionbook")).unwrap();
+        std::fs::write(data_dir.join("actionbook/bridge-port"), port.to_string()).unwrap();
+        std::fs::write(data_dir.join("actionbook/bridge-token"), &token).unwrap();
+
+        let output = tokio::task::spawn_blocking(move || {
+            assert_cmd::cargo::cargo_bin_cmd!("actionbook")
+                .env("HOME", home.path())
+                .env("XDG_DATA_HOME", &data_dir)
+                .args(["--json", "extension", "ping"])
+                .timeout(Duration::from_secs(10))
+                .output()
+                .expect("Should execute")
+        })
+        .await
+        .unwrap();
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let out: serde_json::Value = serde_json::from_str(stdout.trim())
+            .unwrap_or_else(|e| panic!("ping output is not JSON ({}): {}", e, stdout));
+        assert_eq!(
+            out["bridge"], true,
+            "ping should reach the bridge: {}",
+            stdout
+        );
+
+        server_handle.abort();
+    }
+
     /// Test: CLI extension status command via assert_cmd.
     #[test]
     fn cli_extension_status_runs() {
@@ -784,6 +2026,160 @@ mod bridge_tests {
         let _ = result;
     }
 
+    /// Test: `--json extension serve` prints a single ready event on stdout
+    /// before accepting its first connection; everything else goes to stderr.
+    #[tokio::test]
+    async fn cli_json_serve_emits_ready_event_first() {
+        use std::io::{BufRead, Read};
+
+        let tmp = tempfile::tempdir().unwrap();
+        let port = free_port().await;
+        let mut child = std::process::Command::new(env!("CARGO_BIN_EXE_actionbook"))
+            .env("HOME", tmp.path())
+            .env("XDG_DATA_HOME", tmp.path().join("data"))
+            .env("XDG_CONFIG_HOME", tmp.path().join("config"))
+            .args(["--json", "extension", "serve", "--port"])
+            .arg(port.to_string())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::null())
+            .spawn()
+            .expect("Should spawn serve");
+
+        // Block on exactly one stdout line, without polling the port
+        let mut stdout = std::io::BufReader::new(child.stdout.take().unwrap());
+        let first_line = tokio::task::spawn_blocking(move || {
+            let mut line = String::new();
+            stdout.read_line(&mut line).unwrap();
+            (line, stdout)
+        });
+        let (line, mut stdout) = tokio::time::timeout(Duration::from_secs(10), first_line)
+            .await
+            .expect("Ready line should arrive")
+            .unwrap();
+
+        let event: serde_json::Value = serde_json::from_str(line.trim()).unwrap();
+        assert_eq!(event["event"], "ready");
+        assert_eq!(event["port"], port);
+        assert_eq!(event["mode"], "standard");
+        let token = event["token"].as_str().unwrap().to_string();
+        assert!(token.starts_with("abk_"));
+
+        // The first connection made after the ready line is accepted
+        let mut ext_ws = ws_connect(port).await;
+        hello_extension(&mut ext_ws, &token).await;
+        drop(ext_ws);
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        let _ = child.kill();
+        let _ = child.wait();
+        let mut rest = String::new();
+        stdout.read_to_string(&mut rest).unwrap();
+        assert!(
+            rest.trim().is_empty(),
+            "Only the ready event belongs on stdout, got: {}",
+            rest
+        );
+    }
+
+    /// Test: each invocation stamps its JSON lines with its own `run_id`, so
+    /// serve and a later restart differ. `restart` prints a single
+    /// line: the new bridge's ready event, naming the bridge it replaced.
+    #[tokio::test]
+    async fn cli_json_lines_share_run_id() {
+        use std::io::BufRead;
+
+        let tmp = tempfile::tempdir().unwrap();
+        let port = free_port().await;
+        let spawn = |subcommand: &str| {
+            std::process::Command::new(env!("CARGO_BIN_EXE_actionbook"))
+                .env("HOME", tmp.path())
+                .env("XDG_DATA_HOME", tmp.path().join("data"))
+                .env("XDG_CONFIG_HOME", tmp.path().join("config"))
+                .args(["--json", "extension", subcommand, "--port"])
+                .arg(port.to_string())
+                .stdout(std::process::Stdio::piped())
+                .stderr(std::process::Stdio::null())
+                .spawn()
+                .expect("Should spawn actionbook")
+        };
+        let read_lines = |child: &mut std::process::Child, n: usize| {
+            let mut stdout = std::io::BufReader::new(child.stdout.take().unwrap());
+            tokio::task::spawn_blocking(move || {
+                (0..n)
+                    .map(|_| {
+                        let mut line = String::new();
+                        stdout.read_line(&mut line).unwrap();
+                        serde_json::from_str::<serde_json::Value>(line.trim()).unwrap()
+                    })
+                    .collect::<Vec<_>>()
+            })
+        };
+
+        let mut serve = spawn("serve");
+        let serve_lines = tokio::time::timeout(Duration::from_secs(10), read_lines(&mut serve, 1))
+            .await
+            .expect("Ready line should arrive")
+            .unwrap();
+
+        let mut restart = spawn("restart");
+        let restart_lines =
+            tokio::time::timeout(Duration::from_secs(20), read_lines(&mut restart, 1))
+                .await
+                .expect("Restart should print the ready event")
+                .unwrap();
+        let _ = restart.kill();
+        let _ = restart.wait();
+        let _ = serve.kill();
+        let _ = serve.wait();
+
+        assert_eq!(restart_lines[0]["event"], "ready");
+        assert_eq!(restart_lines[0]["replaced"]["pid"], serve.id());
+        let run_id = restart_lines[0]["run_id"].as_str().unwrap();
+        assert_ne!(serve_lines[0]["run_id"], run_id);
+    }
+
+    /// Test: two `serve --stable-token` runs hand out the same token, and
+    /// `--rotate-token` replaces it.
+    #[tokio::test]
+    async fn cli_stable_token_survives_restart() {
+        use std::io::BufRead;
+
+        let tmp = tempfile::tempdir().unwrap();
+        let port = free_port().await;
+        let serve_token = |extra: &[&str]| {
+            let mut child = std::process::Command::new(env!("CARGO_BIN_EXE_actionbook"))
+                .env("HOME", tmp.path())
+                .env("XDG_DATA_HOME", tmp.path().join("data"))
+                .env("XDG_CONFIG_HOME", tmp.path().join("config"))
+                .args(["--json", "extension", "serve", "--port"])
+                .arg(port.to_string())
+                .args(extra)
+                .stdout(std::process::Stdio::piped())
+                .stderr(std::process::Stdio::null())
+                .spawn()
+                .expect("Should spawn serve");
+            let mut line = String::new();
+            std::io::BufReader::new(child.stdout.take().unwrap())
+                .read_line(&mut line)
+                .unwrap();
+            let _ = child.kill();
+            let _ = child.wait();
+            let event: serde_json::Value = serde_json::from_str(line.trim()).unwrap();
+            event["token"].as_str().unwrap().to_string()
+        };
+
+        let first = serve_token(&["--stable-token"]);
+        let second = serve_token(&["--stable-token"]);
+        assert_eq!(first, second);
+
+        let rotated = serve_token(&["--stable-token", "--rotate-token"]);
+        assert_ne!(rotated, first);
+        assert_eq!(serve_token(&["--stable-token"]), rotated);
+
+        // Without the flag every run still gets a fresh token
+        assert_ne!(serve_token(&[]), rotated);
+    }
+
     // --- Issue 1: createTab/activateTab auto-attach tests ---
     // Note: The bridge handles one command per CLI connection. Each command
     // must use a separate WebSocket connection (which is how the real CLI works).
@@ -0,0 +1,163 @@
+//! Integration tests for session-wide header injection and request interception.
+//!
+//! These tests launch a real headless browser against a local echo server
+//! that reflects request headers back in the response body. They are skipped
+//! when no Chromium-based browser is installed.
+//!
+//! Run with: cargo test --test interception_test
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use actionbook::browser::{RequestRule, SessionManager};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+mod common;
+
+/// Start an HTTP server that answers every request with its own headers as
+/// plain text. Returns the server's base URL.
+async fn start_echo_server() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                let mut buf = Vec::new();
+                let mut chunk = [0u8; 4096];
+                while !buf.windows(4).any(|w| w == b"\r\n\r\n") {
+                    match stream.read(&mut chunk).await {
+                        Ok(0) | Err(_) => return,
+                        Ok(n) => buf.extend_from_slice(&chunk[..n]),
+                    }
+                }
+                let head = String::from_utf8_lossy(&buf);
+                let body = head
+                    .split("\r\n\r\n")
+                    .next()
+                    .unwrap_or_default()
+                    .to_string();
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+            });
+        }
+    });
+
+    format!("http://127.0.0.1:{}", port)
+}
+
+/// Navigate and poll the page text until it contains `needle`.
+async fn load_and_wait_for(
+    manager: &SessionManager,
+    profile: &str,
+    url: &str,
+    needle: &str,
+) -> String {
+    manager.goto(Some(profile), url).await.unwrap();
+    let mut text = String::new();
+    for _ in 0..50 {
+        if let Ok(t) = manager.get_text(Some(profile), None).await {
+            text = t;
+            if text.contains(needle) {
+                break;
+            }
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    text
+}
+
+#[tokio::test]
+async fn extra_headers_reach_echo_server() {
+    if !common::browser_available() {
+        return;
+    }
+
+    let profile = "headers-test";
+    let user_data_dir = tempfile::tempdir().unwrap();
+    let manager = common::test_session_manager(profile, user_data_dir.path()).await;
+    let (_browser, mut handler) = manager.get_or_create_session(Some(profile)).await.unwrap();
+    tokio::spawn(async move {
+        use futures::StreamExt;
+        while handler.next().await.is_some() {}
+    });
+
+    let base_url = start_echo_server().await;
+
+    let mut headers = HashMap::new();
+    headers.insert(
+        "X-Actionbook-Test".to_string(),
+        "injected-value".to_string(),
+    );
+    let handle = manager
+        .set_extra_headers(Some(profile), headers)
+        .await
+        .unwrap();
+
+    let text = load_and_wait_for(
+        &manager,
+        profile,
+        &format!("{}/headers", base_url),
+        "injected-value",
+    )
+    .await;
+    assert!(
+        text.to_lowercase()
+            .contains("x-actionbook-test: injected-value"),
+        "Injected header not echoed back: {}",
+        text
+    );
+
+    handle.disable().await.unwrap();
+    manager.close_session(Some(profile)).await.unwrap();
+}
+
+#[tokio::test]
+async fn intercepted_request_gets_rewritten_headers() {
+    if !common::browser_available() {
+        return;
+    }
+
+    let profile = "intercept-test";
+    let user_data_dir = tempfile::tempdir().unwrap();
+    let manager = common::test_session_manager(profile, user_data_dir.path()).await;
+    let (_browser, mut handler) = manager.get_or_create_session(Some(profile)).await.unwrap();
+    tokio::spawn(async move {
+        use futures::StreamExt;
+        while handler.next().await.is_some() {}
+    });
+
+    let base_url = start_echo_server().await;
+
+    let mut headers = HashMap::new();
+    headers.insert("Authorization".to_string(), "Bearer test-token".to_string());
+    let handle = manager
+        .intercept_requests(
+            Some(profile),
+            RequestRule::set_headers(format!("{}/*", base_url), headers),
+        )
+        .await
+        .unwrap();
+    assert!(handle.is_active());
+
+    let text = load_and_wait_for(
+        &manager,
+        profile,
+        &format!("{}/auth", base_url),
+        "Bearer test-token",
+    )
+    .await;
+    assert!(
+        text.contains("Bearer test-token"),
+        "Rewritten header not echoed back: {}",
+        text
+    );
+
+    handle.disable().await.unwrap();
+    manager.close_session(Some(profile)).await.unwrap();
+}